@@ -1,3 +1,4 @@
+use centichain_lib::utils::logging::apply_log_format;
 use libp2p::{
     futures::StreamExt,
     identity, kad, noise, relay,
@@ -18,7 +19,7 @@ struct RelayServerBehaviour {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    flexi_logger::Logger::try_with_str("info")?.start()?;
+    apply_log_format(flexi_logger::Logger::try_with_str("info")?).start()?;
     log::info!("Starting Centichain Relay Server (Bootstrap Node)...");
 
     // 1. Generate keys (In production, load these from file!)
@@ -59,7 +60,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
             // Force Server Mode so this node actively handles DHT queries and stores records
             kad_behaviour.set_mode(Some(kad::Mode::Server));
 
-            // C. Identify - Must match exactly with RPC/GUI nodes (/antigravity/1.0.0)
+            // C. Identify - No chain id suffix: nodes of every network exempt their relay
             let identify = libp2p::identify::Behaviour::new(libp2p::identify::Config::new(
                 "/centichain/1.0.0".to_string(),
                 key.public(),
@@ -0,0 +1,1439 @@
+//! HTTP and WebSocket API served by the RPC node.
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, Request, State,
+    },
+    http::{header, HeaderMap, Method, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Json, Response},
+    routing::{get, post},
+    Router,
+};
+use centichain_lib::{
+    chain::{retarget_difficulty, Block, TokenomicsInfo, Transaction},
+    consensus::mempool::Mempool,
+    consensus::{Consensus, NodeConsensusStatus},
+    storage::Storage,
+    utils::constants::{
+        ADDRESS_TXS_MAX_LIMIT, DIFFICULTY_RETARGET_WINDOW, RICH_LIST_DEFAULT_LIMIT,
+        RICH_LIST_MAX_LIMIT,
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+use tokio::sync::broadcast;
+use tower_http::cors::{Any, CorsLayer};
+
+use crate::faucet::Faucet;
+use crate::jsonrpc;
+use crate::metrics;
+use crate::webhooks::Webhooks;
+
+// --- Shared State for API ---
+pub struct AppState {
+    pub storage: Arc<Storage>,
+    pub mempool: Arc<Mempool>,
+    pub consensus: Arc<Mutex<Consensus>>,
+    pub chain_index: Arc<AtomicU64>,
+    pub peer_count: Arc<std::sync::atomic::AtomicUsize>,
+    pub tx_sender: tokio::sync::mpsc::Sender<Transaction>, // To submit tx to P2P
+    pub evt_sender: broadcast::Sender<Event>,              // Broadcast events to WebSockets
+    /// Bearer token required by write routes (`RPC_API_KEY`); `None` leaves them open
+    pub api_key: Option<String>,
+    /// Testnet faucet (`FAUCET_ENABLED`); `None` answers its route with 403
+    pub faucet: Option<Faucet>,
+    /// Started the chain (`RPC_GENESIS_NODE`), so height 0 is all there is
+    pub genesis_node: bool,
+    /// Callbacks the events are pushed to
+    pub webhooks: Arc<Webhooks>,
+    /// VDF squarings per second this machine last measured
+    pub vdf_ips: Arc<AtomicU64>,
+}
+
+#[derive(Clone, Serialize, Debug)]
+#[serde(tag = "type", content = "data")]
+pub enum Event {
+    NewBlock(Block),
+    NewTransaction(Transaction),
+}
+
+impl Event {
+    /// Serialized `type` tag
+    fn kind(&self) -> &'static str {
+        match self {
+            Event::NewBlock(_) => "NewBlock",
+            Event::NewTransaction(_) => "NewTransaction",
+        }
+    }
+
+    /// Whether the transaction, or a transaction in the block, is sent or
+    /// received by `address`
+    pub(crate) fn touches(&self, address: &str) -> bool {
+        let touches = |tx: &Transaction| {
+            tx.sender == address || tx.credits().iter().any(|(r, _)| *r == address)
+        };
+        match self {
+            Event::NewBlock(block) => block.transactions.iter().any(touches),
+            Event::NewTransaction(tx) => touches(tx),
+        }
+    }
+}
+
+/// Filter a WebSocket client sets by sending it as JSON; empty fields match everything.
+#[derive(Deserialize, Default, Debug)]
+struct Subscription {
+    #[serde(default)]
+    types: Vec<String>,
+    /// Only transactions sent or received by this address, and blocks containing one
+    address: Option<String>,
+}
+
+impl Subscription {
+    fn matches(&self, event: &Event) -> bool {
+        if !self.types.is_empty() && !self.types.iter().any(|t| t == event.kind()) {
+            return false;
+        }
+        self.address
+            .as_deref()
+            .is_none_or(|address| event.touches(address))
+    }
+}
+
+/// Builds the API router over the shared node state.
+pub fn router(state: Arc<AppState>) -> Router {
+    let cors = CorsLayer::new()
+        .allow_origin(Any)
+        .allow_methods(vec![Method::GET, Method::POST])
+        .allow_headers(Any);
+
+    // Routes that change node or network state
+    let writes = Router::new()
+        .route("/api/v1/broadcast", post(broadcast_tx))
+        .route(
+            "/api/v1/equivocations",
+            get(get_slashings).post(report_equivocation),
+        )
+        .route("/api/v1/webhooks", post(register_webhook))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            require_api_key,
+        ));
+
+    Router::new()
+        .route("/api/v1/status", get(get_status))
+        .route("/api/v1/blocks", get(get_blocks_paginated)) // New
+        .route("/api/v1/blocks/index/:index", get(get_block_by_index))
+        .route("/api/v1/blocks/hash/:hash", get(get_block_by_hash))
+        .route("/api/v1/transactions/:id", get(get_transaction))
+        .route("/api/v1/balance/:address", get(get_balance))
+        .route(
+            "/api/v1/address/:address/transactions",
+            get(get_address_transactions),
+        )
+        .route("/api/v1/richlist", get(get_rich_list))
+        .route("/api/v1/mempool", get(get_mempool))
+        .route("/api/v1/validators", get(get_validators))
+        .route("/api/v1/validators/:peer_id", get(get_validator))
+        .route("/api/v1/network/stats", get(get_network_stats)) // New
+        .route("/api/v1/tokenomics", get(get_tokenomics))
+        .route("/api/v1/vdf", get(get_vdf_status))
+        .route("/api/v1/faucet", post(faucet_drip))
+        .route("/rpc", post(jsonrpc::handle_rpc))
+        .route("/ws", get(websocket_handler)) // New
+        .route("/metrics", get(get_metrics))
+        .route("/health", get(get_health))
+        .route("/ready", get(get_ready))
+        .merge(writes)
+        .layer(cors)
+        .with_state(state)
+}
+
+// --- API Handlers ---
+
+#[derive(Serialize)]
+struct StatusResponse {
+    node_type: String,
+    chain_height: u64,
+    peer_count: usize,
+    network: String,
+}
+
+/// Whether `headers` carry `Authorization: Bearer <RPC_API_KEY>`, or no key is set.
+pub(crate) fn has_api_key(state: &AppState, headers: &HeaderMap) -> bool {
+    let Some(expected) = &state.api_key else {
+        return true;
+    };
+    let provided = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    provided == Some(expected.as_str())
+}
+
+/// Rejects requests without `Authorization: Bearer <RPC_API_KEY>` when a key is set.
+async fn require_api_key(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !has_api_key(&state, request.headers()) {
+        return (StatusCode::UNAUTHORIZED, "Invalid or missing API key").into_response();
+    }
+    next.run(request).await
+}
+
+// WS Handler
+async fn websocket_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    ws.on_upgrade(|socket| websocket_connection(socket, state))
+}
+
+async fn websocket_connection(mut socket: WebSocket, state: Arc<AppState>) {
+    let mut rx = state.evt_sender.subscribe();
+    if let Err(e) = socket
+        .send(Message::Text(
+            "Connected to Centichain Real-time Feed".to_string(),
+        ))
+        .await
+    {
+        log::error!("WS send error: {}", e);
+        return;
+    }
+
+    // Everything is forwarded until the client sends a subscription
+    let mut subscription = Subscription::default();
+    loop {
+        tokio::select! {
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Text(text))) => match serde_json::from_str(&text) {
+                    Ok(filter) => subscription = filter,
+                    Err(e) => log::debug!("Ignoring invalid WS subscription: {}", e),
+                },
+                Some(Ok(_)) => {}
+                Some(Err(_)) | None => break,
+            },
+            event = rx.recv() => match event {
+                Ok(event) => {
+                    if !subscription.matches(&event) {
+                        continue;
+                    }
+                    if let Ok(json) = serde_json::to_string(&event) {
+                        if socket.send(Message::Text(json)).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+        }
+    }
+}
+
+async fn get_status(State(state): State<Arc<AppState>>) -> Json<StatusResponse> {
+    let height = state.chain_index.load(Ordering::Relaxed);
+    let peers = state.peer_count.load(Ordering::Relaxed);
+
+    Json(StatusResponse {
+        node_type: "RPC".to_string(),
+        chain_height: height,
+        peer_count: peers,
+        network: "Centichain Mainnet".to_string(),
+    })
+}
+
+/// Liveness: answers whenever the process is up.
+async fn get_health() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "status": "ok" }))
+}
+
+/// Readiness: 200 once the node has a peer and a chain to serve, else 503.
+async fn get_ready(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let height = state.chain_index.load(Ordering::Relaxed);
+    let peers = state.peer_count.load(Ordering::Relaxed);
+    let ready = peers > 0 && (height > 0 || state.genesis_node);
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(serde_json::json!({ "ready": ready })))
+}
+
+async fn get_metrics(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let recent = state
+        .storage
+        .get_recent_blocks(metrics::INTERVAL_SAMPLE_BLOCKS)
+        .unwrap_or_default();
+
+    let mut body = String::new();
+    metrics::gauge(
+        &mut body,
+        "chain_height",
+        "Index of the latest block",
+        state.chain_index.load(Ordering::Relaxed),
+    );
+    metrics::gauge(
+        &mut body,
+        "peer_count",
+        "Connected peers",
+        state.peer_count.load(Ordering::Relaxed) as u64,
+    );
+    metrics::gauge(
+        &mut body,
+        "mempool_size",
+        "Pending transactions in the mempool",
+        state.mempool.len() as u64,
+    );
+    metrics::gauge(
+        &mut body,
+        "total_blocks",
+        "Blocks held in local storage",
+        state.storage.get_total_blocks().unwrap_or(0),
+    );
+    metrics::interval_histogram(
+        &mut body,
+        "block_interval_seconds",
+        "Seconds between consecutive recent blocks",
+        &metrics::block_intervals(&recent),
+    );
+
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
+
+/// A block as served by the API, flagged once it can no longer be reorganized
+#[derive(Serialize)]
+pub(crate) struct BlockResponse {
+    #[serde(flatten)]
+    block: Block,
+    finalized: bool,
+}
+
+impl BlockResponse {
+    pub(crate) fn new(block: Block, tip_index: u64) -> Self {
+        let finalized = centichain_lib::chain::is_finalized(block.index, tip_index);
+        BlockResponse { block, finalized }
+    }
+}
+
+#[derive(Deserialize)]
+struct Pagination {
+    page: Option<usize>,
+    limit: Option<usize>,
+}
+
+async fn get_blocks_paginated(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<Pagination>,
+) -> impl IntoResponse {
+    let page = params.page.unwrap_or(0);
+    let limit = params.limit.unwrap_or(20);
+
+    let tip = state.storage.get_latest_index().unwrap_or(0);
+    match state.storage.get_blocks_paginated(page, limit) {
+        Ok(blocks) => Json(
+            blocks
+                .into_iter()
+                .map(|block| BlockResponse::new(block, tip))
+                .collect::<Vec<_>>(),
+        )
+        .into_response(),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Storage error").into_response(),
+    }
+}
+
+#[derive(Serialize)]
+struct NetworkStats {
+    supply: u64,
+    max_supply: u64,
+    circulating: u64,
+    halving_block: u64,
+    current_reward: u64,
+    mining_difficulty: u64,
+}
+
+async fn get_network_stats(State(state): State<Arc<AppState>>) -> Json<NetworkStats> {
+    let height = state.chain_index.load(Ordering::Relaxed);
+    let burned = state.storage.get_burned_fees().unwrap_or(0);
+    let supply = centichain_lib::chain::calculate_circulating_supply(height, burned);
+    let reward = centichain_lib::chain::calculate_mining_reward(height + 1);
+
+    // Calculate simple halving info
+    let current_interval = height / centichain_lib::utils::constants::HALVING_INTERVAL;
+    let next_halving = (current_interval + 1) * centichain_lib::utils::constants::HALVING_INTERVAL;
+
+    Json(NetworkStats {
+        supply,
+        max_supply: centichain_lib::utils::constants::TOTAL_SUPPLY,
+        circulating: supply, // Simplifying for now
+        halving_block: next_halving,
+        current_reward: reward,
+        mining_difficulty: 200_000, // Hardcoded for VDF PoC
+    })
+}
+
+async fn get_tokenomics(State(state): State<Arc<AppState>>) -> Json<TokenomicsInfo> {
+    let height = state.chain_index.load(Ordering::Relaxed);
+    let burned = state.storage.get_burned_fees().unwrap_or(0);
+    Json(TokenomicsInfo::at_height(height, burned))
+}
+
+#[derive(Serialize)]
+struct VdfStatusResponse {
+    iterations_per_second: u64,
+    /// Difficulty the next block must be solved at
+    difficulty: u64,
+}
+
+async fn get_vdf_status(State(state): State<Arc<AppState>>) -> Json<VdfStatusResponse> {
+    let recent = state
+        .storage
+        .get_recent_blocks(DIFFICULTY_RETARGET_WINDOW)
+        .unwrap_or_default();
+    Json(VdfStatusResponse {
+        iterations_per_second: state.vdf_ips.load(Ordering::Relaxed),
+        difficulty: retarget_difficulty(&recent),
+    })
+}
+
+async fn get_block_by_index(
+    State(state): State<Arc<AppState>>,
+    Path(index): Path<u64>,
+) -> impl IntoResponse {
+    let tip = state.storage.get_latest_index().unwrap_or(0);
+    match state.storage.get_block(index) {
+        Ok(Some(block)) => Json(BlockResponse::new(block, tip)).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, "Block not found").into_response(),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Storage error").into_response(),
+    }
+}
+
+async fn get_block_by_hash(
+    State(state): State<Arc<AppState>>,
+    Path(hash): Path<String>,
+) -> impl IntoResponse {
+    let tip = state.storage.get_latest_index().unwrap_or(0);
+    match state.storage.get_block_by_hash(&hash) {
+        Ok(Some(block)) => Json(BlockResponse::new(block, tip)).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, "Block not found").into_response(),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Storage error").into_response(),
+    }
+}
+
+async fn get_transaction(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match state.storage.get_transaction_by_id(&id) {
+        Ok(Some((tx, block))) => Json(serde_json::json!({
+            "transaction": tx,
+            "block_index": block.index,
+            "block_hash": block.hash,
+            "status": "confirmed"
+        }))
+        .into_response(),
+        Ok(None) => {
+            // Check mempool
+            let pending = state.mempool.get_pending_transactions();
+            if let Some(tx) = pending.iter().find(|t| t.id == id) {
+                Json(serde_json::json!({
+                    "transaction": tx,
+                    "status": "pending"
+                }))
+                .into_response()
+            } else {
+                (StatusCode::NOT_FOUND, "Transaction not found").into_response()
+            }
+        }
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Storage error").into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct BalanceQuery {
+    /// Balance as of this block instead of the chain tip
+    height: Option<u64>,
+}
+
+async fn get_balance(
+    State(state): State<Arc<AppState>>,
+    Path(address): Path<String>,
+    Query(params): Query<BalanceQuery>,
+) -> impl IntoResponse {
+    if let Some(height) = params.height {
+        return match state.storage.balance_at_height(&address, height) {
+            Ok(balance) => Json(serde_json::json!({
+                "address": address,
+                "balance": balance,
+                "height": height,
+                "currency": "AGT"
+            }))
+            .into_response(),
+            Err(e) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+        };
+    }
+
+    match state.storage.calculate_balance(&address) {
+        Ok(balance) => Json(serde_json::json!({
+            "address": address,
+            "balance": balance,
+            "currency": "AGT"
+        }))
+        .into_response(),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Storage error").into_response(),
+    }
+}
+
+#[derive(Serialize)]
+struct AddressTransaction {
+    transaction: Transaction,
+    block_index: u64,
+}
+
+async fn get_address_transactions(
+    State(state): State<Arc<AppState>>,
+    Path(address): Path<String>,
+    Query(params): Query<Pagination>,
+) -> impl IntoResponse {
+    let page = params.page.unwrap_or(0);
+    let limit = params.limit.unwrap_or(20).min(ADDRESS_TXS_MAX_LIMIT);
+
+    match state
+        .storage
+        .get_transactions_for_address(&address, page, limit)
+    {
+        Ok(txs) => Json(
+            txs.into_iter()
+                .map(|(transaction, block_index)| AddressTransaction {
+                    transaction,
+                    block_index,
+                })
+                .collect::<Vec<_>>(),
+        )
+        .into_response(),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Storage error").into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct RichListQuery {
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct RichListEntry {
+    address: String,
+    balance: u64,
+}
+
+async fn get_rich_list(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<RichListQuery>,
+) -> impl IntoResponse {
+    let limit = params
+        .limit
+        .unwrap_or(RICH_LIST_DEFAULT_LIMIT)
+        .min(RICH_LIST_MAX_LIMIT);
+    match state.storage.get_top_balances(limit) {
+        Ok(top) => Json(
+            top.into_iter()
+                .map(|(address, balance)| RichListEntry { address, balance })
+                .collect::<Vec<_>>(),
+        )
+        .into_response(),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Storage error").into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct MempoolQuery {
+    sender: Option<String>,
+    receiver: Option<String>,
+    page: Option<usize>,
+    limit: Option<usize>,
+}
+
+async fn get_mempool(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<MempoolQuery>,
+) -> Json<Vec<Transaction>> {
+    let page = params.page.unwrap_or(0);
+    let limit = params.limit.unwrap_or(100);
+
+    let mut pending: Vec<Transaction> = state
+        .mempool
+        .get_pending_transactions()
+        .into_iter()
+        .filter(|tx| params.sender.as_ref().is_none_or(|s| tx.sender == *s))
+        .filter(|tx| {
+            params.receiver.as_ref().is_none_or(|r| {
+                tx.credits()
+                    .iter()
+                    .any(|(receiver, _)| *receiver == r.as_str())
+            })
+        })
+        .collect();
+    // Oldest first, so pages stay stable as new transactions arrive
+    pending.sort_by(|a, b| (a.timestamp, &a.id).cmp(&(b.timestamp, &b.id)));
+
+    Json(
+        pending
+            .into_iter()
+            .skip(page.saturating_mul(limit))
+            .take(limit)
+            .collect(),
+    )
+}
+
+#[derive(Serialize)]
+struct ValidatorInfo {
+    peer_id: String,
+    trust_score: f64,
+    is_active: bool,
+    is_verified: bool,
+    activated_at: Option<u64>,
+    missed_slots: u64,
+}
+
+#[derive(Serialize)]
+struct ValidatorsResponse {
+    epoch: u64,
+    slot: u64,
+    active_shards: u16,
+    validators: Vec<ValidatorInfo>,
+}
+
+async fn get_validators(State(state): State<Arc<AppState>>) -> Json<ValidatorsResponse> {
+    // Copy out under the lock; the swarm task shares it
+    let mut response = {
+        let consensus = state.consensus.lock().unwrap();
+        ValidatorsResponse {
+            epoch: consensus.current_epoch(),
+            slot: consensus.current_slot(),
+            active_shards: consensus.calculate_active_shards(),
+            validators: consensus
+                .nodes
+                .values()
+                .map(|node| ValidatorInfo {
+                    peer_id: node.peer_id.clone(),
+                    trust_score: node.trust_score,
+                    is_active: node.is_active,
+                    is_verified: node.is_verified,
+                    activated_at: node.activated_at,
+                    missed_slots: node.missed_slots,
+                })
+                .collect(),
+        }
+    };
+    response
+        .validators
+        .sort_by(|a, b| a.peer_id.cmp(&b.peer_id));
+    Json(response)
+}
+
+async fn get_validator(
+    State(state): State<Arc<AppState>>,
+    Path(peer_id): Path<String>,
+) -> impl IntoResponse {
+    let status: Option<NodeConsensusStatus> = {
+        let consensus = state.consensus.lock().unwrap();
+        consensus
+            .nodes
+            .contains_key(&peer_id)
+            .then(|| consensus.get_node_status(&peer_id))
+    };
+    match status {
+        Some(status) => Json(status).into_response(),
+        None => (StatusCode::NOT_FOUND, "Validator not found").into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct BroadcastRequest {
+    transaction: Transaction,
+}
+
+async fn broadcast_tx(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<BroadcastRequest>,
+) -> impl IntoResponse {
+    // Basic validation
+    // Verify signature logic would ideally be here or in mempool
+
+    if let Err(e) = state.mempool.add_transaction(payload.transaction.clone()) {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!("Invalid transaction: {}", e),
+        )
+            .into_response();
+    }
+
+    // Send to P2P loop to broadcast
+    if let Err(_) = state.tx_sender.send(payload.transaction.clone()).await {
+        return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to broadcast").into_response();
+    }
+
+    Json(serde_json::json!({
+        "status": "accepted",
+        "tx_id": payload.transaction.id
+    }))
+    .into_response()
+}
+
+/// Two blocks signed by one author for one slot
+#[derive(Deserialize)]
+struct EquivocationReport {
+    first: Block,
+    second: Block,
+}
+
+/// Reported equivocations that led to a slash
+async fn get_slashings(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    match state.storage.load_slashings() {
+        Ok(slashings) => Json(slashings).into_response(),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Storage error").into_response(),
+    }
+}
+
+async fn report_equivocation(
+    State(state): State<Arc<AppState>>,
+    Json(report): Json<EquivocationReport>,
+) -> impl IntoResponse {
+    let result = state
+        .consensus
+        .lock()
+        .unwrap()
+        .report_equivocation(&report.first, &report.second);
+    match result {
+        Ok(evidence) => Json(evidence).into_response(),
+        Err(e) => (StatusCode::BAD_REQUEST, format!("Invalid evidence: {}", e)).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct WebhookRequest {
+    url: String,
+    /// Only events touching this address
+    address: Option<String>,
+}
+
+async fn register_webhook(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<WebhookRequest>,
+) -> impl IntoResponse {
+    match state.webhooks.register(payload.url, payload.address) {
+        Ok(webhook) => {
+            log::info!("Registered webhook {} for {}", webhook.id, webhook.url);
+            Json(webhook).into_response()
+        }
+        Err(e) => (StatusCode::BAD_REQUEST, e).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct FaucetRequest {
+    address: String,
+}
+
+async fn faucet_drip(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<FaucetRequest>,
+) -> impl IntoResponse {
+    let Some(faucet) = &state.faucet else {
+        return (StatusCode::FORBIDDEN, "Faucet is disabled").into_response();
+    };
+    let address = payload.address;
+    if address.parse::<libp2p::PeerId>().is_err() {
+        return (StatusCode::BAD_REQUEST, "Invalid address").into_response();
+    }
+    if let Err(wait) = faucet.reserve(&address, std::time::Instant::now()) {
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            format!("Faucet already used; try again in {}s", wait.as_secs()),
+        )
+            .into_response();
+    }
+
+    let shard_id = state
+        .consensus
+        .lock()
+        .unwrap()
+        .get_assigned_shard(faucet.address(), 0);
+    let tx = match faucet.drip(&state.mempool, &address, shard_id) {
+        Ok(tx) => tx,
+        Err(e) => {
+            faucet.release(&address);
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                format!("Faucet drip failed: {}", e),
+            )
+                .into_response();
+        }
+    };
+    if state.tx_sender.send(tx.clone()).await.is_err() {
+        return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to broadcast").into_response();
+    }
+
+    Json(serde_json::json!({
+        "status": "accepted",
+        "tx_id": tx.id,
+        "amount": tx.amount
+    }))
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::{to_bytes, Body},
+        http::Request,
+    };
+    use centichain_lib::chain::SYSTEM_SIG_REWARD;
+    use centichain_lib::utils::constants::{
+        FINALITY_DEPTH, GENESIS_SUPPLY, HALVING_INTERVAL, INITIAL_REWARD, TARGET_BLOCK_TIME,
+    };
+    use libp2p::identity::Keypair;
+    use tower::ServiceExt;
+
+    fn test_state(funded: &[&Keypair]) -> Arc<AppState> {
+        let path =
+            std::env::temp_dir().join(format!("centichain-test-{}.db", uuid::Uuid::new_v4()));
+        let storage = Arc::new(Storage::new(path.to_str().unwrap()).unwrap());
+        let funding = funded
+            .iter()
+            .map(|keypair| Transaction {
+                id: format!("funding-{}", keypair.public().to_peer_id()),
+                sender: "SYSTEM".into(),
+                receiver: keypair.public().to_peer_id().to_string(),
+                amount: 100_000_000,
+                shard_id: 0,
+                timestamp: 0,
+                nonce: 0,
+                fee: 0,
+                signature: SYSTEM_SIG_REWARD.into(),
+                sender_pubkey: String::new(),
+                outputs: Vec::new(),
+                memo: None,
+                not_before_height: None,
+                alias: None,
+            })
+            .collect();
+        let genesis = Block::new(0, "miner".into(), funding, String::new(), 100, 100, 0, 0, 0);
+        storage.save_block(&genesis).unwrap();
+
+        let (tx_sender, mut tx_receiver) = tokio::sync::mpsc::channel(16);
+        tokio::spawn(async move { while tx_receiver.recv().await.is_some() {} });
+        let (evt_sender, _) = broadcast::channel(1);
+        Arc::new(AppState {
+            mempool: Arc::new(Mempool::new(storage.clone())),
+            storage,
+            consensus: Arc::new(Mutex::new(Consensus::new())),
+            chain_index: Arc::new(AtomicU64::new(0)),
+            peer_count: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+            tx_sender,
+            evt_sender,
+            api_key: None,
+            faucet: None,
+            genesis_node: false,
+            webhooks: Arc::new(Webhooks::new()),
+            vdf_ips: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    fn transfer(keypair: &Keypair, receiver: &str, nonce: u64) -> Transaction {
+        let mut tx = Transaction {
+            id: String::new(),
+            sender: keypair.public().to_peer_id().to_string(),
+            receiver: receiver.to_string(),
+            amount: 10_000,
+            shard_id: 0,
+            timestamp: nonce,
+            nonce,
+            fee: 0,
+            signature: String::new(),
+            sender_pubkey: String::new(),
+            outputs: Vec::new(),
+            memo: None,
+            not_before_height: None,
+            alias: None,
+        };
+        tx.id = tx.compute_id();
+        tx.sign_with_keypair(keypair).unwrap();
+        tx
+    }
+
+    async fn get(state: &Arc<AppState>, uri: &str) -> axum::response::Response {
+        router(state.clone())
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn get_json(state: &Arc<AppState>, uri: &str) -> serde_json::Value {
+        let response = get(state, uri).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    fn ids(value: &serde_json::Value) -> Vec<&str> {
+        value
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tx| tx["id"].as_str().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn block_routes_flag_final_blocks() {
+        let state = test_state(&[]);
+        let genesis = get_json(&state, "/api/v1/blocks/index/0").await;
+        assert_eq!(genesis["index"], 0);
+        assert_eq!(genesis["finalized"], false);
+
+        let mut parent = state.storage.get_block(0).unwrap().unwrap();
+        for index in 1..FINALITY_DEPTH {
+            let block = Block::new(
+                index,
+                "miner".into(),
+                vec![],
+                parent.hash.clone(),
+                100,
+                100,
+                0,
+                0,
+                0,
+            );
+            state.storage.save_block(&block).unwrap();
+            parent = block;
+        }
+        let hash = state.storage.get_block(0).unwrap().unwrap().hash;
+        let genesis = get_json(&state, &format!("/api/v1/blocks/hash/{}", hash)).await;
+        assert_eq!(genesis["finalized"], true);
+        let tip = get_json(&state, &format!("/api/v1/blocks/index/{}", parent.index)).await;
+        assert_eq!(tip["finalized"], false);
+    }
+
+    #[tokio::test]
+    async fn rich_list_route_limits_holders() {
+        let (alice, bob) = (Keypair::generate_ed25519(), Keypair::generate_ed25519());
+        let state = test_state(&[&alice, &bob]);
+        let all = get_json(&state, "/api/v1/richlist").await;
+        assert_eq!(all.as_array().unwrap().len(), 2);
+        let top = get_json(&state, "/api/v1/richlist?limit=1").await;
+        assert_eq!(top.as_array().unwrap().len(), 1);
+        assert_eq!(top[0]["balance"], 100_000_000);
+    }
+
+    #[tokio::test]
+    async fn mempool_route_filters_and_paginates() {
+        let alice = Keypair::generate_ed25519();
+        let bob = Keypair::generate_ed25519();
+        let exchange = Keypair::generate_ed25519()
+            .public()
+            .to_peer_id()
+            .to_string();
+        let state = test_state(&[&alice, &bob]);
+
+        let deposits = [transfer(&alice, &exchange, 0), transfer(&bob, &exchange, 0)];
+        let other = transfer(&alice, &bob.public().to_peer_id().to_string(), 1);
+        for tx in deposits.iter().chain([&other]) {
+            state.mempool.add_transaction(tx.clone()).unwrap();
+        }
+
+        let all = get_json(&state, "/api/v1/mempool").await;
+        assert_eq!(all.as_array().unwrap().len(), 3);
+
+        let incoming = get_json(&state, &format!("/api/v1/mempool?receiver={exchange}")).await;
+        let mut expected: Vec<&str> = deposits.iter().map(|tx| tx.id.as_str()).collect();
+        let mut got = ids(&incoming);
+        expected.sort();
+        got.sort();
+        assert_eq!(got, expected);
+
+        let from_alice = format!(
+            "/api/v1/mempool?sender={}&receiver={exchange}",
+            alice.public().to_peer_id()
+        );
+        assert_eq!(
+            ids(&get_json(&state, &from_alice).await),
+            [deposits[0].id.as_str()]
+        );
+
+        let first = get_json(&state, "/api/v1/mempool?limit=2").await;
+        let rest = get_json(&state, "/api/v1/mempool?limit=2&page=1").await;
+        assert_eq!(ids(&first).len(), 2);
+        assert_eq!(ids(&rest).len(), 1);
+        assert!(!ids(&first).contains(&ids(&rest)[0]));
+    }
+
+    #[tokio::test]
+    async fn validators_route_reports_consensus_nodes() {
+        let state = test_state(&[]);
+        {
+            let mut consensus = state.consensus.lock().unwrap();
+            consensus.register_node("validator-b".into());
+            consensus.register_node("validator-a".into());
+            consensus.nodes.get_mut("validator-b").unwrap().missed_slots = 2;
+        }
+
+        let all = get_json(&state, "/api/v1/validators").await;
+        assert_eq!(all["active_shards"], 1);
+        assert!(all["slot"].as_u64().unwrap() > 0);
+        let validators = all["validators"].as_array().unwrap();
+        assert_eq!(validators.len(), 2);
+        assert_eq!(validators[0]["peer_id"], "validator-a");
+        assert_eq!(validators[1]["missed_slots"], 2);
+        assert_eq!(validators[1]["is_active"], false);
+
+        let one = get_json(&state, "/api/v1/validators/validator-a").await;
+        assert!(one["state"].is_string());
+        assert_eq!(
+            get(&state, "/api/v1/validators/unknown").await.status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[tokio::test]
+    async fn equivocations_route_lists_slashings() {
+        let state = test_state(&[]);
+        assert_eq!(
+            get_json(&state, "/api/v1/equivocations").await,
+            serde_json::json!([])
+        );
+
+        let evidence = centichain_lib::consensus::EquivocationEvidence {
+            author: "validator-a".into(),
+            slot: 7,
+            first_hash: "aa".into(),
+            second_hash: "bb".into(),
+            detected_at: 0,
+        };
+        state.storage.save_slashing(&evidence).unwrap();
+        let slashings = get_json(&state, "/api/v1/equivocations").await;
+        assert_eq!(slashings, serde_json::json!([evidence]));
+    }
+
+    #[tokio::test]
+    async fn balance_route_answers_at_past_heights() {
+        let alice = Keypair::generate_ed25519();
+        let state = test_state(&[&alice]);
+        let address = alice.public().to_peer_id().to_string();
+        let payment = transfer(&alice, "receiver", 0);
+        let spent = payment.amount + payment.fee();
+        let block = Block::new(
+            1,
+            "miner".into(),
+            vec![payment],
+            String::new(),
+            100,
+            100,
+            0,
+            0,
+            0,
+        );
+        state.storage.save_block(&block).unwrap();
+
+        let now = get_json(&state, &format!("/api/v1/balance/{address}")).await;
+        assert_eq!(now["balance"], 100_000_000 - spent);
+        let then = get_json(&state, &format!("/api/v1/balance/{address}?height=0")).await;
+        assert_eq!(then["balance"], 100_000_000);
+        assert_eq!(then["height"], 0);
+
+        let response = get(&state, &format!("/api/v1/balance/{address}?height=9")).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn address_route_lists_history_newest_first() {
+        let alice = Keypair::generate_ed25519();
+        let state = test_state(&[&alice]);
+        let address = alice.public().to_peer_id().to_string();
+        let payment = transfer(&alice, "receiver", 0);
+        let block = Block::new(
+            1,
+            "miner".into(),
+            vec![payment.clone()],
+            String::new(),
+            100,
+            100,
+            0,
+            0,
+            0,
+        );
+        state.storage.save_block(&block).unwrap();
+
+        let uri = format!("/api/v1/address/{address}/transactions");
+        let all = get_json(&state, &uri).await;
+        assert_eq!(all[0]["transaction"]["id"], payment.id);
+        assert_eq!(all[0]["block_index"], 1);
+        assert_eq!(all[1]["block_index"], 0);
+        assert_eq!(all.as_array().unwrap().len(), 2);
+
+        let second = get_json(&state, &format!("{uri}?page=1&limit=1")).await;
+        assert_eq!(second[0]["transaction"]["id"], format!("funding-{address}"));
+        let far = get_json(
+            &state,
+            &format!("{uri}?page={}&limit={}", usize::MAX, usize::MAX),
+        )
+        .await;
+        assert_eq!(far, serde_json::json!([]));
+        let received = get_json(&state, "/api/v1/address/receiver/transactions").await;
+        assert_eq!(received[0]["transaction"]["id"], payment.id);
+    }
+
+    #[tokio::test]
+    async fn metrics_route_reports_gauges() {
+        let state = test_state(&[]);
+        for (index, gap) in [(1, 2), (2, 3), (3, 100)] {
+            let parent = state.storage.get_block(index - 1).unwrap().unwrap();
+            let mut block = Block::new(
+                index,
+                "miner".into(),
+                vec![],
+                parent.hash,
+                100,
+                100,
+                0,
+                0,
+                0,
+            );
+            block.timestamp = parent.timestamp + gap;
+            state.storage.save_block(&block).unwrap();
+        }
+        state.chain_index.store(3, Ordering::Relaxed);
+        state.peer_count.store(4, Ordering::Relaxed);
+
+        let response = get(&state, "/metrics").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        let value = |name: &str| {
+            text.lines()
+                .find_map(|line| line.strip_prefix(&format!("{name} ")))
+                .unwrap_or_else(|| panic!("{name} missing from:\n{text}"))
+                .to_string()
+        };
+
+        assert!(text.contains("# TYPE chain_height gauge"));
+        assert!(text.contains("# TYPE peer_count gauge"));
+        assert_eq!(value("chain_height"), "3");
+        assert_eq!(value("peer_count"), "4");
+        assert_eq!(value("total_blocks"), "4");
+        assert_eq!(value("mempool_size"), "0");
+        assert_eq!(value("block_interval_seconds_bucket{le=\"2\"}"), "1");
+        assert_eq!(value("block_interval_seconds_bucket{le=\"+Inf\"}"), "3");
+        assert_eq!(value("block_interval_seconds_sum"), "105");
+    }
+
+    #[tokio::test]
+    async fn broadcast_requires_configured_api_key() {
+        let alice = Keypair::generate_ed25519();
+        let mut state = test_state(&[&alice]);
+        Arc::get_mut(&mut state).unwrap().api_key = Some("secret".into());
+        let bob = Keypair::generate_ed25519()
+            .public()
+            .to_peer_id()
+            .to_string();
+        let body = serde_json::json!({ "transaction": transfer(&alice, &bob, 0) }).to_string();
+
+        let broadcast = |auth: Option<&str>| {
+            let mut request =
+                Request::post("/api/v1/broadcast").header(header::CONTENT_TYPE, "application/json");
+            if let Some(auth) = auth {
+                request = request.header(header::AUTHORIZATION, auth);
+            }
+            router(state.clone()).oneshot(request.body(Body::from(body.clone())).unwrap())
+        };
+
+        let missing = broadcast(None).await.unwrap();
+        assert_eq!(missing.status(), StatusCode::UNAUTHORIZED);
+        let wrong = broadcast(Some("Bearer guess")).await.unwrap();
+        assert_eq!(wrong.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(state.mempool.len(), 0);
+
+        let accepted = broadcast(Some("Bearer secret")).await.unwrap();
+        assert_eq!(accepted.status(), StatusCode::OK);
+        assert_eq!(state.mempool.len(), 1);
+
+        // Reads stay public
+        assert_eq!(
+            get(&state, "/api/v1/mempool").await.status(),
+            StatusCode::OK
+        );
+    }
+
+    async fn request_drip(state: &Arc<AppState>, address: &str) -> axum::response::Response {
+        let body = serde_json::json!({ "address": address }).to_string();
+        router(state.clone())
+            .oneshot(
+                Request::post("/api/v1/faucet")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn faucet_is_forbidden_unless_enabled() {
+        let state = test_state(&[]);
+        let address = Keypair::generate_ed25519()
+            .public()
+            .to_peer_id()
+            .to_string();
+        let response = request_drip(&state, &address).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn ready_once_the_node_has_peers_and_a_chain() {
+        let mut state = test_state(&[]);
+        assert_eq!(get(&state, "/health").await.status(), StatusCode::OK);
+        assert_eq!(
+            get(&state, "/ready").await.status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+
+        state.peer_count.store(1, Ordering::Relaxed);
+        assert_eq!(
+            get(&state, "/ready").await.status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        state.chain_index.store(5, Ordering::Relaxed);
+        assert_eq!(get(&state, "/ready").await.status(), StatusCode::OK);
+        assert_eq!(get_json(&state, "/ready").await["ready"], true);
+
+        state.peer_count.store(0, Ordering::Relaxed);
+        assert_eq!(
+            get(&state, "/ready").await.status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+
+        // The genesis node has nothing beyond height 0 to wait for
+        state.chain_index.store(0, Ordering::Relaxed);
+        state.peer_count.store(1, Ordering::Relaxed);
+        Arc::get_mut(&mut state).unwrap().genesis_node = true;
+        assert_eq!(get(&state, "/ready").await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn faucet_drips_once_per_cooldown() {
+        let source = Keypair::generate_ed25519();
+        let mut state = test_state(&[&source]);
+        let wallet = centichain_lib::wallet::Wallet::from_keypair_bytes(
+            source.to_protobuf_encoding().unwrap(),
+        )
+        .unwrap();
+        Arc::get_mut(&mut state).unwrap().faucet = Some(Faucet::new(wallet, 5_000));
+        let address = Keypair::generate_ed25519()
+            .public()
+            .to_peer_id()
+            .to_string();
+
+        let response = request_drip(&state, &address).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let drip: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let pending = state.mempool.get_pending_transactions();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, drip["tx_id"].as_str().unwrap());
+        assert_eq!(
+            (pending[0].receiver.as_str(), pending[0].amount),
+            (address.as_str(), 5_000)
+        );
+
+        let again = request_drip(&state, &address).await;
+        assert_eq!(again.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(state.mempool.len(), 1);
+
+        // The limit is per address
+        let other = Keypair::generate_ed25519()
+            .public()
+            .to_peer_id()
+            .to_string();
+        assert_eq!(request_drip(&state, &other).await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn tokenomics_route_follows_chain_height() {
+        let state = test_state(&[]);
+        let genesis = get_json(&state, "/api/v1/tokenomics").await;
+        assert_eq!(
+            genesis,
+            serde_json::to_value(TokenomicsInfo::at_height(0, 0)).unwrap()
+        );
+        assert_eq!(genesis["circulating_supply"], GENESIS_SUPPLY);
+        assert_eq!(genesis["next_halving_at"], HALVING_INTERVAL);
+
+        // Just past the first halving
+        let height = HALVING_INTERVAL + 5;
+        state.chain_index.store(height, Ordering::Relaxed);
+        let halved = get_json(&state, "/api/v1/tokenomics").await;
+        assert_eq!(halved["current_reward"], INITIAL_REWARD / 2);
+        assert_eq!(
+            halved["circulating_supply"],
+            GENESIS_SUPPLY + HALVING_INTERVAL * INITIAL_REWARD + 5 * (INITIAL_REWARD / 2)
+        );
+        assert_eq!(halved["next_halving_at"], 2 * HALVING_INTERVAL);
+        assert_eq!(halved["blocks_until_halving"], HALVING_INTERVAL - 5);
+    }
+
+    #[tokio::test]
+    async fn vdf_route_reports_speed_and_next_difficulty() {
+        let state = test_state(&[]);
+        state.vdf_ips.store(1_200_000, Ordering::Relaxed);
+        let genesis = get_json(&state, "/api/v1/vdf").await;
+        assert_eq!(genesis["iterations_per_second"], 1_200_000);
+        assert_eq!(genesis["difficulty"], 100);
+
+        // A block on target keeps its difficulty for the next one
+        let parent = state.storage.get_block(0).unwrap().unwrap();
+        let mut block = Block::new(
+            1,
+            "miner".into(),
+            vec![],
+            parent.hash.clone(),
+            100,
+            1_000_000,
+            0,
+            0,
+            0,
+        );
+        block.timestamp = parent.timestamp + TARGET_BLOCK_TIME;
+        state.storage.save_block(&block).unwrap();
+        let next = get_json(&state, "/api/v1/vdf").await;
+        assert_eq!(next["difficulty"], 1_000_000);
+    }
+
+    async fn rpc(state: &Arc<AppState>, body: &str) -> serde_json::Value {
+        let response = router(state.clone())
+            .oneshot(
+                Request::post("/rpc")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn json_rpc_answers_calls_and_batches() {
+        let alice = Keypair::generate_ed25519();
+        let state = test_state(&[&alice]);
+        let address = alice.public().to_peer_id().to_string();
+
+        let reply = rpc(
+            &state,
+            r#"{"jsonrpc":"2.0","method":"ag_blockNumber","id":7}"#,
+        )
+        .await;
+        assert_eq!(
+            reply,
+            serde_json::json!({ "jsonrpc": "2.0", "result": 0, "id": 7 })
+        );
+
+        // Answers keep their ids; the notification gets none
+        let batch = serde_json::json!([
+            { "jsonrpc": "2.0", "method": "ag_getBalance", "params": [address], "id": "a" },
+            { "jsonrpc": "2.0", "method": "ag_getBlockByNumber", "params": [0], "id": "b" },
+            { "jsonrpc": "2.0", "method": "ag_blockNumber" },
+        ]);
+        let replies = rpc(&state, &batch.to_string()).await;
+        let replies = replies.as_array().unwrap();
+        assert_eq!(replies.len(), 2);
+        assert_eq!(replies[0]["id"], "a");
+        assert_eq!(replies[0]["result"], 100_000_000);
+        assert_eq!(replies[1]["id"], "b");
+        assert_eq!(replies[1]["result"]["index"], 0);
+    }
+
+    #[tokio::test]
+    async fn json_rpc_reports_unknown_methods_and_bad_json() {
+        let state = test_state(&[]);
+        let unknown = rpc(
+            &state,
+            r#"{"jsonrpc":"2.0","method":"eth_mine","params":[],"id":1}"#,
+        )
+        .await;
+        assert_eq!(unknown["id"], 1);
+        assert_eq!(unknown["error"]["code"], jsonrpc::METHOD_NOT_FOUND);
+        assert!(unknown.get("result").is_none());
+
+        let malformed = rpc(&state, r#"{"jsonrpc":"2.0","method":"#).await;
+        assert_eq!(malformed["id"], serde_json::Value::Null);
+        assert_eq!(malformed["error"]["code"], jsonrpc::PARSE_ERROR);
+    }
+
+    #[test]
+    fn filtered_subscriber_only_receives_matching_transactions() {
+        let wallet = "12D3KooWWallet";
+        let tx = |sender: &str, receiver: &str| Transaction {
+            id: format!("{sender}->{receiver}"),
+            sender: sender.into(),
+            receiver: receiver.into(),
+            amount: 1,
+            shard_id: 0,
+            timestamp: 0,
+            nonce: 0,
+            fee: 0,
+            signature: String::new(),
+            sender_pubkey: String::new(),
+            outputs: Vec::new(),
+            memo: None,
+            not_before_height: None,
+            alias: None,
+        };
+        let events = [
+            Event::NewTransaction(tx("alice", wallet)),
+            Event::NewTransaction(tx("alice", "bob")),
+            Event::NewTransaction(tx(wallet, "carol")),
+            Event::NewBlock(Block::new(
+                1,
+                "miner".into(),
+                vec![tx("alice", wallet)],
+                String::new(),
+                100,
+                100,
+                0,
+                0,
+                0,
+            )),
+        ];
+        let delivered = |subscription: &Subscription| -> Vec<String> {
+            events
+                .iter()
+                .filter(|event| subscription.matches(event))
+                .map(|event| match event {
+                    Event::NewTransaction(tx) => tx.id.clone(),
+                    Event::NewBlock(block) => format!("block {}", block.index),
+                })
+                .collect()
+        };
+
+        let filtered: Subscription = serde_json::from_str(&format!(
+            r#"{{"types":["NewTransaction"],"address":"{wallet}"}}"#
+        ))
+        .unwrap();
+        assert_eq!(
+            delivered(&filtered),
+            [format!("alice->{wallet}"), format!("{wallet}->carol")]
+        );
+
+        assert_eq!(delivered(&Subscription::default()).len(), events.len());
+    }
+}
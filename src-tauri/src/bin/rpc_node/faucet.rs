@@ -0,0 +1,116 @@
+//! Testnet faucet for the `/api/v1/faucet` route.
+//!
+//! Enabled with `FAUCET_ENABLED=1`; the coins come from the wallet whose
+//! hex-encoded keypair is in `FAUCET_PRIVATE_KEY`. Each address may be
+//! dripped once per [`DRIP_COOLDOWN`], tracked in memory only.
+
+use centichain_lib::{
+    chain::{calculate_fee, Transaction},
+    consensus::mempool::Mempool,
+    utils::constants::ONE_AGT,
+    wallet::Wallet,
+};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Coins sent per drip unless `FAUCET_AMOUNT` overrides it
+pub const DEFAULT_DRIP_AMOUNT: u64 = 10 * ONE_AGT;
+
+/// How long an address waits between drips
+pub const DRIP_COOLDOWN: Duration = Duration::from_secs(60 * 60);
+
+pub struct Faucet {
+    wallet: Wallet,
+    pub amount: u64,
+    /// When each address was last dripped
+    last_drip: Mutex<HashMap<String, Instant>>,
+}
+
+impl Faucet {
+    pub fn new(wallet: Wallet, amount: u64) -> Self {
+        Faucet {
+            wallet,
+            amount,
+            last_drip: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The faucet configured by the environment, or `None` if it is disabled.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let enabled = std::env::var("FAUCET_ENABLED")
+            .is_ok_and(|value| matches!(value.as_str(), "1" | "true"));
+        if !enabled {
+            return Ok(None);
+        }
+        let key = std::env::var("FAUCET_PRIVATE_KEY")
+            .map_err(|_| "FAUCET_ENABLED requires FAUCET_PRIVATE_KEY")?;
+        let keypair = hex::decode(key.trim())
+            .map_err(|e| format!("Invalid FAUCET_PRIVATE_KEY hex: {}", e))?;
+        let amount = match std::env::var("FAUCET_AMOUNT") {
+            Ok(amount) => amount
+                .parse()
+                .map_err(|e| format!("Invalid FAUCET_AMOUNT: {}", e))?,
+            Err(_) => DEFAULT_DRIP_AMOUNT,
+        };
+        Ok(Some(Faucet::new(
+            Wallet::from_keypair_bytes(keypair)?,
+            amount,
+        )))
+    }
+
+    pub fn address(&self) -> &str {
+        &self.wallet.address
+    }
+
+    /// Claims a drip for `address` at `now`; while it is cooling down, returns
+    /// how long it still has to wait.
+    pub fn reserve(&self, address: &str, now: Instant) -> Result<(), Duration> {
+        let mut last_drip = self.last_drip.lock().unwrap();
+        if let Some(last) = last_drip.get(address) {
+            let elapsed = now.saturating_duration_since(*last);
+            if elapsed < DRIP_COOLDOWN {
+                return Err(DRIP_COOLDOWN - elapsed);
+            }
+        }
+        last_drip.insert(address.to_string(), now);
+        Ok(())
+    }
+
+    /// Gives back a reservation whose drip could not be sent.
+    pub fn release(&self, address: &str) {
+        self.last_drip.lock().unwrap().remove(address);
+    }
+
+    /// Signs a transfer of the drip amount to `address` and adds it to the mempool.
+    pub fn drip(
+        &self,
+        mempool: &Mempool,
+        address: &str,
+        shard_id: u16,
+    ) -> Result<Transaction, String> {
+        let mut tx = Transaction {
+            id: String::new(),
+            sender: self.wallet.address.clone(),
+            receiver: address.to_string(),
+            amount: self.amount,
+            shard_id,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            nonce: mempool.next_nonce(&self.wallet.address),
+            fee: calculate_fee(self.amount),
+            signature: String::new(),
+            sender_pubkey: String::new(),
+            outputs: Vec::new(),
+            memo: None,
+            not_before_height: None,
+            alias: None,
+        };
+        tx.id = tx.compute_id();
+        tx.sign_with_keypair(&self.wallet.get_keypair()?)?;
+        mempool.add_transaction(tx.clone())?;
+        Ok(tx)
+    }
+}
@@ -0,0 +1,218 @@
+//! JSON-RPC 2.0 endpoint (`POST /rpc`) for tools that expect an
+//! Ethereum-style interface rather than the REST routes.
+//!
+//! Methods take positional `params`:
+//! - `ag_blockNumber` → chain height
+//! - `ag_getBlockByNumber [index]` → block, or `null`
+//! - `ag_getBalance [address, height?]` → balance at the tip or at `height`
+//! - `ag_sendRawTransaction [transaction]` → transaction id; needs the API key
+//!   like `/api/v1/broadcast`
+//! - `ag_getTransaction [id]` → confirmed or pending transaction, or `null`
+//!
+//! Batches are answered with an array; notifications (calls without `id`)
+//! are run but get no answer.
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use centichain_lib::chain::Transaction;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::api::{has_api_key, AppState, BlockResponse};
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+/// Server error: a write method called without the API key
+pub const UNAUTHORIZED: i64 = -32001;
+/// Server error: the node refused the transaction
+pub const REJECTED: i64 = -32002;
+
+#[derive(Serialize, Debug)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        RpcError {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Response envelope for the call with `id`
+fn envelope(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": id }),
+        Err(error) => json!({ "jsonrpc": "2.0", "error": error, "id": id }),
+    }
+}
+
+pub async fn handle_rpc(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let authorized = has_api_key(&state, &headers);
+    let reply = match serde_json::from_slice::<Value>(&body) {
+        Err(e) => Some(envelope(
+            Value::Null,
+            Err(RpcError::new(PARSE_ERROR, format!("Parse error: {}", e))),
+        )),
+        Ok(Value::Array(calls)) if calls.is_empty() => Some(envelope(
+            Value::Null,
+            Err(RpcError::new(INVALID_REQUEST, "Empty batch")),
+        )),
+        Ok(Value::Array(calls)) => {
+            let mut replies = Vec::new();
+            for call in calls {
+                replies.extend(handle_call(&state, call, authorized).await);
+            }
+            (!replies.is_empty()).then_some(Value::Array(replies))
+        }
+        Ok(call) => handle_call(&state, call, authorized).await,
+    };
+    match reply {
+        Some(reply) => Json(reply).into_response(),
+        // Only notifications were sent
+        None => StatusCode::NO_CONTENT.into_response(),
+    }
+}
+
+/// Runs one call; `None` for a notification.
+async fn handle_call(state: &AppState, call: Value, authorized: bool) -> Option<Value> {
+    let Value::Object(mut call) = call else {
+        return Some(envelope(
+            Value::Null,
+            Err(RpcError::new(INVALID_REQUEST, "Request must be an object")),
+        ));
+    };
+    let id = call.remove("id");
+    if let Some(id) = &id {
+        if !(id.is_string() || id.is_number() || id.is_null()) {
+            return Some(envelope(
+                Value::Null,
+                Err(RpcError::new(INVALID_REQUEST, "Invalid id")),
+            ));
+        }
+    }
+
+    let is_v2 = call.get("jsonrpc").and_then(Value::as_str) == Some("2.0");
+    let result = match call.remove("method") {
+        Some(Value::String(method)) if is_v2 => {
+            match call.remove("params").unwrap_or(Value::Array(Vec::new())) {
+                Value::Array(params) => dispatch(state, &method, params, authorized).await,
+                _ => Err(RpcError::new(INVALID_PARAMS, "params must be an array")),
+            }
+        }
+        _ => Err(RpcError::new(
+            INVALID_REQUEST,
+            "Expected jsonrpc \"2.0\" and a method name",
+        )),
+    };
+    id.map(|id| envelope(id, result))
+}
+
+/// Positional parameter `index`, `None` when missing or `null`
+fn param<T: DeserializeOwned>(params: &[Value], index: usize) -> Result<Option<T>, RpcError> {
+    match params.get(index) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => T::deserialize(value)
+            .map(Some)
+            .map_err(|e| RpcError::new(INVALID_PARAMS, format!("Invalid param {}: {}", index, e))),
+    }
+}
+
+fn required<T: DeserializeOwned>(params: &[Value], index: usize) -> Result<T, RpcError> {
+    param(params, index)?
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("Missing param {}", index)))
+}
+
+fn storage_error(e: impl std::fmt::Display) -> RpcError {
+    RpcError::new(INTERNAL_ERROR, format!("Storage error: {}", e))
+}
+
+async fn dispatch(
+    state: &AppState,
+    method: &str,
+    params: Vec<Value>,
+    authorized: bool,
+) -> Result<Value, RpcError> {
+    match method {
+        "ag_blockNumber" => {
+            let height = state.storage.get_latest_index().map_err(storage_error)?;
+            Ok(json!(height))
+        }
+        "ag_getBlockByNumber" => {
+            let index: u64 = required(&params, 0)?;
+            let tip = state.storage.get_latest_index().unwrap_or(0);
+            let block = state.storage.get_block(index).map_err(storage_error)?;
+            Ok(json!(block.map(|block| BlockResponse::new(block, tip))))
+        }
+        "ag_getBalance" => {
+            let address: String = required(&params, 0)?;
+            let balance = match param::<u64>(&params, 1)? {
+                Some(height) => state
+                    .storage
+                    .balance_at_height(&address, height)
+                    .map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))?,
+                None => state
+                    .storage
+                    .calculate_balance(&address)
+                    .map_err(storage_error)?,
+            };
+            Ok(json!(balance))
+        }
+        "ag_sendRawTransaction" => {
+            if !authorized {
+                return Err(RpcError::new(UNAUTHORIZED, "Invalid or missing API key"));
+            }
+            let tx: Transaction = required(&params, 0)?;
+            state
+                .mempool
+                .add_transaction(tx.clone())
+                .map_err(|e| RpcError::new(REJECTED, format!("Invalid transaction: {}", e)))?;
+            state
+                .tx_sender
+                .send(tx.clone())
+                .await
+                .map_err(|_| RpcError::new(INTERNAL_ERROR, "Failed to broadcast"))?;
+            Ok(json!(tx.id))
+        }
+        "ag_getTransaction" => {
+            let id: String = required(&params, 0)?;
+            if let Some((tx, block)) = state
+                .storage
+                .get_transaction_by_id(&id)
+                .map_err(storage_error)?
+            {
+                return Ok(json!({
+                    "transaction": tx,
+                    "block_index": block.index,
+                    "block_hash": block.hash,
+                    "status": "confirmed"
+                }));
+            }
+            let pending = state.mempool.get_pending_transactions();
+            Ok(match pending.into_iter().find(|tx| tx.id == id) {
+                Some(tx) => json!({ "transaction": tx, "status": "pending" }),
+                None => Value::Null,
+            })
+        }
+        _ => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("Method not found: {}", method),
+        )),
+    }
+}
@@ -1,19 +1,21 @@
-use axum::{
-    extract::{
-        ws::{Message, WebSocket, WebSocketUpgrade},
-        Path, Query, State,
-    },
-    http::{Method, StatusCode},
-    response::{IntoResponse, Json},
-    routing::{get, post},
-    Router,
-};
+mod api;
+mod faucet;
+mod jsonrpc;
+mod metrics;
+mod webhooks;
+
+use api::{AppState, Event};
 use centichain_lib::{
-    chain::{Block, SyncRequest, SyncResponse, Transaction},
+    chain::{Block, SyncRequest, SyncResponse, Transaction, TxCancellation},
     consensus::mempool::Mempool,
-    consensus::Consensus,
-    network::message_id_fn,
-    storage::Storage,
+    consensus::{vdf::CentichainVDF, Consensus},
+    network::{decode_gossip, encode_gossip, message_id_fn, sync::serve_sync_request},
+    open_storage,
+    utils::{
+        constants::{VDF_BENCHMARK_DIFFICULTY, VDF_BENCHMARK_INTERVAL_SECS},
+        logging::apply_log_format,
+        paths::legacy_db_paths,
+    },
 };
 use libp2p::{
     futures::StreamExt,
@@ -23,7 +25,6 @@ use libp2p::{
     swarm::{NetworkBehaviour, SwarmEvent},
     tcp, yamux, Multiaddr, PeerId, SwarmBuilder,
 };
-use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 use std::sync::{
     atomic::{AtomicU64, Ordering},
@@ -32,34 +33,19 @@ use std::sync::{
 use std::time::Duration;
 use tokio::io;
 use tokio::sync::broadcast;
-use tower_http::cors::{Any, CorsLayer};
-
-// --- Shared State for API ---
-struct AppState {
-    storage: Arc<Storage>,
-    mempool: Arc<Mempool>,
-    _consensus: Arc<Mutex<Consensus>>,
-    chain_index: Arc<AtomicU64>,
-    peer_count: Arc<std::sync::atomic::AtomicUsize>,
-    tx_sender: tokio::sync::mpsc::Sender<Transaction>, // To submit tx to P2P
-    evt_sender: broadcast::Sender<Event>,              // Broadcast events to WebSockets
-}
-
-#[derive(Clone, Serialize, Debug)]
-#[serde(tag = "type", content = "data")]
-enum Event {
-    NewBlock(Block),
-    NewTransaction(Transaction),
-}
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize Logger
-    flexi_logger::Logger::try_with_str("info")?.start()?;
+    apply_log_format(flexi_logger::Logger::try_with_str("info")?).start()?;
     log::info!("Starting Centichain RPC Node (Advanced)...");
 
     // Initialize Components
-    let storage = Arc::new(Storage::new("centichain.db")?); // Same DB as main app if running in same dir
+    // Same data directory as the desktop app; an older RPC database in the
+    // working directory is copied over on first start
+    let mut legacy_dbs = legacy_db_paths();
+    legacy_dbs.push("centichain.db".into());
+    let storage = Arc::new(open_storage(&legacy_dbs)?);
     let mempool = Arc::new(Mempool::new(storage.clone()));
     let consensus = Arc::new(Mutex::new(Consensus::new()));
 
@@ -123,7 +109,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 relay_client,
                 dcutr: libp2p::dcutr::Behaviour::new(key.public().to_peer_id()),
                 identify: libp2p::identify::Behaviour::new(libp2p::identify::Config::new(
-                    "/centichain/1.0.0".to_string(),
+                    centichain_lib::network::identify_protocol_version(
+                        centichain_lib::chain::chain_id(),
+                    ),
                     key.public(),
                 )),
                 ping: libp2p::ping::Behaviour::new(libp2p::ping::Config::new()),
@@ -142,11 +130,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Subscribe topics (Standardized Shard 0 for RPC)
     let topic_blocks = gossipsub::IdentTopic::new("centichain-shard-0-blocks");
     let topic_transactions = gossipsub::IdentTopic::new("centichain-shard-0-txs");
+    let topic_tx_cancels = gossipsub::IdentTopic::new("centichain-shard-0-tx-cancels");
     swarm.behaviour_mut().gossipsub.subscribe(&topic_blocks)?;
     swarm
         .behaviour_mut()
         .gossipsub
         .subscribe(&topic_transactions)?;
+    swarm
+        .behaviour_mut()
+        .gossipsub
+        .subscribe(&topic_tx_cancels)?;
 
     // Listen
     swarm.listen_on("/ip4/0.0.0.0/tcp/9091".parse()?)?; // Use 9091 to allow running alongside Relay (9090)
@@ -203,8 +196,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             tokio::select! {
                 Some(tx) = tx_submit_receiver.recv() => {
                     log::info!("API Broadcasting TX: {}", tx.id);
-                    let json = serde_json::to_vec(&tx).unwrap();
-                    if let Err(e) = swarm.behaviour_mut().gossipsub.publish(topic_transactions.clone(), json) {
+                    let data = encode_gossip(&tx).unwrap();
+                    if let Err(e) = swarm.behaviour_mut().gossipsub.publish(topic_transactions.clone(), data) {
                         log::error!("Gossip publish error: {:?}", e);
                     } else {
                          // Also notify WS clients that WE sent a tx
@@ -218,7 +211,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     )) => {
                         let topic = message.topic.clone();
                         if topic == topic_blocks.hash() {
-                            if let Ok(block) = serde_json::from_slice::<Block>(&message.data) {
+                            if let Ok(block) = decode_gossip::<Block>(&message.data) {
                                 if block.is_vdf_valid() {
                                     if p2p_storage.get_block(block.index).unwrap_or(None).is_none() {
                                         let _ = p2p_storage.save_block(&block);
@@ -226,7 +219,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
                                         // Clean mempool
                                         let tx_ids: Vec<String> = block.transactions.iter().map(|t| t.id.clone()).collect();
-                                        p2p_mempool.remove_transactions(&tx_ids);
+                                        p2p_mempool.mark_mined(&tx_ids);
 
                                         // Notify WS
                                         let _ = p2p_evt_sender.send(Event::NewBlock(block));
@@ -234,12 +227,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                 }
                             }
                         } else if topic == topic_transactions.hash() {
-                            if let Ok(tx) = serde_json::from_slice::<Transaction>(&message.data) {
-                                if let Ok(_) = p2p_mempool.add_transaction(tx.clone()) {
+                            if let Ok(tx) = decode_gossip::<Transaction>(&message.data) {
+                                if let Ok(true) = p2p_mempool.add_transaction(tx.clone()) {
                                     // Notify WS
                                     let _ = p2p_evt_sender.send(Event::NewTransaction(tx));
                                 }
                             }
+                        } else if topic == topic_tx_cancels.hash() {
+                            if let Ok(cancel) = decode_gossip::<TxCancellation>(&message.data) {
+                                if let Err(e) = p2p_mempool.cancel_transaction(&cancel) {
+                                    log::debug!("Ignored cancellation of {}: {}", cancel.tx_id, e);
+                                }
+                            }
                         }
                     }
                     SwarmEvent::Behaviour(HeaderlessBehaviourEvent::Identify(libp2p::identify::Event::Received {
@@ -289,42 +288,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     )) => {
                         match message {
                             libp2p::request_response::Message::Request { request, channel, .. } => {
-                                match request {
-                                    SyncRequest::GetHeight => {
-                                        let h = p2p_storage.get_latest_index().unwrap_or(0);
-                                        let _ = swarm.behaviour_mut().sync.send_response(channel, SyncResponse::Height(h));
-                                    },
-                                    SyncRequest::GetBlock(idx) => {
-                                        let b = p2p_storage.get_block(idx).unwrap_or(None);
-                                        let _ = swarm.behaviour_mut().sync.send_response(channel, SyncResponse::Block(b));
-                                    },
-                                    SyncRequest::GetBlocksRange(start, end) => {
-                                        let mut blocks = Vec::new();
-                                        for i in start..=end {
-                                            if let Ok(Some(b)) = p2p_storage.get_block(i) {
-                                                blocks.push(b);
-                                            } else {
-                                                break;
-                                            }
-                                        }
-                                        let _ = swarm.behaviour_mut().sync.send_response(channel, SyncResponse::BlocksBatch(blocks));
-                                    },
-                                    SyncRequest::GetMempool => {
-                                        let txs = p2p_mempool.get_pending_transactions();
-                                        let _ = swarm.behaviour_mut().sync.send_response(channel, SyncResponse::Mempool(txs));
-                                    },
-                                    SyncRequest::GetHeaders(start, end) => {
-                                        let mut headers = Vec::new();
-                                        for i in start..=end {
-                                            if let Ok(Some(b)) = p2p_storage.get_block(i) {
-                                                headers.push(centichain_lib::chain::Header::from_block(&b));
-                                            } else {
-                                                break;
-                                            }
-                                        }
-                                        let _ = swarm.behaviour_mut().sync.send_response(channel, SyncResponse::HeadersBatch(headers));
-                                    }
-                                }
+                                let response = serve_sync_request(&p2p_storage, &p2p_mempool, request);
+                                let _ = swarm.behaviour_mut().sync.send_response(channel, response);
                             },
                              libp2p::request_response::Message::Response { response, .. } => {
                                 match response {
@@ -339,17 +304,25 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                     },
                                     SyncResponse::Block(None) => {},
                                     SyncResponse::BlocksBatch(blocks) => {
-                                        for block in blocks {
-                                            if block.is_vdf_valid() {
-                                                if let Ok(_) = p2p_storage.save_block(&block) {
-                                                     p2p_evt_sender.send(Event::NewBlock(block.clone())).ok();
-                                                     p2p_chain_index.store(block.index, Ordering::Relaxed);
+                                        let valid: Vec<_> = blocks
+                                            .into_iter()
+                                            .take_while(|block| block.is_vdf_valid())
+                                            .collect();
+                                        match p2p_storage.save_blocks_batch(&valid) {
+                                            Ok(()) => {
+                                                for block in valid {
+                                                    p2p_chain_index.store(block.index, Ordering::Relaxed);
+                                                    p2p_evt_sender.send(Event::NewBlock(block)).ok();
                                                 }
                                             }
+                                            Err(e) => log::warn!("Sync batch rejected: {}", e),
                                         }
                                     },
                                     SyncResponse::Mempool(_m) => {},
                                     SyncResponse::HeadersBatch(_) => {},
+                                    SyncResponse::Balance(_) => {},
+                                    // Not advertised, so never sent to us
+                                    SyncResponse::CompressedBlocksBatch(_) => {},
                                 }
                             },
                         }
@@ -363,34 +336,53 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     });
 
+    // --- Webhooks ---
+    let webhooks = Arc::new(webhooks::Webhooks::new());
+    tokio::spawn(webhooks::dispatch_events(
+        webhooks.clone(),
+        evt_sender.subscribe(),
+    ));
+
+    // --- VDF Benchmark ---
+    let vdf_ips = Arc::new(AtomicU64::new(0));
+    let benchmark_ips = vdf_ips.clone();
+    tokio::spawn(async move {
+        loop {
+            let measured = tokio::task::spawn_blocking(|| {
+                CentichainVDF::new(VDF_BENCHMARK_DIFFICULTY).measure_ips()
+            })
+            .await;
+            if let Ok(ips) = measured {
+                benchmark_ips.store(ips, Ordering::Relaxed);
+            }
+            tokio::time::sleep(Duration::from_secs(VDF_BENCHMARK_INTERVAL_SECS)).await;
+        }
+    });
+
     // --- API Server ---
     let app_state = Arc::new(AppState {
         storage,
         mempool,
-        _consensus: consensus,
+        consensus,
         chain_index,
         peer_count,
         tx_sender: tx_submit_sender,
         evt_sender,
+        api_key: std::env::var("RPC_API_KEY")
+            .ok()
+            .filter(|key| !key.is_empty()),
+        faucet: faucet::Faucet::from_env()?,
+        genesis_node: std::env::var("RPC_GENESIS_NODE")
+            .is_ok_and(|value| matches!(value.as_str(), "1" | "true")),
+        webhooks,
+        vdf_ips,
     });
 
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(vec![Method::GET, Method::POST])
-        .allow_headers(Any);
+    if let Some(faucet) = &app_state.faucet {
+        log::info!("Faucet enabled, dripping from {}", faucet.address());
+    }
 
-    let app = Router::new()
-        .route("/api/v1/status", get(get_status))
-        .route("/api/v1/blocks", get(get_blocks_paginated)) // New
-        .route("/api/v1/blocks/index/:index", get(get_block_by_index))
-        .route("/api/v1/blocks/hash/:hash", get(get_block_by_hash))
-        .route("/api/v1/transactions/:id", get(get_transaction))
-        .route("/api/v1/balance/:address", get(get_balance))
-        .route("/api/v1/broadcast", post(broadcast_tx))
-        .route("/api/v1/network/stats", get(get_network_stats)) // New
-        .route("/ws", get(websocket_handler)) // New
-        .layer(cors)
-        .with_state(app_state);
+    let app = api::router(app_state);
 
     let addr = SocketAddr::from(([0, 0, 0, 0], 3000));
     log::info!("RPC API listening on http://{}", addr);
@@ -401,209 +393,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-// --- API Handlers ---
-
-#[derive(Serialize)]
-struct StatusResponse {
-    node_type: String,
-    chain_height: u64,
-    peer_count: usize,
-    network: String,
-}
-
-// WS Handler
-async fn websocket_handler(
-    ws: WebSocketUpgrade,
-    State(state): State<Arc<AppState>>,
-) -> impl IntoResponse {
-    ws.on_upgrade(|socket| websocket_connection(socket, state))
-}
-
-async fn websocket_connection(mut socket: WebSocket, state: Arc<AppState>) {
-    let mut rx = state.evt_sender.subscribe();
-    if let Err(e) = socket
-        .send(Message::Text(
-            "Connected to Centichain Real-time Feed".to_string(),
-        ))
-        .await
-    {
-        log::error!("WS send error: {}", e);
-        return;
-    }
-
-    loop {
-        match rx.recv().await {
-            Ok(event) => {
-                if let Ok(json) = serde_json::to_string(&event) {
-                    if socket.send(Message::Text(json)).await.is_err() {
-                        break;
-                    }
-                }
-            }
-            Err(broadcast::error::RecvError::Lagged(_)) => {}
-            Err(broadcast::error::RecvError::Closed) => break,
-        }
-    }
-}
-
-async fn get_status(State(state): State<Arc<AppState>>) -> Json<StatusResponse> {
-    let height = state.chain_index.load(Ordering::Relaxed);
-    let peers = state.peer_count.load(Ordering::Relaxed);
-
-    Json(StatusResponse {
-        node_type: "RPC".to_string(),
-        chain_height: height,
-        peer_count: peers,
-        network: "Centichain Mainnet".to_string(),
-    })
-}
-
-#[derive(Deserialize)]
-struct Pagination {
-    page: Option<usize>,
-    limit: Option<usize>,
-}
-
-async fn get_blocks_paginated(
-    State(state): State<Arc<AppState>>,
-    Query(params): Query<Pagination>,
-) -> impl IntoResponse {
-    let page = params.page.unwrap_or(0);
-    let limit = params.limit.unwrap_or(20);
-
-    match state.storage.get_blocks_paginated(page, limit) {
-        Ok(blocks) => Json(blocks).into_response(),
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Storage error").into_response(),
-    }
-}
-
-#[derive(Serialize)]
-struct NetworkStats {
-    supply: u64,
-    max_supply: u64,
-    circulating: u64,
-    halving_block: u64,
-    current_reward: u64,
-    mining_difficulty: u64,
-}
-
-async fn get_network_stats(State(state): State<Arc<AppState>>) -> Json<NetworkStats> {
-    let height = state.chain_index.load(Ordering::Relaxed);
-    let supply = centichain_lib::chain::calculate_circulating_supply(height);
-    let reward = centichain_lib::chain::calculate_mining_reward(height + 1);
-
-    // Calculate simple halving info
-    let current_interval = height / centichain_lib::utils::constants::HALVING_INTERVAL;
-    let next_halving = (current_interval + 1) * centichain_lib::utils::constants::HALVING_INTERVAL;
-
-    Json(NetworkStats {
-        supply,
-        max_supply: centichain_lib::utils::constants::TOTAL_SUPPLY,
-        circulating: supply, // Simplifying for now
-        halving_block: next_halving,
-        current_reward: reward,
-        mining_difficulty: 200_000, // Hardcoded for VDF PoC
-    })
-}
-
-async fn get_block_by_index(
-    State(state): State<Arc<AppState>>,
-    Path(index): Path<u64>,
-) -> impl IntoResponse {
-    match state.storage.get_block(index) {
-        Ok(Some(block)) => Json(block).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Block not found").into_response(),
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Storage error").into_response(),
-    }
-}
-
-async fn get_block_by_hash(
-    State(state): State<Arc<AppState>>,
-    Path(hash): Path<String>,
-) -> impl IntoResponse {
-    match state.storage.get_block_by_hash(&hash) {
-        Ok(Some(block)) => Json(block).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Block not found").into_response(),
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Storage error").into_response(),
-    }
-}
-
-async fn get_transaction(
-    State(state): State<Arc<AppState>>,
-    Path(id): Path<String>,
-) -> impl IntoResponse {
-    match state.storage.get_transaction_by_id(&id) {
-        Ok(Some((tx, block))) => Json(serde_json::json!({
-            "transaction": tx,
-            "block_index": block.index,
-            "block_hash": block.hash,
-            "status": "confirmed"
-        }))
-        .into_response(),
-        Ok(None) => {
-            // Check mempool
-            let pending = state.mempool.get_pending_transactions();
-            if let Some(tx) = pending.iter().find(|t| t.id == id) {
-                Json(serde_json::json!({
-                    "transaction": tx,
-                    "status": "pending"
-                }))
-                .into_response()
-            } else {
-                (StatusCode::NOT_FOUND, "Transaction not found").into_response()
-            }
-        }
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Storage error").into_response(),
-    }
-}
-
-async fn get_balance(
-    State(state): State<Arc<AppState>>,
-    Path(address): Path<String>,
-) -> impl IntoResponse {
-    match state.storage.calculate_balance(&address) {
-        Ok(balance) => Json(serde_json::json!({
-            "address": address,
-            "balance": balance,
-            "currency": "AGT"
-        }))
-        .into_response(),
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Storage error").into_response(),
-    }
-}
-
-#[derive(Deserialize)]
-struct BroadcastRequest {
-    transaction: Transaction,
-}
-
-async fn broadcast_tx(
-    State(state): State<Arc<AppState>>,
-    Json(payload): Json<BroadcastRequest>,
-) -> impl IntoResponse {
-    // Basic validation
-    // Verify signature logic would ideally be here or in mempool
-
-    if let Err(e) = state.mempool.add_transaction(payload.transaction.clone()) {
-        return (
-            StatusCode::BAD_REQUEST,
-            format!("Invalid transaction: {}", e),
-        )
-            .into_response();
-    }
-
-    // Send to P2P loop to broadcast
-    if let Err(_) = state.tx_sender.send(payload.transaction.clone()).await {
-        return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to broadcast").into_response();
-    }
-
-    Json(serde_json::json!({
-        "status": "accepted",
-        "tx_id": payload.transaction.id
-    }))
-    .into_response()
-}
-
 // --- Network Behaviour ---
 #[derive(NetworkBehaviour)]
 pub struct HeaderlessBehaviour {
@@ -0,0 +1,38 @@
+//! Prometheus text-format rendering for the `/metrics` route.
+
+use centichain_lib::chain::Block;
+use std::fmt::Write;
+
+/// Number of most recent blocks the interval histogram is computed over
+pub const INTERVAL_SAMPLE_BLOCKS: usize = 1000;
+
+/// Upper bounds (seconds) of the block interval histogram buckets
+const INTERVAL_BUCKETS: [u64; 7] = [1, 2, 4, 8, 16, 32, 64];
+
+pub fn gauge(out: &mut String, name: &str, help: &str, value: u64) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} gauge");
+    let _ = writeln!(out, "{name} {value}");
+}
+
+/// Writes a histogram of `samples` (seconds) with cumulative buckets.
+pub fn interval_histogram(out: &mut String, name: &str, help: &str, samples: &[u64]) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} histogram");
+    for bound in INTERVAL_BUCKETS {
+        let count = samples.iter().filter(|s| **s <= bound).count();
+        let _ = writeln!(out, "{name}_bucket{{le=\"{bound}\"}} {count}");
+    }
+    let _ = writeln!(out, "{name}_bucket{{le=\"+Inf\"}} {}", samples.len());
+    let _ = writeln!(out, "{name}_sum {}", samples.iter().sum::<u64>());
+    let _ = writeln!(out, "{name}_count {}", samples.len());
+}
+
+/// Seconds between consecutive blocks, from blocks ordered newest first.
+pub fn block_intervals(recent: &[Block]) -> Vec<u64> {
+    recent
+        .windows(2)
+        .filter(|pair| pair[0].index == pair[1].index + 1)
+        .map(|pair| pair[0].timestamp.saturating_sub(pair[1].timestamp))
+        .collect()
+}
@@ -0,0 +1,255 @@
+//! Webhooks registered through `/api/v1/webhooks`, for backends that want
+//! events pushed to them instead of holding a WebSocket open.
+//!
+//! Every event the P2P loop publishes is POSTed, as the same JSON the
+//! WebSocket feed sends, to each webhook whose address filter it matches.
+//! The body is signed with the secret handed out at registration:
+//! `X-Centichain-Signature: sha256=<hex HMAC-SHA256 of the body>`.
+//! Registrations are kept in memory only.
+
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+use crate::api::Event;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Header carrying the body signature
+pub const SIGNATURE_HEADER: &str = "X-Centichain-Signature";
+
+/// Most webhooks the node keeps
+pub const MAX_WEBHOOKS: usize = 100;
+
+/// How long one delivery may take
+pub const DELIVERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Deliveries tried per event before it is dropped
+pub const MAX_DELIVERY_ATTEMPTS: u32 = 3;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Webhook {
+    pub id: String,
+    pub url: String,
+    /// Only blocks and transactions touching this address
+    pub address: Option<String>,
+    /// Key the receiver verifies signatures with
+    pub secret: String,
+}
+
+impl Webhook {
+    fn matches(&self, event: &Event) -> bool {
+        self.address
+            .as_deref()
+            .is_none_or(|address| event.touches(address))
+    }
+}
+
+pub struct Webhooks {
+    hooks: Mutex<Vec<Webhook>>,
+    client: reqwest::Client,
+}
+
+impl Default for Webhooks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Webhooks {
+    pub fn new() -> Self {
+        Webhooks {
+            hooks: Mutex::new(Vec::new()),
+            client: reqwest::Client::builder()
+                .timeout(DELIVERY_TIMEOUT)
+                .build()
+                .expect("HTTP client with only a timeout builds"),
+        }
+    }
+
+    /// Registers `url` and returns the webhook with its signing secret.
+    pub fn register(&self, url: String, address: Option<String>) -> Result<Webhook, String> {
+        let parsed = reqwest::Url::parse(&url).map_err(|e| format!("Invalid URL: {}", e))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err("Webhook URL must be http or https".to_string());
+        }
+        let mut hooks = self.hooks.lock().unwrap();
+        if hooks.len() >= MAX_WEBHOOKS {
+            return Err(format!(
+                "At most {} webhooks can be registered",
+                MAX_WEBHOOKS
+            ));
+        }
+        let webhook = Webhook {
+            id: uuid::Uuid::new_v4().to_string(),
+            url,
+            address: address.filter(|address| !address.is_empty()),
+            secret: hex::encode(rand::random::<[u8; 32]>()),
+        };
+        hooks.push(webhook.clone());
+        Ok(webhook)
+    }
+
+    /// Sends `event` to every matching webhook in the background.
+    pub fn notify(&self, event: &Event) {
+        let hooks: Vec<Webhook> = self
+            .hooks
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|hook| hook.matches(event))
+            .cloned()
+            .collect();
+        if hooks.is_empty() {
+            return;
+        }
+        let body = match serde_json::to_vec(event) {
+            Ok(body) => body,
+            Err(e) => {
+                log::error!("Cannot serialize webhook event: {}", e);
+                return;
+            }
+        };
+        for hook in hooks {
+            tokio::spawn(deliver(self.client.clone(), hook, body.clone()));
+        }
+    }
+}
+
+/// Hex HMAC-SHA256 of `body` under `secret`
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// POSTs `body` to `hook`, retrying failures with a growing pause.
+async fn deliver(client: reqwest::Client, hook: Webhook, body: Vec<u8>) {
+    let signature = format!("sha256={}", sign(&hook.secret, &body));
+    for attempt in 1..=MAX_DELIVERY_ATTEMPTS {
+        let result = client
+            .post(&hook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, &signature)
+            .body(body.clone())
+            .send()
+            .await;
+        match result {
+            Ok(response) if response.status().is_success() => return,
+            Ok(response) => log::debug!(
+                "Webhook {} answered {} (attempt {})",
+                hook.id,
+                response.status(),
+                attempt
+            ),
+            Err(e) => log::debug!("Webhook {} failed: {} (attempt {})", hook.id, e, attempt),
+        }
+        if attempt < MAX_DELIVERY_ATTEMPTS {
+            tokio::time::sleep(Duration::from_secs(attempt as u64)).await;
+        }
+    }
+    log::warn!(
+        "Dropping event for webhook {} after {} attempts",
+        hook.id,
+        MAX_DELIVERY_ATTEMPTS
+    );
+}
+
+/// Forwards the events the P2P loop publishes to the webhooks until the
+/// channel closes.
+pub async fn dispatch_events(webhooks: Arc<Webhooks>, mut events: broadcast::Receiver<Event>) {
+    loop {
+        match events.recv().await {
+            Ok(event) => webhooks.notify(&event),
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                log::warn!("Webhooks skipped {} events", skipped);
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Bytes, extract::State, http::HeaderMap, routing::post, Router};
+    use centichain_lib::chain::Transaction;
+    use tokio::sync::mpsc;
+
+    fn transfer(sender: &str, receiver: &str) -> Transaction {
+        Transaction {
+            id: format!("{sender}->{receiver}"),
+            sender: sender.into(),
+            receiver: receiver.into(),
+            amount: 10,
+            shard_id: 0,
+            timestamp: 0,
+            nonce: 0,
+            fee: 0,
+            signature: String::new(),
+            sender_pubkey: String::new(),
+            outputs: Vec::new(),
+            memo: None,
+            not_before_height: None,
+            alias: None,
+        }
+    }
+
+    /// Local server handing every request it receives to the returned channel
+    async fn mock_receiver() -> (String, mpsc::Receiver<(HeaderMap, Bytes)>) {
+        let (sender, receiver) = mpsc::channel(8);
+        let app = Router::new()
+            .route(
+                "/hook",
+                post(
+                    |State(sender): State<mpsc::Sender<(HeaderMap, Bytes)>>,
+                     headers: HeaderMap,
+                     body: Bytes| async move {
+                        sender.send((headers, body)).await.unwrap();
+                    },
+                ),
+            )
+            .with_state(sender);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, receiver)
+    }
+
+    #[tokio::test]
+    async fn webhook_fires_for_matching_transactions() {
+        let (url, mut received) = mock_receiver().await;
+        let webhooks = Arc::new(Webhooks::new());
+        let hook = webhooks
+            .register(url, Some("merchant".to_string()))
+            .unwrap();
+        assert!(webhooks.register("ftp://example.com".into(), None).is_err());
+
+        let (events, receiver) = broadcast::channel(8);
+        tokio::spawn(dispatch_events(webhooks, receiver));
+        events
+            .send(Event::NewTransaction(transfer("alice", "bob")))
+            .unwrap();
+        events
+            .send(Event::NewTransaction(transfer("alice", "merchant")))
+            .unwrap();
+
+        let (headers, body) = tokio::time::timeout(Duration::from_secs(5), received.recv())
+            .await
+            .expect("webhook was called")
+            .unwrap();
+        let event: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(event["type"], "NewTransaction");
+        assert_eq!(event["data"]["id"], "alice->merchant");
+        assert_eq!(
+            headers[SIGNATURE_HEADER],
+            format!("sha256={}", sign(&hook.secret, &body))
+        );
+        // The unrelated transfer was never sent
+        assert!(received.try_recv().is_err());
+    }
+}
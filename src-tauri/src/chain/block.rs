@@ -2,12 +2,28 @@
 //!
 //! Core blockchain block implementation.
 
-use crate::chain::{calculate_merkle_root, Transaction};
+use crate::chain::{calculate_merkle_root, fee_policy, FeePolicy, Receipt, Transaction};
 use crate::consensus::vdf::CentichainVDF;
 use crate::utils::constants::*;
+use libp2p::identity::Keypair;
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::time::SystemTime;
 
+/// Chain id committed to by every block hash, set from `AppSettings` at startup
+static CHAIN_ID: AtomicU32 = AtomicU32::new(DEFAULT_CHAIN_ID);
+
+/// Network this node hashes and validates blocks for.
+pub fn chain_id() -> u32 {
+    CHAIN_ID.load(Ordering::Relaxed)
+}
+
+/// Switches the network blocks are hashed for. Blocks stored under another
+/// chain id no longer validate afterwards.
+pub fn set_chain_id(chain_id: u32) {
+    CHAIN_ID.store(chain_id, Ordering::Relaxed);
+}
+
 /// A blockchain block
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Block {
@@ -37,6 +53,11 @@ pub struct Block {
     pub total_fees: u64,
     pub block_reward: u64,
     pub total_reward: u64,
+
+    /// Receipts backing the block's cross-shard mints; witness data, not
+    /// part of the hash
+    #[serde(default)]
+    pub receipts: Vec<Receipt>,
 }
 
 impl Block {
@@ -78,6 +99,7 @@ impl Block {
             block_reward,
             total_reward: total_fees + block_reward,
             shard_id,
+            receipts: Vec::new(),
         };
         block.size = block.calculate_size();
         block.hash = block.calculate_hash();
@@ -86,22 +108,7 @@ impl Block {
 
     /// Calculate block hash
     pub fn calculate_hash(&self) -> String {
-        use sha2::{Digest, Sha256};
-        let mut hasher = Sha256::new();
-        hasher.update(self.index.to_be_bytes());
-        hasher.update(self.timestamp.to_be_bytes());
-        hasher.update(self.author.as_bytes());
-        hasher.update(self.previous_hash.as_bytes());
-        hasher.update(self.vdf_proof.as_bytes());
-        hasher.update(self.merkle_root.as_bytes());
-        hasher.update(self.state_root.as_bytes());
-        hasher.update(self.nonce.to_be_bytes());
-        hasher.update(self.vdf_difficulty.to_be_bytes());
-        hasher.update(self.version.to_be_bytes());
-        hasher.update(self.total_fees.to_be_bytes());
-        hasher.update(self.block_reward.to_be_bytes());
-        hasher.update(self.total_reward.to_be_bytes());
-        hex::encode(hasher.finalize())
+        Header::from_block(self).calculate_hash()
     }
 
     /// Calculate serialized block size
@@ -114,15 +121,297 @@ impl Block {
 
     /// Verify VDF proof
     pub fn is_vdf_valid(&self) -> bool {
-        let vdf = CentichainVDF::new(self.vdf_difficulty);
-        let mut clone = self.clone();
-        clone.vdf_proof = String::new();
-        let challenge = clone.calculate_hash();
-        vdf.verify(challenge.as_bytes(), &self.vdf_proof)
+        Header::from_block(self).is_vdf_valid()
+    }
+
+    /// Signs the final hash with the author's key, so the block can be
+    /// attributed to its author, e.g. as equivocation evidence.
+    pub fn sign_with_keypair(&mut self, keypair: &Keypair) -> Result<(), String> {
+        let sig = keypair
+            .sign(self.hash.as_bytes())
+            .map_err(|e| format!("Signing failed: {e}"))?;
+        self.signature = hex::encode(sig);
+        Ok(())
+    }
+
+    /// The hash matches the contents and is signed by `author`.
+    pub fn verify_signature(&self) -> Result<(), String> {
+        self.validate_integrity()?;
+        if self.signature.is_empty() {
+            return Err("Block is not signed".into());
+        }
+        if !crate::wallet::verify_message(&self.author, self.hash.as_bytes(), &self.signature)? {
+            return Err("Invalid block signature".into());
+        }
+        Ok(())
+    }
+
+    /// Full structural check against the parent block (`None` for genesis):
+    /// [`Block::validate_without_vdf`] plus the VDF proof.
+    pub fn validate(&self, prev: Option<&Block>) -> Result<(), String> {
+        self.validate_without_vdf(prev)?;
+        if !self.is_vdf_valid() {
+            return Err("Invalid VDF proof".into());
+        }
+        Ok(())
+    }
+
+    /// Every structural check but the VDF proof: linkage, timestamp,
+    /// difficulty, hash, merkle root, coinbase and time locks.
+    pub fn validate_without_vdf(&self, prev: Option<&Block>) -> Result<(), String> {
+        self.validate_linkage(prev)?;
+        self.validate_timestamp(prev, crate::utils::unix_now())?;
+        self.validate_difficulty(prev)?;
+        self.validate_integrity()?;
+        self.validate_coinbase()?;
+        self.validate_time_locks()
+    }
+
+    /// Index continuity and `previous_hash` linkage.
+    pub fn validate_linkage(&self, prev: Option<&Block>) -> Result<(), String> {
+        match prev {
+            None if self.index > 0 => Err("Cannot append block > 0 on empty chain".into()),
+            None => {
+                if self.previous_hash
+                    != "0000000000000000000000000000000000000000000000000000000000000000"
+                {
+                    return Err("Genesis previous_hash must be zero".into());
+                }
+                Ok(())
+            }
+            Some(prev) if self.index <= prev.index => Err(format!(
+                "Stale block index {} (tip {})",
+                self.index, prev.index
+            )),
+            Some(prev) if self.index != prev.index + 1 => Err(format!(
+                "Non-contiguous block index {} (expected {})",
+                self.index,
+                prev.index + 1
+            )),
+            Some(prev) => {
+                if self.previous_hash != prev.hash {
+                    return Err("previous_hash does not match chain tip".into());
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Timestamp is no earlier than the parent's and at most
+    /// [`MAX_BLOCK_TIME_DRIFT_SECS`] ahead of `now`.
+    pub fn validate_timestamp(&self, prev: Option<&Block>, now: u64) -> Result<(), String> {
+        if self.timestamp > now.saturating_add(MAX_BLOCK_TIME_DRIFT_SECS) {
+            return Err(format!(
+                "Block timestamp {} is more than {}s in the future",
+                self.timestamp, MAX_BLOCK_TIME_DRIFT_SECS
+            ));
+        }
+        if let Some(prev) = prev {
+            if self.timestamp < prev.timestamp {
+                return Err(format!(
+                    "Block timestamp {} is before its parent's ({})",
+                    self.timestamp, prev.timestamp
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// `vdf_difficulty` is within one retarget step of the parent's.
+    pub fn validate_difficulty(&self, prev: Option<&Block>) -> Result<(), String> {
+        check_difficulty(self.vdf_difficulty, prev.map(|p| p.vdf_difficulty))
+    }
+
+    /// Recomputed hash and merkle root must match the header.
+    pub fn validate_integrity(&self) -> Result<(), String> {
+        if self.hash != self.calculate_hash() {
+            return Err("Block hash mismatch".into());
+        }
+        if self.merkle_root != calculate_merkle_root(&self.transactions) {
+            return Err("Merkle root mismatch".into());
+        }
+        Ok(())
+    }
+
+    /// No transaction is included before its `not_before_height`.
+    pub fn validate_time_locks(&self) -> Result<(), String> {
+        match self
+            .transactions
+            .iter()
+            .find(|tx| !tx.is_mature(self.index))
+        {
+            Some(tx) => Err(format!(
+                "Transaction {} is locked until height {}",
+                tx.id,
+                tx.not_before_height.unwrap_or_default()
+            )),
+            None => Ok(()),
+        }
     }
+
+    /// Exactly one SYSTEM coinbase (genesis: its allocations), paying the
+    /// author (or the reward address it chose) the scheduled reward plus its
+    /// share of the fees of the block's user transactions under the network's
+    /// [`FeePolicy`], with matching header totals.
+    pub fn validate_coinbase(&self) -> Result<(), String> {
+        self.validate_coinbase_with(&fee_policy())
+    }
+
+    /// [`validate_coinbase`](Block::validate_coinbase) under `policy`.
+    pub fn validate_coinbase_with(&self, policy: &FeePolicy) -> Result<(), String> {
+        if self.index == 0 {
+            return self.validate_genesis_allocations();
+        }
+        let coinbases: Vec<&Transaction> = self
+            .transactions
+            .iter()
+            .filter(|tx| tx.is_system())
+            .collect();
+        let [coinbase] = coinbases[..] else {
+            return Err(format!(
+                "Block must contain exactly one SYSTEM reward tx, found {}",
+                coinbases.len()
+            ));
+        };
+        if coinbase.receiver != self.author && coinbase.receiver.parse::<libp2p::PeerId>().is_err()
+        {
+            return Err("SYSTEM reward must pay the block author or a valid address".into());
+        }
+
+        let reward = calculate_mining_reward(self.index);
+        let fees = self
+            .transactions
+            .iter()
+            .filter(|tx| !tx.is_system() && !tx.is_receipt_mint())
+            .fold(0u64, |sum, tx| sum.saturating_add(tx.fee()));
+        let split = policy.split(fees);
+        let expected = reward.saturating_add(fees).saturating_sub(split.burned);
+        if coinbase.amount != expected {
+            return Err(format!(
+                "Coinbase pays {}, expected {} (reward {} + fees {} - burned {})",
+                coinbase.amount, expected, reward, fees, split.burned
+            ));
+        }
+        if coinbase.outputs != policy.coinbase_outputs(&coinbase.receiver, reward, fees) {
+            return Err("Coinbase does not pay the treasury its share of fees".into());
+        }
+
+        if self.block_reward != reward {
+            return Err(format!(
+                "block_reward mismatch: header {}, expected {}",
+                self.block_reward, reward
+            ));
+        }
+        if self.total_fees != fees {
+            return Err(format!(
+                "total_fees mismatch: header {}, computed {}",
+                self.total_fees, fees
+            ));
+        }
+        if self.total_reward != self.total_fees.saturating_add(self.block_reward) {
+            return Err("total_reward must equal total_fees + block_reward".into());
+        }
+        Ok(())
+    }
+
+    /// Genesis mints the network's allocations instead of a reward: nothing
+    /// but SYSTEM transactions, adding up to the header's reward.
+    fn validate_genesis_allocations(&self) -> Result<(), String> {
+        if self.transactions.iter().any(|tx| !tx.is_system()) {
+            return Err("Genesis block may only contain allocations".into());
+        }
+        let minted = self
+            .transactions
+            .iter()
+            .try_fold(0u64, |sum, tx| sum.checked_add(tx.amount))
+            .ok_or("Genesis allocations overflow")?;
+        if minted > TOTAL_SUPPLY {
+            return Err("Genesis allocations exceed total supply".into());
+        }
+        if minted != self.block_reward || self.total_reward != self.block_reward {
+            return Err(format!(
+                "Genesis mints {}, header reward {} / total {}",
+                minted, self.block_reward, self.total_reward
+            ));
+        }
+        Ok(())
+    }
+
+    /// Fees of this block that its coinbase paid to no one.
+    pub fn burned_fees(&self) -> u64 {
+        let paid: u64 = self
+            .transactions
+            .iter()
+            .filter(|tx| tx.is_system())
+            .map(|tx| tx.amount)
+            .sum();
+        self.total_reward.saturating_sub(paid)
+    }
+
+    /// Transactions of this block other than its coinbase or genesis mints.
+    pub fn user_transaction_count(&self) -> u64 {
+        self.transactions
+            .iter()
+            .filter(|tx| !tx.is_system())
+            .count() as u64
+    }
+}
+
+/// `difficulty` is allowed after a parent with `parent_difficulty` (`None` for genesis).
+fn check_difficulty(difficulty: u64, parent_difficulty: Option<u64>) -> Result<(), String> {
+    let (min, max) = match parent_difficulty {
+        Some(parent) => difficulty_bounds(parent),
+        None => (MIN_VDF_DIFFICULTY, u64::MAX),
+    };
+    if difficulty < min || difficulty > max {
+        return Err(format!(
+            "VDF difficulty {} outside allowed range {}..={}",
+            difficulty, min, max
+        ));
+    }
+    Ok(())
+}
+
+/// Difficulties a child of a block with `parent_difficulty` may use.
+pub fn difficulty_bounds(parent_difficulty: u64) -> (u64, u64) {
+    let step = parent_difficulty / DIFFICULTY_MAX_STEP_DIVISOR;
+    (
+        parent_difficulty
+            .saturating_sub(step)
+            .max(MIN_VDF_DIFFICULTY),
+        parent_difficulty
+            .saturating_add(step)
+            .max(MIN_VDF_DIFFICULTY),
+    )
+}
+
+/// VDF difficulty for the block after `recent` (newest first, as returned by
+/// `Storage::get_recent_blocks`). The parent's difficulty is scaled by how far
+/// the average block interval is from [`TARGET_BLOCK_TIME`], one bounded step
+/// at a time.
+pub fn retarget_difficulty(recent: &[Block]) -> u64 {
+    let Some(parent) = recent.first() else {
+        return MIN_VDF_DIFFICULTY;
+    };
+    let (min, max) = difficulty_bounds(parent.vdf_difficulty);
+    let oldest = &recent[recent.len().min(DIFFICULTY_RETARGET_WINDOW) - 1];
+    let intervals = parent.index.saturating_sub(oldest.index);
+    if intervals == 0 {
+        return parent.vdf_difficulty.clamp(min, max);
+    }
+
+    let elapsed = parent.timestamp.saturating_sub(oldest.timestamp);
+    if elapsed == 0 {
+        return max;
+    }
+    let expected = intervals * TARGET_BLOCK_TIME;
+    let scaled = parent.vdf_difficulty as u128 * expected as u128 / elapsed as u128;
+    (scaled.min(u64::MAX as u128) as u64).clamp(min, max)
 }
 
 /// Block header (lightweight version for sync)
+///
+/// Carries every hashed field, so a header chain can be verified without bodies.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Header {
     pub index: u64,
@@ -133,6 +422,22 @@ pub struct Header {
     pub vdf_difficulty: u64,
     pub shard_id: u32,
     pub timestamp: u64,
+    #[serde(default)]
+    pub vdf_proof: String,
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default)]
+    pub merkle_root: String,
+    #[serde(default)]
+    pub state_root: String,
+    #[serde(default)]
+    pub nonce: u64,
+    #[serde(default)]
+    pub total_fees: u64,
+    #[serde(default)]
+    pub block_reward: u64,
+    #[serde(default)]
+    pub total_reward: u64,
 }
 
 impl Header {
@@ -147,8 +452,56 @@ impl Header {
             vdf_difficulty: block.vdf_difficulty,
             shard_id: block.shard_id,
             timestamp: block.timestamp,
+            vdf_proof: block.vdf_proof.clone(),
+            version: block.version,
+            merkle_root: block.merkle_root.clone(),
+            state_root: block.state_root.clone(),
+            nonce: block.nonce,
+            total_fees: block.total_fees,
+            block_reward: block.block_reward,
+            total_reward: block.total_reward,
         }
     }
+
+    /// VDF proof over the header hash computed without the proof, as for blocks.
+    pub fn is_vdf_valid(&self) -> bool {
+        let vdf = CentichainVDF::new(self.vdf_difficulty);
+        let mut clone = self.clone();
+        clone.vdf_proof = String::new();
+        let challenge = clone.calculate_hash();
+        vdf.verify(challenge.as_bytes(), &self.vdf_proof)
+    }
+
+    /// `vdf_difficulty` is within one retarget step of the parent header's.
+    pub fn validate_difficulty(&self, prev: Option<&Header>) -> Result<(), String> {
+        check_difficulty(self.vdf_difficulty, prev.map(|p| p.vdf_difficulty))
+    }
+
+    /// Block hash committed to by this header; matches [`Block::calculate_hash`].
+    pub fn calculate_hash(&self) -> String {
+        self.calculate_hash_for(chain_id())
+    }
+
+    /// Block hash on the network identified by `chain_id`.
+    pub fn calculate_hash_for(&self, chain_id: u32) -> String {
+        use sha2::{Digest, Sha256};
+        let mut hasher = Sha256::new();
+        hasher.update(chain_id.to_be_bytes());
+        hasher.update(self.index.to_be_bytes());
+        hasher.update(self.timestamp.to_be_bytes());
+        hasher.update(self.author.as_bytes());
+        hasher.update(self.previous_hash.as_bytes());
+        hasher.update(self.vdf_proof.as_bytes());
+        hasher.update(self.merkle_root.as_bytes());
+        hasher.update(self.state_root.as_bytes());
+        hasher.update(self.nonce.to_be_bytes());
+        hasher.update(self.vdf_difficulty.to_be_bytes());
+        hasher.update(self.version.to_be_bytes());
+        hasher.update(self.total_fees.to_be_bytes());
+        hasher.update(self.block_reward.to_be_bytes());
+        hasher.update(self.total_reward.to_be_bytes());
+        hex::encode(hasher.finalize())
+    }
 }
 
 /// Sync protocol requests
@@ -159,6 +512,12 @@ pub enum SyncRequest {
     GetHeaders(u64, u64),
     GetHeight,
     GetMempool,
+    /// Answered with [`SyncResponse::Block`].
+    GetBlockByHash(String),
+    /// Balance of an address, for light nodes that keep no state
+    GetBalance(String),
+    /// The block containing a transaction, answered with [`SyncResponse::Block`]
+    GetTransaction(String),
 }
 
 /// Sync protocol responses
@@ -166,9 +525,13 @@ pub enum SyncRequest {
 pub enum SyncResponse {
     Block(Option<Block>),
     BlocksBatch(Vec<Block>),
+    /// Gzipped bincode of a `BlocksBatch`, only sent to peers advertising
+    /// `CAPABILITY_GZIP_SYNC`
+    CompressedBlocksBatch(Vec<u8>),
     HeadersBatch(Vec<Header>),
     Height(u64),
     Mempool(Vec<Transaction>),
+    Balance(u64),
 }
 
 /// Calculate mining reward based on block index
@@ -185,8 +548,8 @@ pub fn calculate_mining_reward(index: u64) -> u64 {
     }
 }
 
-/// Calculate circulating supply up to given height
-pub fn calculate_circulating_supply(height: u64) -> u64 {
+/// Calculate circulating supply up to given height, less the `burned` fees
+pub fn calculate_circulating_supply(height: u64, burned: u64) -> u64 {
     let mut supply = GENESIS_SUPPLY;
     let mut current_reward = INITIAL_REWARD;
     let mut blocks_processed = 0;
@@ -208,5 +571,407 @@ pub fn calculate_circulating_supply(height: u64) -> u64 {
         }
     }
 
-    supply
+    supply.saturating_sub(burned)
+}
+
+/// Issuance figures at a chain height
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct TokenomicsInfo {
+    pub total_supply: u64,
+    pub max_supply: u64,
+    pub circulating_supply: u64,
+    pub burned_fees: u64,
+    pub remaining_supply: u64,
+    pub next_halving_at: u64,
+    pub blocks_until_halving: u64,
+    pub current_reward: u64,
+    pub halving_interval: u64,
+}
+
+impl TokenomicsInfo {
+    /// Figures at `height`, less the `burned` fees.
+    pub fn at_height(height: u64, burned: u64) -> Self {
+        // Standard Halving Logic
+        let next_halving = (height / HALVING_INTERVAL + 1) * HALVING_INTERVAL;
+        let circulating = calculate_circulating_supply(height, burned);
+
+        TokenomicsInfo {
+            total_supply: TOTAL_SUPPLY,
+            max_supply: TOTAL_SUPPLY,
+            circulating_supply: circulating,
+            burned_fees: burned,
+            remaining_supply: TOTAL_SUPPLY.saturating_sub(circulating),
+            next_halving_at: next_halving,
+            blocks_until_halving: next_halving.saturating_sub(height),
+            current_reward: calculate_mining_reward(height),
+            halving_interval: HALVING_INTERVAL,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::{SYSTEM_SIG_GENESIS, SYSTEM_SIG_REWARD};
+
+    const AUTHOR: &str = "12D3KooWAuthor";
+
+    fn sealed_block(index: u64, previous_hash: &str) -> Block {
+        let sig = if index == 0 {
+            SYSTEM_SIG_GENESIS
+        } else {
+            SYSTEM_SIG_REWARD
+        };
+        let tx = Transaction {
+            id: format!("coinbase-{index}"),
+            sender: "SYSTEM".into(),
+            receiver: AUTHOR.into(),
+            amount: calculate_mining_reward(index),
+            shard_id: 0,
+            timestamp: 0,
+            nonce: 0,
+            fee: 0,
+            signature: sig.into(),
+            sender_pubkey: String::new(),
+            outputs: Vec::new(),
+            memo: None,
+            not_before_height: None,
+            alias: None,
+        };
+        let mut block = Block::new(
+            index,
+            AUTHOR.into(),
+            vec![tx],
+            previous_hash.into(),
+            100,
+            100,
+            0,
+            0,
+            calculate_mining_reward(index),
+        );
+        let challenge = block.calculate_hash();
+        block.vdf_proof = CentichainVDF::new(100).solve(challenge.as_bytes());
+        block.hash = block.calculate_hash();
+        block
+    }
+
+    fn genesis_and_child() -> (Block, Block) {
+        let genesis = sealed_block(
+            0,
+            "0000000000000000000000000000000000000000000000000000000000000000",
+        );
+        let child = sealed_block(1, &genesis.hash);
+        (genesis, child)
+    }
+
+    #[test]
+    fn accepts_valid_chain() {
+        let (genesis, child) = genesis_and_child();
+        assert_eq!(genesis.validate(None), Ok(()));
+        assert_eq!(child.validate(Some(&genesis)), Ok(()));
+    }
+
+    #[test]
+    fn rejects_index_gap() {
+        let (genesis, _) = genesis_and_child();
+        let orphan = sealed_block(2, &genesis.hash);
+        let err = orphan.validate(Some(&genesis)).unwrap_err();
+        assert!(err.contains("Non-contiguous"), "{err}");
+    }
+
+    #[test]
+    fn rejects_wrong_previous_hash() {
+        let (genesis, _) = genesis_and_child();
+        let child = sealed_block(1, &"ab".repeat(32));
+        let err = child.validate(Some(&genesis)).unwrap_err();
+        assert!(err.contains("previous_hash"), "{err}");
+    }
+
+    #[test]
+    fn rejects_tampered_hash() {
+        let (genesis, mut child) = genesis_and_child();
+        child.timestamp += 1;
+        let err = child.validate(Some(&genesis)).unwrap_err();
+        assert_eq!(err, "Block hash mismatch");
+    }
+
+    #[test]
+    fn rejects_merkle_mismatch() {
+        let (genesis, mut child) = genesis_and_child();
+        child.transactions[0].id = "swapped".into();
+        let err = child.validate(Some(&genesis)).unwrap_err();
+        assert_eq!(err, "Merkle root mismatch");
+    }
+
+    #[test]
+    fn rejects_bad_vdf_proof() {
+        let (genesis, mut child) = genesis_and_child();
+        child.vdf_proof = "00".repeat(32);
+        child.hash = child.calculate_hash();
+        let err = child.validate(Some(&genesis)).unwrap_err();
+        assert_eq!(err, "Invalid VDF proof");
+    }
+
+    #[test]
+    fn chain_id_separates_block_hashes() {
+        let (genesis, mut child) = genesis_and_child();
+        let header = Header::from_block(&child);
+        assert_eq!(child.hash, header.calculate_hash_for(DEFAULT_CHAIN_ID));
+        assert_ne!(
+            header.calculate_hash_for(DEFAULT_CHAIN_ID),
+            header.calculate_hash_for(DEFAULT_CHAIN_ID + 1)
+        );
+
+        // A block sealed for another network does not validate here
+        child.hash = header.calculate_hash_for(DEFAULT_CHAIN_ID + 1);
+        let err = child.validate(Some(&genesis)).unwrap_err();
+        assert_eq!(err, "Block hash mismatch");
+    }
+
+    /// Re-commits `block` after its transactions were edited.
+    fn resealed(mut block: Block) -> Block {
+        block.merkle_root = calculate_merkle_root(&block.transactions);
+        block.vdf_proof = String::new();
+        let challenge = block.calculate_hash();
+        block.vdf_proof = CentichainVDF::new(100).solve(challenge.as_bytes());
+        block.hash = block.calculate_hash();
+        block
+    }
+
+    #[test]
+    fn coinbase_pays_reward_plus_fees() {
+        let (genesis, mut child) = genesis_and_child();
+        let mut transfer = child.transactions[0].clone();
+        transfer.id = "transfer".into();
+        transfer.sender = "12D3KooWSender".into();
+        transfer.amount = 50_000;
+        let fee = transfer.fee();
+        child.transactions.push(transfer);
+        child.transactions[0].amount += fee;
+        child.total_fees = fee;
+        child.total_reward += fee;
+        let child = resealed(child);
+        assert_eq!(child.validate(Some(&genesis)), Ok(()));
+
+        // Header totals must agree with each other
+        let mut skewed = child.clone();
+        skewed.total_reward += 1;
+        let err = resealed(skewed).validate(Some(&genesis)).unwrap_err();
+        assert!(err.contains("total_reward"), "{err}");
+    }
+
+    #[test]
+    fn rejects_premature_time_locked_tx() {
+        let (genesis, mut child) = genesis_and_child();
+        let mut locked = child.transactions[0].clone();
+        locked.id = "locked".into();
+        locked.sender = "12D3KooWSender".into();
+        locked.amount = 50_000;
+        locked.not_before_height = Some(2);
+        let fee = locked.fee();
+        child.transactions.push(locked);
+        child.transactions[0].amount += fee;
+        child.total_fees = fee;
+        child.total_reward += fee;
+
+        let err = resealed(child.clone())
+            .validate(Some(&genesis))
+            .unwrap_err();
+        assert_eq!(err, "Transaction locked is locked until height 2");
+
+        child.transactions[1].not_before_height = Some(1);
+        assert_eq!(resealed(child).validate(Some(&genesis)), Ok(()));
+    }
+
+    /// Genesis and a child carrying one transfer whose fee `policy` splits.
+    fn child_paying_fees(policy: &FeePolicy) -> (Block, Block) {
+        let (genesis, mut child) = genesis_and_child();
+        let mut transfer = child.transactions[0].clone();
+        transfer.id = "transfer".into();
+        transfer.sender = "12D3KooWSender".into();
+        transfer.amount = 50_000_000;
+        let fee = transfer.fee();
+        child.transactions.push(transfer);
+
+        let reward = child.block_reward;
+        child.transactions[0].amount = reward + fee - policy.split(fee).burned;
+        child.transactions[0].outputs = policy.coinbase_outputs(AUTHOR, reward, fee);
+        child.total_fees = fee;
+        child.total_reward += fee;
+        (genesis, resealed(child))
+    }
+
+    #[test]
+    fn burn_policy_pays_no_fees_to_the_miner() {
+        let burn = FeePolicy {
+            burn_bps: 10_000,
+            ..Default::default()
+        };
+        let (_, child) = child_paying_fees(&burn);
+        assert_eq!(child.validate_coinbase_with(&burn), Ok(()));
+        assert_eq!(child.transactions[0].amount, child.block_reward);
+        assert_eq!(child.burned_fees(), child.total_fees);
+
+        // Nodes without the burn expect the fees in the coinbase
+        let err = child
+            .validate_coinbase_with(&FeePolicy::default())
+            .unwrap_err();
+        assert!(err.starts_with("Coinbase pays"), "{err}");
+        let (_, greedy) = child_paying_fees(&FeePolicy::default());
+        assert!(greedy.validate_coinbase_with(&burn).is_err());
+    }
+
+    #[test]
+    fn split_policy_pays_the_treasury() {
+        let treasury = libp2p::identity::Keypair::generate_ed25519()
+            .public()
+            .to_peer_id()
+            .to_string();
+        let policy = FeePolicy {
+            burn_bps: 2_000,
+            treasury_bps: 3_000,
+            treasury_address: Some(treasury.clone()),
+        };
+        let (_, child) = child_paying_fees(&policy);
+        assert_eq!(child.validate_coinbase_with(&policy), Ok(()));
+
+        let fee = child.total_fees;
+        let coinbase = &child.transactions[0];
+        assert_eq!(
+            coinbase.outputs,
+            vec![
+                (AUTHOR.to_string(), child.block_reward + fee / 2),
+                (treasury, fee * 3 / 10),
+            ]
+        );
+        assert_eq!(child.burned_fees(), fee / 5);
+
+        // Keeping the treasury share is caught
+        let mut skimmed = child.clone();
+        skimmed.transactions[0].outputs.clear();
+        let err = resealed(skimmed)
+            .validate_coinbase_with(&policy)
+            .unwrap_err();
+        assert!(err.contains("treasury"), "{err}");
+    }
+
+    #[test]
+    fn rejects_inflated_coinbase() {
+        let (genesis, mut child) = genesis_and_child();
+        child.transactions[0].amount += 1_000_000;
+        let err = resealed(child).validate(Some(&genesis)).unwrap_err();
+        assert!(err.starts_with("Coinbase pays"), "{err}");
+
+        // Fees the block doesn't contain can't be claimed either
+        let (genesis, mut child) = genesis_and_child();
+        child.transactions[0].amount += 5_000;
+        child.total_fees = 5_000;
+        child.total_reward += 5_000;
+        let err = resealed(child).validate(Some(&genesis)).unwrap_err();
+        assert!(err.starts_with("Coinbase pays"), "{err}");
+    }
+
+    #[test]
+    fn rejects_missing_or_extra_coinbase() {
+        let (genesis, child) = genesis_and_child();
+
+        let mut missing = child.clone();
+        missing.transactions[0].sender = "12D3KooWSender".into();
+        let err = resealed(missing).validate(Some(&genesis)).unwrap_err();
+        assert!(err.contains("found 0"), "{err}");
+
+        let mut doubled = child;
+        let mut second = doubled.transactions[0].clone();
+        second.id = "coinbase-again".into();
+        doubled.transactions.push(second);
+        let err = resealed(doubled).validate(Some(&genesis)).unwrap_err();
+        assert!(err.contains("found 2"), "{err}");
+    }
+
+    #[test]
+    fn coinbase_may_pay_a_reward_address() {
+        let (genesis, mut child) = genesis_and_child();
+        let cold_wallet = libp2p::identity::Keypair::generate_ed25519()
+            .public()
+            .to_peer_id()
+            .to_string();
+        child.transactions[0].receiver = cold_wallet;
+        assert_eq!(resealed(child.clone()).validate(Some(&genesis)), Ok(()));
+
+        child.transactions[0].receiver = "not-an-address".into();
+        let err = resealed(child).validate(Some(&genesis)).unwrap_err();
+        assert!(err.contains("SYSTEM reward must pay"), "{err}");
+    }
+
+    #[test]
+    fn rejects_future_and_backwards_timestamps() {
+        let (genesis, child) = genesis_and_child();
+        let now = child.timestamp;
+
+        let mut future = child.clone();
+        future.timestamp = now + MAX_BLOCK_TIME_DRIFT_SECS + 1;
+        let err = future.validate_timestamp(Some(&genesis), now).unwrap_err();
+        assert!(err.contains("in the future"), "{err}");
+        assert!(resealed(future).validate(Some(&genesis)).is_err());
+
+        let mut backwards = child.clone();
+        backwards.timestamp = genesis.timestamp - 1;
+        let err = resealed(backwards).validate(Some(&genesis)).unwrap_err();
+        assert!(err.contains("before its parent"), "{err}");
+
+        let mut ahead = child;
+        ahead.timestamp = now + MAX_BLOCK_TIME_DRIFT_SECS / 2;
+        assert_eq!(ahead.validate_timestamp(Some(&genesis), now), Ok(()));
+        assert_eq!(resealed(ahead).validate(Some(&genesis)), Ok(()));
+    }
+
+    /// `count` blocks at `difficulty`, `interval` seconds apart, newest first.
+    fn timed_chain(count: u64, interval: u64, difficulty: u64) -> Vec<Block> {
+        (0..count)
+            .rev()
+            .map(|index| {
+                let mut block = sealed_block(index, "");
+                block.timestamp = 1_000 + index * interval;
+                block.vdf_difficulty = difficulty;
+                block
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fast_blocks_raise_difficulty() {
+        assert_eq!(
+            retarget_difficulty(&timed_chain(21, TARGET_BLOCK_TIME, 1_000)),
+            1_000
+        );
+        // Twice as fast as the target, capped at one step up
+        assert_eq!(retarget_difficulty(&timed_chain(21, 1, 1_000)), 1_250);
+        assert_eq!(retarget_difficulty(&timed_chain(5, 0, 1_000)), 1_250);
+    }
+
+    #[test]
+    fn slow_blocks_lower_difficulty() {
+        // Four intervals over 10s against an 8s target scale by 0.8
+        let mut chain = timed_chain(5, TARGET_BLOCK_TIME, 1_000);
+        chain[0].timestamp += 10 - 4 * TARGET_BLOCK_TIME;
+        assert_eq!(retarget_difficulty(&chain), 800);
+        // Far too slow, capped at one step down and never below the minimum
+        assert_eq!(retarget_difficulty(&timed_chain(21, 10, 1_000)), 750);
+        assert_eq!(
+            retarget_difficulty(&timed_chain(21, 10, MIN_VDF_DIFFICULTY)),
+            MIN_VDF_DIFFICULTY
+        );
+    }
+
+    #[test]
+    fn rejects_difficulty_outside_retarget_window() {
+        let (genesis, mut child) = genesis_and_child();
+        for (difficulty, ok) in [(99, false), (100, true), (125, true), (126, false)] {
+            child.vdf_difficulty = difficulty;
+            assert_eq!(child.validate_difficulty(Some(&genesis)).is_ok(), ok);
+        }
+        child.vdf_difficulty = 126;
+        let err = resealed(child).validate(Some(&genesis)).unwrap_err();
+        assert!(err.contains("VDF difficulty"), "{err}");
+    }
 }
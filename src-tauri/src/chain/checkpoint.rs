@@ -0,0 +1,74 @@
+//! # Checkpoints
+//!
+//! Trusted `(height, hash)` pairs that pin the canonical chain. A block or
+//! header at a checkpoint height must carry the pinned hash, so a peer cannot
+//! feed a syncing node a fabricated history that forks below a checkpoint.
+
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+/// Block hash the chain must have at `height`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    pub height: u64,
+    pub hash: String,
+}
+
+impl Checkpoint {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.hash.len() != 64 || hex::decode(&self.hash).is_err() {
+            return Err(format!(
+                "Checkpoint {} hash must be 64 hex characters",
+                self.height
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Checks `hash` against the checkpoint at `height`, if there is one.
+pub fn check_checkpoint(checkpoints: &[Checkpoint], height: u64, hash: &str) -> Result<(), String> {
+    match checkpoints.iter().find(|c| c.height == height) {
+        Some(checkpoint) if !checkpoint.hash.eq_ignore_ascii_case(hash) => Err(format!(
+            "Block #{} does not match checkpoint {}",
+            height, checkpoint.hash
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Checkpoints of the network, set from `AppSettings` at startup
+static CHECKPOINTS: RwLock<Vec<Checkpoint>> = RwLock::new(Vec::new());
+
+/// Checkpoints this node syncs and validates blocks against.
+pub fn checkpoints() -> Vec<Checkpoint> {
+    CHECKPOINTS.read().unwrap().clone()
+}
+
+pub fn set_checkpoints(checkpoints: Vec<Checkpoint>) {
+    *CHECKPOINTS.write().unwrap() = checkpoints;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_checkpoint_heights_are_pinned() {
+        let pinned = vec![Checkpoint {
+            height: 10,
+            hash: "ab".repeat(32),
+        }];
+        assert!(check_checkpoint(&pinned, 10, &"ab".repeat(32)).is_ok());
+        assert!(check_checkpoint(&pinned, 10, &"AB".repeat(32)).is_ok());
+        assert!(check_checkpoint(&pinned, 10, &"cd".repeat(32)).is_err());
+        assert!(check_checkpoint(&pinned, 11, &"cd".repeat(32)).is_ok());
+
+        assert!(pinned[0].validate().is_ok());
+        let short = Checkpoint {
+            height: 1,
+            hash: "abc".into(),
+        };
+        assert!(short.validate().is_err());
+    }
+}
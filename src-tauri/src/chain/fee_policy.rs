@@ -0,0 +1,147 @@
+//! # Fee Policy
+//!
+//! How a block's fees are divided: a share is burned, a share goes to the
+//! treasury and the rest to the block producer. The coinbase pays the producer
+//! and, as a second output, the treasury; burned fees are paid to no one.
+//! Like the chain id, every node on a network must run the same policy.
+
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+/// Basis points in a whole
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Share of block fees burned and sent to the treasury, in basis points
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct FeePolicy {
+    pub burn_bps: u16,
+    pub treasury_bps: u16,
+    /// Receives the treasury share; required when `treasury_bps` is non-zero
+    pub treasury_address: Option<String>,
+}
+
+/// One block's fees after applying a [`FeePolicy`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeSplit {
+    pub miner: u64,
+    pub treasury: u64,
+    pub burned: u64,
+}
+
+impl FeePolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if u64::from(self.burn_bps) + u64::from(self.treasury_bps) > BPS_DENOMINATOR {
+            return Err("Burn and treasury shares exceed 100% of fees".into());
+        }
+        if self.treasury_bps > 0 {
+            let address = self
+                .treasury_address
+                .as_ref()
+                .ok_or("Treasury share set without a treasury address")?;
+            address
+                .parse::<libp2p::PeerId>()
+                .map_err(|_| format!("Invalid treasury address: {}", address))?;
+        }
+        Ok(())
+    }
+
+    /// Divides `fees`; rounding remainders go to the miner.
+    pub fn split(&self, fees: u64) -> FeeSplit {
+        let share =
+            |bps: u16| (u128::from(fees) * u128::from(bps) / u128::from(BPS_DENOMINATOR)) as u64;
+        let burned = share(self.burn_bps);
+        let treasury = match self.treasury_address {
+            Some(_) => share(self.treasury_bps),
+            None => 0,
+        };
+        FeeSplit {
+            miner: fees.saturating_sub(burned).saturating_sub(treasury),
+            treasury,
+            burned,
+        }
+    }
+
+    /// Outputs of a coinbase paying `receiver` the block reward plus its fee
+    /// share: empty when the treasury gets nothing (a plain coinbase),
+    /// otherwise the receiver followed by the treasury.
+    pub fn coinbase_outputs(&self, receiver: &str, reward: u64, fees: u64) -> Vec<(String, u64)> {
+        let split = self.split(fees);
+        match &self.treasury_address {
+            Some(treasury) if split.treasury > 0 => vec![
+                (receiver.to_string(), reward.saturating_add(split.miner)),
+                (treasury.clone(), split.treasury),
+            ],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Fee policy of the network, set from `AppSettings` at startup
+static FEE_POLICY: RwLock<FeePolicy> = RwLock::new(FeePolicy {
+    burn_bps: 0,
+    treasury_bps: 0,
+    treasury_address: None,
+});
+
+/// Policy this node builds and validates coinbases with.
+pub fn fee_policy() -> FeePolicy {
+    FEE_POLICY.read().unwrap().clone()
+}
+
+/// Switches the fee policy. Blocks built under another policy no longer
+/// validate afterwards.
+pub fn set_fee_policy(policy: FeePolicy) {
+    *FEE_POLICY.write().unwrap() = policy;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn treasury() -> String {
+        libp2p::identity::Keypair::generate_ed25519()
+            .public()
+            .to_peer_id()
+            .to_string()
+    }
+
+    #[test]
+    fn splits_fees_by_basis_points() {
+        let burn = FeePolicy {
+            burn_bps: 10_000,
+            ..Default::default()
+        };
+        let split = burn.split(12_345);
+        assert_eq!((split.miner, split.treasury, split.burned), (0, 0, 12_345));
+
+        let mixed = FeePolicy {
+            burn_bps: 2_500,
+            treasury_bps: 1_000,
+            treasury_address: Some(treasury()),
+        };
+        let split = mixed.split(10_001);
+        assert_eq!(
+            (split.miner, split.treasury, split.burned),
+            (6_501, 1_000, 2_500)
+        );
+        assert_eq!(FeePolicy::default().split(10_001).miner, 10_001);
+    }
+
+    #[test]
+    fn rejects_inconsistent_policies() {
+        let over = FeePolicy {
+            burn_bps: 6_000,
+            treasury_bps: 5_000,
+            treasury_address: Some(treasury()),
+        };
+        assert!(over.validate().is_err());
+
+        let homeless = FeePolicy {
+            treasury_bps: 100,
+            ..Default::default()
+        };
+        assert!(homeless.validate().is_err());
+        assert!(FeePolicy::default().validate().is_ok());
+    }
+}
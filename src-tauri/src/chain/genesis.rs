@@ -0,0 +1,177 @@
+//! # Genesis Configuration
+//!
+//! Operators starting a new network describe its first block in a
+//! `genesis.json` next to the database: the chain id and how the genesis
+//! supply is allocated. Without one, the node creating genesis receives the
+//! whole [`GENESIS_SUPPLY`].
+
+use super::{chain_id, Block, Transaction, SYSTEM_SIG_GENESIS};
+use crate::utils::constants::{GENESIS_SUPPLY, TOTAL_SUPPLY};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// File name of the genesis configuration in the data directory
+pub const GENESIS_CONFIG_FILE: &str = "genesis.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GenesisConfig {
+    pub chain_id: u32,
+    /// Coins minted at genesis; the allocations must add up to it
+    pub total_supply: u64,
+    /// `(address, amount)` balances the chain starts with
+    pub allocations: Vec<(String, u64)>,
+}
+
+impl GenesisConfig {
+    /// The default genesis: the whole supply to `address`.
+    pub fn single(address: &str) -> Self {
+        GenesisConfig {
+            chain_id: chain_id(),
+            total_supply: GENESIS_SUPPLY,
+            allocations: vec![(address.to_string(), GENESIS_SUPPLY)],
+        }
+    }
+
+    /// Reads `path`; `Ok(None)` if there is no such file.
+    pub fn load(path: &Path) -> Result<Option<Self>, String> {
+        let json = match std::fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("Cannot read {}: {}", path.display(), e)),
+        };
+        let config: Self = serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid genesis config {}: {}", path.display(), e))?;
+        config.validate()?;
+        Ok(Some(config))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.allocations.is_empty() {
+            return Err("Genesis config has no allocations".into());
+        }
+        if self.total_supply > TOTAL_SUPPLY {
+            return Err(format!(
+                "Genesis supply {} exceeds the total supply {}",
+                self.total_supply, TOTAL_SUPPLY
+            ));
+        }
+        let mut total = 0u64;
+        for (address, amount) in &self.allocations {
+            address
+                .parse::<libp2p::PeerId>()
+                .map_err(|_| format!("Invalid genesis address: {}", address))?;
+            if *amount == 0 {
+                return Err(format!("Genesis allocation to {} is zero", address));
+            }
+            total = total
+                .checked_add(*amount)
+                .ok_or("Genesis allocations overflow")?;
+        }
+        if total != self.total_supply {
+            return Err(format!(
+                "Genesis allocations add up to {}, not the total supply {}",
+                total, self.total_supply
+            ));
+        }
+        Ok(())
+    }
+
+    /// One SYSTEM genesis transaction per allocation. The first keeps the id
+    /// `genesis`, so a single allocation builds the same block as before.
+    pub fn transactions(&self) -> Vec<Transaction> {
+        self.allocations
+            .iter()
+            .enumerate()
+            .map(|(i, (address, amount))| Transaction {
+                id: match i {
+                    0 => "genesis".to_string(),
+                    i => format!("genesis-{}", i),
+                },
+                sender: "SYSTEM".to_string(),
+                receiver: address.clone(),
+                amount: *amount,
+                shard_id: 0,
+                timestamp: 0,
+                nonce: 0,
+                fee: 0,
+                signature: SYSTEM_SIG_GENESIS.to_string(),
+                sender_pubkey: String::new(),
+                outputs: Vec::new(),
+                memo: None,
+                not_before_height: None,
+                alias: None,
+            })
+            .collect()
+    }
+
+    /// Unsealed genesis block authored by `author`; the caller fills in the
+    /// state root and VDF proof.
+    pub fn block(&self, author: &str) -> Block {
+        Block::new(
+            0,
+            author.to_string(),
+            self.transactions(),
+            "0000000000000000000000000000000000000000000000000000000000000000".to_string(),
+            100,
+            100, // Low difficulty for genesis
+            0,
+            0,
+            self.total_supply,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::{try_accept_block, BlockAcceptResult};
+    use crate::storage::Storage;
+
+    fn address() -> String {
+        libp2p::identity::Keypair::generate_ed25519()
+            .public()
+            .to_peer_id()
+            .to_string()
+    }
+
+    #[test]
+    fn allocations_become_starting_balances() {
+        let (founder, team, treasury) = (address(), address(), address());
+        let config = GenesisConfig {
+            chain_id: chain_id(),
+            total_supply: 1_000_000,
+            allocations: vec![
+                (founder.clone(), 600_000),
+                (team.clone(), 300_000),
+                (treasury.clone(), 100_000),
+            ],
+        };
+        config.validate().unwrap();
+
+        let storage = Storage::temp();
+        let mut block = config.block(&founder);
+        block.state_root = storage.compute_state_root_after(&block).unwrap();
+        block.hash = block.calculate_hash();
+        assert_eq!(
+            try_accept_block(&storage, &block, None, true),
+            Ok(BlockAcceptResult::Accepted)
+        );
+
+        for (account, balance) in [(founder, 600_000), (team, 300_000), (treasury, 100_000)] {
+            assert_eq!(storage.calculate_balance(&account).unwrap(), balance);
+        }
+    }
+
+    #[test]
+    fn rejects_allocations_not_matching_the_supply() {
+        let mut config = GenesisConfig::single(&address());
+        assert!(config.validate().is_ok());
+        assert_eq!(config.transactions()[0].id, "genesis");
+
+        config.allocations.push((address(), 1));
+        assert!(config.validate().is_err());
+        config.allocations.push(("not-an-address".into(), 0));
+        config.total_supply += 1;
+        assert!(config.validate().is_err());
+    }
+}
@@ -1,42 +1,105 @@
 //! # Merkle Tree Utilities
 //!
-//! Functions for calculating and verifying Merkle roots.
+//! Functions for calculating Merkle roots and verifying inclusion proofs.
 
 use crate::chain::Transaction;
 use sha2::{Digest, Sha256};
 
 /// Calculate Merkle root from a list of transactions
 pub fn calculate_merkle_root(transactions: &[Transaction]) -> String {
-    if transactions.is_empty() {
-        return "0000000000000000000000000000000000000000000000000000000000000000".to_string();
+    let leaves = transactions.iter().map(|tx| hash_leaf(&tx.id)).collect();
+    merkle_root_from_leaves(leaves)
+}
+
+/// Folds pre-hashed leaves into a root, duplicating the last node on odd levels.
+pub fn merkle_root_from_leaves(leaves: Vec<Vec<u8>>) -> String {
+    MerkleTree::from_leaves(leaves).root()
+}
+
+/// Binary Merkle tree over transaction ids, kept level by level for proof generation.
+#[derive(Debug, Clone)]
+pub struct MerkleTree {
+    /// `levels[0]` holds the leaf hashes, the last level holds the root.
+    levels: Vec<Vec<Vec<u8>>>,
+}
+
+impl MerkleTree {
+    /// Builds the tree with `SHA256(tx_id)` leaves, matching [`calculate_merkle_root`].
+    pub fn build(tx_ids: &[String]) -> Self {
+        Self::from_leaves(tx_ids.iter().map(|id| hash_leaf(id)).collect())
+    }
+
+    fn from_leaves(leaves: Vec<Vec<u8>>) -> Self {
+        let mut levels = vec![leaves];
+        while levels.last().is_some_and(|level| level.len() > 1) {
+            let level = levels.last().unwrap();
+            let next_level = level
+                .chunks(2)
+                .map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+                .collect();
+            levels.push(next_level);
+        }
+        MerkleTree { levels }
     }
 
-    let mut hashes: Vec<Vec<u8>> = transactions
-        .iter()
-        .map(|tx| {
-            let mut hasher = Sha256::new();
-            hasher.update(tx.id.as_bytes());
-            hasher.finalize().to_vec()
-        })
-        .collect();
-
-    while hashes.len() > 1 {
-        if hashes.len() % 2 != 0 {
-            let last = hashes.last().unwrap().clone();
-            hashes.push(last);
+    pub fn root(&self) -> String {
+        match self.levels.last().and_then(|level| level.first()) {
+            Some(root) => hex::encode(root),
+            None => "0000000000000000000000000000000000000000000000000000000000000000".to_string(),
+        }
+    }
+
+    /// Hex sibling hashes from leaf to root for the leaf at `index` (empty if out of range).
+    pub fn proof(&self, index: usize) -> Vec<String> {
+        if index >= self.levels[0].len() {
+            return Vec::new();
         }
 
-        let mut next_level = Vec::new();
-        for chunk in hashes.chunks(2) {
-            let mut hasher = Sha256::new();
-            hasher.update(&chunk[0]);
-            hasher.update(&chunk[1]);
-            next_level.push(hasher.finalize().to_vec());
+        let mut proof = Vec::new();
+        let mut position = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling = if position.is_multiple_of(2) {
+                level.get(position + 1).unwrap_or(&level[position])
+            } else {
+                &level[position - 1]
+            };
+            proof.push(hex::encode(sibling));
+            position /= 2;
         }
-        hashes = next_level;
+        proof
     }
+}
+
+/// Checks that `leaf` (a tx id) sits at `index` under `root` using `proof`.
+pub fn verify_proof(leaf: &str, proof: &[String], root: &str, index: usize) -> bool {
+    let mut current = hash_leaf(leaf);
+    let mut position = index;
+    for sibling_hex in proof {
+        let sibling = match hex::decode(sibling_hex) {
+            Ok(bytes) => bytes,
+            Err(_) => return false,
+        };
+        current = if position.is_multiple_of(2) {
+            hash_pair(&current, &sibling)
+        } else {
+            hash_pair(&sibling, &current)
+        };
+        position /= 2;
+    }
+    position == 0 && hex::encode(current) == root
+}
 
-    hex::encode(&hashes[0])
+fn hash_leaf(tx_id: &str) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(tx_id.as_bytes());
+    hasher.finalize().to_vec()
+}
+
+fn hash_pair(left: &[u8], right: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().to_vec()
 }
 
 #[cfg(test)]
@@ -61,8 +124,14 @@ mod tests {
             amount: 100,
             shard_id: 0,
             timestamp: 0,
+            nonce: 0,
+            fee: 0,
             signature: "s".to_string(),
             sender_pubkey: String::new(),
+            outputs: Vec::new(),
+            memo: None,
+            not_before_height: None,
+            alias: None,
         };
         let root = calculate_merkle_root(&[tx]);
         let mut hasher = Sha256::new();
@@ -80,8 +149,14 @@ mod tests {
             amount: 100,
             shard_id: 0,
             timestamp: 0,
+            nonce: 0,
+            fee: 0,
             signature: "s".to_string(),
             sender_pubkey: String::new(),
+            outputs: Vec::new(),
+            memo: None,
+            not_before_height: None,
+            alias: None,
         };
         let tx2 = Transaction {
             id: "tx2".to_string(),
@@ -90,8 +165,14 @@ mod tests {
             amount: 200,
             shard_id: 0,
             timestamp: 0,
+            nonce: 0,
+            fee: 0,
             signature: "s".to_string(),
             sender_pubkey: String::new(),
+            outputs: Vec::new(),
+            memo: None,
+            not_before_height: None,
+            alias: None,
         };
 
         let root = calculate_merkle_root(&[tx1, tx2]);
@@ -100,4 +181,61 @@ mod tests {
             "0000000000000000000000000000000000000000000000000000000000000000"
         );
     }
+
+    fn ids(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("tx{i}")).collect()
+    }
+
+    fn assert_round_trip(n: usize) {
+        let tx_ids = ids(n);
+        let tree = MerkleTree::build(&tx_ids);
+        let root = tree.root();
+
+        for (index, id) in tx_ids.iter().enumerate() {
+            let proof = tree.proof(index);
+            assert!(
+                verify_proof(id, &proof, &root, index),
+                "size {n}, leaf {index}"
+            );
+            assert!(!verify_proof("forged", &proof, &root, index));
+            if n > 1 {
+                assert!(!verify_proof(id, &proof, &root, (index + 1) % n));
+            }
+        }
+    }
+
+    #[test]
+    fn test_proof_round_trip_sizes() {
+        for n in [1, 2, 3, 8] {
+            assert_round_trip(n);
+        }
+    }
+
+    #[test]
+    fn test_tree_root_matches_calculate_merkle_root() {
+        let txs: Vec<Transaction> = ids(3)
+            .into_iter()
+            .map(|id| Transaction {
+                id,
+                sender: "a".to_string(),
+                receiver: "b".to_string(),
+                amount: 1,
+                shard_id: 0,
+                timestamp: 0,
+                nonce: 0,
+                fee: 0,
+                signature: "s".to_string(),
+                sender_pubkey: String::new(),
+                outputs: Vec::new(),
+                memo: None,
+                not_before_height: None,
+                alias: None,
+            })
+            .collect();
+        let tx_ids: Vec<String> = txs.iter().map(|tx| tx.id.clone()).collect();
+        assert_eq!(
+            MerkleTree::build(&tx_ids).root(),
+            calculate_merkle_root(&txs)
+        );
+    }
 }
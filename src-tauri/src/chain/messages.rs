@@ -25,6 +25,10 @@ pub struct NodeStatusUpdate {
 
     /// Signature to verify authenticity (peer signs their own status)
     pub signature: String,
+
+    /// Set on the last message of a node that is shutting down
+    #[serde(default)]
+    pub leaving: bool,
 }
 
 impl NodeStatusUpdate {
@@ -40,6 +44,15 @@ impl NodeStatusUpdate {
             mining_active,
             timestamp,
             signature: String::new(), // Will be filled by caller if needed
+            leaving: false,
+        }
+    }
+
+    /// Farewell sent by a stopping node so peers stop electing it
+    pub fn leaving(peer_id: String) -> Self {
+        Self {
+            leaving: true,
+            ..Self::new(peer_id, false)
         }
     }
 }
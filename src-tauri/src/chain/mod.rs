@@ -3,13 +3,20 @@
 //! Core blockchain types: Block, Transaction, Receipt, Messages, etc.
 
 pub mod block;
+pub mod checkpoint;
+pub mod fee_policy;
+pub mod genesis;
 pub mod merkle;
 pub mod messages;
 pub mod receipt;
+pub mod snapshot;
 pub mod transaction;
 pub mod validation;
 
 pub use block::*;
+pub use checkpoint::*;
+pub use fee_policy::*;
+pub use genesis::*;
 pub use merkle::*;
 pub use messages::*;
 pub use receipt::*;
@@ -2,7 +2,13 @@
 //!
 //! Structures for cross-shard transaction receipts and cross-links.
 
+use crate::chain::{
+    verify_proof, Block, Header, Transaction, SYSTEM_SIG_RECEIPT, SYSTEM_XSHARD, XSHARD_TX_PREFIX,
+};
+use crate::crypto::bls;
+use crate::utils::constants::EPOCH_DURATION;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
 /// Status of a cross-shard transfer to ensure atomicity
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -23,7 +29,106 @@ pub struct Receipt {
     pub receiver: String,
     pub block_hash: String,
     pub merkle_proof: Vec<String>,
+    /// Position of the transaction in the source block, the leaf `merkle_proof` starts at
+    #[serde(default)]
+    pub tx_index: usize,
     pub status: ReceiptStatus,
+    /// Header of the source block, which the target shard doesn't store
+    #[serde(default)]
+    pub source_header: Option<Header>,
+    /// The proven transfer
+    #[serde(default)]
+    pub source_tx: Option<Transaction>,
+    /// The source shard committee's CrossLink for the source block, attached
+    /// by the target shard's leader when it mints the receipt
+    #[serde(default)]
+    pub crosslink: Option<CrossLink>,
+}
+
+impl Receipt {
+    /// Sets the Merkle proof and source data of the receipt for
+    /// `block.transactions[tx_index]`.
+    pub fn prove_in(&mut self, block: &Block, tx_index: usize, merkle_proof: Vec<String>) {
+        self.block_hash = block.hash.clone();
+        self.tx_index = tx_index;
+        self.merkle_proof = merkle_proof;
+        self.source_header = Some(Header::from_block(block));
+        self.source_tx = block.transactions.get(tx_index).cloned();
+    }
+
+    /// Checks the receipt against the source block header it carries:
+    /// `merkle_proof` must lead from the source transaction to the header's
+    /// Merkle root, and that transaction must pay `amount` to `receiver`.
+    pub fn verify_inclusion(&self) -> Result<(), String> {
+        let header = self
+            .source_header
+            .as_ref()
+            .ok_or("Receipt carries no source block header")?;
+        if header.hash != self.block_hash
+            || header.calculate_hash() != header.hash
+            || header.shard_id != self.source_shard as u32
+        {
+            return Err("Receipt does not match its source block".into());
+        }
+        let tx = self
+            .source_tx
+            .as_ref()
+            .filter(|tx| tx.id == self.original_tx_id && tx.compute_id() == tx.id)
+            .ok_or("Receipt does not carry the proven transaction")?;
+        if !verify_proof(
+            &self.original_tx_id,
+            &self.merkle_proof,
+            &header.merkle_root,
+            self.tx_index,
+        ) {
+            return Err("Invalid Merkle proof".into());
+        }
+        if !tx.outputs.is_empty() || tx.receiver != self.receiver || tx.amount != self.amount {
+            return Err("Receipt does not match the proven transaction".into());
+        }
+        Ok(())
+    }
+
+    /// [`Receipt::verify_inclusion`], plus the source block must be the one
+    /// `committee`, the source shard's BLS keys, signed a CrossLink for.
+    pub fn verify_crosslinked(&self, committee: &[String]) -> Result<(), String> {
+        let link = self
+            .crosslink
+            .as_ref()
+            .ok_or("Receipt carries no cross-link")?;
+        let height = self.source_header.as_ref().map(|header| header.index);
+        if link.shard_id != self.source_shard
+            || link.block_hash != self.block_hash
+            || Some(link.block_height) != height
+        {
+            return Err("Cross-link is for another block".into());
+        }
+        link.verify(committee)?;
+        self.verify_inclusion()
+    }
+
+    /// Builds the target-shard mint that credits `receiver` with `amount`.
+    pub fn to_mint_tx(&self) -> Transaction {
+        Transaction {
+            id: format!("{}{}", XSHARD_TX_PREFIX, self.original_tx_id),
+            sender: SYSTEM_XSHARD.to_string(),
+            receiver: self.receiver.clone(),
+            amount: self.amount,
+            shard_id: self.target_shard,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            nonce: 0,
+            fee: 0,
+            signature: SYSTEM_SIG_RECEIPT.to_string(),
+            sender_pubkey: String::new(),
+            outputs: Vec::new(),
+            memo: None,
+            not_before_height: None,
+            alias: None,
+        }
+    }
 }
 
 /// Cross-Link is a summary of a Shard's block header, signed by the shard's committee,
@@ -34,9 +139,72 @@ pub struct CrossLink {
     pub block_height: u64,
     pub block_hash: String,
     pub state_root: String,
+    /// Epoch of the block, which fixes the committee that signs the link
+    pub epoch: u64,
+    /// BLS public keys (hex) of the committee members in `signature`
+    pub signers: Vec<String>,
+    /// Aggregate BLS signature of `signers` over [`CrossLink::signing_payload`]
     pub signature: String,
 }
 
+impl CrossLink {
+    /// Unsigned link for `block`; committee members sign its payload.
+    pub fn for_block(block: &Block) -> Self {
+        CrossLink {
+            shard_id: block.shard_id as u16,
+            block_height: block.index,
+            block_hash: block.hash.clone(),
+            state_root: block.state_root.clone(),
+            epoch: block.timestamp / EPOCH_DURATION,
+            signers: Vec::new(),
+            signature: String::new(),
+        }
+    }
+
+    /// Bytes each committee member signs: the link without its signers.
+    pub fn signing_payload(&self) -> Vec<u8> {
+        format!(
+            "{}:{}:{}:{}:{}",
+            self.shard_id, self.block_height, self.block_hash, self.state_root, self.epoch
+        )
+        .into_bytes()
+    }
+
+    /// Sets the signature to the aggregate of `shares`, `(public key,
+    /// signature)` pairs over the payload.
+    pub fn aggregate(&mut self, shares: &[(String, String)]) -> Result<(), String> {
+        let signatures: Vec<String> = shares.iter().map(|(_, sig)| sig.clone()).collect();
+        self.signature = bls::aggregate(&signatures)?;
+        self.signers = shares.iter().map(|(key, _)| key.clone()).collect();
+        Ok(())
+    }
+
+    /// The signature aggregates signatures of the payload by more than two
+    /// thirds of `committee`, the BLS public keys of the shard's validators.
+    pub fn verify(&self, committee: &[String]) -> Result<(), String> {
+        let distinct: HashSet<&String> = self.signers.iter().collect();
+        if distinct.len() != self.signers.len() {
+            return Err("Cross-link names a signer twice".into());
+        }
+        if self.signers.iter().any(|key| !committee.contains(key)) {
+            return Err("Cross-link signer is not on the shard committee".into());
+        }
+        if !has_supermajority(self.signers.len(), committee.len()) {
+            return Err(format!(
+                "Cross-link signed by {} of {} committee members",
+                self.signers.len(),
+                committee.len()
+            ));
+        }
+        bls::verify_aggregate(&self.signature, &self.signing_payload(), &self.signers)
+    }
+}
+
+/// More than two thirds of a committee of `size`
+pub fn has_supermajority(signers: usize, size: usize) -> bool {
+    signers * 3 > size * 2
+}
+
 /// Message broadcast via P2P when a node solves the VDF
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct VdfProofMessage {
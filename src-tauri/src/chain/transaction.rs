@@ -2,37 +2,108 @@
 //!
 //! Transaction structure, Ed25519 signing, and verification.
 
+use crate::utils::constants::{MAX_ALIAS_LEN, MAX_MEMO_BYTES, MAX_TX_OUTPUTS, MIN_ALIAS_LEN};
 use libp2p::identity::{Keypair, PublicKey};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 /// Sentinel signatures for protocol-level (coinbase / genesis) transactions.
 pub const SYSTEM_SIG_GENESIS: &str = "SYSTEM:genesis";
 pub const SYSTEM_SIG_REWARD: &str = "SYSTEM:reward";
+pub const SYSTEM_SIG_RECEIPT: &str = "SYSTEM:receipt";
+
+/// Sender used for mints that settle a cross-shard receipt on the target shard.
+pub const SYSTEM_XSHARD: &str = "SYSTEM_XSHARD";
+/// Id prefix of receipt mints; the remainder is the source `original_tx_id`.
+pub const XSHARD_TX_PREFIX: &str = "xshard-";
 
 /// A blockchain transaction
 #[derive(Serialize, Deserialize, Debug, Clone, Hash)]
 pub struct Transaction {
+    /// Content hash, see [`compute_id`](Transaction::compute_id).
     pub id: String,
     pub sender: String,
     pub receiver: String,
     pub amount: u64,
     pub shard_id: u16,
     pub timestamp: u64,
+    /// Sender's account nonce; each value is mined once, in increasing order.
+    #[serde(default)]
+    pub nonce: u64,
+    /// Fee offered by the sender; values below [`calculate_fee`] pay the minimum.
+    #[serde(default)]
+    pub fee: u64,
     /// Hex-encoded Ed25519 signature over [`signing_payload`](Transaction::signing_payload).
     pub signature: String,
     /// Hex-encoded protobuf public key — required to verify user transactions on the network.
     #[serde(default)]
     pub sender_pubkey: String,
+    /// Receivers of a batch payment. Empty for a plain transfer; otherwise
+    /// `receiver` repeats the first output and `amount` is the outputs' total.
+    #[serde(default)]
+    pub outputs: Vec<(String, u64)>,
+    /// Free-form reference for the receiver (invoice id, deposit tag), at most
+    /// [`MAX_MEMO_BYTES`] bytes.
+    #[serde(default)]
+    pub memo: Option<String>,
+    /// Earliest block height that may include this transaction; until then it
+    /// waits in the mempool.
+    #[serde(default)]
+    pub not_before_height: Option<u64>,
+    /// Name this transaction registers for its sender, see [`validate_alias`].
+    /// A registration moves no coins: zero amount, the sender as receiver.
+    #[serde(default)]
+    pub alias: Option<String>,
 }
 
 impl Transaction {
+    fn content_payload(&self) -> String {
+        let mut payload = format!(
+            "{}|{}|{}|{}|{}|{}|{}",
+            self.sender,
+            self.receiver,
+            self.amount,
+            self.shard_id,
+            self.timestamp,
+            self.nonce,
+            self.fee
+        );
+        // Plain transfers keep the payload (and id) they had before batch payments
+        for (receiver, amount) in &self.outputs {
+            payload.push_str(&format!("|{}:{}", receiver, amount));
+        }
+        // Hex keeps a memo containing '|' from mimicking other fields
+        if let Some(memo) = &self.memo {
+            payload.push_str(&format!("|memo:{}", hex::encode(memo)));
+        }
+        if let Some(height) = self.not_before_height {
+            payload.push_str(&format!("|lock:{}", height));
+        }
+        if let Some(alias) = &self.alias {
+            payload.push_str(&format!("|alias:{}", alias));
+        }
+        payload
+    }
+
+    /// Every `(receiver, amount)` this transaction credits.
+    pub fn credits(&self) -> Vec<(&str, u64)> {
+        if self.outputs.is_empty() {
+            return vec![(self.receiver.as_str(), self.amount)];
+        }
+        self.outputs
+            .iter()
+            .map(|(receiver, amount)| (receiver.as_str(), *amount))
+            .collect()
+    }
+
+    /// SHA-256 (hex) over the transaction contents; identical transfers share an id.
+    pub fn compute_id(&self) -> String {
+        hex::encode(Sha256::digest(self.content_payload().as_bytes()))
+    }
+
     /// Canonical byte payload that must be signed (prevents tampering after signing).
     pub fn signing_payload(&self) -> Vec<u8> {
-        format!(
-            "{}|{}|{}|{}|{}|{}",
-            self.sender, self.receiver, self.amount, self.shard_id, self.timestamp, self.id
-        )
-        .into_bytes()
+        format!("{}|{}", self.content_payload(), self.id).into_bytes()
     }
 
     /// Signs this transaction in-place using the wallet keypair.
@@ -47,10 +118,37 @@ impl Transaction {
         Ok(())
     }
 
+    /// Fee charged to the sender: the offered fee, but never below the minimum.
+    pub fn fee(&self) -> u64 {
+        self.fee.max(calculate_fee(self.amount))
+    }
+
+    /// Whether the time lock, if any, allows inclusion in block `height`.
+    pub fn is_mature(&self, height: u64) -> bool {
+        self.not_before_height.is_none_or(|lock| height >= lock)
+    }
+
+    pub fn is_alias_registration(&self) -> bool {
+        self.alias.is_some()
+    }
+
     pub fn is_system(&self) -> bool {
         self.sender == "SYSTEM"
     }
 
+    /// True for mints that credit a cross-shard receipt.
+    pub fn is_receipt_mint(&self) -> bool {
+        self.sender == SYSTEM_XSHARD
+    }
+
+    /// Source-shard tx id settled by this mint, if it is one.
+    pub fn receipt_source_id(&self) -> Option<&str> {
+        if !self.is_receipt_mint() {
+            return None;
+        }
+        self.id.strip_prefix(XSHARD_TX_PREFIX)
+    }
+
     /// Validates structure, signature, and addresses.
     pub fn validate(&self) -> Result<(), String> {
         if self.id.is_empty() {
@@ -59,13 +157,40 @@ impl Transaction {
         if self.receiver.is_empty() {
             return Err("Receiver is empty".into());
         }
-        if self.amount == 0 && !self.is_system() {
+        if self.amount == 0 && !self.is_system() && !self.is_alias_registration() {
             return Err("Amount must be greater than zero".into());
         }
+        if let Some(alias) = &self.alias {
+            validate_alias(alias)?;
+            if self.amount != 0 || self.receiver != self.sender || !self.outputs.is_empty() {
+                return Err("Alias registrations cannot transfer coins".into());
+            }
+        }
+
+        if self
+            .memo
+            .as_ref()
+            .is_some_and(|memo| memo.len() > MAX_MEMO_BYTES)
+        {
+            return Err(format!("Memo exceeds {} bytes", MAX_MEMO_BYTES));
+        }
+        if !self.outputs.is_empty() {
+            if self.is_receipt_mint() {
+                return Err("Cross-shard mints cannot have outputs".into());
+            }
+            // A coinbase may split its payout with the fee treasury
+            self.validate_outputs()?;
+        }
 
         if self.is_system() {
             return self.validate_system_signature();
         }
+        if self.is_receipt_mint() {
+            if self.signature != SYSTEM_SIG_RECEIPT || self.receipt_source_id().is_none() {
+                return Err("Invalid cross-shard mint".into());
+            }
+            return Ok(());
+        }
 
         self.receiver
             .parse::<libp2p::PeerId>()
@@ -100,6 +225,37 @@ impl Transaction {
         Ok(())
     }
 
+    /// Batch outputs: valid receivers, non-zero amounts, consistent header fields.
+    fn validate_outputs(&self) -> Result<(), String> {
+        if self.outputs.len() > MAX_TX_OUTPUTS {
+            return Err(format!(
+                "Too many outputs: {} (max {})",
+                self.outputs.len(),
+                MAX_TX_OUTPUTS
+            ));
+        }
+        let mut total = 0u64;
+        for (receiver, amount) in &self.outputs {
+            receiver
+                .parse::<libp2p::PeerId>()
+                .map_err(|_| format!("Invalid output receiver: {}", receiver))?;
+            if *amount == 0 {
+                return Err("Output amount must be greater than zero".into());
+            }
+            total = total.checked_add(*amount).ok_or("Output total overflows")?;
+        }
+        if self.amount != total {
+            return Err(format!(
+                "Amount {} does not match the outputs' total {}",
+                self.amount, total
+            ));
+        }
+        if self.receiver != self.outputs[0].0 {
+            return Err("Receiver must be the first output".into());
+        }
+        Ok(())
+    }
+
     fn validate_system_signature(&self) -> Result<(), String> {
         match self.signature.as_str() {
             SYSTEM_SIG_GENESIS | SYSTEM_SIG_REWARD => Ok(()),
@@ -107,7 +263,10 @@ impl Transaction {
                 // Legacy blocks created before Phase 1
                 Ok(())
             }
-            _ => Err(format!("Invalid SYSTEM transaction signature: {}", self.signature)),
+            _ => Err(format!(
+                "Invalid SYSTEM transaction signature: {}",
+                self.signature
+            )),
         }
     }
 
@@ -120,6 +279,73 @@ impl Transaction {
     }
 }
 
+/// Sender-signed request to drop a pending transaction from every mempool.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TxCancellation {
+    pub tx_id: String,
+    pub sender: String,
+    /// Hex-encoded protobuf public key of `sender`
+    pub sender_pubkey: String,
+    /// Hex-encoded signature over [`signing_payload`](TxCancellation::signing_payload)
+    pub signature: String,
+}
+
+impl TxCancellation {
+    /// Cancellation of `tx_id`, signed by its sender's `keypair`.
+    pub fn signed(tx_id: &str, keypair: &Keypair) -> Result<Self, String> {
+        let mut cancel = TxCancellation {
+            tx_id: tx_id.to_string(),
+            sender: keypair.public().to_peer_id().to_string(),
+            sender_pubkey: hex::encode(keypair.public().encode_protobuf()),
+            signature: String::new(),
+        };
+        let sig = keypair
+            .sign(&cancel.signing_payload())
+            .map_err(|e| format!("Signing failed: {e}"))?;
+        cancel.signature = hex::encode(sig);
+        Ok(cancel)
+    }
+
+    pub fn signing_payload(&self) -> Vec<u8> {
+        format!("cancel|{}|{}", self.sender, self.tx_id).into_bytes()
+    }
+
+    /// Checks the signature and that the key belongs to `sender`.
+    pub fn verify(&self) -> Result<(), String> {
+        let pubkey_bytes = hex::decode(&self.sender_pubkey)
+            .map_err(|_| "Invalid sender_pubkey hex".to_string())?;
+        let public_key = PublicKey::try_decode_protobuf(&pubkey_bytes)
+            .map_err(|_| "Invalid sender_pubkey protobuf".to_string())?;
+        if public_key.to_peer_id().to_string() != self.sender {
+            return Err("sender_pubkey does not match sender PeerId".into());
+        }
+        let sig_bytes =
+            hex::decode(&self.signature).map_err(|_| "Invalid signature hex".to_string())?;
+        if !public_key.verify(&self.signing_payload(), &sig_bytes) {
+            return Err("Invalid cancellation signature".into());
+        }
+        Ok(())
+    }
+}
+
+/// Aliases are lowercase letters, digits, `-` and `_`, so they never parse
+/// as a PeerId and can stand in for one as a receiver.
+pub fn validate_alias(alias: &str) -> Result<(), String> {
+    if !(MIN_ALIAS_LEN..=MAX_ALIAS_LEN).contains(&alias.len()) {
+        return Err(format!(
+            "Alias must be {} to {} characters",
+            MIN_ALIAS_LEN, MAX_ALIAS_LEN
+        ));
+    }
+    if !alias
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        return Err("Alias may only contain a-z, 0-9, '-' and '_'".into());
+    }
+    Ok(())
+}
+
 /// Calculates transaction fee (0.01%, minimum 0.001 AGT)
 pub fn calculate_fee(amount: u64) -> u64 {
     let fee = (amount as f64 * 0.0001).ceil() as u64;
@@ -134,7 +360,10 @@ mod tests {
     fn sign_and_verify_roundtrip() {
         let keypair = Keypair::generate_ed25519();
         let sender = keypair.public().to_peer_id().to_string();
-        let receiver = Keypair::generate_ed25519().public().to_peer_id().to_string();
+        let receiver = Keypair::generate_ed25519()
+            .public()
+            .to_peer_id()
+            .to_string();
 
         let mut tx = Transaction {
             id: uuid::Uuid::new_v4().to_string(),
@@ -143,8 +372,14 @@ mod tests {
             amount: 1_000_000,
             shard_id: 0,
             timestamp: 1_700_000_000,
+            nonce: 0,
+            fee: 0,
             signature: String::new(),
             sender_pubkey: String::new(),
+            outputs: Vec::new(),
+            memo: None,
+            not_before_height: None,
+            alias: None,
         };
 
         tx.sign_with_keypair(&keypair).unwrap();
@@ -153,4 +388,123 @@ mod tests {
         tx.amount += 1;
         assert!(tx.validate().is_err());
     }
+
+    #[test]
+    fn signing_payload_excludes_signature() {
+        let keypair = Keypair::generate_ed25519();
+        let mut tx = Transaction {
+            id: "tx-1".into(),
+            sender: keypair.public().to_peer_id().to_string(),
+            receiver: Keypair::generate_ed25519()
+                .public()
+                .to_peer_id()
+                .to_string(),
+            amount: 5_000,
+            shard_id: 0,
+            timestamp: 1_700_000_000,
+            nonce: 0,
+            fee: 0,
+            signature: String::new(),
+            sender_pubkey: String::new(),
+            outputs: Vec::new(),
+            memo: None,
+            not_before_height: None,
+            alias: None,
+        };
+
+        let unsigned_payload = tx.signing_payload();
+        tx.sign_with_keypair(&keypair).unwrap();
+        let first_sig = tx.signature.clone();
+
+        assert_eq!(tx.signing_payload(), unsigned_payload);
+        tx.sign_with_keypair(&keypair).unwrap();
+        assert_eq!(tx.signature, first_sig);
+    }
+
+    #[test]
+    fn id_commits_to_every_field() {
+        let tx = Transaction {
+            id: String::new(),
+            sender: "alice".into(),
+            receiver: "bob".into(),
+            amount: 5_000,
+            shard_id: 1,
+            timestamp: 1_700_000_000,
+            nonce: 3,
+            fee: 0,
+            signature: String::new(),
+            sender_pubkey: String::new(),
+            outputs: Vec::new(),
+            memo: None,
+            not_before_height: None,
+            alias: None,
+        };
+        let id = tx.compute_id();
+        assert_eq!(id.len(), 64);
+        assert_eq!(tx.clone().compute_id(), id);
+
+        let tampered: [fn(&mut Transaction); 11] = [
+            |t| t.sender.push('x'),
+            |t| t.receiver.push('x'),
+            |t| t.amount += 1,
+            |t| t.shard_id += 1,
+            |t| t.timestamp += 1,
+            |t| t.nonce += 1,
+            |t| t.fee += 1,
+            |t| t.outputs.push(("carol".into(), 1)),
+            |t| t.memo = Some("invoice-1".into()),
+            |t| t.not_before_height = Some(10),
+            |t| t.alias = Some("alice".into()),
+        ];
+        for tamper in tampered {
+            let mut t = tx.clone();
+            tamper(&mut t);
+            assert_ne!(t.compute_id(), id);
+        }
+    }
+
+    #[test]
+    fn memo_round_trips_and_is_signed() {
+        let keypair = Keypair::generate_ed25519();
+        let mut tx = Transaction {
+            id: String::new(),
+            sender: keypair.public().to_peer_id().to_string(),
+            receiver: Keypair::generate_ed25519()
+                .public()
+                .to_peer_id()
+                .to_string(),
+            amount: 5_000,
+            shard_id: 0,
+            timestamp: 1_700_000_000,
+            nonce: 0,
+            fee: 0,
+            signature: String::new(),
+            sender_pubkey: String::new(),
+            outputs: Vec::new(),
+            memo: Some("invoice #42 | user 7".into()),
+            not_before_height: None,
+            alias: None,
+        };
+        tx.id = tx.compute_id();
+        tx.sign_with_keypair(&keypair).unwrap();
+
+        let json: Transaction = serde_json::from_str(&serde_json::to_string(&tx).unwrap()).unwrap();
+        let binary: Transaction = bincode::deserialize(&bincode::serialize(&tx).unwrap()).unwrap();
+        for decoded in [json, binary] {
+            assert_eq!(decoded.memo.as_deref(), Some("invoice #42 | user 7"));
+            assert!(decoded.validate().is_ok());
+        }
+
+        let mut tampered = tx.clone();
+        tampered.memo = Some("invoice #43 | user 7".into());
+        assert!(tampered.validate().is_err());
+
+        tx.memo = Some("x".repeat(MAX_MEMO_BYTES + 1));
+        tx.id = tx.compute_id();
+        tx.sign_with_keypair(&keypair).unwrap();
+        assert_eq!(
+            tx.validate(),
+            Err(format!("Memo exceeds {} bytes", MAX_MEMO_BYTES))
+        );
+    }
 }
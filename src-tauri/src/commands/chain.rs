@@ -1,6 +1,18 @@
-use crate::chain::{Block, Transaction};
-use crate::state::AppState;
+use crate::chain::{
+    resolve_receiver, Block, Receipt, SyncRequest, SyncResponse, TokenomicsInfo, Transaction,
+    TxCancellation,
+};
+use crate::commands::error::CommandError;
+use crate::commands::wallet::has_encrypted_key;
+use crate::network::{sync::verify_body, P2PCommand};
+use crate::state::{AppState, NodeType};
+use crate::utils::constants::{
+    LIGHT_QUERY_TIMEOUT_SECS, PERFORMANCE_STATS_WINDOW, RICH_LIST_DEFAULT_LIMIT,
+    RICH_LIST_MAX_LIMIT,
+};
+use std::collections::BTreeMap;
 use std::sync::atomic::Ordering;
+use std::time::Duration;
 use tauri::State;
 
 #[derive(serde::Serialize)]
@@ -9,28 +21,119 @@ pub struct ChainStats {
     pub height: u64,
 }
 
+/// Throughput measured over recent blocks, to compare with the advertised
+/// `shard_tps_limit`.
+#[derive(serde::Serialize, Debug, PartialEq)]
+pub struct PerformanceStats {
+    /// Blocks the numbers were measured over
+    pub sample_blocks: usize,
+    /// Seconds between consecutive blocks, on average
+    pub avg_block_time: f64,
+    /// User transactions per second over the whole sample
+    pub tps: f64,
+    /// Highest transactions per second between two consecutive blocks
+    pub peak_tps: f64,
+}
+
+/// Load of one shard over recent blocks and in the mempool
+#[derive(serde::Serialize, Debug, PartialEq)]
+pub struct ShardMetrics {
+    pub shard_id: u16,
+    /// Blocks of the shard among the recent blocks
+    pub blocks: usize,
+    /// User transactions in those blocks
+    pub transactions: u64,
+    /// Realized TPS, measured like [`PerformanceStats::tps`]
+    pub tps: f64,
+    /// Pending transactions sent to the shard
+    pub mempool_depth: usize,
+}
+
+/// One holder on the rich list
+#[derive(serde::Serialize, Debug, PartialEq)]
+pub struct RichListEntry {
+    pub address: String,
+    pub balance: u64,
+}
+
+/// Chain totals for auditing issuance: the balances of all addresses should
+/// add up to the supply the emission schedule allows at `height`.
+#[derive(serde::Serialize, Debug, PartialEq)]
+pub struct ChainAudit {
+    pub height: u64,
+    pub total_transactions: u64,
+    pub burned_fees: u64,
+    /// Sum of every balance in the state
+    pub balances_total: u64,
+    /// Genesis supply plus block rewards up to `height`, less burned fees
+    pub expected_supply: u64,
+    /// `balances_total - expected_supply`; anything but zero means coins were
+    /// created or lost outside the schedule
+    pub discrepancy: i64,
+}
+
 #[derive(serde::Serialize)]
-pub struct TokenomicsInfo {
-    pub total_supply: u64,
-    pub max_supply: u64,
-    pub circulating_supply: u64,
-    pub remaining_supply: u64,
-    pub next_halving_at: u64,
-    pub blocks_until_halving: u64,
-    pub current_reward: u64,
-    pub halving_interval: u64,
+pub struct MempoolStatus {
+    pub size: usize,
+    pub capacity: usize,
+}
+
+/// True when this node keeps only headers and asks full peers for the rest.
+pub(crate) fn is_light_node(state: &AppState) -> bool {
+    *state.node_type.lock().unwrap() == NodeType::Light
+}
+
+/// Sends `request` to a connected peer through the P2P task and waits for its answer.
+pub(crate) async fn query_peer(
+    state: &AppState,
+    request: SyncRequest,
+) -> Result<SyncResponse, String> {
+    let sender = state
+        .p2p_cmd_sender
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("Node is not running")?;
+    let (reply, answer) = tokio::sync::oneshot::channel();
+    sender
+        .send(P2PCommand::QueryPeer { request, reply })
+        .await
+        .map_err(|_| "Node is not running")?;
+    tokio::time::timeout(Duration::from_secs(LIGHT_QUERY_TIMEOUT_SECS), answer)
+        .await
+        .map_err(|_| "Peer did not answer in time")?
+        .map_err(|_| "Node stopped before the peer answered")?
+}
+
+/// Fetches a block body from a peer, checked against the stored header.
+async fn fetch_block(state: &AppState, request: SyncRequest) -> Result<Option<Block>, String> {
+    match query_peer(state, request).await? {
+        SyncResponse::Block(Some(block)) => {
+            verify_body(&state.storage, &block)?;
+            Ok(Some(block))
+        }
+        SyncResponse::Block(None) => Ok(None),
+        _ => Err("Unexpected answer from peer".into()),
+    }
 }
 
 #[tauri::command]
-pub fn get_block(state: State<'_, AppState>, index: u64) -> Result<Option<Block>, String> {
+pub async fn get_block(state: State<'_, AppState>, index: u64) -> Result<Option<Block>, String> {
+    if is_light_node(&state) {
+        return fetch_block(&state, SyncRequest::GetBlock(index)).await;
+    }
     state.storage.get_block(index).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub fn get_block_by_hash(
+pub async fn get_block_by_hash(
     state: State<'_, AppState>,
     hash: String,
 ) -> Result<Option<Block>, String> {
+    if is_light_node(&state) {
+        let block = fetch_block(&state, SyncRequest::GetBlockByHash(hash.clone())).await?;
+        return Ok(block.filter(|block| block.hash == hash));
+    }
     state
         .storage
         .get_block_by_hash(&hash)
@@ -38,10 +141,17 @@ pub fn get_block_by_hash(
 }
 
 #[tauri::command]
-pub fn get_transaction(
+pub async fn get_transaction(
     state: State<'_, AppState>,
     id: String,
 ) -> Result<Option<(Transaction, Block)>, String> {
+    if is_light_node(&state) {
+        let block = fetch_block(&state, SyncRequest::GetTransaction(id.clone())).await?;
+        return Ok(block.and_then(|block| {
+            let tx = block.transactions.iter().find(|tx| tx.id == id)?.clone();
+            Some((tx, block))
+        }));
+    }
     state
         .storage
         .get_transaction_by_id(&id)
@@ -50,6 +160,12 @@ pub fn get_transaction(
 
 #[tauri::command]
 pub async fn get_latest_block(state: State<'_, AppState>) -> Result<Option<Block>, String> {
+    if is_light_node(&state) {
+        let Some(tip) = state.storage.get_header_tip().map_err(|e| e.to_string())? else {
+            return Ok(None);
+        };
+        return fetch_block(&state, SyncRequest::GetBlock(tip)).await;
+    }
     let latest_index = state
         .storage
         .get_latest_index()
@@ -101,6 +217,123 @@ pub fn get_chain_stats(state: State<'_, AppState>) -> Result<ChainStats, String>
     })
 }
 
+/// Block time and realized TPS from the timestamps and transaction counts of
+/// `blocks`, in any order. Coinbase and other system transactions don't count.
+pub(crate) fn performance_stats(blocks: &[Block]) -> PerformanceStats {
+    let mut blocks: Vec<&Block> = blocks.iter().collect();
+    blocks.sort_by_key(|b| b.index);
+    let user_txs = |b: &Block| b.transactions.iter().filter(|tx| !tx.is_system()).count();
+
+    let mut stats = PerformanceStats {
+        sample_blocks: blocks.len(),
+        avg_block_time: 0.0,
+        tps: 0.0,
+        peak_tps: 0.0,
+    };
+    if blocks.len() < 2 {
+        return stats;
+    }
+
+    // The first block's transactions were made before the sample starts
+    let (first, last) = (blocks[0], blocks[blocks.len() - 1]);
+    let span = last.timestamp.saturating_sub(first.timestamp).max(1) as f64;
+    let txs: usize = blocks[1..].iter().map(|b| user_txs(b)).sum();
+    stats.avg_block_time = span / (blocks.len() - 1) as f64;
+    stats.tps = txs as f64 / span;
+    for pair in blocks.windows(2) {
+        // Timestamps have second resolution, so back-to-back blocks count as one second
+        let interval = pair[1].timestamp.saturating_sub(pair[0].timestamp).max(1);
+        stats.peak_tps = stats
+            .peak_tps
+            .max(user_txs(pair[1]) as f64 / interval as f64);
+    }
+    stats
+}
+
+/// Splits `blocks` by shard and pairs them with each shard's mempool depth.
+/// Every shard in `0..active_shards` is listed, idle or not.
+pub(crate) fn shard_metrics(
+    blocks: Vec<Block>,
+    mempool_depths: &BTreeMap<u16, usize>,
+    active_shards: u16,
+) -> Vec<ShardMetrics> {
+    let mut by_shard: BTreeMap<u16, Vec<Block>> = (0..active_shards)
+        .map(|shard| (shard, Vec::new()))
+        .collect();
+    for block in blocks {
+        by_shard
+            .entry(block.shard_id as u16)
+            .or_default()
+            .push(block);
+    }
+    for shard in mempool_depths.keys() {
+        by_shard.entry(*shard).or_default();
+    }
+
+    by_shard
+        .into_iter()
+        .map(|(shard_id, blocks)| ShardMetrics {
+            shard_id,
+            blocks: blocks.len(),
+            transactions: blocks.iter().map(Block::user_transaction_count).sum(),
+            tps: performance_stats(&blocks).tps,
+            mempool_depth: mempool_depths.get(&shard_id).copied().unwrap_or(0),
+        })
+        .collect()
+}
+
+/// Average block time, realized TPS and peak TPS over the last `window` blocks.
+#[tauri::command]
+pub fn get_performance_stats(
+    state: State<'_, AppState>,
+    window: Option<usize>,
+) -> Result<PerformanceStats, String> {
+    let blocks = state
+        .storage
+        .get_recent_blocks(window.unwrap_or(PERFORMANCE_STATS_WINDOW))
+        .map_err(|e| e.to_string())?;
+    Ok(performance_stats(&blocks))
+}
+
+/// Blocks, transactions and TPS of every shard over the last `window`
+/// blocks, with the transactions pending for each.
+#[tauri::command]
+pub fn get_shard_metrics(
+    state: State<'_, AppState>,
+    window: Option<usize>,
+) -> Result<Vec<ShardMetrics>, String> {
+    let blocks = state
+        .storage
+        .get_recent_blocks(window.unwrap_or(PERFORMANCE_STATS_WINDOW))
+        .map_err(|e| e.to_string())?;
+    let active_shards = state.consensus.lock().unwrap().calculate_active_shards();
+    Ok(shard_metrics(
+        blocks,
+        &state.mempool.depth_by_shard(),
+        active_shards,
+    ))
+}
+
+/// Largest balances, richest first; `limit` defaults to
+/// `RICH_LIST_DEFAULT_LIMIT` and is capped at `RICH_LIST_MAX_LIMIT`.
+#[tauri::command]
+pub fn get_rich_list(
+    state: State<'_, AppState>,
+    limit: Option<usize>,
+) -> Result<Vec<RichListEntry>, String> {
+    let limit = limit
+        .unwrap_or(RICH_LIST_DEFAULT_LIMIT)
+        .min(RICH_LIST_MAX_LIMIT);
+    let top = state
+        .storage
+        .get_top_balances(limit)
+        .map_err(|e| e.to_string())?;
+    Ok(top
+        .into_iter()
+        .map(|(address, balance)| RichListEntry { address, balance })
+        .collect())
+}
+
 #[tauri::command]
 pub fn get_mined_blocks_count(state: State<'_, AppState>) -> u64 {
     let count = state.mined_by_me_count.load(Ordering::Relaxed);
@@ -118,29 +351,50 @@ pub fn get_mined_blocks_count(state: State<'_, AppState>) -> u64 {
     count
 }
 
+/// Sends `amount` to `receiver`, or, given `outputs`, one batch payment
+/// crediting each `(receiver, amount)` pair with a single fee on the total.
+/// An optional `memo` travels with the transaction for the receiver, and
+/// `not_before_height` keeps it from being mined before that block.
 #[tauri::command]
 pub fn submit_transaction(
     state: State<'_, AppState>,
     receiver: String,
     amount: u64,
-) -> Result<String, String> {
+    outputs: Option<Vec<(String, u64)>>,
+    memo: Option<String>,
+    not_before_height: Option<u64>,
+) -> Result<String, CommandError> {
+    // Receivers that are not addresses are looked up as on-chain aliases
+    let resolve = |receiver: &str| {
+        resolve_receiver(&state.storage, receiver).map_err(CommandError::InvalidAddress)
+    };
+    let outputs = outputs
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(receiver, amount)| Ok((resolve(&receiver)?, amount)))
+        .collect::<Result<Vec<_>, CommandError>>()?;
+    let memo = memo.filter(|memo| !memo.is_empty());
+    let (receiver, amount) = match outputs.first() {
+        Some((first, _)) => {
+            let total = outputs
+                .iter()
+                .try_fold(0u64, |sum, (_, amount)| sum.checked_add(*amount))
+                .ok_or("Output total overflows")?;
+            (first.clone(), total)
+        }
+        None => (resolve(&receiver)?, amount),
+    };
+
     let wallet_guard = state.wallet.lock().unwrap();
 
     // Check Peer Count
     if state.peer_count.load(Ordering::Relaxed) == 0 {
-        return Err("Not connected to network (0 peers). Try restarting or wait.".to_string());
+        return Err(CommandError::NotConnected);
     }
 
-    // Validate Address
-    if let Err(_) = receiver.parse::<libp2p::PeerId>() {
-        return Err("Invalid receiver address. Address must be a valid Network Identity (e.g., starts with 12D3...)".to_string());
-    }
+    // Amount, self-send and receiver address are checked by the mempool
 
     if let Some(wallet) = wallet_guard.as_ref() {
-        if receiver == wallet.address {
-            return Err("You cannot send coins to your own address.".to_string());
-        }
-
         // Fee Logic
         let dynamic_fee = crate::chain::calculate_fee(amount);
         let balance = state
@@ -155,24 +409,29 @@ pub fn submit_transaction(
         // Check Balance
         let total_required = amount.saturating_add(dynamic_fee);
         if total_required > effective_balance {
-            let divisor = crate::utils::constants::ONE_AGT as f64;
-            return Err(format!(
-                "Insufficient funds. Balance: {:.6} AGT (Pending spent: {:.6}), Required: {:.6} AGT",
-                balance as f64 / divisor,
-                pending_spend as f64 / divisor,
-                total_required as f64 / divisor
-            ));
+            return Err(CommandError::InsufficientFunds {
+                balance: effective_balance,
+                required: total_required,
+            });
         }
 
         // Calculate Shard ID for the user transaction
         let shard_id = {
             let consensus = state.consensus.lock().unwrap();
-            consensus.get_assigned_shard(&wallet.address, 0)
+            let shard_id = consensus.get_assigned_shard(&wallet.address, 0);
+            // Batch payments settle within the sender's shard
+            if outputs
+                .iter()
+                .any(|(receiver, _)| consensus.get_assigned_shard(receiver, 0) != shard_id)
+            {
+                return Err("All outputs of a batch payment must be in your shard".into());
+            }
+            shard_id
         };
 
         // Create and sign transaction
         let mut tx = Transaction {
-            id: uuid::Uuid::new_v4().to_string(),
+            id: String::new(),
             sender: wallet.address.clone(),
             receiver,
             amount,
@@ -181,14 +440,22 @@ pub fn submit_transaction(
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap()
                 .as_secs(),
+            nonce: state.mempool.next_nonce(&wallet.address),
+            fee: dynamic_fee,
             signature: String::new(),
             sender_pubkey: String::new(),
+            outputs,
+            memo,
+            not_before_height,
+            alias: None,
         };
 
-        let keypair = wallet.get_keypair();
+        tx.id = tx.compute_id();
+
+        let keypair = wallet.get_keypair()?;
         tx.sign_with_keypair(&keypair)?;
 
-        state.mempool.add_transaction(tx.clone()).map_err(|e| e)?;
+        state.mempool.add_transaction(tx.clone())?;
 
         // Broadcast to P2P
         let sender_guard = state.tx_sender.lock().unwrap();
@@ -199,9 +466,133 @@ pub fn submit_transaction(
         }
 
         Ok(tx.id)
+    } else if has_encrypted_key(&state.storage) {
+        // The wallet exists but has not been unlocked
+        Err(CommandError::WalletLocked)
     } else {
-        Err("No wallet".to_string())
+        Err(CommandError::NoWallet)
+    }
+}
+
+/// Re-signs a stuck pending transaction with a higher fee (replace-by-fee).
+/// Without `fee`, the smallest accepted bump is used.
+#[tauri::command]
+pub fn replace_transaction(
+    state: State<'_, AppState>,
+    tx_id: String,
+    fee: Option<u64>,
+) -> Result<String, String> {
+    let wallet_guard = state.wallet.lock().unwrap();
+    let wallet = wallet_guard.as_ref().ok_or("No wallet")?;
+
+    let original = state
+        .mempool
+        .get_pending_transactions()
+        .into_iter()
+        .find(|tx| tx.id == tx_id)
+        .ok_or("Transaction is not pending")?;
+    if original.sender != wallet.address {
+        return Err("Only your own transactions can be replaced".to_string());
+    }
+
+    let bump = 100 + state.mempool.min_fee_bump_percent;
+    let min_fee = original
+        .fee()
+        .saturating_mul(bump)
+        .div_ceil(100)
+        .max(original.fee() + 1);
+
+    let mut tx = original;
+    tx.fee = fee.unwrap_or(min_fee);
+    tx.id = tx.compute_id();
+    tx.sign_with_keypair(&wallet.get_keypair()?)?;
+
+    state.mempool.add_transaction(tx.clone())?;
+
+    let sender_guard = state.tx_sender.lock().unwrap();
+    if let Some(sender) = sender_guard.as_ref() {
+        if let Err(e) = sender.try_send(tx.clone()) {
+            log::error!("Broadcast Channel Error: {}", e);
+        }
+    }
+
+    Ok(tx.id)
+}
+
+/// Pulls a pending transaction of the local wallet back out of the mempool
+/// and asks peers to do the same; later nonces of the wallet go with it.
+/// Returns the ids of the dropped transactions.
+#[tauri::command]
+pub fn cancel_transaction(
+    state: State<'_, AppState>,
+    tx_id: String,
+) -> Result<Vec<String>, String> {
+    let wallet_guard = state.wallet.lock().unwrap();
+    let wallet = wallet_guard.as_ref().ok_or("No wallet")?;
+
+    let cancel = TxCancellation::signed(&tx_id, &wallet.get_keypair()?)?;
+    let dropped = state.mempool.cancel_transaction(&cancel)?;
+
+    if let Some(sender) = state.p2p_cmd_sender.lock().unwrap().as_ref() {
+        let cmd = crate::network::P2PCommand::BroadcastCancellation(cancel);
+        if let Err(e) = sender.try_send(cmd) {
+            log::error!("Broadcast Channel Error: {}", e);
+        }
+    }
+
+    Ok(dropped.into_iter().map(|tx| tx.id).collect())
+}
+
+/// Claims `alias` on-chain for the local wallet so others can send to the
+/// name; the first registration to be mined wins. Returns the tx id.
+#[tauri::command]
+pub fn register_alias(state: State<'_, AppState>, alias: String) -> Result<String, String> {
+    let wallet_guard = state.wallet.lock().unwrap();
+    let wallet = wallet_guard.as_ref().ok_or("No wallet")?;
+
+    let shard_id = state
+        .consensus
+        .lock()
+        .unwrap()
+        .get_assigned_shard(&wallet.address, 0);
+    let mut tx = Transaction {
+        id: String::new(),
+        sender: wallet.address.clone(),
+        receiver: wallet.address.clone(),
+        amount: 0,
+        shard_id,
+        timestamp: crate::utils::unix_now(),
+        nonce: state.mempool.next_nonce(&wallet.address),
+        fee: crate::chain::calculate_fee(0),
+        signature: String::new(),
+        sender_pubkey: String::new(),
+        outputs: Vec::new(),
+        memo: None,
+        not_before_height: None,
+        alias: Some(alias),
+    };
+    tx.id = tx.compute_id();
+    tx.sign_with_keypair(&wallet.get_keypair()?)?;
+
+    state.mempool.add_transaction(tx.clone())?;
+
+    let sender_guard = state.tx_sender.lock().unwrap();
+    if let Some(sender) = sender_guard.as_ref() {
+        if let Err(e) = sender.try_send(tx.clone()) {
+            log::error!("Broadcast Channel Error: {}", e);
+        }
     }
+
+    Ok(tx.id)
+}
+
+/// Address registered under `alias`, if any.
+#[tauri::command]
+pub fn resolve_alias(state: State<'_, AppState>, alias: String) -> Result<Option<String>, String> {
+    state
+        .storage
+        .resolve_alias(&alias)
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -209,6 +600,32 @@ pub fn get_mempool_transactions(state: State<'_, AppState>) -> Vec<Transaction>
     state.mempool.get_pending_transactions()
 }
 
+/// Suggested fee for sending `amount` within `target_blocks` blocks, given
+/// how much is already waiting in the mempool.
+#[tauri::command]
+pub fn estimate_fee(
+    state: State<'_, AppState>,
+    amount: u64,
+    target_blocks: u64,
+) -> crate::consensus::mempool::FeeEstimate {
+    state.mempool.estimate_fee(amount, target_blocks)
+}
+
+#[tauri::command]
+pub fn get_mempool_status(state: State<'_, AppState>) -> MempoolStatus {
+    MempoolStatus {
+        size: state.mempool.len(),
+        capacity: state.mempool.capacity(),
+    }
+}
+
+/// Cross-shard receipts this node generated or is minting, newest first,
+/// with their status.
+#[tauri::command]
+pub fn get_pending_receipts(state: State<'_, AppState>) -> Vec<Receipt> {
+    state.mempool.get_tracked_receipts()
+}
+
 #[tauri::command]
 pub fn reset_chain_data(state: State<'_, AppState>) -> Result<(), String> {
     state.storage.reset_blocks().map_err(|e| e.to_string())?;
@@ -218,27 +635,108 @@ pub fn reset_chain_data(state: State<'_, AppState>) -> Result<(), String> {
     Ok(())
 }
 
+/// Balance of `address` as it stood at block `height`.
+#[tauri::command]
+pub fn get_balance_at_height(
+    state: State<'_, AppState>,
+    address: String,
+    height: u64,
+) -> Result<u64, String> {
+    state
+        .storage
+        .balance_at_height(&address, height)
+        .map_err(|e| e.to_string())
+}
+
+#[derive(serde::Serialize)]
+pub struct AddressTransaction {
+    pub transaction: Transaction,
+    pub block_index: u64,
+}
+
+/// Transactions sent or received by `address`, newest first. `page` is 0-based.
+#[tauri::command]
+pub fn get_address_transactions(
+    state: State<'_, AppState>,
+    address: String,
+    page: usize,
+    limit: usize,
+) -> Result<Vec<AddressTransaction>, String> {
+    let txs = state
+        .storage
+        .get_transactions_for_address(&address, page, limit)
+        .map_err(|e| e.to_string())?;
+    Ok(txs
+        .into_iter()
+        .map(|(transaction, block_index)| AddressTransaction {
+            transaction,
+            block_index,
+        })
+        .collect())
+}
+
+/// Rebuilds balances from the stored blocks; returns the new state root.
+#[tauri::command]
+pub fn recompute_state(state: State<'_, AppState>) -> Result<String, String> {
+    state.storage.recompute_state().map_err(|e| e.to_string())
+}
+
+/// Writes the chain to a JSONL backup at `path`; returns the number of blocks.
+#[tauri::command]
+pub fn export_chain(state: State<'_, AppState>, path: String) -> Result<u64, String> {
+    crate::chain::snapshot::export_chain(&state.storage, std::path::Path::new(&path))
+}
+
+/// Validates and applies a backup made by [`export_chain`]. The node must be stopped.
+#[tauri::command]
+pub fn import_chain(state: State<'_, AppState>, path: String) -> Result<u64, String> {
+    if state.is_running.load(Ordering::Relaxed) {
+        return Err("Stop the node before importing a chain".into());
+    }
+    let imported =
+        crate::chain::snapshot::import_chain(&state.storage, std::path::Path::new(&path))?;
+    let height = state
+        .storage
+        .get_latest_index()
+        .map_err(|e| e.to_string())?;
+    state.chain_index.store(height, Ordering::Relaxed);
+    Ok(imported)
+}
+
 #[tauri::command]
 pub fn get_tokenomics_info(state: State<'_, AppState>) -> TokenomicsInfo {
     let height = state.chain_index.load(Ordering::Relaxed);
-    // Standard Halving Logic
-    let current_interval = height / crate::utils::constants::HALVING_INTERVAL;
-    let next_halving = (current_interval + 1) * crate::utils::constants::HALVING_INTERVAL;
-
-    let halving_interval = crate::utils::constants::HALVING_INTERVAL;
-
-    let circulating = crate::chain::calculate_circulating_supply(height);
-
-    TokenomicsInfo {
-        total_supply: crate::utils::constants::TOTAL_SUPPLY,
-        max_supply: crate::utils::constants::TOTAL_SUPPLY,
-        circulating_supply: circulating,
-        remaining_supply: crate::utils::constants::TOTAL_SUPPLY.saturating_sub(circulating),
-        next_halving_at: next_halving,
-        blocks_until_halving: next_halving.saturating_sub(height),
-        current_reward: crate::chain::calculate_mining_reward(height),
-        halving_interval,
+    let burned = state.storage.get_burned_fees().unwrap_or(0);
+    TokenomicsInfo::at_height(height, burned)
+}
+
+#[tauri::command]
+pub fn get_chain_audit(state: State<'_, AppState>) -> Result<ChainAudit, String> {
+    let storage = &state.storage;
+    let height = storage.get_latest_index().map_err(|e| e.to_string())?;
+    let burned_fees = storage.get_burned_fees().map_err(|e| e.to_string())?;
+    let total_transactions = storage
+        .count_total_transactions()
+        .map_err(|e| e.to_string())?;
+    let balances_total = storage.sum_balances().map_err(|e| e.to_string())?;
+    let expected_supply = crate::chain::calculate_circulating_supply(height, burned_fees);
+    let discrepancy = balances_total as i64 - expected_supply as i64;
+    if discrepancy != 0 {
+        log::warn!(
+            "Supply audit at height {}: balances total {} but {} were issued",
+            height,
+            balances_total,
+            expected_supply
+        );
     }
+    Ok(ChainAudit {
+        height,
+        total_transactions,
+        burned_fees,
+        balances_total,
+        expected_supply,
+        discrepancy,
+    })
 }
 
 #[tauri::command]
@@ -276,3 +774,162 @@ pub fn get_consensus_status(state: State<'_, AppState>) -> crate::consensus::Nod
 
     consensus_guard.get_node_status(&peer_id)
 }
+
+/// Who produces the next `count` slots of this node's shard, capped at one epoch.
+#[tauri::command]
+pub fn get_leader_schedule(
+    state: State<'_, AppState>,
+    count: u64,
+) -> Result<Vec<crate::consensus::ScheduledSlot>, String> {
+    let consensus = state.consensus.lock().unwrap();
+    let peer_id = consensus
+        .local_peer_id
+        .clone()
+        .ok_or("Node is not running")?;
+    let count = count.min(crate::consensus::Consensus::slots_per_epoch());
+    Ok(consensus.leader_schedule(&peer_id, crate::utils::unix_now(), count))
+}
+
+/// Latest cross-linked block height of every shard, by shard id
+#[tauri::command]
+pub fn get_shard_finality(state: State<'_, AppState>) -> BTreeMap<u16, u64> {
+    state.consensus.lock().unwrap().beacon.finalized_heights()
+}
+
+/// Recorded cases of a block author producing two blocks for one slot
+#[tauri::command]
+pub fn get_equivocations(
+    state: State<'_, AppState>,
+) -> Result<Vec<crate::consensus::EquivocationEvidence>, String> {
+    state
+        .storage
+        .load_equivocations()
+        .map_err(|e| e.to_string())
+}
+
+/// Equivocation evidence that was reported and led to a slash
+#[tauri::command]
+pub fn get_slashings(
+    state: State<'_, AppState>,
+) -> Result<Vec<crate::consensus::EquivocationEvidence>, String> {
+    state.storage.load_slashings().map_err(|e| e.to_string())
+}
+
+/// Slashes the author of two conflicting blocks it signed for one slot.
+/// Forged, identical or different-slot blocks are rejected.
+#[tauri::command]
+pub fn report_equivocation(
+    state: State<'_, AppState>,
+    first: Block,
+    second: Block,
+) -> Result<crate::consensus::EquivocationEvidence, String> {
+    let evidence = state
+        .consensus
+        .lock()
+        .unwrap()
+        .report_equivocation(&first, &second)?;
+    log::warn!(
+        "Equivocation reported: {} produced {} and {} for slot {}",
+        evidence.author,
+        evidence.first_hash,
+        evidence.second_hash,
+        evidence.slot
+    );
+    Ok(evidence)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tx(sender: &str, n: usize) -> Transaction {
+        Transaction {
+            id: format!("{}-{}", sender, n),
+            sender: sender.into(),
+            receiver: "bob".into(),
+            amount: 1,
+            shard_id: 0,
+            timestamp: 0,
+            nonce: n as u64,
+            fee: 0,
+            signature: String::new(),
+            sender_pubkey: String::new(),
+            outputs: Vec::new(),
+            memo: None,
+            not_before_height: None,
+            alias: None,
+        }
+    }
+
+    fn block(index: u64, timestamp: u64, user_txs: usize) -> Block {
+        let mut transactions = vec![tx("SYSTEM", 0)];
+        transactions.extend((0..user_txs).map(|n| tx("alice", n)));
+        let mut block = Block::new(
+            index,
+            "miner".into(),
+            transactions,
+            String::new(),
+            0,
+            0,
+            0,
+            0,
+            0,
+        );
+        block.timestamp = timestamp;
+        block
+    }
+
+    #[test]
+    fn performance_stats_measure_recent_blocks() {
+        // Newest first, as storage returns them
+        let blocks = vec![
+            block(3, 110, 6),
+            block(2, 104, 10),
+            block(1, 102, 4),
+            block(0, 100, 5),
+        ];
+        let stats = performance_stats(&blocks);
+
+        assert_eq!(stats.sample_blocks, 4);
+        assert!((stats.avg_block_time - 10.0 / 3.0).abs() < 1e-9);
+        // 20 user txs after the first block, over 10 seconds
+        assert_eq!(stats.tps, 2.0);
+        // 10 txs two seconds after block 1
+        assert_eq!(stats.peak_tps, 5.0);
+
+        assert_eq!(performance_stats(&blocks[..1]).tps, 0.0);
+        assert_eq!(performance_stats(&[]).sample_blocks, 0);
+    }
+
+    #[test]
+    fn shard_metrics_count_each_shard_separately() {
+        let on_shard = |shard_id, index, timestamp, user_txs| {
+            let mut block = block(index, timestamp, user_txs);
+            block.shard_id = shard_id;
+            block
+        };
+        let blocks = vec![
+            on_shard(0, 2, 104, 8),
+            on_shard(1, 2, 104, 1),
+            on_shard(0, 1, 100, 3),
+            on_shard(1, 1, 102, 2),
+        ];
+        let depths = BTreeMap::from([(1, 40), (3, 2)]);
+        let metrics = shard_metrics(blocks, &depths, 3);
+
+        let ids: Vec<u16> = metrics.iter().map(|m| m.shard_id).collect();
+        assert_eq!(ids, vec![0, 1, 2, 3]);
+        assert_eq!(
+            (metrics[0].blocks, metrics[0].transactions, metrics[0].tps),
+            (2, 11, 2.0)
+        );
+        assert_eq!(
+            (metrics[0].mempool_depth, metrics[1].mempool_depth),
+            (0, 40)
+        );
+        assert_eq!((metrics[1].transactions, metrics[1].tps), (3, 0.5));
+        // Idle shards are listed too
+        assert_eq!((metrics[2].blocks, metrics[2].mempool_depth), (0, 0));
+        assert_eq!(metrics[3].mempool_depth, 2);
+    }
+}
@@ -0,0 +1,124 @@
+//! Errors returned to the frontend by Tauri commands.
+//!
+//! Serialized as `{ kind, message, ... }`: `kind` is a stable discriminant the
+//! UI can branch on, `message` is for display, and variants with data add their
+//! fields alongside.
+
+use crate::utils::constants::ONE_AGT;
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum CommandError {
+    #[error("No wallet loaded")]
+    NoWallet,
+    #[error("Wallet is locked; unlock it first")]
+    WalletLocked,
+    #[error(
+        "Insufficient funds. Available: {} AGT, Required: {} AGT",
+        agt(*.balance),
+        agt(*.required)
+    )]
+    InsufficientFunds { balance: u64, required: u64 },
+    #[error("Not connected to network (0 peers). Try restarting or wait.")]
+    NotConnected,
+    #[error("{0}")]
+    InvalidAddress(String),
+    #[error("{0}")]
+    InvalidKey(String),
+    #[error("Storage error: {0}")]
+    StorageError(String),
+    /// Anything the UI has no special handling for
+    #[error("{0}")]
+    Other(String),
+}
+
+impl CommandError {
+    /// Stable name of the variant, sent as `kind`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CommandError::NoWallet => "NoWallet",
+            CommandError::WalletLocked => "WalletLocked",
+            CommandError::InsufficientFunds { .. } => "InsufficientFunds",
+            CommandError::NotConnected => "NotConnected",
+            CommandError::InvalidAddress(_) => "InvalidAddress",
+            CommandError::InvalidKey(_) => "InvalidKey",
+            CommandError::StorageError(_) => "StorageError",
+            CommandError::Other(_) => "Other",
+        }
+    }
+}
+
+/// Atomic units as AGT with six decimals.
+fn agt(amount: u64) -> String {
+    format!("{:.6}", amount as f64 / ONE_AGT as f64)
+}
+
+impl Serialize for CommandError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("kind", self.kind())?;
+        map.serialize_entry("message", &self.to_string())?;
+        if let CommandError::InsufficientFunds { balance, required } = self {
+            map.serialize_entry("balance", balance)?;
+            map.serialize_entry("required", required)?;
+        }
+        map.end()
+    }
+}
+
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        CommandError::Other(message)
+    }
+}
+
+impl From<&str> for CommandError {
+    fn from(message: &str) -> Self {
+        CommandError::Other(message.to_string())
+    }
+}
+
+impl From<anyhow::Error> for CommandError {
+    fn from(e: anyhow::Error) -> Self {
+        CommandError::StorageError(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn serializes_kind_and_message() {
+        assert_eq!(
+            serde_json::to_value(CommandError::NotConnected).unwrap(),
+            json!({
+                "kind": "NotConnected",
+                "message": "Not connected to network (0 peers). Try restarting or wait.",
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(CommandError::InvalidKey("Invalid hex: odd length".into()))
+                .unwrap(),
+            json!({ "kind": "InvalidKey", "message": "Invalid hex: odd length" })
+        );
+    }
+
+    #[test]
+    fn insufficient_funds_carries_the_amounts() {
+        let error = CommandError::InsufficientFunds {
+            balance: ONE_AGT / 2,
+            required: 2 * ONE_AGT,
+        };
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            json!({
+                "kind": "InsufficientFunds",
+                "message": "Insufficient funds. Available: 0.500000 AGT, Required: 2.000000 AGT",
+                "balance": ONE_AGT / 2,
+                "required": 2 * ONE_AGT,
+            })
+        );
+    }
+}
@@ -9,14 +9,13 @@ pub fn greet(name: &str) -> String {
 
 #[tauri::command]
 pub fn get_app_settings(state: State<'_, AppState>) -> AppSettings {
-    match state.storage.get_setting("app_settings") {
-        Ok(Some(json)) => serde_json::from_str(&json).unwrap_or_default(),
-        _ => AppSettings::default(),
-    }
+    AppSettings::load(&state.storage)
 }
 
 #[tauri::command]
 pub fn save_app_settings(state: State<'_, AppState>, settings: AppSettings) -> Result<(), String> {
+    settings.validate()?;
+
     // Get the current mining status before update
     let old_mining = state.mining_enabled.load(Ordering::Relaxed);
     let new_mining = settings.mining_enabled;
@@ -53,6 +52,10 @@ pub fn save_app_settings(state: State<'_, AppState>, settings: AppSettings) -> R
         let mut nt = state.node_type.lock().unwrap();
         *nt = settings.node_type.clone();
     }
+    state
+        .storage
+        .set_min_free_disk(settings.min_free_disk_mb.saturating_mul(1024 * 1024));
+    state.consensus.lock().unwrap().quarantine = settings.quarantine_policy();
 
     let json = serde_json::to_string(&settings).map_err(|e| e.to_string())?;
     state
@@ -62,6 +65,21 @@ pub fn save_app_settings(state: State<'_, AppState>, settings: AppSettings) -> R
     Ok(())
 }
 
+/// Filter the logger is running with
+#[tauri::command]
+pub fn get_log_level() -> String {
+    crate::utils::logging::log_level()
+}
+
+/// Switches the log level (e.g. to `debug` while chasing a sync issue)
+/// without restarting the app
+#[tauri::command]
+pub fn set_log_level(level: String) -> Result<(), String> {
+    crate::utils::logging::set_log_level(&level)?;
+    log::info!("Log level set to {}", level.trim());
+    Ok(())
+}
+
 #[tauri::command]
 pub fn exit_app() {
     std::process::exit(0);
@@ -1,4 +1,5 @@
 pub mod chain;
+pub mod error;
 pub mod general;
 pub mod network;
 pub mod node;
@@ -1,18 +1,22 @@
+use crate::consensus::Consensus;
+use crate::network::commands::{parse_peer_address, PeerInfo};
+use crate::network::peer_score::PeerScoreInfo;
+use crate::network::P2PCommand;
 use crate::state::AppState;
+use crate::storage::PeerRecord;
+use libp2p::{Multiaddr, PeerId};
+use std::collections::HashMap;
 use tauri::State;
 
-#[derive(serde::Serialize, Clone)]
-pub struct PeerInfo {
-    pub peer_id: String,
-    pub trust_score: f64,
-    pub is_verified: bool,
-    pub latency: u64,
-    pub addresses: Vec<String>,
-}
-
 #[tauri::command]
 pub fn get_network_info(state: State<'_, AppState>) -> Vec<PeerInfo> {
     let consensus = state.consensus.lock().unwrap();
+    let latencies = state.peer_latencies.lock().unwrap();
+    peer_infos(&consensus, &latencies)
+}
+
+/// Known nodes with their last ping latency in milliseconds (0 if never pinged)
+fn peer_infos(consensus: &Consensus, latencies: &HashMap<String, u64>) -> Vec<PeerInfo> {
     consensus
         .nodes
         .values()
@@ -20,12 +24,95 @@ pub fn get_network_info(state: State<'_, AppState>) -> Vec<PeerInfo> {
             peer_id: n.peer_id.clone(),
             trust_score: n.trust_score,
             is_verified: n.is_verified,
-            latency: 0,
+            latency: latencies.get(&n.peer_id).copied().unwrap_or(0),
             addresses: n.addresses.clone(),
         })
         .collect()
 }
 
+/// Hands `cmd` to the running P2P node.
+async fn send_p2p_command(state: &AppState, cmd: P2PCommand) -> Result<(), String> {
+    let sender = state
+        .p2p_cmd_sender
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("Node is not running")?;
+    sender
+        .send(cmd)
+        .await
+        .map_err(|_| "Node is not running".into())
+}
+
+/// Dials `address` directly, for when discovery does not find a peer.
+#[tauri::command]
+pub async fn dial_peer(state: State<'_, AppState>, address: String) -> Result<(), String> {
+    let address: Multiaddr = address
+        .parse()
+        .map_err(|e| format!("Invalid multiaddr: {}", e))?;
+    send_p2p_command(&state, P2PCommand::DialPeer(address)).await
+}
+
+/// Connects to a known node by its full address (ending in `/p2p/<peer id>`)
+/// and remembers it, so it is dialed again on every start.
+#[tauri::command]
+pub async fn add_peer(state: State<'_, AppState>, multiaddr: String) -> Result<(), String> {
+    let (peer, address) = parse_peer_address(&multiaddr)?;
+    // Stored like identified listen addresses, without the peer id
+    let mut transport = address.clone();
+    transport.pop();
+    let record = PeerRecord {
+        peer_id: peer.to_string(),
+        addresses: vec![transport.to_string()],
+        last_seen: crate::utils::unix_now(),
+        manual: true,
+    };
+    state
+        .storage
+        .save_peer(&record)
+        .map_err(|e| format!("Failed to save peer: {}", e))?;
+
+    // A stopped node dials it on the next start
+    if state.p2p_cmd_sender.lock().unwrap().is_none() {
+        return Ok(());
+    }
+    send_p2p_command(&state, P2PCommand::AddPeer(address)).await
+}
+
+#[tauri::command]
+pub async fn disconnect_peer(state: State<'_, AppState>, peer_id: String) -> Result<(), String> {
+    let peer: PeerId = peer_id
+        .parse()
+        .map_err(|_| format!("Invalid peer id: {}", peer_id))?;
+    send_p2p_command(&state, P2PCommand::DisconnectPeer(peer)).await
+}
+
+/// Peers the swarm holds a connection to right now.
+#[tauri::command]
+pub async fn get_connected_peers(state: State<'_, AppState>) -> Result<Vec<PeerInfo>, String> {
+    let (reply, peers) = tokio::sync::oneshot::channel();
+    send_p2p_command(&state, P2PCommand::GetConnectedPeers(reply)).await?;
+    peers
+        .await
+        .map_err(|_| "Node stopped before answering".into())
+}
+
+/// Current peer -> connections graph, including this node's own connections
+#[tauri::command]
+pub fn get_network_topology(state: State<'_, AppState>) -> HashMap<String, Vec<String>> {
+    state.network_graph.lock().unwrap().clone()
+}
+
+/// Misbehaviour scores of tracked peers, lowest (and banned) first
+#[tauri::command]
+pub fn get_peer_scores(state: State<'_, AppState>) -> Vec<PeerScoreInfo> {
+    state
+        .peer_scores
+        .lock()
+        .unwrap()
+        .snapshot(std::time::Instant::now())
+}
+
 #[derive(serde::Serialize)]
 pub struct SelfNodeInfo {
     pub peer_id: String,
@@ -64,3 +151,91 @@ pub fn get_self_node_info(state: State<'_, AppState>) -> Option<SelfNodeInfo> {
         }
     })
 }
+
+#[derive(serde::Serialize, Debug, PartialEq)]
+pub struct AddressShard {
+    pub address: String,
+    pub shard_id: u16,
+    pub total_shards: u16,
+    /// Whether the unlocked wallet sends from the same shard; None while locked
+    pub same_shard_as_sender: Option<bool>,
+}
+
+/// Shard an address settles on. Transfers to another shard than the sender's
+/// go through a cross-shard receipt and may take an extra block.
+#[tauri::command]
+pub fn get_shard_for_address(state: State<'_, AppState>, address: String) -> AddressShard {
+    let sender = state
+        .wallet
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|w| w.address.clone());
+    let consensus = state.consensus.lock().unwrap();
+    address_shard(&consensus, address, sender.as_deref())
+}
+
+fn address_shard(consensus: &Consensus, address: String, sender: Option<&str>) -> AddressShard {
+    // Transactions and receipts use the epoch 0 assignment
+    let shard_id = consensus.get_assigned_shard(&address, 0);
+    AddressShard {
+        same_shard_as_sender: sender.map(|s| consensus.get_assigned_shard(s, 0) == shard_id),
+        total_shards: consensus.calculate_active_shards(),
+        shard_id,
+        address,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::p2p::record_ping;
+    use libp2p::identity::Keypair;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    #[test]
+    fn network_info_reports_ping_latency() {
+        let peer = Keypair::generate_ed25519().public().to_peer_id();
+        let mut consensus = Consensus::new();
+        consensus.register_node(peer.to_string());
+        let latencies = Mutex::new(HashMap::new());
+
+        let latency = |latencies: &Mutex<HashMap<String, u64>>| {
+            peer_infos(&consensus, &latencies.lock().unwrap())[0].latency
+        };
+        assert_eq!(latency(&latencies), 0);
+
+        record_ping(&latencies, &peer, &Ok(Duration::from_millis(42)));
+        assert_eq!(latency(&latencies), 42);
+
+        record_ping(&latencies, &peer, &Err(libp2p::ping::Failure::Timeout));
+        assert_eq!(latency(&latencies), 0);
+    }
+
+    #[test]
+    fn address_shard_is_stable_for_a_validator_count() {
+        let mut consensus = Consensus::new();
+        for i in 0..120 {
+            consensus.register_node(format!("validator-{i}"));
+        }
+        let address = Keypair::generate_ed25519()
+            .public()
+            .to_peer_id()
+            .to_string();
+
+        let info = address_shard(&consensus, address.clone(), None);
+        assert_eq!(info.total_shards, 2);
+        assert!(info.shard_id < info.total_shards);
+        assert_eq!(info.same_shard_as_sender, None);
+        assert_eq!(address_shard(&consensus, address.clone(), None), info);
+
+        // Same validator count, different validators: same assignment
+        consensus.nodes.remove("validator-0");
+        consensus.register_node("validator-new".to_string());
+        assert_eq!(address_shard(&consensus, address.clone(), None), info);
+
+        let own = address_shard(&consensus, address.clone(), Some(&address));
+        assert_eq!(own.same_shard_as_sender, Some(true));
+    }
+}
@@ -1,3 +1,5 @@
+use crate::network::P2PCommand;
+use crate::node::shutdown::{self, SHUTDOWN_DRAIN};
 use crate::state::AppState;
 use std::sync::atomic::Ordering;
 use tauri::{AppHandle, State};
@@ -10,11 +12,34 @@ pub async fn start_node(
     crate::node::manager::start_node_service(app_handle, state).await
 }
 
+/// Stops the node after telling peers it is leaving and flushing storage.
 #[tauri::command]
-pub fn stop_node(state: State<'_, AppState>) -> Result<String, String> {
-    state.is_running.store(false, Ordering::Relaxed);
-    // Note: We don't necessarily need to increment run_id here since is_running=false is checked.
-    // But incrementing ensures double safety.
-    state.run_id.fetch_add(1, Ordering::Relaxed);
+pub async fn stop_node(state: State<'_, AppState>) -> Result<String, String> {
+    if state.is_running.load(Ordering::Relaxed) {
+        // Leave leader election first so no block is produced meanwhile
+        state.consensus.lock().unwrap().retire_local();
+
+        let sender = state.p2p_cmd_sender.lock().unwrap().clone();
+        if let Some(sender) = sender {
+            let (reply, announced) = tokio::sync::oneshot::channel();
+            if sender.try_send(P2PCommand::AnnounceLeaving(reply)).is_ok() {
+                let _ = tokio::time::timeout(SHUTDOWN_DRAIN, announced).await;
+                // Keep the swarm polling so the farewell actually goes out
+                tokio::time::sleep(SHUTDOWN_DRAIN).await;
+            }
+        }
+    }
+
+    shutdown::halt(&state.is_running, &state.run_id, &state.consensus);
+
+    state
+        .consensus
+        .lock()
+        .unwrap()
+        .persist_to_storage(&state.storage);
+    state
+        .storage
+        .flush()
+        .map_err(|e| format!("Failed to flush storage: {}", e))?;
     Ok("Node stopped".to_string())
 }
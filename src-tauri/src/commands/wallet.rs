@@ -1,55 +1,110 @@
+use crate::chain::{SyncRequest, SyncResponse};
+use crate::commands::chain::{is_light_node, query_peer};
+use crate::commands::error::CommandError;
 use crate::state::AppState;
+use crate::storage::Storage;
+use crate::wallet::encryption::{self, StoredKey};
+use crate::wallet::hd::{AccountInfo, HdRecord};
+use crate::wallet::keystore::Keystore;
 use crate::wallet::{self, Wallet};
 use rand::RngCore;
 use std::sync::atomic::Ordering;
 use tauri::State;
 
-#[tauri::command]
-pub fn create_wallet(state: State<'_, AppState>) -> Result<wallet::WalletExport, String> {
-    let mut wallet_guard = state.wallet.lock().unwrap();
+const NO_ACCOUNTS: &str = "Wallet was imported from a single key and has no accounts";
+
+/// Seals `secret` for storage, encrypted when a non-empty password is given.
+fn seal_secret(secret: &[u8], address: &str, password: Option<&str>) -> Result<StoredKey, String> {
+    Ok(match password.filter(|p| !p.is_empty()) {
+        Some(password) => StoredKey::Encrypted(encryption::encrypt_key(secret, address, password)?),
+        None => StoredKey::Plain(secret.to_vec()),
+    })
+}
+
+/// Persists the keypair, encrypted when a non-empty password is given.
+fn persist_wallet_key(
+    storage: &Storage,
+    wallet: &Wallet,
+    password: Option<&str>,
+) -> Result<(), String> {
+    let stored = seal_secret(&wallet.keypair, &wallet.address, password)?;
+    storage
+        .save_wallet_keys(&stored.to_json()?)
+        .map_err(|e| format!("Failed to save wallet: {}", e))
+}
+
+fn load_hd_record(storage: &Storage) -> Result<Option<HdRecord>, String> {
+    storage
+        .get_wallet_hd()
+        .map_err(|e| e.to_string())?
+        .map(|json| HdRecord::parse(&json))
+        .transpose()
+}
+
+fn save_hd_record(storage: &Storage, record: &HdRecord) -> Result<(), String> {
+    storage
+        .save_wallet_hd(&record.to_json()?)
+        .map_err(|e| format!("Failed to save wallet: {}", e))
+}
+
+/// Persists the seed of a new or restored HD wallet, starting with one account.
+fn persist_hd_seed(
+    storage: &Storage,
+    wallet: &Wallet,
+    password: Option<&str>,
+) -> Result<(), String> {
+    let seed = wallet.seed.as_deref().ok_or("Wallet has no seed")?;
+    let record = HdRecord {
+        seed: seal_secret(seed, &wallet.address, password)?,
+        accounts: 1,
+        active: 0,
+    };
+    save_hd_record(storage, &record)
+}
+
+/// Makes `wallet` the loaded wallet and recounts the blocks it mined.
+fn activate_wallet(state: &AppState, wallet: Wallet) -> String {
+    let address = wallet.address.clone();
+    *state.wallet.lock().unwrap() = Some(wallet);
+
+    let count = state.storage.count_blocks_by_author(&address).unwrap_or(0);
+    state.mined_by_me_count.store(count, Ordering::Relaxed);
+    address
+}
 
+fn loaded_wallet(state: &AppState) -> Result<Wallet, String> {
+    state
+        .wallet
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or_else(|| "No wallet loaded".to_string())
+}
+
+#[tauri::command]
+pub fn create_wallet(
+    state: State<'_, AppState>,
+    password: Option<String>,
+) -> Result<wallet::WalletExport, String> {
     // Generate Mnemonic (12 words) using 16 bytes of entropy
     let mut entropy = [0u8; 16];
     rand::thread_rng().fill_bytes(&mut entropy);
     let mnemonic = bip39::Mnemonic::from_entropy(&entropy).map_err(|e| e.to_string())?;
-    let seed = mnemonic.to_seed("");
 
-    // Derive keypair from seed (simplified for lab, using first 32 bytes)
-    let mut key_bytes = [0u8; 32];
-    key_bytes.copy_from_slice(&seed[0..32]);
-
-    let keypair = libp2p::identity::Keypair::ed25519_from_bytes(key_bytes).unwrap();
-    let peer_id = keypair.public().to_peer_id();
-    let address = peer_id.to_string();
-
-    let keypair_bytes = keypair.to_protobuf_encoding().unwrap();
-    let keys_json = serde_json::to_string(&keypair_bytes).unwrap();
-
-    // Save to DB
-    let _ = state.storage.save_wallet_keys(&keys_json);
+    // First account of the HD wallet behind the mnemonic
+    let new_wallet = Wallet::from_seed(mnemonic.to_seed("").to_vec(), 0)?;
 
     let export = wallet::WalletExport {
-        address: address.clone(),
-        private_key: hex::encode(&keypair_bytes),
+        address: new_wallet.address.clone(),
+        private_key: hex::encode(&new_wallet.keypair),
         mnemonic: mnemonic.to_string(),
     };
 
-    let new_wallet = Wallet {
-        start_timestamp: std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs(),
-        address: address.clone(),
-        alias: None,
-        keypair: keypair_bytes,
-    };
-
-    *wallet_guard = Some(new_wallet);
-
-    // Update mined blocks counter for the new wallet
-    let count = state.storage.count_blocks_by_author(&address).unwrap_or(0);
-    state.mined_by_me_count.store(count, Ordering::Relaxed);
+    // Save to DB
+    persist_wallet_key(&state.storage, &new_wallet, password.as_deref())?;
+    persist_hd_seed(&state.storage, &new_wallet, password.as_deref())?;
 
+    activate_wallet(&state, new_wallet);
     Ok(export)
 }
 
@@ -57,68 +112,207 @@ pub fn create_wallet(state: State<'_, AppState>) -> Result<wallet::WalletExport,
 pub fn import_wallet(
     state: State<'_, AppState>,
     private_key_hex: String,
-) -> Result<String, String> {
-    let mut wallet_guard = state.wallet.lock().unwrap();
-
-    let keypair_bytes = if private_key_hex.split_whitespace().count() == 12 {
-        // Handle Mnemonic
-        let mnemonic = bip39::Mnemonic::parse(&private_key_hex)
-            .map_err(|e| format!("Invalid mnemonic: {}", e))?;
-        let seed = mnemonic.to_seed("");
-        let mut key_bytes = [0u8; 32];
-        key_bytes.copy_from_slice(&seed[0..32]);
-        let keypair = libp2p::identity::Keypair::ed25519_from_bytes(key_bytes).unwrap();
-        keypair.to_protobuf_encoding().unwrap()
+    password: Option<String>,
+) -> Result<String, CommandError> {
+    // A mnemonic restores the HD wallet; a hex key has no accounts to derive
+    let new_wallet = Wallet::import(&private_key_hex).map_err(CommandError::InvalidKey)?;
+
+    persist_wallet_key(&state.storage, &new_wallet, password.as_deref())?;
+    if new_wallet.seed.is_some() {
+        persist_hd_seed(&state.storage, &new_wallet, password.as_deref())?;
     } else {
-        // Handle HEX
-        hex::decode(private_key_hex).map_err(|e| format!("Invalid hex: {}", e))?
+        state.storage.delete_wallet_hd()?;
+    }
+
+    Ok(activate_wallet(&state, new_wallet))
+}
+
+/// Password-encrypted JSON keystore of the loaded wallet's key.
+#[tauri::command]
+pub fn export_keystore(state: State<'_, AppState>, password: String) -> Result<String, String> {
+    let wallet = loaded_wallet(&state)?;
+    Keystore::seal(&wallet, &password)?.to_json()
+}
+
+/// Loads a wallet from a keystore, keeping it encrypted at rest with the same password.
+#[tauri::command]
+pub fn import_keystore(
+    state: State<'_, AppState>,
+    json: String,
+    password: String,
+) -> Result<String, String> {
+    let new_wallet = Keystore::parse(&json)?.open(&password)?;
+
+    persist_wallet_key(&state.storage, &new_wallet, Some(&password))?;
+    state
+        .storage
+        .delete_wallet_hd()
+        .map_err(|e| e.to_string())?;
+
+    Ok(activate_wallet(&state, new_wallet))
+}
+
+/// Decrypts the stored wallet into memory. A legacy plaintext key is
+/// encrypted with `password` on first unlock.
+#[tauri::command]
+pub fn unlock_wallet(state: State<'_, AppState>, password: String) -> Result<String, String> {
+    if password.is_empty() {
+        return Err("Password is required".to_string());
+    }
+
+    let keys_json = state
+        .storage
+        .get_wallet_keys()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "No wallet found. Please create or import a wallet first.".to_string())?;
+
+    let (mut unlocked, needs_migration) = match StoredKey::parse(&keys_json)? {
+        StoredKey::Encrypted(encrypted) => (
+            Wallet::from_keypair_bytes(encryption::decrypt_key(&encrypted, &password)?)?,
+            false,
+        ),
+        StoredKey::Plain(keypair_bytes) => (Wallet::from_keypair_bytes(keypair_bytes)?, true),
     };
 
-    // Validate keypair
-    let keypair = libp2p::identity::Keypair::from_protobuf_encoding(&keypair_bytes)
-        .map_err(|e| format!("Invalid keypair data: {}", e))?;
+    if needs_migration {
+        persist_wallet_key(&state.storage, &unlocked, Some(&password))?;
+        log::info!("Migrated plaintext wallet key to encrypted storage");
+    }
+
+    // HD wallets sign with their selected account
+    if let Some(mut record) = load_hd_record(&state.storage)? {
+        let seed = match &record.seed {
+            StoredKey::Encrypted(encrypted) => encryption::decrypt_key(encrypted, &password)?,
+            StoredKey::Plain(seed) => seed.clone(),
+        };
+        if !record.seed.is_encrypted() {
+            record.seed = seal_secret(&seed, &unlocked.address, Some(&password))?;
+            save_hd_record(&state.storage, &record)?;
+        }
+        unlocked = Wallet::from_seed(seed, record.active)?;
+    }
 
-    let address = keypair.public().to_peer_id().to_string();
+    Ok(activate_wallet(&state, unlocked))
+}
 
-    let new_wallet = Wallet {
-        start_timestamp: std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs(),
-        address: address.clone(),
-        alias: None,
-        keypair: keypair_bytes,
+/// True when an encrypted wallet exists on disk but has not been unlocked yet.
+#[tauri::command]
+pub fn is_wallet_locked(state: State<'_, AppState>) -> bool {
+    state.wallet.lock().unwrap().is_none() && has_encrypted_key(&state.storage)
+}
+
+/// True when the stored wallet key is password-encrypted.
+pub(crate) fn has_encrypted_key(storage: &Storage) -> bool {
+    matches!(
+        storage
+            .get_wallet_keys()
+            .ok()
+            .flatten()
+            .map(|json| StoredKey::parse(&json)),
+        Some(Ok(StoredKey::Encrypted(_)))
+    )
+}
+
+#[tauri::command]
+pub async fn get_wallet_info(
+    state: State<'_, AppState>,
+) -> Result<Option<wallet::WalletInfo>, String> {
+    let Some(w) = state.wallet.lock().unwrap().clone() else {
+        return Ok(None);
+    };
+    // Light nodes keep no balances and ask a full peer
+    let total_balance = if is_light_node(&state) {
+        match query_peer(&state, SyncRequest::GetBalance(w.address.clone())).await {
+            Ok(SyncResponse::Balance(balance)) => balance,
+            Ok(_) => 0,
+            Err(e) => {
+                log::warn!("Light: balance of {} unavailable: {}", w.address, e);
+                0
+            }
+        }
+    } else {
+        state.storage.calculate_balance(&w.address).unwrap_or(0)
     };
+    let pending_spend = state.mempool.get_total_pending_spend(&w.address);
+    let available_balance = total_balance.saturating_sub(pending_spend);
 
-    let keys_json = serde_json::to_string(&new_wallet.keypair).unwrap();
-    let _ = state.storage.save_wallet_keys(&keys_json);
+    Ok(Some(wallet::WalletInfo {
+        address: w.address.clone(),
+        balance: available_balance,
+        alias: w.alias.clone(),
+        private_key: Some(hex::encode(&w.keypair)),
+        account: w.account,
+    }))
+}
 
-    *wallet_guard = Some(new_wallet);
+/// Derives the next account of the HD wallet without switching to it.
+#[tauri::command]
+pub fn add_account(state: State<'_, AppState>) -> Result<AccountInfo, String> {
+    let wallet = loaded_wallet(&state)?;
+    let mut record = load_hd_record(&state.storage)?.ok_or(NO_ACCOUNTS)?;
 
-    // Update mined blocks counter for the new wallet
-    let count = state.storage.count_blocks_by_author(&address).unwrap_or(0);
-    state.mined_by_me_count.store(count, Ordering::Relaxed);
+    let index = record.accounts;
+    let account = wallet.derive_account(index)?;
+    record.accounts += 1;
+    save_hd_record(&state.storage, &record)?;
 
-    Ok(address)
+    Ok(AccountInfo {
+        index,
+        address: account.address,
+        active: false,
+    })
 }
 
 #[tauri::command]
-pub fn get_wallet_info(state: State<'_, AppState>) -> Option<wallet::WalletInfo> {
-    let wallet_guard = state.wallet.lock().unwrap();
-    if let Some(w) = wallet_guard.as_ref() {
-        let total_balance = state.storage.calculate_balance(&w.address).unwrap_or(0);
-        let pending_spend = state.mempool.get_total_pending_spend(&w.address);
-        let available_balance = total_balance.saturating_sub(pending_spend);
-
-        Some(wallet::WalletInfo {
-            address: w.address.clone(),
-            balance: available_balance,
-            alias: w.alias.clone(),
-            private_key: Some(hex::encode(&w.keypair)),
+pub fn list_accounts(state: State<'_, AppState>) -> Result<Vec<AccountInfo>, String> {
+    let wallet = loaded_wallet(&state)?;
+    let Some(record) = load_hd_record(&state.storage)? else {
+        // A single imported key is its own only account
+        return Ok(vec![AccountInfo {
+            index: 0,
+            address: wallet.address,
+            active: true,
+        }]);
+    };
+
+    (0..record.accounts)
+        .map(|index| {
+            Ok(AccountInfo {
+                index,
+                address: wallet.derive_account(index)?.address,
+                active: wallet.account == Some(index),
+            })
         })
-    } else {
-        None
+        .collect()
+}
+
+/// Signs and mines with account `index` from now on; returns its address.
+#[tauri::command]
+pub fn switch_active_account(state: State<'_, AppState>, index: u32) -> Result<String, String> {
+    let wallet = loaded_wallet(&state)?;
+    let mut record = load_hd_record(&state.storage)?.ok_or(NO_ACCOUNTS)?;
+    if index >= record.accounts {
+        return Err(format!("Account {} has not been added", index));
     }
+
+    let account = wallet.derive_account(index)?;
+    record.active = index;
+    save_hd_record(&state.storage, &record)?;
+
+    Ok(activate_wallet(&state, account))
+}
+
+/// Hex signature over the UTF-8 bytes of `message` with the loaded wallet key.
+#[tauri::command]
+pub fn sign_message(state: State<'_, AppState>, message: String) -> Result<String, String> {
+    let wallet = loaded_wallet(&state)?;
+    Ok(hex::encode(wallet.sign_message(message.as_bytes())?))
+}
+
+/// Whether `signature` proves `address` signed `message`.
+#[tauri::command]
+pub fn verify_message(address: String, message: String, signature: String) -> Result<bool, String> {
+    wallet::verify_message(&address, message.as_bytes(), &signature)
 }
 
 #[tauri::command]
@@ -0,0 +1,360 @@
+//! # Beacon Module
+//!
+//! Validators announce a BLS key ([`CommitteeKey`]) and sign a [`CrossLink`]
+//! for every block of their shard they produce or accept ([`CrossLinkVote`]).
+//! Once more than two thirds of a shard's committee signed the same link,
+//! the beacon aggregates the signatures into one and keeps the link as the
+//! shard's newest; the block it points at is the shard's finalized height as
+//! far as the beacon knows. Recent links back the cross-shard receipts other
+//! shards mint.
+
+use super::Consensus;
+use crate::chain::{has_supermajority, Block, CrossLink, Receipt};
+use crate::crypto::bls;
+use crate::utils::constants::CROSSLINK_HISTORY;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// A validator's BLS key with its proof of possession
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CommitteeKey {
+    pub peer_id: String,
+    pub public_key: String,
+    pub proof: String,
+}
+
+impl CommitteeKey {
+    pub fn new(peer_id: String, secret: &bls::SecretKey) -> Self {
+        CommitteeKey {
+            peer_id,
+            public_key: bls::public_key(secret),
+            proof: bls::prove_possession(secret),
+        }
+    }
+}
+
+/// One committee member's signature over an unsigned CrossLink
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CrossLinkVote {
+    pub link: CrossLink,
+    pub public_key: String,
+    pub signature: String,
+}
+
+impl CrossLinkVote {
+    pub fn new(block: &Block, secret: &bls::SecretKey) -> Self {
+        let link = CrossLink::for_block(block);
+        let signature = bls::sign(secret, &link.signing_payload());
+        CrossLinkVote {
+            link,
+            public_key: bls::public_key(secret),
+            signature,
+        }
+    }
+}
+
+/// Messages on the cross-link topic
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum BeaconMessage {
+    Key(CommitteeKey),
+    Vote(CrossLinkVote),
+}
+
+/// `(public key, signature)` shares of the committee members that signed a link
+type Shares = Vec<(String, String)>;
+
+#[derive(Debug, Default)]
+pub struct Beacon {
+    /// Recorded links per shard id by block height, the newest
+    /// `CROSSLINK_HISTORY` of each
+    links: HashMap<u16, BTreeMap<u64, CrossLink>>,
+    /// BLS key of each validator that announced one
+    keys: HashMap<String, String>,
+    /// Links being signed, by shard, height and block hash, with the shares
+    /// collected so far
+    votes: HashMap<(u16, u64, String), (CrossLink, Shares)>,
+}
+
+impl Beacon {
+    /// Registers the BLS key of `key.peer_id` after checking its proof of possession.
+    pub fn register_key(&mut self, key: CommitteeKey) -> Result<(), String> {
+        bls::verify_possession(&key.public_key, &key.proof)?;
+        self.keys.insert(key.peer_id, key.public_key);
+        Ok(())
+    }
+
+    pub fn public_key_of(&self, peer_id: &str) -> Option<&String> {
+        self.keys.get(peer_id)
+    }
+
+    /// Counts `vote` from a member of `committee`. Returns the aggregated
+    /// link once it is signed by more than two thirds of the committee.
+    pub fn add_vote(
+        &mut self,
+        vote: CrossLinkVote,
+        committee: &[String],
+    ) -> Result<Option<CrossLink>, String> {
+        if !committee.contains(&vote.public_key) {
+            return Err("Vote from outside the shard committee".into());
+        }
+        bls::verify(
+            &vote.signature,
+            &vote.link.signing_payload(),
+            &vote.public_key,
+        )?;
+        let link = vote.link;
+        if !self.is_newer(&link) {
+            return Ok(None);
+        }
+
+        let key = (link.shard_id, link.block_height, link.block_hash.clone());
+        let (pending, shares) = self
+            .votes
+            .entry(key)
+            .or_insert_with(|| (link.clone(), Vec::new()));
+        if pending.signing_payload() != link.signing_payload() {
+            return Err("Vote signs a different link for the same block".into());
+        }
+        if shares.iter().any(|(key, _)| *key == vote.public_key) {
+            return Ok(None);
+        }
+        shares.push((vote.public_key, vote.signature));
+        if !has_supermajority(shares.len(), committee.len()) {
+            return Ok(None);
+        }
+
+        let mut link = pending.clone();
+        link.aggregate(shares)?;
+        self.record(link.clone(), committee)?;
+        Ok(Some(link))
+    }
+
+    /// Records `link` if its aggregate signature is valid for `committee`
+    /// and it is newer than the shard's current link; returns whether it was kept.
+    pub fn record(&mut self, link: CrossLink, committee: &[String]) -> Result<bool, String> {
+        link.verify(committee)?;
+        if !self.is_newer(&link) {
+            return Ok(false);
+        }
+        self.votes
+            .retain(|(shard, height, _), _| *shard != link.shard_id || *height > link.block_height);
+        let links = self.links.entry(link.shard_id).or_default();
+        links.insert(link.block_height, link);
+        while links.len() > CROSSLINK_HISTORY {
+            links.pop_first();
+        }
+        Ok(true)
+    }
+
+    fn is_newer(&self, link: &CrossLink) -> bool {
+        self.latest(link.shard_id)
+            .is_none_or(|current| current.block_height < link.block_height)
+    }
+
+    /// Newest link recorded for `shard_id`.
+    pub fn latest(&self, shard_id: u16) -> Option<&CrossLink> {
+        self.links
+            .get(&shard_id)
+            .and_then(|links| links.last_key_value())
+            .map(|(_, link)| link)
+    }
+
+    /// Link recorded for the block at `height` of `shard_id`, if still kept.
+    pub fn link_at(&self, shard_id: u16, height: u64) -> Option<&CrossLink> {
+        self.links.get(&shard_id)?.get(&height)
+    }
+
+    /// Highest cross-linked block of every shard heard from, by shard id.
+    pub fn finalized_heights(&self) -> BTreeMap<u16, u64> {
+        self.links
+            .iter()
+            .filter_map(|(shard, links)| Some((*shard, *links.last_key_value()?.0)))
+            .collect()
+    }
+}
+
+impl Consensus {
+    /// BLS keys of the validators that lead on `shard_id` in `epoch` and
+    /// announced one, sorted.
+    pub fn shard_committee(&self, shard_id: u16, epoch: u64) -> Vec<String> {
+        let mut committee: Vec<String> = self
+            .nodes
+            .keys()
+            .filter(|pid| {
+                self.get_assigned_shard(pid, epoch) == shard_id
+                    && self.is_eligible_for_leadership(pid)
+            })
+            .filter_map(|pid| self.beacon.public_key_of(pid).cloned())
+            .collect();
+        committee.sort();
+        committee
+    }
+
+    /// Counts `vote` toward its shard's CrossLink; returns the link once the
+    /// shard committee's supermajority signed it.
+    pub fn record_crosslink_vote(
+        &mut self,
+        vote: CrossLinkVote,
+    ) -> Result<Option<CrossLink>, String> {
+        let committee = self.shard_committee(vote.link.shard_id, vote.link.epoch);
+        self.beacon.add_vote(vote, &committee)
+    }
+
+    /// `receipt` with the beacon's link for its source block attached, once
+    /// the source shard's committee signed one.
+    pub fn crosslinked_receipt(&self, receipt: &Receipt) -> Option<Receipt> {
+        let height = receipt.source_header.as_ref()?.index;
+        let link = self
+            .beacon
+            .link_at(receipt.source_shard, height)
+            .filter(|link| link.block_hash == receipt.block_hash)?;
+        let mut receipt = receipt.clone();
+        receipt.crosslink = Some(link.clone());
+        Some(receipt)
+    }
+
+    /// Checks a receipt a block mints: its link must be signed by the
+    /// source shard's committee of the link's epoch.
+    pub fn verify_receipt(&self, receipt: &Receipt) -> Result<(), String> {
+        let link = receipt
+            .crosslink
+            .as_ref()
+            .ok_or("Receipt carries no cross-link")?;
+        let committee = self.shard_committee(link.shard_id, link.epoch);
+        receipt.verify_crosslinked(&committee)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::identity::Keypair;
+
+    fn new_members(size: usize) -> Vec<bls::SecretKey> {
+        (0..size)
+            .map(|_| bls::derive_secret_key(&Keypair::generate_ed25519()).unwrap())
+            .collect()
+    }
+
+    fn keys(members: &[bls::SecretKey]) -> Vec<String> {
+        members.iter().map(bls::public_key).collect()
+    }
+
+    fn block(shard_id: u32, height: u64) -> Block {
+        Block::new(
+            height,
+            "leader".into(),
+            vec![],
+            "ab".repeat(32),
+            100,
+            100,
+            shard_id,
+            0,
+            0,
+        )
+    }
+
+    /// Link for `block` signed by all of `signers`
+    fn signed_link(block: &Block, signers: &[bls::SecretKey]) -> CrossLink {
+        let mut link = CrossLink::for_block(block);
+        let shares: Vec<(String, String)> = signers
+            .iter()
+            .map(|sk| (bls::public_key(sk), bls::sign(sk, &link.signing_payload())))
+            .collect();
+        link.aggregate(&shares).unwrap();
+        link
+    }
+
+    #[test]
+    fn keeps_the_latest_link_per_shard() {
+        let (shard_0, shard_1) = (new_members(3), new_members(4));
+        let mut beacon = Beacon::default();
+
+        let (keys_0, keys_1) = (keys(&shard_0), keys(&shard_1));
+        assert!(beacon
+            .record(signed_link(&block(0, 5), &shard_0), &keys_0)
+            .unwrap());
+        assert!(beacon
+            .record(signed_link(&block(1, 3), &shard_1), &keys_1)
+            .unwrap());
+        assert!(beacon
+            .record(signed_link(&block(0, 7), &shard_0), &keys_0)
+            .unwrap());
+        // Late or repeated links don't move a shard back
+        assert!(!beacon
+            .record(signed_link(&block(0, 6), &shard_0), &keys_0)
+            .unwrap());
+        assert!(!beacon
+            .record(signed_link(&block(1, 3), &shard_1), &keys_1)
+            .unwrap());
+
+        assert_eq!(beacon.finalized_heights(), BTreeMap::from([(0, 7), (1, 3)]));
+        assert_eq!(beacon.latest(0).unwrap().block_height, 7);
+        assert!(beacon.latest(2).is_none());
+    }
+
+    #[test]
+    fn votes_aggregate_once_a_supermajority_signed() {
+        let members = new_members(4);
+        let committee = keys(&members);
+        let source = block(2, 10);
+        let mut beacon = Beacon::default();
+
+        for member in &members[..2] {
+            let vote = CrossLinkVote::new(&source, member);
+            assert!(beacon.add_vote(vote.clone(), &committee).unwrap().is_none());
+            // A repeated vote doesn't count twice
+            assert!(beacon.add_vote(vote, &committee).unwrap().is_none());
+        }
+        let outsider = CrossLinkVote::new(&source, &new_members(1)[0]);
+        assert!(beacon.add_vote(outsider, &committee).is_err());
+        assert!(beacon.latest(2).is_none());
+
+        let link = beacon
+            .add_vote(CrossLinkVote::new(&source, &members[2]), &committee)
+            .unwrap()
+            .expect("3 of 4 members signed");
+        assert_eq!(link.signers.len(), 3);
+        link.verify(&committee).unwrap();
+        assert_eq!(beacon.finalized_heights(), BTreeMap::from([(2, 10)]));
+    }
+
+    #[test]
+    fn rejects_forged_or_undersigned_links() {
+        let members = new_members(4);
+        let committee = keys(&members);
+        let mut beacon = Beacon::default();
+
+        // Too few members
+        let undersigned = signed_link(&block(0, 9), &members[..2]);
+        assert!(beacon.record(undersigned, &committee).is_err());
+
+        // Signers claimed beyond those that signed
+        let mut forged = signed_link(&block(0, 9), &members[..2]);
+        forged.signers = committee[..3].to_vec();
+        assert!(beacon.record(forged, &committee).is_err());
+
+        // Signature over another block
+        let mut moved = signed_link(&block(0, 9), &members[..3]);
+        moved.block_height = 10;
+        assert!(beacon.record(moved, &committee).is_err());
+        assert!(beacon.finalized_heights().is_empty());
+    }
+
+    #[test]
+    fn keys_need_a_proof_of_possession() {
+        let members = new_members(2);
+        let mut beacon = Beacon::default();
+        let mut key = CommitteeKey::new("peer".into(), &members[0]);
+        key.proof = bls::prove_possession(&members[1]);
+        assert!(beacon.register_key(key).is_err());
+
+        beacon
+            .register_key(CommitteeKey::new("peer".into(), &members[0]))
+            .unwrap();
+        assert_eq!(
+            beacon.public_key_of("peer"),
+            Some(&bls::public_key(&members[0]))
+        );
+    }
+}
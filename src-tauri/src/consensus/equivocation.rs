@@ -0,0 +1,289 @@
+//! # Equivocation Module
+//!
+//! A leader gets one block per slot. Two different blocks from the same author
+//! for the same slot are proof of misbehaviour: the author loses all trust and
+//! the conflicting hashes are kept as evidence. Anyone holding both signed
+//! blocks can also report them.
+
+use super::Consensus;
+use crate::chain::Block;
+use crate::utils::constants::EQUIVOCATION_WINDOW_SLOTS;
+use serde::{Deserialize, Serialize};
+
+/// Two distinct blocks produced by one author for one slot
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EquivocationEvidence {
+    pub author: String,
+    pub slot: u64,
+    /// Hash of the block seen first
+    pub first_hash: String,
+    /// Hash of the conflicting block
+    pub second_hash: String,
+    /// Unix time (seconds) the conflict was detected
+    pub detected_at: u64,
+}
+
+impl EquivocationEvidence {
+    /// Evidence from two blocks signed by the same author for the same slot.
+    /// Fails unless both signatures verify and the blocks differ.
+    pub fn from_blocks(first: &Block, second: &Block) -> Result<Self, String> {
+        first.verify_signature()?;
+        second.verify_signature()?;
+        if first.author != second.author {
+            return Err("Blocks have different authors".into());
+        }
+        let slot = first.timestamp / Consensus::SLOT_DURATION;
+        if second.timestamp / Consensus::SLOT_DURATION != slot {
+            return Err("Blocks are for different slots".into());
+        }
+        if first.hash == second.hash {
+            return Err("Blocks are identical".into());
+        }
+        Ok(Self {
+            author: first.author.clone(),
+            slot,
+            first_hash: first.hash.clone(),
+            second_hash: second.hash.clone(),
+            detected_at: crate::utils::unix_now(),
+        })
+    }
+}
+
+impl Consensus {
+    /// Records `block` against its author's slot; returns evidence if the
+    /// author already produced a different block for that slot.
+    ///
+    /// Only blocks signed by their author count, so nobody can be framed with
+    /// blocks made up in their name. An equivocating author is slashed and
+    /// the evidence persisted.
+    pub fn observe_block(&mut self, block: &Block) -> Option<EquivocationEvidence> {
+        if block.verify_signature().is_err() {
+            return None;
+        }
+        let slot = block.timestamp / Self::SLOT_DURATION;
+        self.produced_blocks
+            .retain(|(_, seen), _| seen.saturating_add(EQUIVOCATION_WINDOW_SLOTS) >= slot);
+
+        let first_hash = self
+            .produced_blocks
+            .entry((block.author.clone(), slot))
+            .or_insert_with(|| block.hash.clone());
+        if *first_hash == block.hash {
+            return None;
+        }
+
+        let evidence = EquivocationEvidence {
+            author: block.author.clone(),
+            slot,
+            first_hash: first_hash.clone(),
+            second_hash: block.hash.clone(),
+            detected_at: crate::utils::unix_now(),
+        };
+        self.slash_equivocator(&block.author);
+        if let Some(storage) = &self.storage {
+            if let Err(e) = storage.save_equivocation(&evidence) {
+                log::warn!("Failed to persist equivocation evidence: {}", e);
+            }
+        }
+        Some(evidence)
+    }
+
+    /// Slashes the author of two conflicting signed blocks submitted from
+    /// outside, e.g. by a monitor. The evidence goes to the slashing table.
+    pub fn report_equivocation(
+        &mut self,
+        first: &Block,
+        second: &Block,
+    ) -> Result<EquivocationEvidence, String> {
+        let evidence = EquivocationEvidence::from_blocks(first, second)?;
+        if !self.nodes.contains_key(&evidence.author) {
+            return Err(format!("{} is not a known validator", evidence.author));
+        }
+        self.slash_equivocator(&evidence.author);
+        if let Some(storage) = &self.storage {
+            storage
+                .save_slashing(&evidence)
+                .map_err(|e| format!("Failed to record slashing: {}", e))?;
+        }
+        Ok(evidence)
+    }
+
+    /// Slashes a node for equivocation: trust drops to zero, and active status
+    /// and its VDF verification are revoked, so it has to prove itself again.
+    pub fn slash_equivocator(&mut self, peer_id: &str) {
+        if let Some(node) = self.nodes.get_mut(peer_id) {
+            node.trust_score = 0.0;
+            node.is_active = false;
+            node.activated_at = None;
+            node.is_verified = false;
+            node.vdf_proof = None;
+            log::warn!("SLASHED Node {} for equivocation", peer_id);
+            self.persist_node(peer_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Storage;
+    use libp2p::identity::Keypair;
+    use std::sync::Arc;
+
+    fn block(author: &str, timestamp: u64, hash: &str) -> Block {
+        let mut b = Block::new(7, author.into(), vec![], "00".into(), 100, 100, 0, 0, 0);
+        b.timestamp = timestamp;
+        b.hash = hash.into();
+        b
+    }
+
+    fn signed_block(keypair: &Keypair, timestamp: u64, nonce: u64) -> Block {
+        let author = keypair.public().to_peer_id().to_string();
+        let mut b = Block::new(7, author, vec![], "00".into(), 100, 100, 0, 0, 0);
+        b.timestamp = timestamp;
+        b.nonce = nonce;
+        b.hash = b.calculate_hash();
+        b.sign_with_keypair(keypair).unwrap();
+        b
+    }
+
+    fn trusted_consensus(storage: Option<Arc<Storage>>, author: &str) -> Consensus {
+        let mut consensus = match storage {
+            Some(storage) => Consensus::with_storage(storage),
+            None => Consensus::new(),
+        };
+        consensus.register_node(author.into());
+        let node = consensus.nodes.get_mut(author).unwrap();
+        node.activate();
+        node.is_verified = true;
+        node.trust_score = 1.0;
+        consensus
+    }
+
+    #[test]
+    fn conflicting_same_slot_blocks_slash_the_author() {
+        let storage = Arc::new(Storage::temp());
+        let keypair = Keypair::generate_ed25519();
+        let author = keypair.public().to_peer_id().to_string();
+        let mut consensus = trusted_consensus(Some(storage.clone()), &author);
+
+        let first = signed_block(&keypair, 1_000, 1);
+        assert_eq!(consensus.observe_block(&first), None);
+        // The same block again, or the next slot, is fine
+        assert_eq!(consensus.observe_block(&first), None);
+        assert_eq!(
+            consensus.observe_block(&signed_block(&keypair, 1_002, 2)),
+            None
+        );
+
+        let conflicting = signed_block(&keypair, 1_001, 3);
+        let evidence = consensus
+            .observe_block(&conflicting)
+            .expect("equivocation detected");
+        assert_eq!(evidence.slot, 500);
+        assert_eq!(
+            (evidence.first_hash.as_str(), evidence.second_hash.as_str()),
+            (first.hash.as_str(), conflicting.hash.as_str())
+        );
+
+        let node = consensus.nodes.get(&author).unwrap();
+        assert_eq!(node.trust_score, 0.0);
+        assert!(!node.is_active);
+
+        // Evidence and the slashed state survive a restart
+        drop(consensus);
+        assert_eq!(storage.load_equivocations().unwrap(), vec![evidence]);
+        let restored = Consensus::with_storage(storage);
+        assert_eq!(restored.nodes.get(&author).unwrap().trust_score, 0.0);
+    }
+
+    #[test]
+    fn unsigned_or_forged_blocks_are_no_evidence() {
+        let keypair = Keypair::generate_ed25519();
+        let author = keypair.public().to_peer_id().to_string();
+        let mut consensus = trusted_consensus(None, &author);
+
+        // Unsigned blocks in the author's name
+        assert_eq!(consensus.observe_block(&block(&author, 1_000, "aa")), None);
+        assert_eq!(consensus.observe_block(&block(&author, 1_001, "bb")), None);
+
+        // Signed by someone else in the author's name
+        let forger = Keypair::generate_ed25519();
+        for nonce in [1, 2] {
+            let mut forged = signed_block(&forger, 1_000, nonce);
+            forged.author = author.clone();
+            forged.hash = forged.calculate_hash();
+            forged.sign_with_keypair(&forger).unwrap();
+            assert_eq!(consensus.observe_block(&forged), None);
+        }
+
+        // The author's own block is the first one on record for the slot
+        assert_eq!(
+            consensus.observe_block(&signed_block(&keypair, 1_000, 3)),
+            None
+        );
+        assert_eq!(consensus.nodes.get(&author).unwrap().trust_score, 1.0);
+    }
+
+    #[test]
+    fn reported_equivocation_slashes_the_author() {
+        let storage = Arc::new(Storage::temp());
+        let keypair = Keypair::generate_ed25519();
+        let author = keypair.public().to_peer_id().to_string();
+        let mut consensus = trusted_consensus(Some(storage.clone()), &author);
+
+        let first = signed_block(&keypair, 1_000, 1);
+        let second = signed_block(&keypair, 1_001, 2);
+        let evidence = consensus.report_equivocation(&first, &second).unwrap();
+        assert_eq!(evidence.slot, 500);
+        assert_eq!(
+            (evidence.first_hash.as_str(), evidence.second_hash.as_str()),
+            (first.hash.as_str(), second.hash.as_str())
+        );
+
+        let node = consensus.nodes.get(&author).unwrap();
+        assert_eq!(node.trust_score, 0.0);
+        assert!(!node.is_active && !node.is_verified);
+        assert!(!consensus.is_eligible_for_leadership(&author));
+        // Missed slots don't lift the score off zero again
+        consensus.slash_node(&author);
+        assert_eq!(consensus.nodes.get(&author).unwrap().trust_score, 0.0);
+
+        assert_eq!(storage.load_slashings().unwrap(), vec![evidence]);
+    }
+
+    #[test]
+    fn forged_or_identical_blocks_are_rejected() {
+        let keypair = Keypair::generate_ed25519();
+        let author = keypair.public().to_peer_id().to_string();
+        let mut consensus = trusted_consensus(None, &author);
+        let first = signed_block(&keypair, 1_000, 1);
+
+        assert_eq!(
+            consensus.report_equivocation(&first, &first),
+            Err("Blocks are identical".to_string())
+        );
+
+        // Signed by someone else in the author's name
+        let mut forged = signed_block(&Keypair::generate_ed25519(), 1_001, 2);
+        forged.author = author.clone();
+        forged.hash = forged.calculate_hash();
+        assert_eq!(
+            consensus.report_equivocation(&first, &forged),
+            Err("Invalid block signature".to_string())
+        );
+
+        // Changed after signing
+        let mut tampered = signed_block(&keypair, 1_001, 2);
+        tampered.nonce = 3;
+        assert!(consensus.report_equivocation(&first, &tampered).is_err());
+
+        let next_slot = signed_block(&keypair, 1_002, 2);
+        assert_eq!(
+            consensus.report_equivocation(&first, &next_slot),
+            Err("Blocks are for different slots".to_string())
+        );
+
+        assert_eq!(consensus.nodes.get(&author).unwrap().trust_score, 1.0);
+    }
+}
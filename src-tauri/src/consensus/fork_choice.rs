@@ -0,0 +1,81 @@
+//! # Fork Choice Module
+//!
+//! Deterministic rule for picking between competing blocks at the same height.
+//! Every node applies the same rule, so all honest nodes converge on one chain.
+
+use super::Consensus;
+use crate::chain::Block;
+use std::cmp::Ordering;
+
+impl Consensus {
+    /// Compares two competing blocks; `Ordering::Greater` means `a` is canonical.
+    ///
+    /// Competing blocks share a parent, so cumulative VDF difficulty differs only
+    /// by each block's own `vdf_difficulty`. Ties go to the lower block hash.
+    pub fn choose_canonical(a: &Block, b: &Block) -> Ordering {
+        Self::choose_branch(std::slice::from_ref(a), std::slice::from_ref(b))
+    }
+
+    /// Compares two branches forking from the same parent by cumulative VDF
+    /// difficulty; ties go to the branch whose first block has the lower hash.
+    pub fn choose_branch(a: &[Block], b: &[Block]) -> Ordering {
+        let weight = |branch: &[Block]| -> u128 {
+            branch.iter().map(|blk| blk.vdf_difficulty as u128).sum()
+        };
+        let first_hash = |branch: &[Block]| branch.first().map(|blk| blk.hash.clone());
+        weight(a)
+            .cmp(&weight(b))
+            .then_with(|| first_hash(b).cmp(&first_hash(a)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(difficulty: u64, hash: &str) -> Block {
+        let mut b = Block::new(
+            5,
+            "author".into(),
+            vec![],
+            "parent".into(),
+            100,
+            difficulty,
+            0,
+            0,
+            0,
+        );
+        b.hash = hash.into();
+        b
+    }
+
+    #[test]
+    fn heavier_block_wins_at_equal_height() {
+        let light = block(100, "00aa");
+        let heavy = block(300, "ffff");
+
+        assert_eq!(
+            Consensus::choose_canonical(&heavy, &light),
+            Ordering::Greater
+        );
+        assert_eq!(Consensus::choose_canonical(&light, &heavy), Ordering::Less);
+    }
+
+    #[test]
+    fn equal_difficulty_breaks_tie_on_lower_hash() {
+        let low = block(200, "0abc");
+        let high = block(200, "fabc");
+
+        assert_eq!(Consensus::choose_canonical(&low, &high), Ordering::Greater);
+        assert_eq!(Consensus::choose_canonical(&high, &low), Ordering::Less);
+        assert_eq!(Consensus::choose_canonical(&low, &low), Ordering::Equal);
+    }
+
+    #[test]
+    fn longer_branch_outweighs_single_heavy_block() {
+        let long = vec![block(200, "ff01"), block(200, "ff02")];
+        let short = vec![block(300, "0001")];
+
+        assert_eq!(Consensus::choose_branch(&long, &short), Ordering::Greater);
+    }
+}
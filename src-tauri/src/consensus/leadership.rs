@@ -3,7 +3,7 @@
 //! Handles leader election and eligibility logic for the consensus mechanism.
 //! This module contains the core algorithms for determining who can produce blocks.
 
-use super::node_state::NodeState;
+use super::node_state::{NodeState, ScheduledSlot};
 use super::Consensus;
 
 // =============================================================================
@@ -103,6 +103,7 @@ impl Consensus {
     pub fn update_active_status(&mut self) {
         let node_count = self.nodes.len();
         let q_duration = self.get_quarantine_duration();
+        let mut changed = Vec::new();
 
         for (_, node) in self.nodes.iter_mut() {
             // Demote nodes with critically low trust
@@ -111,6 +112,7 @@ impl Consensus {
                     node.is_active = false;
                     node.activated_at = None;
                     log::warn!("Node {} DEMOTED due to low trust", node.peer_id);
+                    changed.push(node.peer_id.clone());
                 }
                 continue;
             }
@@ -146,8 +148,13 @@ impl Consensus {
                     node.current_uptime(),
                     q_duration
                 );
+                changed.push(node.peer_id.clone());
             }
         }
+
+        for peer_id in changed {
+            self.persist_node(&peer_id);
+        }
     }
 
     /// Deterministic Round-Robin Leader Election
@@ -283,4 +290,24 @@ impl Consensus {
         }
         leaders
     }
+
+    /// Leaders of the next `count` slots in `peer_id`'s shard, starting with
+    /// the slot containing `now` (Unix seconds).
+    pub fn leader_schedule(&self, peer_id: &str, now: u64, count: u64) -> Vec<ScheduledSlot> {
+        let start_slot = now / Self::SLOT_DURATION;
+        let shard_id = self.get_assigned_shard(peer_id, Self::epoch_for_slot(start_slot));
+        self.get_future_leaders(start_slot, count, shard_id)
+            .into_iter()
+            .map(|(slot, leader)| {
+                let starts_at = Self::slot_start_time(slot);
+                ScheduledSlot {
+                    slot,
+                    is_me: leader.as_deref() == Some(peer_id),
+                    leader,
+                    starts_at,
+                    starts_in: starts_at.saturating_sub(now),
+                }
+            })
+            .collect()
+    }
 }
@@ -1,19 +1,62 @@
-use crate::chain::{validate_transaction, Transaction};
+use crate::chain::{
+    calculate_fee, validate_transaction, Receipt, ReceiptStatus, Transaction, TxCancellation,
+};
 use crate::storage::Storage;
-use std::collections::HashMap;
+use crate::utils::constants::{
+    MAX_MEMPOOL_SIZE, MAX_TRACKED_RECEIPTS, MAX_TXS_PER_BLOCK, MEMPOOL_TX_MAX_AGE_SECS,
+    MIN_RBF_FEE_BUMP_PERCENT, RECENTLY_MINED_TTL_SECS,
+};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, VecDeque};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 #[derive(Clone)]
 pub struct Mempool {
     pub pending_txs: Arc<Mutex<HashMap<String, Transaction>>>,
+    /// Incoming cross-shard receipts awaiting a mint on this shard, keyed by `original_tx_id`.
+    pub pending_receipts: Arc<Mutex<HashMap<String, Receipt>>>,
+    /// Cross-shard receipts this node generated or queued, oldest first, with
+    /// their progress; shown to the UI.
+    pub tracked_receipts: Arc<Mutex<VecDeque<Receipt>>>,
+    /// Ids of transactions mined in the last `RECENTLY_MINED_TTL_SECS`, with
+    /// when they were mined
+    pub recently_mined: Arc<Mutex<HashMap<String, Instant>>>,
     pub storage: Arc<Storage>,
+    /// Capacity; when full, the lowest-fee tx is evicted for a better-paying one.
+    pub max_size: usize,
+    /// Fee increase (percent) required to replace a pending tx with the same nonce.
+    pub min_fee_bump_percent: u64,
+    /// Transactions older than this (seconds, by their timestamp) are swept out.
+    pub max_age_secs: u64,
+    /// User transactions that fit in one block (the coinbase takes a slot).
+    pub block_capacity: usize,
+}
+
+/// Fee suggestion for a transfer, see [`Mempool::estimate_fee`].
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FeeEstimate {
+    /// Protocol minimum for the amount
+    pub min_fee: u64,
+    /// Fee expected to be mined within the target number of blocks
+    pub suggested_fee: u64,
+    /// Ready transactions currently competing for block space
+    pub pending: usize,
 }
 
 impl Mempool {
     pub fn new(storage: Arc<Storage>) -> Self {
         Mempool {
             pending_txs: Arc::new(Mutex::new(HashMap::new())),
+            pending_receipts: Arc::new(Mutex::new(HashMap::new())),
+            tracked_receipts: Arc::new(Mutex::new(VecDeque::new())),
+            recently_mined: Arc::new(Mutex::new(HashMap::new())),
             storage,
+            max_size: MAX_MEMPOOL_SIZE,
+            min_fee_bump_percent: MIN_RBF_FEE_BUMP_PERCENT,
+            max_age_secs: MEMPOOL_TX_MAX_AGE_SECS,
+            block_capacity: MAX_TXS_PER_BLOCK as usize - 1,
         }
     }
 
@@ -30,6 +73,9 @@ impl Mempool {
         }
     }
 
+    /// Drops pending transactions the chain has mined or made invalid; returns
+    /// how many were dropped. Costs a few lookups per transaction whatever the
+    /// chain height.
     pub fn reconcile_with_chain(&self) -> Result<usize, String> {
         let pending_txs = {
             let pool = self.pending_txs.lock().unwrap();
@@ -38,19 +84,33 @@ impl Mempool {
 
         let mut removed_count = 0;
         for tx in pending_txs {
-            // 1. Check if tx exists in any block
+            // 1. Drop txs the tx index has in a block
             if self.is_tx_mined(&tx.id).unwrap_or(false) {
                 self.remove_transactions(&[tx.id.clone()]);
                 removed_count += 1;
                 continue;
             }
 
-            // 2. Check if still valid (Sender has enough balance)
+            // 2. Drop txs whose nonce was already used on-chain
+            if tx.nonce < self.storage.get_next_nonce(&tx.sender).unwrap_or(0) {
+                self.remove_transactions(std::slice::from_ref(&tx.id));
+                removed_count += 1;
+                continue;
+            }
+
+            // 3. Drop alias claims that lost the name to another registration
+            if let Some(alias) = &tx.alias {
+                if self.storage.resolve_alias(alias).ok().flatten().is_some() {
+                    self.remove_transactions(std::slice::from_ref(&tx.id));
+                    removed_count += 1;
+                    continue;
+                }
+            }
+
+            // 4. Check if still valid (Sender has enough balance)
             if tx.sender != "SYSTEM" {
                 let balance = self.storage.calculate_balance(&tx.sender).unwrap_or(0);
-                let required = tx
-                    .amount
-                    .saturating_add(crate::chain::calculate_fee(tx.amount));
+                let required = tx.amount.saturating_add(tx.fee());
                 if balance < required {
                     log::info!("Dropping invalid mempool tx {}: Insufficient funds (Balance: {}, Required: {})", tx.id, balance, required);
                     self.remove_transactions(&[tx.id]);
@@ -62,23 +122,134 @@ impl Mempool {
         Ok(removed_count)
     }
 
+    /// Drops transactions older than `max_age_secs` as of `now` (unix seconds),
+    /// in memory and on disk; returns what was dropped. Time-locked
+    /// transactions are kept until they can be mined.
+    pub fn sweep_expired(&self, now: u64) -> Vec<Transaction> {
+        let cutoff = now.saturating_sub(self.max_age_secs);
+        let next_height = self.storage.get_latest_index().unwrap_or(0) + 1;
+        let expired: Vec<Transaction> = {
+            let pool = self.pending_txs.lock().unwrap();
+            pool.values()
+                .filter(|tx| tx.timestamp < cutoff && tx.is_mature(next_height))
+                .cloned()
+                .collect()
+        };
+        let ids: Vec<String> = expired.iter().map(|tx| tx.id.clone()).collect();
+        self.remove_transactions(&ids);
+        expired
+    }
+
     fn is_tx_mined(&self, tx_id: &str) -> Result<bool, anyhow::Error> {
         self.storage.is_tx_mined(tx_id)
     }
 
-    pub fn add_transaction(&self, tx: Transaction) -> Result<(), String> {
-        if tx.is_system() {
+    /// Validates and inserts `tx`. Returns `false` when it was already
+    /// pending or was mined recently, so gossip handlers can ignore repeats.
+    pub fn add_transaction(&self, tx: Transaction) -> Result<bool, String> {
+        if tx.is_system() || tx.is_receipt_mint() {
             return Err("SYSTEM transactions cannot enter the mempool".into());
         }
+        // Repeats from other mesh peers skip validation
+        let pending = self.pending_txs.lock().unwrap().contains_key(&tx.id);
+        if pending || self.was_mined_recently(&tx.id) {
+            return Ok(false);
+        }
+
+        // Guards shared by local submissions and gossip; an alias registration
+        // is checked by `validate` instead
+        if tx.amount == 0 && !tx.is_alias_registration() {
+            return Err("Amount must be greater than zero".into());
+        }
+        if !tx.is_alias_registration()
+            && tx
+                .credits()
+                .iter()
+                .any(|(receiver, _)| *receiver == tx.sender)
+        {
+            return Err("Cannot send coins to your own address".into());
+        }
+        if tx.receiver.parse::<libp2p::PeerId>().is_err() {
+            return Err("Invalid receiver address: not a valid PeerId".into());
+        }
 
         tx.validate()?;
+        if tx.id != tx.compute_id() {
+            return Err("Transaction id does not match its contents".into());
+        }
+
+        let confirmed_nonce = self
+            .storage
+            .get_next_nonce(&tx.sender)
+            .map_err(|e| e.to_string())?;
+        if tx.nonce < confirmed_nonce {
+            return Err(format!(
+                "Nonce too low: {} (next is {})",
+                tx.nonce, confirmed_nonce
+            ));
+        }
 
-        let pending_spend = self.get_total_pending_spend(&tx.sender);
+        // Replace-by-fee: same sender + nonce must pay a sufficiently higher fee
+        let replaced = self.find_by_nonce(&tx.sender, tx.nonce);
+        let mut replaced_spend = 0;
+        if let Some(old) = &replaced {
+            if old.id == tx.id {
+                return Ok(false);
+            }
+            let bumped = old.fee().saturating_mul(100 + self.min_fee_bump_percent) / 100;
+            let min_fee = bumped.max(old.fee() + 1);
+            if tx.fee() < min_fee {
+                return Err(format!(
+                    "Replacement fee too low: {} (need at least {})",
+                    tx.fee(),
+                    min_fee
+                ));
+            }
+            replaced_spend = old.amount.saturating_add(old.fee());
+        }
+
+        let pending_spend = self
+            .get_total_pending_spend(&tx.sender)
+            .saturating_sub(replaced_spend);
         validate_transaction(&tx, &self.storage, pending_spend)?;
 
         let mut pool = self.pending_txs.lock().unwrap();
         if pool.contains_key(&tx.id) {
-            return Err("Transaction already in mempool".to_string());
+            return Ok(false);
+        }
+        // First come, first served: one pending claim per name
+        if let Some(alias) = &tx.alias {
+            if pool
+                .values()
+                .any(|p| p.alias.as_ref() == Some(alias) && p.sender != tx.sender)
+            {
+                return Err(format!("Alias {} is already being registered", alias));
+            }
+        }
+        if let Some(old) = replaced {
+            pool.remove(&old.id);
+            if let Err(e) = self.storage.remove_pending_tx(&old.id) {
+                log::warn!("Failed to remove replaced mempool tx {}: {}", old.id, e);
+            }
+            log::info!("Mempool: {} replaced {} (fee bump)", tx.id, old.id);
+        }
+
+        if pool.len() >= self.max_size {
+            // Cheapest tx goes first; among equal fees, the newest
+            let cheapest = pool
+                .values()
+                .min_by_key(|p| (p.fee(), Reverse(p.timestamp)))
+                .map(|p| (p.id.clone(), p.fee()));
+            match cheapest {
+                Some((id, fee)) if tx.fee() > fee => {
+                    pool.remove(&id);
+                    if let Err(e) = self.storage.remove_pending_tx(&id) {
+                        log::warn!("Failed to remove evicted mempool tx {}: {}", id, e);
+                    }
+                    log::info!("Mempool full: evicted {} for {}", id, tx.id);
+                }
+                _ => return Err("Mempool full: fee too low".into()),
+            }
         }
 
         // Save to Persistence
@@ -87,22 +258,264 @@ impl Mempool {
         }
 
         pool.insert(tx.id.clone(), tx);
+        Ok(true)
+    }
+
+    fn was_mined_recently(&self, tx_id: &str) -> bool {
+        let ttl = Duration::from_secs(RECENTLY_MINED_TTL_SECS);
+        self.recently_mined
+            .lock()
+            .unwrap()
+            .get(tx_id)
+            .is_some_and(|mined| mined.elapsed() < ttl)
+    }
+
+    /// Removes transactions a block included and remembers them for
+    /// `RECENTLY_MINED_TTL_SECS`.
+    pub fn mark_mined(&self, tx_ids: &[String]) {
+        self.remove_transactions(tx_ids);
+        let ttl = Duration::from_secs(RECENTLY_MINED_TTL_SECS);
+        let now = Instant::now();
+        let mut mined = self.recently_mined.lock().unwrap();
+        mined.retain(|_, at| now.duration_since(*at) < ttl);
+        for id in tx_ids {
+            mined.insert(id.clone(), now);
+        }
+    }
+
+    /// Forgets that transactions were mined, e.g. when a reorg orphaned
+    /// their block.
+    pub fn unmark_mined(&self, tx_ids: &[String]) {
+        let mut mined = self.recently_mined.lock().unwrap();
+        for id in tx_ids {
+            mined.remove(id);
+        }
+    }
+
+    /// Drops the pending tx named by a sender-signed cancellation, together
+    /// with that sender's later nonces, which could no longer be mined.
+    /// Returns the dropped transactions, cancelled one first.
+    pub fn cancel_transaction(&self, cancel: &TxCancellation) -> Result<Vec<Transaction>, String> {
+        if self.is_tx_mined(&cancel.tx_id).map_err(|e| e.to_string())? {
+            return Err("Transaction is already mined".into());
+        }
+        cancel.verify()?;
+
+        let dropped = {
+            let pool = self.pending_txs.lock().unwrap();
+            let tx = pool
+                .get(&cancel.tx_id)
+                .ok_or("Transaction is not pending")?;
+            if tx.sender != cancel.sender {
+                return Err("Only the sender can cancel a transaction".into());
+            }
+            let mut dropped: Vec<Transaction> = pool
+                .values()
+                .filter(|p| p.sender == tx.sender && p.nonce >= tx.nonce)
+                .cloned()
+                .collect();
+            dropped.sort_by_key(|p| p.nonce);
+            dropped
+        };
+        let ids: Vec<String> = dropped.iter().map(|tx| tx.id.clone()).collect();
+        self.remove_transactions(&ids);
+        Ok(dropped)
+    }
+
+    /// Pending tx from `sender` with the given nonce, if any.
+    pub fn find_by_nonce(&self, sender: &str, nonce: u64) -> Option<Transaction> {
+        let pool = self.pending_txs.lock().unwrap();
+        pool.values()
+            .find(|p| p.sender == sender && p.nonce == nonce)
+            .cloned()
+    }
+
+    /// Queues a receipt for minting. Receipts already settled on-chain, or
+    /// whose Merkle proof doesn't lead to the source block header they carry,
+    /// are rejected. The block minting it must also carry the source shard's
+    /// CrossLink for that header.
+    pub fn add_receipt(&self, receipt: Receipt) -> Result<(), String> {
+        if self
+            .storage
+            .is_receipt_applied(&receipt.original_tx_id)
+            .map_err(|e| e.to_string())?
+        {
+            return Err("Receipt already applied".into());
+        }
+        if let Err(e) = receipt.verify_inclusion() {
+            log::warn!(
+                "Rejected cross-shard receipt {} from block {}: {}",
+                receipt.original_tx_id,
+                receipt.block_hash,
+                e
+            );
+            return Err(e);
+        }
+
+        let mut queue = self.pending_receipts.lock().unwrap();
+        if queue.contains_key(&receipt.original_tx_id) {
+            return Err("Receipt already queued".into());
+        }
+        queue.insert(receipt.original_tx_id.clone(), receipt);
         Ok(())
     }
 
+    pub fn get_pending_receipts(&self) -> Vec<Receipt> {
+        let queue = self.pending_receipts.lock().unwrap();
+        queue.values().cloned().collect()
+    }
+
+    /// Starts following `receipt` until its mint is on-chain. A receipt
+    /// already followed keeps its status; the oldest one makes room when full.
+    pub fn track_receipt(&self, receipt: &Receipt) {
+        let mut tracked = self.tracked_receipts.lock().unwrap();
+        if tracked
+            .iter()
+            .any(|r| r.original_tx_id == receipt.original_tx_id)
+        {
+            return;
+        }
+        if tracked.len() >= MAX_TRACKED_RECEIPTS {
+            tracked.pop_front();
+        }
+        tracked.push_back(receipt.clone());
+    }
+
+    /// Marks followed receipts whose mints were just applied as claimed.
+    pub fn mark_receipts_claimed(&self, original_tx_ids: &[String]) {
+        let mut tracked = self.tracked_receipts.lock().unwrap();
+        for receipt in tracked.iter_mut() {
+            if original_tx_ids.contains(&receipt.original_tx_id) {
+                receipt.status = ReceiptStatus::Claimed;
+            }
+        }
+    }
+
+    /// Followed receipts, newest first.
+    pub fn get_tracked_receipts(&self) -> Vec<Receipt> {
+        let tracked = self.tracked_receipts.lock().unwrap();
+        tracked.iter().rev().cloned().collect()
+    }
+
+    pub fn remove_receipts(&self, original_tx_ids: &[String]) {
+        let mut queue = self.pending_receipts.lock().unwrap();
+        for id in original_tx_ids {
+            queue.remove(id);
+        }
+    }
+
     pub fn get_pending_transactions(&self) -> Vec<Transaction> {
         let pool = self.pending_txs.lock().unwrap();
         pool.values().cloned().collect()
     }
 
+    /// Pending transaction count per shard id the transactions were sent to.
+    pub fn depth_by_shard(&self) -> BTreeMap<u16, usize> {
+        let pool = self.pending_txs.lock().unwrap();
+        let mut depths = BTreeMap::new();
+        for tx in pool.values() {
+            *depths.entry(tx.shard_id).or_default() += 1;
+        }
+        depths
+    }
+
+    /// Pending transactions grouped by the shard `shard_of` routes their
+    /// sender to, for re-gossiping them after the shard count changed.
+    pub fn pending_by_shard(
+        &self,
+        shard_of: impl Fn(&str) -> u16,
+    ) -> BTreeMap<u16, Vec<Transaction>> {
+        let mut by_shard: BTreeMap<u16, Vec<Transaction>> = BTreeMap::new();
+        for tx in self.get_pending_transactions() {
+            by_shard.entry(shard_of(&tx.sender)).or_default().push(tx);
+        }
+        by_shard
+    }
+
+    /// Pending transactions that can be mined now: each sender's txs in nonce
+    /// order, starting at the confirmed nonce and stopping at the first gap.
+    pub fn get_ready_transactions(&self) -> Vec<Transaction> {
+        let mut by_sender: HashMap<String, Vec<Transaction>> = HashMap::new();
+        for tx in self.get_pending_transactions() {
+            by_sender.entry(tx.sender.clone()).or_default().push(tx);
+        }
+
+        let mut ready = Vec::new();
+        for (sender, mut txs) in by_sender {
+            txs.sort_by_key(|tx| tx.nonce);
+            let confirmed = self.storage.get_next_nonce(&sender).unwrap_or(0);
+            ready.extend(
+                (confirmed..)
+                    .zip(txs)
+                    .take_while(|(expected, tx)| tx.nonce == *expected)
+                    .map(|(_, tx)| tx),
+            );
+        }
+        ready
+    }
+
+    /// Ready transactions by fee priority (highest fee first, older first on
+    /// ties), without reordering any sender's nonce sequence.
+    pub fn get_pending_sorted(&self) -> Vec<Transaction> {
+        let priority = |tx: &Transaction| (tx.fee(), Reverse(tx.timestamp));
+
+        let mut queues: HashMap<String, VecDeque<Transaction>> = HashMap::new();
+        for tx in self.get_ready_transactions() {
+            queues.entry(tx.sender.clone()).or_default().push_back(tx);
+        }
+
+        // Max-heap over each sender's lowest-nonce tx
+        let mut heads: BinaryHeap<_> = queues
+            .iter()
+            .filter_map(|(sender, q)| q.front().map(|tx| (priority(tx), sender.clone())))
+            .collect();
+
+        let mut sorted = Vec::new();
+        while let Some((_, sender)) = heads.pop() {
+            let queue = queues.get_mut(&sender).unwrap();
+            if let Some(tx) = queue.pop_front() {
+                sorted.push(tx);
+            }
+            if let Some(next) = queue.front() {
+                heads.push((priority(next), sender));
+            }
+        }
+        sorted
+    }
+
+    /// Nonce to assign to the sender's next transaction, counting pending ones.
+    pub fn next_nonce(&self, address: &str) -> u64 {
+        let confirmed = self.storage.get_next_nonce(address).unwrap_or(0);
+        let pool = self.pending_txs.lock().unwrap();
+        pool.values()
+            .filter(|tx| tx.sender == address)
+            .map(|tx| tx.nonce + 1)
+            .fold(confirmed, u64::max)
+    }
+
+    /// Fee for a transfer of `amount` to be mined within `target_blocks`:
+    /// the minimum while the ready queue fits in that many blocks, otherwise
+    /// just above the fee of the last transaction that would still fit.
+    pub fn estimate_fee(&self, amount: u64, target_blocks: u64) -> FeeEstimate {
+        let min_fee = calculate_fee(amount);
+        let queue = self.get_pending_sorted();
+        let room = (target_blocks.max(1) as usize).saturating_mul(self.block_capacity);
+        let suggested_fee = match queue.get(room.saturating_sub(1)) {
+            Some(last_in) => min_fee.max(last_in.fee() + 1),
+            None => min_fee,
+        };
+        FeeEstimate {
+            min_fee,
+            suggested_fee,
+            pending: queue.len(),
+        }
+    }
+
     pub fn get_total_pending_spend(&self, address: &str) -> u64 {
         let pool = self.pending_txs.lock().unwrap();
         pool.values()
             .filter(|tx| tx.sender == address && tx.sender != "SYSTEM")
-            .map(|tx| {
-                tx.amount
-                    .saturating_add(crate::chain::calculate_fee(tx.amount))
-            })
+            .map(|tx| tx.amount.saturating_add(tx.fee()))
             .sum()
     }
 
@@ -121,6 +534,10 @@ impl Mempool {
         }
     }
 
+    pub fn capacity(&self) -> usize {
+        self.max_size
+    }
+
     pub fn len(&self) -> usize {
         let pool = self.pending_txs.lock().unwrap();
         pool.len()
@@ -134,3 +551,698 @@ impl Mempool {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::{resolve_receiver, Block, MerkleTree, SYSTEM_SIG_REWARD};
+    use crate::utils::constants::MAX_MEMO_BYTES;
+    use libp2p::identity::Keypair;
+    use std::sync::atomic::Ordering;
+
+    fn funded_mempool(keypairs: &[&Keypair]) -> Mempool {
+        let storage = Arc::new(Storage::temp());
+        let funding = keypairs
+            .iter()
+            .map(|keypair| Transaction {
+                id: format!("funding-{}", keypair.public().to_peer_id()),
+                sender: "SYSTEM".into(),
+                receiver: keypair.public().to_peer_id().to_string(),
+                amount: 100_000_000,
+                shard_id: 0,
+                timestamp: 0,
+                nonce: 0,
+                fee: 0,
+                signature: SYSTEM_SIG_REWARD.into(),
+                sender_pubkey: String::new(),
+                outputs: Vec::new(),
+                memo: None,
+                not_before_height: None,
+                alias: None,
+            })
+            .collect();
+        let block = Block::new(0, "miner".into(), funding, String::new(), 100, 100, 0, 0, 0);
+        storage.save_block(&block).unwrap();
+        Mempool::new(storage)
+    }
+
+    fn signed_tx(keypair: &Keypair, nonce: u64) -> Transaction {
+        signed_tx_for(keypair, nonce, 10_000)
+    }
+
+    fn resigned(keypair: &Keypair, mut tx: Transaction, fee: u64) -> Transaction {
+        tx.fee = fee;
+        tx.id = tx.compute_id();
+        tx.sign_with_keypair(keypair).unwrap();
+        tx
+    }
+
+    fn signed_tx_for(keypair: &Keypair, nonce: u64, amount: u64) -> Transaction {
+        let mut tx = Transaction {
+            id: String::new(),
+            sender: keypair.public().to_peer_id().to_string(),
+            receiver: Keypair::generate_ed25519()
+                .public()
+                .to_peer_id()
+                .to_string(),
+            amount,
+            shard_id: 0,
+            timestamp: 0,
+            nonce,
+            fee: 0,
+            signature: String::new(),
+            sender_pubkey: String::new(),
+            outputs: Vec::new(),
+            memo: None,
+            not_before_height: None,
+            alias: None,
+        };
+        tx.id = tx.compute_id();
+        tx.sign_with_keypair(keypair).unwrap();
+        tx
+    }
+
+    #[test]
+    fn depth_is_counted_per_target_shard() {
+        let (alice, bob) = (Keypair::generate_ed25519(), Keypair::generate_ed25519());
+        let mempool = funded_mempool(&[&alice, &bob]);
+        let to_shard = |keypair: &Keypair, nonce, shard_id| {
+            let mut tx = signed_tx(keypair, nonce);
+            tx.shard_id = shard_id;
+            resigned(keypair, tx, 0)
+        };
+        let moved = to_shard(&bob, 0, 2);
+        for tx in [
+            to_shard(&alice, 0, 0),
+            to_shard(&alice, 1, 0),
+            moved.clone(),
+        ] {
+            assert!(mempool.add_transaction(tx).unwrap());
+        }
+        assert_eq!(mempool.depth_by_shard(), BTreeMap::from([(0, 2), (2, 1)]));
+
+        mempool.remove_transactions(&[moved.id]);
+        assert_eq!(mempool.depth_by_shard(), BTreeMap::from([(0, 2)]));
+    }
+
+    #[test]
+    fn batch_payment_needs_funds_for_the_total() {
+        let keypair = Keypair::generate_ed25519();
+        let mempool = funded_mempool(&[&keypair]);
+        let batch = |amounts: &[u64]| {
+            let mut tx = signed_tx_for(&keypair, 0, amounts.iter().sum());
+            tx.outputs = amounts
+                .iter()
+                .map(|amount| {
+                    let receiver = Keypair::generate_ed25519().public().to_peer_id();
+                    (receiver.to_string(), *amount)
+                })
+                .collect();
+            tx.receiver = tx.outputs[0].0.clone();
+            resigned(&keypair, tx, 0)
+        };
+
+        assert!(mempool
+            .add_transaction(batch(&[40_000_000, 40_000_000, 40_000_000]))
+            .is_err());
+        mempool
+            .add_transaction(batch(&[30_000_000, 30_000_000, 30_000_000]))
+            .unwrap();
+    }
+
+    #[test]
+    fn sender_must_cover_tx_on_top_of_pending_spend() {
+        let keypair = Keypair::generate_ed25519();
+        let broke = Keypair::generate_ed25519();
+        let mempool = funded_mempool(&[&keypair]);
+
+        mempool
+            .add_transaction(signed_tx_for(&keypair, 0, 60_000_000))
+            .unwrap();
+        let err = mempool
+            .add_transaction(signed_tx_for(&broke, 0, 10_000))
+            .unwrap_err();
+        assert!(err.starts_with("Insufficient funds"), "{err}");
+        // Affordable alone, but not after the pending 60M
+        let err = mempool
+            .add_transaction(signed_tx_for(&keypair, 1, 60_000_000))
+            .unwrap_err();
+        assert!(err.starts_with("Insufficient funds"), "{err}");
+        assert_eq!(mempool.len(), 1);
+    }
+
+    #[test]
+    fn rejects_oversized_memo() {
+        let keypair = Keypair::generate_ed25519();
+        let mempool = funded_mempool(&[&keypair]);
+        let with_memo = |nonce, len| {
+            let mut tx = signed_tx(&keypair, nonce);
+            tx.memo = Some("m".repeat(len));
+            resigned(&keypair, tx, 0)
+        };
+
+        assert!(mempool
+            .add_transaction(with_memo(0, MAX_MEMO_BYTES + 1))
+            .is_err());
+        mempool
+            .add_transaction(with_memo(0, MAX_MEMO_BYTES))
+            .unwrap();
+    }
+
+    #[test]
+    fn sender_cancels_pending_tx_and_later_nonces() {
+        let (keypair, other) = (Keypair::generate_ed25519(), Keypair::generate_ed25519());
+        let mempool = funded_mempool(&[&keypair, &other]);
+        let txs: Vec<Transaction> = (0..3).map(|nonce| signed_tx(&keypair, nonce)).collect();
+        for tx in &txs {
+            mempool.add_transaction(tx.clone()).unwrap();
+        }
+
+        // Only the sender's signature counts
+        let forged = TxCancellation::signed(&txs[1].id, &other).unwrap();
+        assert!(mempool.cancel_transaction(&forged).is_err());
+        let mut tampered = TxCancellation::signed(&txs[0].id, &keypair).unwrap();
+        tampered.tx_id = txs[1].id.clone();
+        assert!(mempool.cancel_transaction(&tampered).is_err());
+
+        let cancel = TxCancellation::signed(&txs[1].id, &keypair).unwrap();
+        let dropped: Vec<String> = mempool
+            .cancel_transaction(&cancel)
+            .unwrap()
+            .into_iter()
+            .map(|tx| tx.id)
+            .collect();
+        assert_eq!(dropped, vec![txs[1].id.clone(), txs[2].id.clone()]);
+
+        let pending = mempool.get_pending_transactions();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, txs[0].id);
+        assert_eq!(mempool.storage.get_all_pending_txs().unwrap().len(), 1);
+        assert!(mempool.cancel_transaction(&cancel).is_err());
+    }
+
+    #[test]
+    fn cannot_cancel_mined_tx() {
+        let keypair = Keypair::generate_ed25519();
+        let mempool = funded_mempool(&[&keypair]);
+        let tx = signed_tx(&keypair, 0);
+        mempool.add_transaction(tx.clone()).unwrap();
+        let block = Block::new(
+            1,
+            "miner".into(),
+            vec![tx.clone()],
+            String::new(),
+            100,
+            100,
+            0,
+            0,
+            0,
+        );
+        mempool.storage.save_block(&block).unwrap();
+
+        let cancel = TxCancellation::signed(&tx.id, &keypair).unwrap();
+        assert_eq!(
+            mempool.cancel_transaction(&cancel).unwrap_err(),
+            "Transaction is already mined"
+        );
+    }
+
+    fn alias_claim(keypair: &Keypair, alias: &str) -> Transaction {
+        let mut tx = signed_tx(keypair, 0);
+        tx.receiver = tx.sender.clone();
+        tx.amount = 0;
+        tx.alias = Some(alias.into());
+        resigned(keypair, tx, 0)
+    }
+
+    #[test]
+    fn alias_is_registered_once_and_resolves_for_sends() {
+        let (alice, bob) = (Keypair::generate_ed25519(), Keypair::generate_ed25519());
+        let mempool = funded_mempool(&[&alice, &bob]);
+        let claim = alias_claim(&alice, "alice");
+        mempool.add_transaction(claim.clone()).unwrap();
+        assert_eq!(
+            mempool.add_transaction(alias_claim(&bob, "alice")),
+            Err("Alias alice is already being registered".to_string())
+        );
+
+        let block = Block::new(
+            1,
+            "miner".into(),
+            vec![claim],
+            String::new(),
+            100,
+            100,
+            0,
+            0,
+            0,
+        );
+        mempool.storage.save_block(&block).unwrap();
+        mempool.reconcile_with_chain().unwrap();
+        assert_eq!(
+            mempool.add_transaction(alias_claim(&bob, "alice")),
+            Err("Alias alice is already registered".to_string())
+        );
+
+        let owner = alice.public().to_peer_id().to_string();
+        assert_eq!(
+            resolve_receiver(&mempool.storage, "alice"),
+            Ok(owner.clone())
+        );
+        assert_eq!(resolve_receiver(&mempool.storage, &owner), Ok(owner));
+        assert!(resolve_receiver(&mempool.storage, "carol").is_err());
+        assert!(mempool.add_transaction(alias_claim(&bob, "Bob!")).is_err());
+    }
+
+    #[test]
+    fn reconcile_drops_mined_txs_without_reading_blocks() {
+        let keypair = Keypair::generate_ed25519();
+        let mempool = funded_mempool(&[&keypair]);
+        let mined = signed_tx(&keypair, 0);
+        let pending = signed_tx(&keypair, 1);
+        mempool.add_transaction(mined.clone()).unwrap();
+        mempool.add_transaction(pending.clone()).unwrap();
+
+        let block = Block::new(
+            1,
+            "miner".into(),
+            vec![mined],
+            String::new(),
+            100,
+            100,
+            0,
+            0,
+            0,
+        );
+        mempool.storage.save_block(&block).unwrap();
+
+        let reads = mempool.storage.block_reads.load(Ordering::Relaxed);
+        assert_eq!(mempool.reconcile_with_chain(), Ok(1));
+        assert_eq!(mempool.storage.block_reads.load(Ordering::Relaxed), reads);
+        let left: Vec<String> = mempool
+            .get_pending_transactions()
+            .into_iter()
+            .map(|tx| tx.id)
+            .collect();
+        assert_eq!(left, vec![pending.id]);
+    }
+
+    #[test]
+    fn estimate_is_base_fee_when_mempool_is_quiet() {
+        let keypair = Keypair::generate_ed25519();
+        let mempool = funded_mempool(&[&keypair]);
+        let base = calculate_fee(50_000_000);
+        assert_eq!(mempool.estimate_fee(50_000_000, 1).suggested_fee, base);
+
+        mempool.add_transaction(signed_tx(&keypair, 0)).unwrap();
+        let estimate = mempool.estimate_fee(50_000_000, 1);
+        assert_eq!((estimate.min_fee, estimate.suggested_fee), (base, base));
+        assert_eq!(estimate.pending, 1);
+    }
+
+    #[test]
+    fn estimate_outbids_the_queue_when_congested() {
+        let keypairs: Vec<Keypair> = (0..4).map(|_| Keypair::generate_ed25519()).collect();
+        let mut mempool = funded_mempool(&keypairs.iter().collect::<Vec<_>>());
+        mempool.block_capacity = 2;
+        for (keypair, fee) in keypairs.iter().zip([5_000, 4_000, 3_000, 2_000]) {
+            let tx = resigned(keypair, signed_tx(keypair, 0), fee);
+            mempool.add_transaction(tx).unwrap();
+        }
+
+        // One block holds the 5k and 4k payers; beat the 4k one
+        let base = calculate_fee(10_000);
+        assert_eq!(mempool.estimate_fee(10_000, 1).suggested_fee, 4_001);
+        assert_eq!(mempool.estimate_fee(10_000, 2).suggested_fee, 2_001);
+        assert_eq!(mempool.estimate_fee(10_000, 3).suggested_fee, base);
+    }
+
+    #[test]
+    fn rejects_nonce_below_confirmed() {
+        let keypair = Keypair::generate_ed25519();
+        let mempool = funded_mempool(&[&keypair]);
+
+        let first = signed_tx(&keypair, 0);
+        mempool.add_transaction(first.clone()).unwrap();
+        let block = Block::new(
+            1,
+            "miner".into(),
+            vec![first],
+            String::new(),
+            100,
+            100,
+            0,
+            0,
+            0,
+        );
+        mempool.storage.save_block(&block).unwrap();
+        mempool.remove_transactions(std::slice::from_ref(&block.transactions[0].id));
+
+        // Replay of nonce 0 with different contents
+        assert!(mempool.add_transaction(signed_tx(&keypair, 0)).is_err());
+        mempool.add_transaction(signed_tx(&keypair, 1)).unwrap();
+        assert_eq!(
+            mempool.next_nonce(&keypair.public().to_peer_id().to_string()),
+            2
+        );
+    }
+
+    #[test]
+    fn repeated_and_recently_mined_transactions_are_already_present() {
+        let keypair = Keypair::generate_ed25519();
+        let mempool = funded_mempool(&[&keypair]);
+        let tx = signed_tx(&keypair, 0);
+        assert_eq!(mempool.add_transaction(tx.clone()), Ok(true));
+        assert_eq!(mempool.add_transaction(tx.clone()), Ok(false));
+        assert_eq!(mempool.len(), 1);
+
+        // Re-gossip of a mined tx is dropped, until a reorg orphans its block
+        mempool.mark_mined(std::slice::from_ref(&tx.id));
+        assert_eq!(mempool.len(), 0);
+        assert_eq!(mempool.add_transaction(tx.clone()), Ok(false));
+        mempool.unmark_mined(std::slice::from_ref(&tx.id));
+        assert_eq!(mempool.add_transaction(tx), Ok(true));
+    }
+
+    #[test]
+    fn rejects_id_not_matching_contents() {
+        let keypair = Keypair::generate_ed25519();
+        let mempool = funded_mempool(&[&keypair]);
+
+        let mut tx = signed_tx(&keypair, 0);
+        tx.id = uuid::Uuid::new_v4().to_string();
+        tx.sign_with_keypair(&keypair).unwrap();
+        assert_eq!(
+            mempool.add_transaction(tx),
+            Err("Transaction id does not match its contents".to_string())
+        );
+    }
+
+    #[test]
+    fn ready_transactions_stop_at_nonce_gap() {
+        let keypair = Keypair::generate_ed25519();
+        let mempool = funded_mempool(&[&keypair]);
+        for nonce in [2, 0, 1, 4] {
+            mempool.add_transaction(signed_tx(&keypair, nonce)).unwrap();
+        }
+
+        let ready: Vec<u64> = mempool
+            .get_ready_transactions()
+            .iter()
+            .map(|tx| tx.nonce)
+            .collect();
+        assert_eq!(ready, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn high_fee_tx_wins_the_only_block_slot() {
+        let (low, high) = (Keypair::generate_ed25519(), Keypair::generate_ed25519());
+        let mempool = funded_mempool(&[&low, &high]);
+        let cheap = signed_tx_for(&low, 0, 10_000);
+        let pricey = signed_tx_for(&high, 0, 50_000_000);
+        mempool.add_transaction(cheap).unwrap();
+        mempool.add_transaction(pricey.clone()).unwrap();
+
+        let block_capacity = 1;
+        let included: Vec<_> = mempool
+            .get_pending_sorted()
+            .into_iter()
+            .take(block_capacity)
+            .collect();
+        assert_eq!(included.len(), 1);
+        assert_eq!(included[0].id, pricey.id);
+    }
+
+    #[test]
+    fn fee_ordering_keeps_sender_nonce_order() {
+        let (alice, bob) = (Keypair::generate_ed25519(), Keypair::generate_ed25519());
+        let mempool = funded_mempool(&[&alice, &bob]);
+        mempool
+            .add_transaction(signed_tx_for(&alice, 0, 10_000))
+            .unwrap();
+        mempool
+            .add_transaction(signed_tx_for(&alice, 1, 50_000_000))
+            .unwrap();
+        mempool
+            .add_transaction(signed_tx_for(&bob, 0, 20_000_000))
+            .unwrap();
+
+        let order: Vec<(bool, u64)> = mempool
+            .get_pending_sorted()
+            .iter()
+            .map(|tx| (tx.sender == bob.public().to_peer_id().to_string(), tx.nonce))
+            .collect();
+        assert_eq!(order, vec![(true, 0), (false, 0), (false, 1)]);
+    }
+
+    #[test]
+    fn full_pool_evicts_lowest_fee() {
+        let (a, b, c) = (
+            Keypair::generate_ed25519(),
+            Keypair::generate_ed25519(),
+            Keypair::generate_ed25519(),
+        );
+        let mut mempool = funded_mempool(&[&a, &b, &c]);
+        mempool.max_size = 2;
+
+        let cheap = signed_tx_for(&a, 0, 10_000);
+        let mid = signed_tx_for(&b, 0, 20_000_000);
+        mempool.add_transaction(cheap.clone()).unwrap();
+        mempool.add_transaction(mid.clone()).unwrap();
+
+        let rich = signed_tx_for(&c, 0, 50_000_000);
+        mempool.add_transaction(rich.clone()).unwrap();
+
+        assert_eq!(mempool.len(), 2);
+        let ids: Vec<String> = mempool
+            .get_pending_transactions()
+            .into_iter()
+            .map(|tx| tx.id)
+            .collect();
+        assert!(!ids.contains(&cheap.id));
+        assert!(ids.contains(&mid.id) && ids.contains(&rich.id));
+        let persisted = mempool.storage.get_all_pending_txs().unwrap();
+        assert!(persisted.iter().all(|tx| tx.id != cheap.id));
+    }
+
+    #[test]
+    fn full_pool_rejects_cheaper_tx() {
+        let (a, b) = (Keypair::generate_ed25519(), Keypair::generate_ed25519());
+        let mut mempool = funded_mempool(&[&a, &b]);
+        mempool.max_size = 1;
+
+        mempool
+            .add_transaction(signed_tx_for(&a, 0, 20_000_000))
+            .unwrap();
+        assert_eq!(
+            mempool.add_transaction(signed_tx_for(&b, 0, 10_000)),
+            Err("Mempool full: fee too low".to_string())
+        );
+        assert_eq!(mempool.len(), 1);
+    }
+
+    #[test]
+    fn rbf_replaces_with_sufficient_fee_bump() {
+        let keypair = Keypair::generate_ed25519();
+        let mempool = funded_mempool(&[&keypair]);
+        let original = signed_tx(&keypair, 0);
+        mempool.add_transaction(original.clone()).unwrap();
+
+        let bumped = resigned(&keypair, original.clone(), original.fee() * 2);
+        mempool.add_transaction(bumped.clone()).unwrap();
+
+        let pending = mempool.get_pending_transactions();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, bumped.id);
+        let persisted = mempool.storage.get_all_pending_txs().unwrap();
+        assert!(persisted.iter().all(|tx| tx.id != original.id));
+    }
+
+    #[test]
+    fn rbf_rejects_too_small_bump() {
+        let keypair = Keypair::generate_ed25519();
+        let mempool = funded_mempool(&[&keypair]);
+        let original = signed_tx(&keypair, 0);
+        mempool.add_transaction(original.clone()).unwrap();
+
+        // +5% is below the 10% minimum
+        let nudged = resigned(&keypair, original.clone(), original.fee() * 105 / 100);
+        assert!(mempool.add_transaction(nudged).is_err());
+        assert_eq!(mempool.get_pending_transactions()[0].id, original.id);
+    }
+
+    #[test]
+    fn sweep_drops_only_expired_transactions() {
+        let (old_sender, fresh_sender) = (Keypair::generate_ed25519(), Keypair::generate_ed25519());
+        let mempool = funded_mempool(&[&old_sender, &fresh_sender]);
+        let now = 1_700_000_000;
+
+        let mut stale = signed_tx(&old_sender, 0);
+        stale.timestamp = now - mempool.max_age_secs - 1;
+        let stale = resigned(&old_sender, stale, 0);
+        let mut locked = signed_tx(&fresh_sender, 1);
+        locked.timestamp = stale.timestamp;
+        locked.not_before_height = Some(100);
+        let locked = resigned(&fresh_sender, locked, 0);
+        let mut fresh = signed_tx(&fresh_sender, 0);
+        fresh.timestamp = now - 10;
+        let fresh = resigned(&fresh_sender, fresh, 0);
+        mempool.add_transaction(stale.clone()).unwrap();
+        mempool.add_transaction(fresh.clone()).unwrap();
+        mempool.add_transaction(locked.clone()).unwrap();
+
+        let dropped: Vec<String> = mempool
+            .sweep_expired(now)
+            .into_iter()
+            .map(|tx| tx.id)
+            .collect();
+        assert_eq!(dropped, vec![stale.id.clone()]);
+
+        // The locked tx outlives the age limit until it matures
+        let mut pending: Vec<String> = mempool
+            .get_pending_transactions()
+            .into_iter()
+            .map(|tx| tx.id)
+            .collect();
+        pending.sort();
+        let mut expected = vec![fresh.id.clone(), locked.id.clone()];
+        expected.sort();
+        assert_eq!(pending, expected);
+        let persisted = mempool.storage.get_all_pending_txs().unwrap();
+        assert!(persisted.iter().all(|tx| tx.id != stale.id));
+    }
+
+    #[test]
+    fn rejects_zero_amount_self_send_and_bad_receiver() {
+        let keypair = Keypair::generate_ed25519();
+        let mempool = funded_mempool(&[&keypair]);
+        let with = |edit: fn(&mut Transaction)| {
+            let mut tx = signed_tx(&keypair, 0);
+            edit(&mut tx);
+            resigned(&keypair, tx, 0)
+        };
+
+        let zero = with(|tx| tx.amount = 0);
+        assert_eq!(
+            mempool.add_transaction(zero),
+            Err("Amount must be greater than zero".to_string())
+        );
+        let to_self = with(|tx| tx.receiver = tx.sender.clone());
+        assert_eq!(
+            mempool.add_transaction(to_self),
+            Err("Cannot send coins to your own address".to_string())
+        );
+        let bad_receiver = with(|tx| tx.receiver = "not-a-peer-id".into());
+        assert!(mempool.add_transaction(bad_receiver).is_err());
+        assert!(mempool.get_pending_transactions().is_empty());
+
+        mempool.add_transaction(signed_tx(&keypair, 0)).unwrap();
+        assert_eq!(mempool.len(), 1);
+    }
+
+    /// Receipt for `block.transactions[index]` with its inclusion proof
+    fn receipt_for(block: &Block, index: usize) -> Receipt {
+        let tx = &block.transactions[index];
+        let tx_ids: Vec<String> = block.transactions.iter().map(|tx| tx.id.clone()).collect();
+        let mut receipt = Receipt {
+            original_tx_id: tx.id.clone(),
+            source_shard: 0,
+            target_shard: 1,
+            amount: tx.amount,
+            receiver: tx.receiver.clone(),
+            block_hash: String::new(),
+            merkle_proof: Vec::new(),
+            tx_index: 0,
+            status: ReceiptStatus::Pending,
+            source_header: None,
+            source_tx: None,
+            crosslink: None,
+        };
+        receipt.prove_in(block, index, MerkleTree::build(&tx_ids).proof(index));
+        receipt
+    }
+
+    #[test]
+    fn receipts_need_a_valid_merkle_proof() {
+        let keypair = Keypair::generate_ed25519();
+        let mempool = funded_mempool(&[]);
+        let storage = mempool.storage.clone();
+        // The source block lives on shard 0; this node only holds shard 1's chain
+        let txs = vec![signed_tx(&keypair, 0), signed_tx(&keypair, 1)];
+        let source = Block::new(7, "miner".into(), txs, "ab".repeat(32), 100, 100, 0, 0, 0);
+
+        let mut tampered = receipt_for(&source, 0);
+        tampered.merkle_proof[0] = "00".repeat(32);
+        assert!(mempool.add_receipt(tampered).is_err());
+        let mut inflated = receipt_for(&source, 0);
+        inflated.amount *= 100;
+        assert!(mempool.add_receipt(inflated).is_err());
+        let mut forged = receipt_for(&source, 0);
+        if let Some(header) = forged.source_header.as_mut() {
+            header.merkle_root = "00".repeat(32);
+        }
+        assert!(mempool.add_receipt(forged).is_err());
+        let mut headerless = receipt_for(&source, 0);
+        headerless.source_header = None;
+        assert!(mempool.add_receipt(headerless).is_err());
+        assert!(mempool.get_pending_receipts().is_empty());
+
+        let receipt = receipt_for(&source, 1);
+        mempool.add_receipt(receipt.clone()).unwrap();
+        let queued = mempool.get_pending_receipts();
+        assert_eq!(queued.len(), 1);
+
+        // The queued receipt mints on the target shard, crediting the receiver once
+        assert_eq!(storage.calculate_balance(&receipt.receiver).unwrap(), 0);
+        let parent = storage.get_block(0).unwrap().unwrap();
+        let mint = Block::new(
+            1,
+            "miner".into(),
+            vec![queued[0].to_mint_tx()],
+            parent.hash,
+            100,
+            100,
+            1,
+            0,
+            0,
+        );
+        storage.save_block(&mint).unwrap();
+        assert_eq!(
+            storage.calculate_balance(&receipt.receiver).unwrap(),
+            receipt.amount
+        );
+    }
+
+    #[test]
+    fn tracked_receipt_goes_from_pending_to_claimed() {
+        let mempool = funded_mempool(&[]);
+        let receipt = Receipt {
+            original_tx_id: "xshard-tx".into(),
+            source_shard: 0,
+            target_shard: 1,
+            amount: 5_000,
+            receiver: "receiver".into(),
+            block_hash: "block".into(),
+            merkle_proof: Vec::new(),
+            tx_index: 0,
+            status: ReceiptStatus::Pending,
+            source_header: None,
+            source_tx: None,
+            crosslink: None,
+        };
+        mempool.track_receipt(&receipt);
+        mempool.track_receipt(&receipt);
+
+        let tracked = mempool.get_tracked_receipts();
+        assert_eq!(tracked.len(), 1);
+        assert_eq!(tracked[0].original_tx_id, "xshard-tx");
+        assert_eq!(tracked[0].target_shard, 1);
+        assert_eq!(tracked[0].status, ReceiptStatus::Pending);
+
+        mempool.mark_receipts_claimed(&["xshard-tx".to_string()]);
+        assert_eq!(
+            mempool.get_tracked_receipts()[0].status,
+            ReceiptStatus::Claimed
+        );
+    }
+}
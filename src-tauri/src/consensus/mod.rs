@@ -8,6 +8,9 @@
 //! - `node_state`: Validator node state and status tracking
 //! - `leadership`: Leader election and eligibility logic
 //! - `epoch`: Time-based consensus mechanics (epochs and slots)
+//! - `fork_choice`: Deterministic choice between competing blocks
+//! - `equivocation`: Detection of leaders producing two blocks for one slot
+//! - `beacon`: Newest CrossLink per shard, the shards' finalized heights
 //! - `sharding`: Horizontal scaling through dynamic sharding
 //! - `mempool`: Transaction pool management
 //! - `vdf`: Verifiable Delay Function for Proof of Patience
@@ -19,10 +22,18 @@
 //! - **Slot-based Production**: Time is divided into slots; each slot has one designated leader per shard
 //! - **Trust Scores**: Nodes earn/lose trust based on block production performance
 
+use crate::storage::Storage;
+use crate::utils::constants::{
+    BASE_QUARANTINE_SECS, MAX_QUARANTINE_SECS, QUARANTINE_PER_VALIDATOR_SECS,
+};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 // Sub-modules
+pub mod beacon;
 pub mod epoch;
+pub mod equivocation;
+pub mod fork_choice;
 pub mod leadership;
 pub mod mempool;
 pub mod node_state;
@@ -30,13 +41,34 @@ pub mod sharding;
 pub mod vdf;
 
 // Re-exports for convenience
-pub use node_state::{NodeConsensusStatus, NodeState};
+pub use beacon::Beacon;
+pub use equivocation::EquivocationEvidence;
+pub use node_state::{NodeConsensusStatus, NodeState, ScheduledSlot};
 pub use vdf::CentichainVDF;
 
 // =============================================================================
 // Core Consensus Struct
 // =============================================================================
 
+/// How long a new validator waits before it may lead: `base_secs` plus
+/// `per_validator_secs` for every known validator, at most `max_secs`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuarantinePolicy {
+    pub base_secs: u64,
+    pub per_validator_secs: u64,
+    pub max_secs: u64,
+}
+
+impl Default for QuarantinePolicy {
+    fn default() -> Self {
+        Self {
+            base_secs: BASE_QUARANTINE_SECS,
+            per_validator_secs: QUARANTINE_PER_VALIDATOR_SECS,
+            max_secs: MAX_QUARANTINE_SECS,
+        }
+    }
+}
+
 /// Main consensus state manager
 ///
 /// Tracks all validator nodes and their states, manages leader election,
@@ -45,14 +77,23 @@ pub struct Consensus {
     /// Map of peer IDs to their node states
     pub nodes: HashMap<String, NodeState>,
 
-    /// Base quarantine duration in seconds
-    pub quarantine_duration: u64,
+    /// Quarantine formula, from the app settings
+    pub quarantine: QuarantinePolicy,
 
     /// VDF instance for Proof of Patience
     pub vdf: CentichainVDF,
 
     /// Local node's peer ID (if set)
     pub local_peer_id: Option<String>,
+
+    /// Backing store for validator state (None = in-memory only)
+    pub storage: Option<Arc<Storage>>,
+
+    /// Hash of the block each author produced per slot, for equivocation checks
+    pub produced_blocks: HashMap<(String, u64), String>,
+
+    /// CrossLinks collected from the shard leaders
+    pub beacon: Beacon,
 }
 
 impl Consensus {
@@ -60,9 +101,29 @@ impl Consensus {
     pub fn new() -> Self {
         Consensus {
             nodes: HashMap::new(),
-            quarantine_duration: 72 * 3600,   // 72 hours base
+            quarantine: QuarantinePolicy::default(),
             vdf: CentichainVDF::new(100_000), // Adjusted for demo (real would be higher)
             local_peer_id: None,
+            storage: None,
+            produced_blocks: HashMap::new(),
+            beacon: Beacon::default(),
+        }
+    }
+
+    /// Creates a Consensus instance rehydrated from, and persisting to, `storage`
+    pub fn with_storage(storage: Arc<Storage>) -> Self {
+        let mut consensus = Self::new();
+        consensus.load_from_storage(&storage);
+        consensus.storage = Some(storage);
+        consensus
+    }
+
+    /// Writes a single node's state through to storage, if attached
+    pub(crate) fn persist_node(&self, peer_id: &str) {
+        if let (Some(storage), Some(node)) = (&self.storage, self.nodes.get(peer_id)) {
+            if let Err(e) = storage.save_node_state(node) {
+                log::warn!("Failed to persist node state for {}: {}", peer_id, e);
+            }
         }
     }
 
@@ -89,6 +150,15 @@ impl Consensus {
                 node.trust_score = 1.0;
                 log::info!("Consensus: Local node FORCE ACTIVATED (Genesis/Authoritative Mode)");
             }
+            self.persist_node(peer_id);
+        }
+    }
+
+    /// Takes the local node out of leader election, e.g. while it shuts down.
+    /// Activation and trust are kept for when it comes back.
+    pub fn retire_local(&mut self) {
+        if let Some(peer_id) = self.local_peer_id.clone() {
+            self.set_peer_mining_status(&peer_id, false);
         }
     }
 
@@ -104,7 +174,10 @@ impl Consensus {
             node.trust_score *= 0.5; // Halve the trust score
 
             if node.trust_score < 0.01 {
-                node.trust_score = 0.01; // Floor at minimum
+                // Floor at minimum, but an equivocator's zero stays zero
+                if node.trust_score > 0.0 {
+                    node.trust_score = 0.01;
+                }
                 node.is_active = false; // Revoke active status
                 node.activated_at = None; // Remove permanent eligibility
                 log::warn!("Node {} DEACTIVATED due to low trust score", peer_id);
@@ -117,6 +190,7 @@ impl Consensus {
                 node.trust_score,
                 node.is_active
             );
+            self.persist_node(peer_id);
         }
     }
 
@@ -125,6 +199,7 @@ impl Consensus {
         if let Some(node) = self.nodes.get_mut(peer_id) {
             node.trust_score = (node.trust_score * 1.1).min(1.0);
             log::info!("REWARDED Node {}: New Score: {}", peer_id, node.trust_score);
+            self.persist_node(peer_id);
         }
     }
 
@@ -177,10 +252,13 @@ impl Consensus {
     pub fn get_quarantine_duration(&self) -> u64 {
         let validator_count = self.nodes.len() as u64;
         if validator_count <= 1 {
-            300 // 5 mins for solo/first peer
+            BASE_QUARANTINE_SECS // 5 mins for solo/first peer
         } else {
-            // +1 hour per validator, up to 72 hours
-            (300 + (validator_count * 3600)).min(72 * 3600)
+            let policy = &self.quarantine;
+            policy
+                .base_secs
+                .saturating_add(validator_count.saturating_mul(policy.per_validator_secs))
+                .min(policy.max_secs)
         }
     }
 }
@@ -361,6 +439,46 @@ mod tests {
         println!("Slot 10: {}, Slot 11: {}", leader_slot_10, leader_slot_11);
     }
 
+    #[test]
+    fn leader_schedule_is_deterministic() {
+        let mut consensus = Consensus::new();
+        for i in 0..3 {
+            let pid = format!("node{}", i);
+            let mut n = NodeState::new(pid.clone());
+            n.activate();
+            n.trust_score = 1.0;
+            n.is_verified = true;
+            consensus.nodes.insert(pid, n);
+        }
+
+        let now = 1_000_001;
+        let schedule = consensus.leader_schedule("node0", now, 6);
+        assert_eq!(schedule, consensus.leader_schedule("node0", now, 6));
+        assert_eq!(schedule.len(), 6);
+        assert_eq!(schedule[0].slot, now / Consensus::SLOT_DURATION);
+        assert_eq!(
+            schedule.iter().map(|s| s.starts_in).collect::<Vec<_>>(),
+            [0, 1, 3, 5, 7, 9]
+        );
+        for s in &schedule {
+            let leader = s.leader.as_deref().expect("all three nodes are eligible");
+            assert_eq!(
+                Some(leader),
+                consensus.get_shard_leader(0, s.slot).as_deref()
+            );
+            assert_eq!(s.is_me, leader == "node0");
+        }
+
+        // Slots nobody is eligible for have no leader
+        let mut idle = Consensus::new();
+        idle.register_node("a".to_string());
+        idle.register_node("b".to_string());
+        assert!(idle
+            .leader_schedule("a", now, 3)
+            .iter()
+            .all(|s| s.leader.is_none() && !s.is_me));
+    }
+
     #[test]
     fn test_block_author_registration_only() {
         let mut consensus = Consensus::new();
@@ -382,6 +500,32 @@ mod tests {
         assert!(node.activated_at.is_none());
     }
 
+    #[test]
+    fn test_node_states_survive_restart() {
+        let storage = Arc::new(Storage::temp());
+
+        let mut consensus = Consensus::with_storage(storage.clone());
+        for i in 0..3 {
+            let pid = format!("node{}", i);
+            let mut n = NodeState::new(pid.clone());
+            n.is_verified = true;
+            n.trust_score = 1.0;
+            if i < 2 {
+                n.activate();
+            }
+            consensus.nodes.insert(pid, n);
+        }
+        consensus.persist_to_storage(&storage);
+        consensus.reward_node(&"node2".to_string());
+        drop(consensus);
+
+        let restored = Consensus::with_storage(storage);
+        assert_eq!(restored.nodes.len(), 3);
+        assert!(restored.nodes["node0"].is_permanently_eligible());
+        assert!(restored.nodes["node1"].is_permanently_eligible());
+        assert!(!restored.nodes["node2"].is_permanently_eligible());
+    }
+
     #[test]
     fn test_persistent_eligibility() {
         // This test verifies the KEY fix: once activated, nodes stay eligible
@@ -418,4 +562,27 @@ mod tests {
             "New node should not be eligible without completing quarantine"
         );
     }
+
+    #[test]
+    fn quarantine_follows_the_configured_policy() {
+        let mut consensus = Consensus::new();
+        consensus.register_node("solo".to_string());
+        consensus.quarantine = QuarantinePolicy {
+            base_secs: 10,
+            per_validator_secs: 5,
+            max_secs: 40,
+        };
+        // A solo node keeps its exemption whatever the policy
+        assert_eq!(consensus.get_quarantine_duration(), 300);
+
+        consensus.register_node("second".to_string());
+        assert_eq!(consensus.get_quarantine_duration(), 20);
+        for i in 0..10 {
+            consensus.register_node(format!("node{}", i));
+        }
+        assert_eq!(consensus.get_quarantine_duration(), 40);
+
+        consensus.quarantine = QuarantinePolicy::default();
+        assert_eq!(consensus.get_quarantine_duration(), 300 + 12 * 3600);
+    }
 }
@@ -132,3 +132,21 @@ pub struct NodeConsensusStatus {
     /// Whether currently the slot leader
     pub is_slot_leader: bool,
 }
+
+/// One upcoming slot of a node's shard and who is due to produce it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledSlot {
+    pub slot: u64,
+
+    /// Elected leader, or None if no validator is eligible for the slot
+    pub leader: Option<String>,
+
+    /// Whether the leader is the node the schedule was computed for
+    pub is_me: bool,
+
+    /// Unix time (seconds) at which the slot opens
+    pub starts_at: u64,
+
+    /// Seconds from now until the slot opens
+    pub starts_in: u64,
+}
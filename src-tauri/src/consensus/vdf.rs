@@ -1,5 +1,31 @@
+//! # Verifiable Delay Function
+//!
+//! Wesolowski VDF over the RSA-2048 group, whose order is unknown. Solving
+//! takes `difficulty` sequential modular squarings, `y = x^(2^T) mod N`, plus a
+//! proof `π = x^⌊2^T / ℓ⌋` for a prime `ℓ` derived from the result. Verifying
+//! checks `π^ℓ · x^(2^T mod ℓ) = y`, which costs two short exponentiations no
+//! matter how large `T` is.
+
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive, Zero};
 use sha2::{Digest, Sha256};
-use std::fmt::Write;
+use std::sync::OnceLock;
+
+/// RSA-2048 challenge modulus; its factorization was never published.
+const RSA_2048: &str = "25195908475657893494027183240048398571429282126204032027777137836043662020707595556264018525880784406918290641249515082189298559149176184502808489120072844992687392807287776735971418347270261896375014971824691165077613379859095700097330459748808428401797429100642458691817195118746121515172654632282216869987549182422433637259085141865462043576798423387184774447920739934236584823824281198163815010674810451660377306056201619676256133844143603833904414952634432190114657544454178424020924616515723350778707749817125772467962926386356373289912154831438167899885040445364023527381951378636564391212010397122822120720357";
+
+/// Bit length of the Fiat-Shamir challenge prime `ℓ`
+const CHALLENGE_PRIME_BITS: u32 = 127;
+
+/// Miller-Rabin witnesses used when searching for `ℓ`
+const MILLER_RABIN_BASES: [u32; 20] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71,
+];
+
+fn modulus() -> &'static BigUint {
+    static MODULUS: OnceLock<BigUint> = OnceLock::new();
+    MODULUS.get_or_init(|| BigUint::parse_bytes(RSA_2048.as_bytes(), 10).unwrap())
+}
 
 pub struct CentichainVDF {
     pub difficulty: u64,
@@ -10,81 +36,152 @@ impl CentichainVDF {
         CentichainVDF { difficulty }
     }
 
-    pub fn solve(&self, challenge: &[u8]) -> String {
-        // Memory-Hard VDF (Simplified Scrypt-like approach)
-        // 1. Initialize a large buffer (e.g., 16MB for demo, 64-128MB for prod)
-        // We use a smaller buffer for dev efficiency but structure allows scaling.
-        const BUFFER_SIZE: usize = 16 * 1024 * 1024; // 16 MB
-        let mut buffer = vec![0u8; BUFFER_SIZE];
+    /// Runs the delay: returns the output `y` and its proof `π`.
+    pub fn evaluate(&self, challenge: &[u8]) -> (BigUint, BigUint) {
+        let n = modulus();
+        let x = hash_to_group(challenge);
 
-        // 2. Fill buffer deterministically derived from challenge
-        let mut hasher = Sha256::new();
-        hasher.update(challenge);
-        let seed = hasher.finalize();
-
-        let mut fill_rng = Sha256::new();
-        fill_rng.update(seed);
-        let mut fill_stream = fill_rng.finalize(); // Initial 32 bytes
-
-        // Fill buffer in 32-byte chunks (pseudo-randomly)
-        for i in (0..BUFFER_SIZE).step_by(32) {
-            fill_rng = Sha256::new();
-            fill_rng.update(fill_stream);
-            fill_stream = fill_rng.finalize();
-            let chunk_size = std::cmp::min(32, BUFFER_SIZE - i);
-            buffer[i..i + chunk_size].copy_from_slice(&fill_stream[0..chunk_size]);
+        let mut y = x.clone();
+        for _ in 0..self.difficulty {
+            y = &y * &y % n;
         }
 
-        // 3. Perform Random Memory Accesses (The "Work")
-        // Number of iterations = difficulty.
-        // Each iteration reads from a random location and writes to another.
-        // This forces CPU to wait for RAM (latency bound).
+        // π = x^⌊2^T / ℓ⌋, built bit by bit with long division of 2^T by ℓ
+        let l = hash_to_prime(&x, &y, self.difficulty)
+            .to_u128()
+            .expect("challenge prime fits in 128 bits");
+        let mut pi = BigUint::one();
+        let mut r: u128 = 1;
+        for _ in 0..self.difficulty {
+            pi = &pi * &pi % n;
+            r <<= 1;
+            if r >= l {
+                r -= l;
+                pi = &pi * &x % n;
+            }
+        }
+        (y, pi)
+    }
 
-        let mut pointer: usize = 0;
-        // Use a u32 from seed to pick indices
-        let mut index_gen = u32::from_le_bytes(seed[0..4].try_into().unwrap());
+    /// Times one solve and returns the squarings per second.
+    pub fn measure_ips(&self) -> u64 {
+        let start = std::time::Instant::now();
+        self.solve(b"heartbeat_challenge");
+        (self.difficulty as f64 / start.elapsed().as_secs_f64()) as u64
+    }
 
-        for _ in 0..self.difficulty {
-            // Pick a random reading location dependent on current state
-            // LCG for speed: x = (a * x + c) % m
-            index_gen = index_gen.wrapping_mul(1664525).wrapping_add(1013904223);
-            let read_index = (index_gen as usize) % BUFFER_SIZE;
+    /// Solves the VDF, encoding the result as `"{difficulty}:{y hex}:{π hex}"`.
+    pub fn solve(&self, challenge: &[u8]) -> String {
+        let (y, pi) = self.evaluate(challenge);
+        format!("{}:{:x}:{:x}", self.difficulty, y, pi)
+    }
 
-            // Read value
-            let val = buffer[read_index];
+    /// Checks a proof from [`solve`](Self::solve). Proofs of more squarings than
+    /// `difficulty` are accepted, since they took at least as long.
+    pub fn verify(&self, challenge: &[u8], proof: &str) -> bool {
+        let mut parts = proof.split(':');
+        let (Some(t), Some(y), Some(pi), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return false;
+        };
+        let (Ok(t), Some(y), Some(pi)) = (
+            t.parse::<u64>(),
+            BigUint::parse_bytes(y.as_bytes(), 16),
+            BigUint::parse_bytes(pi.as_bytes(), 16),
+        ) else {
+            return false;
+        };
+
+        let n = modulus();
+        if t < self.difficulty || y >= *n || pi >= *n || pi.is_zero() {
+            return false;
+        }
 
-            // Modify current pointer location
-            buffer[pointer] = buffer[pointer].wrapping_add(val).wrapping_mul(3);
+        let x = hash_to_group(challenge);
+        let l = hash_to_prime(&x, &y, t);
+        let r = BigUint::from(2u32).modpow(&BigUint::from(t), &l);
+        pi.modpow(&l, n) * x.modpow(&r, n) % n == y
+    }
+}
 
-            // Move pointer
-            pointer = (pointer + 1) % BUFFER_SIZE;
+/// Maps a challenge to a group element by expanding it with SHA-256.
+fn hash_to_group(challenge: &[u8]) -> BigUint {
+    let n = modulus();
+    let mut bytes = Vec::with_capacity(n.bits() as usize / 8 + 32);
+    let mut counter: u32 = 0;
+    while bytes.len() * 8 < n.bits() as usize + 128 {
+        let mut hasher = Sha256::new();
+        hasher.update(b"centichain-vdf-input");
+        hasher.update(counter.to_be_bytes());
+        hasher.update(challenge);
+        bytes.extend_from_slice(&hasher.finalize());
+        counter += 1;
+    }
+    BigUint::from_bytes_be(&bytes) % n
+}
 
-            // Periodically re-hash a chunk to chain dependencies
-            // (Skipping for pure speed in this demo, relying on dependency chain of 'buffer' values)
+/// Fiat-Shamir challenge: the first probable prime derived from `(x, y, t)`.
+fn hash_to_prime(x: &BigUint, y: &BigUint, t: u64) -> BigUint {
+    let top_bit = BigUint::one() << (CHALLENGE_PRIME_BITS - 1);
+    let mut counter: u64 = 0;
+    loop {
+        let mut hasher = Sha256::new();
+        hasher.update(b"centichain-vdf-prime");
+        hasher.update(x.to_bytes_be());
+        hasher.update(y.to_bytes_be());
+        hasher.update(t.to_be_bytes());
+        hasher.update(counter.to_be_bytes());
+        let digest = hasher.finalize();
+
+        let candidate =
+            (BigUint::from_bytes_be(&digest[..16]) % &top_bit) | &top_bit | BigUint::one();
+        if is_probable_prime(&candidate) {
+            return candidate;
         }
+        counter += 1;
+    }
+}
 
-        // 4. Final Hash of the entire buffer (or a sample)
-        let mut final_hasher = Sha256::new();
-        final_hasher.update(&buffer);
-        let result = final_hasher.finalize();
-
-        // Convert to hex string
-        let mut s = String::with_capacity(2 * result.len());
-        for byte in result {
-            write!(&mut s, "{:02x}", byte).unwrap();
+fn is_probable_prime(n: &BigUint) -> bool {
+    let one = BigUint::one();
+    let two = BigUint::from(2u32);
+    if *n < two {
+        return false;
+    }
+    for p in MILLER_RABIN_BASES {
+        let p = BigUint::from(p);
+        if *n == p {
+            return true;
+        }
+        if (n % &p).is_zero() {
+            return false;
         }
-        s
     }
 
-    pub fn verify(&self, challenge: &[u8], proof: &str) -> bool {
-        let calculated = self.solve(challenge);
-        calculated == proof
+    let n_minus_one = n - &one;
+    let s = n_minus_one.trailing_zeros().unwrap_or(0);
+    let d = &n_minus_one >> s;
+    'witness: for a in MILLER_RABIN_BASES {
+        let mut x = BigUint::from(a).modpow(&d, n);
+        if x == one || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = x.modpow(&two, n);
+            if x == n_minus_one {
+                continue 'witness;
+            }
+        }
+        return false;
     }
+    true
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::Instant;
 
     #[test]
     fn test_vdf_solve_verify() {
@@ -94,4 +191,42 @@ mod tests {
         assert!(vdf.verify(challenge, &proof));
         assert!(!vdf.verify(b"wrong_challenge", &proof));
     }
+
+    #[test]
+    fn rejects_forged_or_easier_proofs() {
+        let challenge = b"patience";
+        let proof = CentichainVDF::new(200).solve(challenge);
+        let (y, pi) = CentichainVDF::new(200).evaluate(challenge);
+
+        // A harder requirement rejects the proof; an easier one accepts it
+        assert!(!CentichainVDF::new(201).verify(challenge, &proof));
+        assert!(CentichainVDF::new(50).verify(challenge, &proof));
+
+        // Claiming more squarings than were performed fails
+        assert!(!CentichainVDF::new(200).verify(challenge, &format!("400:{y:x}:{pi:x}")));
+        let bumped = &y + 1u32;
+        assert!(!CentichainVDF::new(200).verify(challenge, &format!("200:{bumped:x}:{pi:x}")));
+        assert!(!CentichainVDF::new(200).verify(challenge, "not a proof"));
+    }
+
+    #[test]
+    fn verify_is_orders_of_magnitude_faster_than_solve() {
+        let vdf = CentichainVDF::new(1 << 16);
+        let challenge = b"proof_of_patience";
+
+        let start = Instant::now();
+        let proof = vdf.solve(challenge);
+        let solve_time = start.elapsed();
+
+        let start = Instant::now();
+        assert!(vdf.verify(challenge, &proof));
+        let verify_time = start.elapsed();
+
+        assert!(
+            verify_time * 100 < solve_time,
+            "verify {:?} vs solve {:?}",
+            verify_time,
+            solve_time
+        );
+    }
 }
@@ -0,0 +1,174 @@
+//! # BLS Signatures
+//!
+//! BLS12-381 signatures over the `blst` crate, with public keys in G1 and
+//! signatures in G2. Signatures of many signers over one message aggregate
+//! into a single signature that is checked against all their public keys at
+//! once; a shard committee signs its [`CrossLink`](crate::chain::CrossLink)s
+//! this way.
+//!
+//! Aggregates are only sound over keys whose owners proved they hold the
+//! secret key ([`prove_possession`]), or a rogue key could cancel out honest
+//! ones. Keys and signatures travel as hex of their compressed encoding.
+
+use blst::min_pk::{AggregateSignature, PublicKey, Signature};
+use blst::BLST_ERROR;
+use libp2p::identity::Keypair;
+use sha2::{Digest, Sha256};
+
+pub use blst::min_pk::SecretKey;
+
+/// Ciphersuite of message signatures (proof-of-possession scheme)
+const SIG_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// Ciphersuite of proofs of possession
+const POP_DST: &[u8] = b"BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// BLS key of a node, derived from its ed25519 identity so a wallet carries
+/// no second secret.
+pub fn derive_secret_key(identity: &Keypair) -> Result<SecretKey, String> {
+    let ed25519 = identity
+        .clone()
+        .try_into_ed25519()
+        .map_err(|_| "BLS keys are derived from ed25519 identities only".to_string())?;
+    let mut hasher = Sha256::new();
+    hasher.update(b"centichain-bls-v1");
+    hasher.update(ed25519.secret().as_ref());
+    SecretKey::key_gen(&hasher.finalize(), &[])
+        .map_err(|e| format!("BLS key generation failed: {:?}", e))
+}
+
+/// Hex public key of `secret`
+pub fn public_key(secret: &SecretKey) -> String {
+    hex::encode(secret.sk_to_pk().to_bytes())
+}
+
+/// Hex signature of `message`
+pub fn sign(secret: &SecretKey, message: &[u8]) -> String {
+    hex::encode(secret.sign(message, SIG_DST, &[]).to_bytes())
+}
+
+/// Signature over the signer's own public key, registered along with it
+pub fn prove_possession(secret: &SecretKey) -> String {
+    let public_key = secret.sk_to_pk().to_bytes();
+    hex::encode(secret.sign(&public_key, POP_DST, &[]).to_bytes())
+}
+
+pub fn verify_possession(public_key: &str, proof: &str) -> Result<(), String> {
+    let key = parse_public_key(public_key)?;
+    let proof = parse_signature(proof)?;
+    match proof.verify(true, &key.to_bytes(), POP_DST, &[], &key, true) {
+        BLST_ERROR::BLST_SUCCESS => Ok(()),
+        e => Err(format!("Invalid proof of possession: {:?}", e)),
+    }
+}
+
+/// Checks one signer's signature of `message`.
+pub fn verify(signature: &str, message: &[u8], public_key: &str) -> Result<(), String> {
+    verify_aggregate(signature, message, &[public_key.to_string()])
+}
+
+/// Combines signatures of one message into a single hex signature.
+pub fn aggregate(signatures: &[String]) -> Result<String, String> {
+    if signatures.is_empty() {
+        return Err("Nothing to aggregate".into());
+    }
+    let signatures = signatures
+        .iter()
+        .map(|signature| parse_signature(signature))
+        .collect::<Result<Vec<_>, _>>()?;
+    let refs: Vec<&Signature> = signatures.iter().collect();
+    let aggregate = AggregateSignature::aggregate(&refs, true)
+        .map_err(|e| format!("BLS aggregation failed: {:?}", e))?;
+    Ok(hex::encode(aggregate.to_signature().to_bytes()))
+}
+
+/// Checks that `signature` aggregates a signature of `message` by every one
+/// of `public_keys`.
+pub fn verify_aggregate(
+    signature: &str,
+    message: &[u8],
+    public_keys: &[String],
+) -> Result<(), String> {
+    if public_keys.is_empty() {
+        return Err("No signers".into());
+    }
+    let signature = parse_signature(signature)?;
+    let keys = public_keys
+        .iter()
+        .map(|key| parse_public_key(key))
+        .collect::<Result<Vec<_>, _>>()?;
+    let refs: Vec<&PublicKey> = keys.iter().collect();
+    match signature.fast_aggregate_verify(true, message, SIG_DST, &refs) {
+        BLST_ERROR::BLST_SUCCESS => Ok(()),
+        e => Err(format!("Invalid BLS signature: {:?}", e)),
+    }
+}
+
+fn parse_public_key(key: &str) -> Result<PublicKey, String> {
+    let bytes = hex::decode(key).map_err(|_| "Invalid BLS public key hex".to_string())?;
+    let key =
+        PublicKey::key_validate(&bytes).map_err(|e| format!("Invalid BLS public key: {:?}", e))?;
+    Ok(key)
+}
+
+fn parse_signature(signature: &str) -> Result<Signature, String> {
+    let bytes = hex::decode(signature).map_err(|_| "Invalid BLS signature hex".to_string())?;
+    Signature::from_bytes(&bytes).map_err(|e| format!("Invalid BLS signature: {:?}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn committee(size: usize) -> Vec<SecretKey> {
+        (0..size)
+            .map(|_| derive_secret_key(&Keypair::generate_ed25519()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn aggregate_verifies_against_all_signers() {
+        let members = committee(4);
+        let message = b"shard 1 block 42";
+        let keys: Vec<String> = members.iter().map(public_key).collect();
+        let signatures: Vec<String> = members.iter().map(|sk| sign(sk, message)).collect();
+        for (signature, key) in signatures.iter().zip(&keys) {
+            verify(signature, message, key).unwrap();
+        }
+
+        let aggregate = aggregate(&signatures).unwrap();
+        verify_aggregate(&aggregate, message, &keys).unwrap();
+        // Every key is required, and only for the signed message
+        assert!(verify_aggregate(&aggregate, message, &keys[..3]).is_err());
+        assert!(verify_aggregate(&aggregate, b"shard 1 block 43", &keys).is_err());
+    }
+
+    #[test]
+    fn forged_aggregate_is_rejected() {
+        let members = committee(3);
+        let outsider = committee(1).remove(0);
+        let message = b"shard 0 block 7";
+        let keys: Vec<String> = members.iter().map(public_key).collect();
+
+        // Two members plus an outsider posing as the third
+        let mut signatures: Vec<String> = members[..2].iter().map(|sk| sign(sk, message)).collect();
+        signatures.push(sign(&outsider, message));
+        let forged = aggregate(&signatures).unwrap();
+        assert!(verify_aggregate(&forged, message, &keys).is_err());
+        assert!(verify_aggregate("00".repeat(96).as_str(), message, &keys).is_err());
+    }
+
+    #[test]
+    fn keys_are_stable_and_prove_possession() {
+        let identity = Keypair::generate_ed25519();
+        let secret = derive_secret_key(&identity).unwrap();
+        let key = public_key(&secret);
+        assert_eq!(public_key(&derive_secret_key(&identity).unwrap()), key);
+
+        verify_possession(&key, &prove_possession(&secret)).unwrap();
+        let other = committee(1).remove(0);
+        assert!(verify_possession(&key, &prove_possession(&other)).is_err());
+        // A message signature is not a proof of possession
+        assert!(verify_possession(&key, &sign(&secret, &hex::decode(&key).unwrap())).is_err());
+    }
+}
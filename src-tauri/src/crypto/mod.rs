@@ -0,0 +1,7 @@
+//! # Cryptography
+//!
+//! Signature schemes used next to the ed25519 keys of wallets and peers.
+//!
+//! - `bls`: Aggregatable BLS signatures for shard committees
+
+pub mod bls;
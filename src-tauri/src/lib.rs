@@ -1,6 +1,7 @@
 pub mod chain;
 pub mod commands;
 pub mod consensus;
+pub mod crypto;
 pub mod network;
 pub mod node;
 pub mod state;
@@ -11,17 +12,57 @@ pub mod wallet;
 use crate::consensus::{mempool::Mempool, Consensus};
 use crate::state::{AppSettings, AppState, NodeType};
 use crate::storage::Storage;
+use crate::wallet::encryption::StoredKey;
+use crate::wallet::hd::HdRecord;
 use crate::wallet::Wallet;
 use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming, WriteMode};
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, AtomicUsize};
 use std::sync::Arc;
 use std::sync::Mutex;
 
+/// Opens the node database in the resolved data directory (see
+/// `utils::paths`), first copying one from the default directory or
+/// `legacy_dbs` there if it has none yet.
+pub fn open_storage(legacy_dbs: &[PathBuf]) -> Result<Storage, String> {
+    use crate::utils::paths::{self, DATA_DIR_ENV, DB_FILE_NAME};
+
+    let default_dir = paths::default_data_dir();
+    let default_db = default_dir.join(DB_FILE_NAME);
+    let env = std::env::var(DATA_DIR_ENV).ok();
+    // Settings are kept in the database, so `data_dir` is read from the default one
+    let configured = if env.is_none() && default_db.is_file() {
+        let storage = Storage::new(&default_db.to_string_lossy())
+            .map_err(|e| format!("Cannot open {}: {}", default_db.display(), e))?;
+        AppSettings::load(&storage).data_dir
+    } else {
+        None
+    };
+    let dir = paths::resolve_data_dir(env, configured, default_dir);
+    let db_path = dir.join(DB_FILE_NAME);
+
+    let mut sources = vec![default_db];
+    sources.extend_from_slice(legacy_dbs);
+    if let Some(source) = paths::migrate_db(&sources, &db_path)? {
+        log::info!(
+            "Copied database {} to {}",
+            source.display(),
+            db_path.display()
+        );
+    }
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+    let storage = Storage::new(&db_path.to_string_lossy())
+        .map_err(|e| format!("Cannot open {}: {}", db_path.display(), e))?;
+    log::info!("Using data directory {}", dir.display());
+    paths::set_data_dir(dir);
+    Ok(storage)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // Initialize Professional Logging
-    Logger::try_with_str("info, centichain_lib=debug")
-        .unwrap()
+    const LOG_SPEC: &str = "info, centichain_lib=debug";
+    let logger = utils::logging::apply_log_format(Logger::try_with_str(LOG_SPEC).unwrap())
         .log_to_file(FileSpec::default().directory("logs").basename("centichain"))
         .write_mode(WriteMode::Async)
         .rotate(
@@ -31,65 +72,69 @@ pub fn run() {
         )
         .start()
         .expect("Failed to initialize logger");
+    utils::logging::install(logger, LOG_SPEC);
 
     // Initialize DB
-    let mut db_path = dirs::data_dir().unwrap_or_else(std::env::temp_dir);
-    db_path.push("centichain");
-    std::fs::create_dir_all(&db_path).ok();
-    db_path.push("centichain.db");
-    let storage = Storage::new(db_path.to_str().unwrap()).expect("Failed to create DB");
+    let storage = open_storage(&utils::paths::legacy_db_paths()).expect("Failed to create DB");
     let storage_arc = Arc::new(storage);
 
     // Initial load of settings
     let (initial_mining, initial_node_type) = match storage_arc.get_setting("app_settings") {
         Ok(Some(json)) => {
             let s = serde_json::from_str::<AppSettings>(&json).unwrap_or_default();
+            chain::set_chain_id(s.chain_id);
+            chain::set_fee_policy(s.fee_policy);
+            chain::set_checkpoints(s.checkpoints);
+            storage_arc.set_min_free_disk(s.min_free_disk_mb.saturating_mul(1024 * 1024));
             (s.mining_enabled, s.node_type)
         }
         _ => (true, NodeType::Pruned),
     };
 
-    // Initial metrics from DB
-    let initial_height = storage_arc.get_latest_index().unwrap_or(0);
+    // Initial metrics from DB; a light node's height is its header tip
+    let initial_height = match initial_node_type {
+        NodeType::Light => storage_arc.get_header_tip().ok().flatten().unwrap_or(0),
+        _ => storage_arc.get_latest_index().unwrap_or(0),
+    };
 
-    // Attempt to derive address for initial count
-    let initial_mined_count = if let Ok(Some(keys_json)) = storage_arc.get_wallet_keys() {
-        if let Ok(kp_bytes) = serde_json::from_str::<Vec<u8>>(&keys_json) {
-            if let Ok(kp) = libp2p::identity::Keypair::from_protobuf_encoding(&kp_bytes) {
-                let addr = kp.public().to_peer_id().to_string();
-                storage_arc.count_blocks_by_author(&addr).unwrap_or(0)
-            } else {
-                0
-            }
-        } else {
-            0
-        }
-    } else {
-        0
+    // Plaintext keys load immediately; encrypted keys stay locked until `unlock_wallet`
+    let stored_key = storage_arc
+        .get_wallet_keys()
+        .ok()
+        .flatten()
+        .and_then(|json| StoredKey::parse(&json).ok());
+
+    let initial_wallet = match &stored_key {
+        Some(StoredKey::Plain(kp_bytes)) => Wallet::from_keypair_bytes(kp_bytes.clone()).ok(),
+        _ => None,
     };
 
-    let initial_wallet = if let Ok(Some(keys_json)) = storage_arc.get_wallet_keys() {
-        if let Ok(kp_bytes) = serde_json::from_str::<Vec<u8>>(&keys_json) {
-            if let Ok(kp) = libp2p::identity::Keypair::from_protobuf_encoding(&kp_bytes) {
-                let addr = kp.public().to_peer_id().to_string();
-                Some(Wallet {
-                    start_timestamp: 0, // Not critical for display
-                    address: addr,
-                    alias: None,
-                    keypair: kp_bytes,
-                })
-            } else {
-                None
-            }
-        } else {
-            None
-        }
-    } else {
-        None
+    // Unencrypted HD wallets open on their selected account
+    let stored_hd = storage_arc
+        .get_wallet_hd()
+        .ok()
+        .flatten()
+        .and_then(|json| HdRecord::parse(&json).ok());
+    let initial_wallet = match stored_hd {
+        Some(HdRecord {
+            seed: StoredKey::Plain(seed),
+            active,
+            ..
+        }) if initial_wallet.is_some() => Wallet::from_seed(seed, active).ok().or(initial_wallet),
+        _ => initial_wallet,
+    };
+
+    // Attempt to derive address for initial count
+    let initial_address = match &stored_key {
+        Some(StoredKey::Encrypted(encrypted)) => Some(encrypted.address.clone()),
+        _ => initial_wallet.as_ref().map(|w| w.address.clone()),
     };
+    let initial_mined_count = initial_address
+        .map(|addr| storage_arc.count_blocks_by_author(&addr).unwrap_or(0))
+        .unwrap_or(0);
 
-    let mut initial_consensus = Consensus::new();
-    initial_consensus.load_from_storage(&storage_arc);
+    let mut initial_consensus = Consensus::with_storage(storage_arc.clone());
+    initial_consensus.quarantine = AppSettings::load(&storage_arc).quarantine_policy();
 
     tauri::Builder::default()
         .manage(AppState {
@@ -124,6 +169,9 @@ pub fn run() {
             node_type: Arc::new(Mutex::new(initial_node_type)),
             vdf_ips: Arc::new(std::sync::atomic::AtomicU64::new(0)),
             p2p_cmd_sender: Arc::new(Mutex::new(None)),
+            peer_scores: Arc::new(Mutex::new(network::peer_score::PeerScores::new())),
+            peer_latencies: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            network_graph: Arc::new(Mutex::new(std::collections::HashMap::new())),
         })
         .plugin(tauri_plugin_opener::init())
         .invoke_handler(tauri::generate_handler![
@@ -131,7 +179,16 @@ pub fn run() {
             commands::wallet::create_wallet,
             commands::wallet::import_wallet,
             commands::wallet::get_wallet_info,
+            commands::wallet::unlock_wallet,
+            commands::wallet::is_wallet_locked,
             commands::wallet::logout_wallet,
+            commands::wallet::add_account,
+            commands::wallet::list_accounts,
+            commands::wallet::switch_active_account,
+            commands::wallet::sign_message,
+            commands::wallet::verify_message,
+            commands::wallet::export_keystore,
+            commands::wallet::import_keystore,
             // Node
             commands::node::start_node,
             commands::node::stop_node,
@@ -143,19 +200,49 @@ pub fn run() {
             commands::chain::get_recent_blocks,
             commands::chain::get_blocks_paginated,
             commands::chain::get_chain_stats,
+            commands::chain::get_performance_stats,
+            commands::chain::get_shard_metrics,
             commands::chain::get_mined_blocks_count,
             commands::chain::submit_transaction,
+            commands::chain::replace_transaction,
+            commands::chain::cancel_transaction,
+            commands::chain::register_alias,
+            commands::chain::resolve_alias,
             commands::chain::get_mempool_transactions,
+            commands::chain::get_mempool_status,
+            commands::chain::get_pending_receipts,
+            commands::chain::estimate_fee,
             commands::chain::reset_chain_data,
+            commands::chain::recompute_state,
+            commands::chain::get_balance_at_height,
+            commands::chain::get_rich_list,
+            commands::chain::get_chain_audit,
+            commands::chain::get_address_transactions,
+            commands::chain::export_chain,
+            commands::chain::import_chain,
             commands::chain::get_tokenomics_info,
             commands::chain::get_consensus_status,
+            commands::chain::get_leader_schedule,
+            commands::chain::get_equivocations,
+            commands::chain::get_slashings,
+            commands::chain::get_shard_finality,
+            commands::chain::report_equivocation,
             // Network
             commands::network::get_network_info,
             commands::network::get_self_node_info,
+            commands::network::get_peer_scores,
+            commands::network::dial_peer,
+            commands::network::add_peer,
+            commands::network::disconnect_peer,
+            commands::network::get_connected_peers,
+            commands::network::get_shard_for_address,
+            commands::network::get_network_topology,
             // General
             commands::general::greet,
             commands::general::get_app_settings,
             commands::general::save_app_settings,
+            commands::general::get_log_level,
+            commands::general::set_log_level,
             commands::general::exit_app
         ])
         .run(tauri::generate_context!())
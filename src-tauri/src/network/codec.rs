@@ -0,0 +1,87 @@
+//! # Gossip Codec
+//!
+//! Blocks, transactions and receipts are gossiped as bincode behind a one-byte
+//! format tag. Untagged payloads are read as JSON, which is what nodes published
+//! before the tag existed; a JSON object never starts with the tag byte.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Leading byte of a bincode-encoded gossip payload
+pub const FORMAT_BINCODE: u8 = 0x01;
+
+/// Encodes `value` for gossip as tagged bincode.
+pub fn encode_gossip<T: Serialize>(value: &T) -> Result<Vec<u8>, String> {
+    let body = bincode::serialize(value).map_err(|e| format!("Encode failed: {}", e))?;
+    let mut data = Vec::with_capacity(body.len() + 1);
+    data.push(FORMAT_BINCODE);
+    data.extend_from_slice(&body);
+    Ok(data)
+}
+
+/// Decodes a gossip payload in either the tagged bincode or the legacy JSON format.
+pub fn decode_gossip<T: DeserializeOwned>(data: &[u8]) -> Result<T, String> {
+    match data.split_first() {
+        Some((&FORMAT_BINCODE, body)) => {
+            bincode::deserialize(body).map_err(|e| format!("Invalid bincode payload: {}", e))
+        }
+        _ => serde_json::from_slice(data).map_err(|e| format!("Invalid JSON payload: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::{Block, Transaction, SYSTEM_SIG_REWARD};
+
+    fn block() -> Block {
+        let coinbase = Transaction {
+            id: "coinbase-1".into(),
+            sender: "SYSTEM".into(),
+            receiver: "miner".into(),
+            amount: 1_000,
+            shard_id: 0,
+            timestamp: 0,
+            nonce: 0,
+            fee: 0,
+            signature: SYSTEM_SIG_REWARD.into(),
+            sender_pubkey: String::new(),
+            outputs: Vec::new(),
+            memo: None,
+            not_before_height: None,
+            alias: None,
+        };
+        Block::new(
+            1,
+            "miner".into(),
+            vec![coinbase],
+            "ab".repeat(32),
+            100,
+            100,
+            0,
+            0,
+            1_000,
+        )
+    }
+
+    #[test]
+    fn block_round_trips_through_bincode() {
+        let block = block();
+        let data = encode_gossip(&block).unwrap();
+        assert_eq!(data[0], FORMAT_BINCODE);
+        assert!(data.len() < serde_json::to_vec(&block).unwrap().len());
+
+        let decoded: Block = decode_gossip(&data).unwrap();
+        assert_eq!(decoded.hash, block.hash);
+        assert_eq!(decoded.calculate_hash(), block.hash);
+        assert_eq!(decoded.transactions[0].id, "coinbase-1");
+    }
+
+    #[test]
+    fn still_reads_legacy_json() {
+        let block = block();
+        let decoded: Block = decode_gossip(&serde_json::to_vec(&block).unwrap()).unwrap();
+        assert_eq!(decoded.hash, block.hash);
+        assert!(decode_gossip::<Block>(&[FORMAT_BINCODE, 0xff]).is_err());
+    }
+}
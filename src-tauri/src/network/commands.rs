@@ -2,6 +2,8 @@
 //!
 //! Defines commands that can be sent to the P2P network layer.
 
+use libp2p::multiaddr::Protocol;
+use libp2p::{Multiaddr, PeerId};
 use serde::{Deserialize, Serialize};
 
 /// Commands that can be sent to the P2P network from other parts of the application
@@ -12,6 +14,90 @@ pub enum P2PCommand {
 
     /// Broadcast mining status change to network
     BroadcastMiningStatus { mining_active: bool },
+
+    /// Tell peers this node is shutting down; replies once the farewell is queued
+    AnnounceLeaving(tokio::sync::oneshot::Sender<()>),
+
+    /// Ask peers to drop a pending transaction of the local wallet
+    BroadcastCancellation(crate::chain::TxCancellation),
+
+    /// Send a sync request to a connected peer and hand back its answer; light
+    /// nodes fetch bodies and balances this way
+    QueryPeer {
+        request: crate::chain::SyncRequest,
+        reply: tokio::sync::oneshot::Sender<Result<crate::chain::SyncResponse, String>>,
+    },
+
+    /// Dial a peer by address, bypassing discovery
+    DialPeer(Multiaddr),
+
+    /// Add a `/p2p/`-terminated address to Kademlia and dial it
+    AddPeer(Multiaddr),
+
+    /// Close every connection to a peer
+    DisconnectPeer(PeerId),
+
+    /// Report the peers the swarm is connected to right now
+    GetConnectedPeers(tokio::sync::oneshot::Sender<Vec<PeerInfo>>),
+}
+
+/// A peer as shown to the UI
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct PeerInfo {
+    pub peer_id: String,
+    pub trust_score: f64,
+    pub is_verified: bool,
+    pub latency: u64,
+    pub addresses: Vec<String>,
+}
+
+/// Swarm operations behind the manual peer commands, so they can be mocked
+pub trait PeerControl {
+    fn dial(&mut self, address: Multiaddr) -> Result<(), String>;
+    fn disconnect(&mut self, peer: PeerId) -> Result<(), String>;
+    /// Records `address` for `peer` in the Kademlia routing table
+    fn add_address(&mut self, peer: PeerId, address: Multiaddr);
+}
+
+/// Parses a peer address such as `/ip4/203.0.113.5/tcp/9000/p2p/12D3...`,
+/// returning the peer id it ends with.
+pub fn parse_peer_address(address: &str) -> Result<(PeerId, Multiaddr), String> {
+    let address: Multiaddr = address
+        .trim()
+        .parse()
+        .map_err(|e| format!("Invalid multiaddr: {}", e))?;
+    match address.iter().last() {
+        Some(Protocol::P2p(peer)) => Ok((peer, address)),
+        _ => Err(format!("{} does not end with /p2p/<peer id>", address)),
+    }
+}
+
+/// Handles `P2PCommand::AddPeer`.
+pub fn add_peer(control: &mut impl PeerControl, address: Multiaddr) {
+    let mut transport = address.clone();
+    let Some(Protocol::P2p(peer)) = transport.pop() else {
+        log::warn!("P2P: Cannot add {} without a peer id", address);
+        return;
+    };
+    log::info!("P2P: Adding peer {} at {}", peer, transport);
+    control.add_address(peer, transport);
+    dial_peer(control, address);
+}
+
+/// Handles `P2PCommand::DialPeer`.
+pub fn dial_peer(control: &mut impl PeerControl, address: Multiaddr) {
+    log::info!("P2P: Manually dialing {}", address);
+    if let Err(e) = control.dial(address.clone()) {
+        log::warn!("P2P: Failed to dial {}: {}", address, e);
+    }
+}
+
+/// Handles `P2PCommand::DisconnectPeer`.
+pub fn disconnect_peer(control: &mut impl PeerControl, peer: PeerId) {
+    log::info!("P2P: Manually disconnecting {}", peer);
+    if let Err(e) = control.disconnect(peer) {
+        log::warn!("P2P: Failed to disconnect {}: {}", peer, e);
+    }
 }
 
 /// Topology update message for network graph visualization
@@ -38,3 +124,65 @@ impl TopologyUpdate {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct MockSwarm {
+        dialed: Vec<Multiaddr>,
+        disconnected: Vec<PeerId>,
+        routing: Vec<(PeerId, Multiaddr)>,
+    }
+
+    impl PeerControl for MockSwarm {
+        fn dial(&mut self, address: Multiaddr) -> Result<(), String> {
+            self.dialed.push(address);
+            Ok(())
+        }
+
+        fn disconnect(&mut self, peer: PeerId) -> Result<(), String> {
+            self.disconnected.push(peer);
+            Err("Not connected".into())
+        }
+
+        fn add_address(&mut self, peer: PeerId, address: Multiaddr) {
+            self.routing.push((peer, address));
+        }
+    }
+
+    #[test]
+    fn dial_peer_command_dials_the_address() {
+        let address: Multiaddr = "/ip4/10.0.0.7/tcp/9000".parse().unwrap();
+        let peer = PeerId::random();
+        let mut swarm = MockSwarm::default();
+
+        dial_peer(&mut swarm, address.clone());
+        // A failed disconnect is only logged
+        disconnect_peer(&mut swarm, peer);
+
+        assert_eq!(swarm.dialed, vec![address]);
+        assert_eq!(swarm.disconnected, vec![peer]);
+    }
+
+    #[test]
+    fn added_peer_is_routed_and_dialed() {
+        let peer = PeerId::random();
+        let text = format!("/ip4/203.0.113.5/tcp/9000/p2p/{}", peer);
+        let (parsed, address) = parse_peer_address(&text).unwrap();
+        assert_eq!(parsed, peer);
+
+        let mut swarm = MockSwarm::default();
+        add_peer(&mut swarm, address.clone());
+        let transport: Multiaddr = "/ip4/203.0.113.5/tcp/9000".parse().unwrap();
+        assert_eq!(swarm.routing, vec![(peer, transport)]);
+        assert_eq!(swarm.dialed, vec![address]);
+    }
+
+    #[test]
+    fn garbage_peer_address_is_rejected() {
+        assert!(parse_peer_address("not a multiaddr").is_err());
+        assert!(parse_peer_address("/ip4/203.0.113.5/tcp/9000").is_err());
+    }
+}
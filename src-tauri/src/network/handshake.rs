@@ -0,0 +1,169 @@
+//! # Protocol Handshake
+//!
+//! Peers advertise `/centichain/{major}.{minor}.{patch}/{chain_id}` through
+//! identify. A different major version means an incompatible wire format and a
+//! different chain id means another network; either way the peer is dropped
+//! before it can feed us blocks.
+//!
+//! Optional features are listed in the identify agent version, e.g.
+//! `centichain/1.0.0 (gzip-sync)`; peers that don't list one never get it.
+
+use std::fmt;
+
+/// Identify protocol name, followed by the version and chain id
+pub const PROTOCOL_NAME: &str = "/centichain";
+
+/// Version of the block, transaction and sync formats spoken by this node
+pub const PROTOCOL_VERSION: &str = "1.0.0";
+
+/// Reads gzipped block batches in sync responses
+pub const CAPABILITY_GZIP_SYNC: &str = "gzip-sync";
+
+/// Optional features this node supports
+const CAPABILITIES: &[&str] = &[CAPABILITY_GZIP_SYNC];
+
+/// Identify protocol version advertised on the network `chain_id`
+pub fn identify_protocol_version(chain_id: u32) -> String {
+    format!("{}/{}/{}", PROTOCOL_NAME, PROTOCOL_VERSION, chain_id)
+}
+
+/// Identify agent version: client, version and supported capabilities
+pub fn identify_agent_version() -> String {
+    format!(
+        "centichain/{} ({})",
+        PROTOCOL_VERSION,
+        CAPABILITIES.join(",")
+    )
+}
+
+/// Whether a peer's identify `agent_version` lists `capability`.
+pub fn has_capability(agent_version: &str, capability: &str) -> bool {
+    agent_version
+        .rsplit_once('(')
+        .and_then(|(_, list)| list.strip_suffix(')'))
+        .is_some_and(|list| list.split(',').any(|c| c.trim() == capability))
+}
+
+/// Why a peer was found incompatible
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Incompatibility {
+    /// Not a `/centichain/x.y.z/...` protocol string at all
+    Unrecognized(String),
+    /// Speaks another major protocol version
+    MajorVersion { local: u64, remote: u64 },
+    /// Runs another network, or advertises none
+    ChainId { local: u32, remote: Option<u32> },
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Incompatibility::Unrecognized(protocol) => {
+                write!(f, "unrecognized protocol {:?}", protocol)
+            }
+            Incompatibility::MajorVersion { local, remote } => write!(
+                f,
+                "protocol major version {} is incompatible with ours ({})",
+                remote, local
+            ),
+            Incompatibility::ChainId { local, remote } => match remote {
+                Some(remote) => write!(f, "peer is on chain {}, we are on {}", remote, local),
+                None => write!(f, "peer advertises no chain id, we are on {}", local),
+            },
+        }
+    }
+}
+
+/// Major component of a `major.minor.patch` version
+fn major_version(version: &str) -> Option<u64> {
+    version.split('.').next()?.parse().ok()
+}
+
+/// Checks the identify `protocol_version` of a peer against ours.
+pub fn check_peer_protocol(protocol_version: &str, chain_id: u32) -> Result<(), Incompatibility> {
+    let unrecognized = || Incompatibility::Unrecognized(protocol_version.to_string());
+    let rest = protocol_version
+        .strip_prefix(PROTOCOL_NAME)
+        .and_then(|rest| rest.strip_prefix('/'))
+        .ok_or_else(unrecognized)?;
+    let (version, remote_chain) = match rest.split_once('/') {
+        Some((version, chain)) => (version, Some(chain)),
+        None => (rest, None),
+    };
+
+    let local = major_version(PROTOCOL_VERSION).expect("PROTOCOL_VERSION is semver");
+    let remote = major_version(version).ok_or_else(unrecognized)?;
+    if remote != local {
+        return Err(Incompatibility::MajorVersion { local, remote });
+    }
+
+    let remote_chain = remote_chain.and_then(|c| c.parse().ok());
+    if remote_chain != Some(chain_id) {
+        return Err(Incompatibility::ChainId {
+            local: chain_id,
+            remote: remote_chain,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_own_protocol_and_minor_differences() {
+        assert_eq!(
+            check_peer_protocol(&identify_protocol_version(7), 7),
+            Ok(())
+        );
+        assert_eq!(check_peer_protocol("/centichain/1.4.2/7", 7), Ok(()));
+    }
+
+    #[test]
+    fn rejects_mismatched_identify_info() {
+        assert_eq!(
+            check_peer_protocol("/centichain/2.0.0/7", 7),
+            Err(Incompatibility::MajorVersion {
+                local: 1,
+                remote: 2
+            })
+        );
+        assert_eq!(
+            check_peer_protocol("/centichain/1.0.0/8", 7),
+            Err(Incompatibility::ChainId {
+                local: 7,
+                remote: Some(8)
+            })
+        );
+        assert_eq!(
+            check_peer_protocol("/centichain/1.0.0", 7),
+            Err(Incompatibility::ChainId {
+                local: 7,
+                remote: None
+            })
+        );
+        assert!(matches!(
+            check_peer_protocol("/ipfs/0.1.0", 7),
+            Err(Incompatibility::Unrecognized(_))
+        ));
+    }
+
+    #[test]
+    fn capabilities_are_read_from_the_agent_version() {
+        assert!(has_capability(
+            &identify_agent_version(),
+            CAPABILITY_GZIP_SYNC
+        ));
+        assert!(has_capability(
+            "centichain/1.2.0 (foo, gzip-sync)",
+            CAPABILITY_GZIP_SYNC
+        ));
+        // Older nodes advertise the libp2p default agent version
+        assert!(!has_capability("rust-libp2p/0.43.0", CAPABILITY_GZIP_SYNC));
+        assert!(!has_capability(
+            "centichain/1.0.0 (gzip-sync-v2)",
+            CAPABILITY_GZIP_SYNC
+        ));
+    }
+}
@@ -5,17 +5,38 @@
 //! ## Structure
 //!
 //! - `behaviour`: libp2p network behaviour definitions
+//! - `codec`: Tagged bincode encoding of gossiped blocks, transactions and receipts
 //! - `commands`: Command types for controlling the P2P layer
+//! - `handshake`: Protocol version and chain id checks on identified peers
+//! - `orphans`: Gossiped blocks waiting for their parent
 //! - `startup`: Node startup state machine
+//! - `sync`: Sync request serving and header-first chain verification
+//! - `topics`: Per-shard gossip topics and following shard reassignment
 //! - `p2p`: Main P2P node implementation
+//! - `peer_limit`: `max_peers` connection cap
+//! - `peer_score`: Misbehaviour scoring and temporary bans
+//! - `relay`: Ordered relay dialing with failover
 
 pub mod behaviour;
+pub mod codec;
 pub mod commands;
+pub mod handshake;
+pub mod orphans;
 pub mod p2p;
+pub mod peer_limit;
+pub mod peer_score;
+pub mod relay;
 pub mod startup;
+pub mod sync;
+pub mod topics;
 
 // Re-exports for convenience
 pub use behaviour::{message_id_fn, CentichainBehaviour, SYNC_PROTOCOL};
+pub use codec::{decode_gossip, encode_gossip};
 pub use commands::{P2PCommand, TopologyUpdate};
+pub use handshake::{
+    check_peer_protocol, has_capability, identify_agent_version, identify_protocol_version,
+    Incompatibility, CAPABILITY_GZIP_SYNC,
+};
 pub use p2p::start_p2p_node;
 pub use startup::{NodeStartupState, StartupConfig};
@@ -0,0 +1,154 @@
+//! # Orphan Blocks
+//!
+//! Gossiped blocks that arrive ahead of their parent wait here instead of
+//! being dropped. Once the parent is on the chain, the waiting descendants
+//! are handed back in order so they can be applied without a resync.
+
+use crate::chain::Block;
+use crate::utils::constants::MAX_ORPHAN_BLOCKS;
+use std::collections::{HashMap, VecDeque};
+
+/// Future blocks keyed by the hash of the parent they wait for
+#[derive(Debug)]
+pub struct OrphanPool {
+    by_parent: HashMap<String, Block>,
+    /// Parent hashes, oldest first, for eviction
+    arrival: VecDeque<String>,
+    capacity: usize,
+}
+
+impl Default for OrphanPool {
+    fn default() -> Self {
+        Self::new(MAX_ORPHAN_BLOCKS)
+    }
+}
+
+impl OrphanPool {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            by_parent: HashMap::new(),
+            arrival: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// Holds `block` until its parent arrives; the oldest orphan makes room
+    /// when the pool is full. A second block for the same parent is ignored.
+    pub fn insert(&mut self, block: Block) -> bool {
+        if self.capacity == 0 || self.by_parent.contains_key(&block.previous_hash) {
+            return false;
+        }
+        while self.by_parent.len() >= self.capacity {
+            let Some(oldest) = self.arrival.pop_front() else {
+                break;
+            };
+            self.by_parent.remove(&oldest);
+        }
+        self.arrival.push_back(block.previous_hash.clone());
+        self.by_parent.insert(block.previous_hash.clone(), block);
+        true
+    }
+
+    /// Removes and returns the orphans that extend `tip_hash`, parent first.
+    pub fn drain_connected(&mut self, tip_hash: &str) -> Vec<Block> {
+        let mut connected = Vec::new();
+        let mut parent = tip_hash.to_string();
+        while let Some(block) = self.by_parent.remove(&parent) {
+            self.arrival.retain(|hash| *hash != parent);
+            parent = block.hash.clone();
+            connected.push(block);
+        }
+        connected
+    }
+
+    /// Drops orphans at or below `height`; they can no longer extend the tip.
+    pub fn discard_up_to(&mut self, height: u64) {
+        self.by_parent.retain(|_, block| block.index > height);
+        let by_parent = &self.by_parent;
+        self.arrival.retain(|hash| by_parent.contains_key(hash));
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_parent.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Storage;
+
+    fn chain(len: u64) -> Vec<Block> {
+        let mut previous_hash = String::new();
+        (0..len)
+            .map(|index| {
+                let block = Block::new(
+                    index,
+                    "miner".into(),
+                    Vec::new(),
+                    previous_hash.clone(),
+                    100,
+                    100,
+                    0,
+                    0,
+                    0,
+                );
+                previous_hash = block.hash.clone();
+                block
+            })
+            .collect()
+    }
+
+    #[test]
+    fn early_block_is_applied_once_its_parent_arrives() {
+        let storage = Storage::temp();
+        let blocks = chain(7);
+        for block in &blocks[..5] {
+            storage.save_block(block).unwrap();
+        }
+
+        // Block 6 is gossiped before block 5
+        let mut pool = OrphanPool::default();
+        assert!(pool.insert(blocks[6].clone()));
+        assert!(pool.drain_connected(&blocks[4].hash).is_empty());
+        assert_eq!(pool.len(), 1);
+
+        storage.save_block(&blocks[5]).unwrap();
+        for orphan in pool.drain_connected(&blocks[5].hash) {
+            storage.save_block(&orphan).unwrap();
+        }
+        assert!(pool.is_empty());
+        assert_eq!(storage.get_latest_index().unwrap(), 6);
+        assert_eq!(storage.get_block(6).unwrap().unwrap().hash, blocks[6].hash);
+    }
+
+    #[test]
+    fn full_pool_evicts_the_oldest_orphan() {
+        let blocks = chain(5);
+        let mut pool = OrphanPool::new(2);
+        for block in &blocks[1..4] {
+            assert!(pool.insert(block.clone()));
+        }
+        assert_eq!(pool.len(), 2);
+        assert!(!pool.insert(blocks[3].clone()));
+
+        // Block 1 was evicted, so nothing connects to genesis any more
+        assert!(pool.drain_connected(&blocks[0].hash).is_empty());
+        let connected: Vec<u64> = pool
+            .drain_connected(&blocks[1].hash)
+            .iter()
+            .map(|b| b.index)
+            .collect();
+        assert_eq!(connected, vec![2, 3]);
+
+        pool.insert(blocks[2].clone());
+        pool.insert(blocks[4].clone());
+        pool.discard_up_to(3);
+        assert_eq!(pool.len(), 1);
+        assert_eq!(pool.drain_connected(&blocks[3].hash)[0].index, 4);
+    }
+}
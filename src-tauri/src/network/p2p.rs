@@ -5,20 +5,32 @@
 
 use futures::StreamExt;
 use libp2p::multiaddr::Protocol;
+use libp2p::swarm::dial_opts::DialOpts;
+use libp2p::swarm::DialError;
 use libp2p::{
-    gossipsub, identity, kad, mdns, noise, relay, swarm::SwarmEvent, tcp, yamux, PeerId,
-    SwarmBuilder,
+    gossipsub, identity, kad, mdns, noise, ping, relay, swarm::SwarmEvent, tcp, yamux, Multiaddr,
+    PeerId, SwarmBuilder,
 };
-use std::collections::HashMap;
-use std::time::Duration;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
 use tokio::io;
 
 use tauri::{AppHandle, Emitter};
 
-use crate::chain::{ingest_block, Block, BlockAcceptResult, SyncRequest, SyncResponse, Transaction};
+use crate::chain::{
+    forks_local_chain, ingest_batch, ingest_block, ingest_branch, ingest_gossip_block,
+    ingest_vdf_verified_block, verify_block_vdf, Block, BlockAcceptResult, Header, SyncRequest,
+    SyncResponse, Transaction, TxCancellation,
+};
+use crate::consensus::beacon::{BeaconMessage, CommitteeKey, CrossLinkVote};
 use crate::consensus::mempool::Mempool;
 use crate::consensus::Consensus;
-use crate::storage::Storage;
+use crate::crypto::bls::SecretKey;
+use crate::storage::{LowDiskSpace, Storage};
+use crate::utils::constants::{
+    KNOWN_PEERS_DIAL_ON_START, KNOWN_PEERS_MAX, KNOWN_PEER_MAX_AGE_SECS, MAX_REORG_DEPTH,
+    SYNC_BLOCKS_BATCH, SYNC_HEADERS_BATCH,
+};
 use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::sync::Mutex;
@@ -26,8 +38,25 @@ use std::sync::Mutex;
 use super::behaviour::{
     message_id_fn, CentichainBehaviour, CentichainBehaviourEvent, SYNC_PROTOCOL,
 };
-use super::commands::{P2PCommand, TopologyUpdate};
+use super::codec::{decode_gossip, encode_gossip};
+use super::commands::{
+    add_peer, dial_peer, disconnect_peer, P2PCommand, PeerControl, PeerInfo, TopologyUpdate,
+};
+use super::handshake::{
+    check_peer_protocol, has_capability, identify_agent_version, identify_protocol_version,
+    Incompatibility, CAPABILITY_GZIP_SYNC,
+};
+use super::orphans::OrphanPool;
+use super::peer_limit::PeerLimit;
+use super::peer_score::{Offense, PeerScores};
+use super::relay::RelayFailover;
 use super::startup::{NodeStartupState, StartupConfig};
+use super::sync::{
+    accept_headers, advance_sync_checkpoint, blocks_range_request, compress_response,
+    decompress_response, find_gap, is_oversized, serve_sync_request, validate_header_chain,
+    verify_checkpoints, HeaderSync, SyncProgress,
+};
+use super::topics::{shard_change, shard_topic_names, ShardCountWatch};
 
 // =============================================================================
 // Main P2P Node Function
@@ -63,6 +92,12 @@ pub async fn start_p2p_node(
     relay_connected: Arc<AtomicBool>,
     wallet_keypair: Option<identity::Keypair>,
     mut cmd_rx: tokio::sync::mpsc::Receiver<P2PCommand>,
+    peer_scores: Arc<Mutex<PeerScores>>,
+    peer_latencies: Arc<Mutex<HashMap<String, u64>>>,
+    network_graph: Arc<Mutex<HashMap<String, Vec<String>>>>,
+    max_peers: u32,
+    listen_port: u16,
+    chain_id: u32,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Initialize keypair and peer ID
     let local_key = wallet_keypair.unwrap_or_else(identity::Keypair::generate_ed25519);
@@ -76,23 +111,29 @@ pub async fn start_p2p_node(
         .set_local_peer_id(local_peer_id.to_string());
 
     // Build the swarm
-    let mut swarm = build_swarm(local_key.clone())?;
+    let mut swarm = build_swarm(local_key.clone(), chain_id)?;
 
     // Setup gossipsub topics
-    let topics = setup_topics(&mut swarm, &consensus, &local_peer_id)?;
-
-    // Listen on all interfaces
-    swarm.listen_on("/ip4/0.0.0.0/tcp/0".parse()?)?;
-
-    // Connect to relays
-    let relay_peer_id_opt = connect_to_relays(
-        &mut swarm,
-        &relay_addrs,
-        &local_peer_id,
-        &consensus,
-        &relay_connected,
-        &app_handle,
-    );
+    let mut topics = setup_topics(&mut swarm, &consensus, &local_peer_id)?;
+
+    // Key this node signs CrossLinks with as a member of its shard committee
+    let bls_key = match crate::crypto::bls::derive_secret_key(&local_key) {
+        Ok(key) => Some(key),
+        Err(e) => {
+            log::warn!("Not signing cross-links: {}", e);
+            None
+        }
+    };
+
+    // Listen on all interfaces; port 0 lets the OS pick one
+    swarm.listen_on(format!("/ip4/0.0.0.0/tcp/{}", listen_port).parse()?)?;
+
+    // Peers from the last run are tried first; relay discovery remains the fallback
+    dial_known_peers(&mut swarm, &storage, max_peers as usize);
+
+    // Connect to the first reachable relay; later ones are fallbacks
+    let mut relay_failover = RelayFailover::new(relay_addrs.clone());
+    dial_next_relay(&mut swarm, &mut relay_failover, &app_handle);
 
     // Bootstrap DHT
     if let Err(e) = swarm.behaviour_mut().kad.bootstrap() {
@@ -104,8 +145,15 @@ pub async fn start_p2p_node(
 
     let _ = app_handle.emit("node-status", "Connecting");
 
-    // Network graph state for topology visualization
-    let mut network_graph: HashMap<String, Vec<String>> = HashMap::new();
+    // Start from a fresh graph that already shows ourselves before the first gossip tick
+    network_graph.lock().unwrap().clear();
+    record_local_connections(&swarm, &network_graph);
+
+    // Headers validated ahead of their block bodies
+    let mut header_sync = HeaderSync::default();
+
+    // Cap on non-relay connections from settings
+    let mut peer_limit = PeerLimit::new(max_peers);
 
     // Startup state machine
     let startup_config = StartupConfig::default();
@@ -115,9 +163,25 @@ pub async fn start_p2p_node(
     let mut check_interval = tokio::time::interval(Duration::from_secs(1));
     let mut discovery_interval = tokio::time::interval(Duration::from_secs(15));
     let mut topology_gossip_interval = tokio::time::interval(Duration::from_secs(30));
+    // Epochs roll over and shard counts change; follow our shard's topics
+    let mut shard_check_interval = tokio::time::interval(Duration::from_secs(30));
+    // Shard count the validator set gave last; a change moves us right away
+    let mut shard_count = ShardCountWatch::new(&consensus.lock().unwrap());
+
+    // Peer id of the active relay, learned once it connects
+    let mut relay_peer_id_opt: Option<PeerId> = None;
+
+    // Gossiped blocks come back here once their VDF proof has been checked
+    let (verified_tx, mut verified_rx) = tokio::sync::mpsc::channel::<VdfVerdict>(64);
+
+    // Callers of `P2PCommand::QueryPeer` waiting for an answer
+    let mut pending_queries = PendingQueries::new();
 
-    // Clone relay_peer_id for use in loop
-    let mut relay_peer_id_opt = relay_peer_id_opt;
+    // Gossiped blocks waiting for their parent
+    let mut orphans = OrphanPool::default();
+
+    // Peers that read gzipped block batches, from their identify info
+    let mut gzip_peers: HashSet<PeerId> = HashSet::new();
 
     // Main event loop
     loop {
@@ -149,6 +213,8 @@ pub async fn start_p2p_node(
                     &local_peer_id,
                     relay_peer_id_opt,
                     &topics,
+                    &mut pending_queries,
+                    &peer_latencies,
                 );
             }
 
@@ -164,6 +230,10 @@ pub async fn start_p2p_node(
                 );
             }
 
+            _ = shard_check_interval.tick() => {
+                follow_shard_assignment(&mut swarm, &consensus, &local_peer_id, &mut topics);
+            }
+
             // Periodic random walk discovery
             _ = discovery_interval.tick() => {
                 let connected = swarm.connected_peers().count();
@@ -174,12 +244,19 @@ pub async fn start_p2p_node(
                 }
             }
 
-            // Topology gossip broadcast
+            // Topology gossip broadcast; the committee key goes out with it for new peers
             _ = topology_gossip_interval.tick() => {
+                announce_committee_key(
+                    &mut swarm,
+                    &topics,
+                    &consensus,
+                    &local_peer_id,
+                    bls_key.as_ref(),
+                );
                 broadcast_topology(
                     &mut swarm,
                     &local_peer_id,
-                    &mut network_graph,
+                    &network_graph,
                     &topics,
                     &app_handle,
                 );
@@ -187,6 +264,7 @@ pub async fn start_p2p_node(
 
             // Peer count check
             _ = check_interval.tick() => {
+                dial_next_relay(&mut swarm, &mut relay_failover, &app_handle);
                 update_peer_counts(
                     &swarm,
                     &peer_count,
@@ -196,22 +274,32 @@ pub async fn start_p2p_node(
                     &startup_state,
                     &app_handle,
                 );
+                follow_shard_count(
+                    &mut swarm,
+                    &consensus,
+                    &mempool,
+                    &local_peer_id,
+                    &mut topics,
+                    &mut shard_count,
+                    &app_handle,
+                );
             }
 
             // Block broadcast from mining
             Some(block) = block_receiver.recv() => {
                 log::info!("Broadcasting mined block index: {}", block.index);
-                let json = serde_json::to_vec(&*block).unwrap();
-                if let Err(e) = swarm.behaviour_mut().gossipsub.publish(topics.shard_blocks.clone(), json) {
+                let data = encode_gossip(&*block).unwrap();
+                if let Err(e) = swarm.behaviour_mut().gossipsub.publish(topics.shard_blocks.clone(), data) {
                     log::error!("Gossip block publish error: {:?}", e);
                 }
+                publish_crosslink_vote(&mut swarm, &topics, &consensus, &block, bls_key.as_ref());
             }
 
             // Transaction broadcast
             Some(tx) = tx_receiver.recv() => {
                 log::info!("Broadcasting local transaction: {}", tx.id);
-                let json = serde_json::to_vec(&tx).unwrap();
-                if let Err(e) = swarm.behaviour_mut().gossipsub.publish(topics.shard_txs.clone(), json) {
+                let data = encode_gossip(&tx).unwrap();
+                if let Err(e) = swarm.behaviour_mut().gossipsub.publish(topics.shard_txs.clone(), data) {
                     log::error!("Gossip tx publish error: {:?}", e);
                 }
             }
@@ -219,10 +307,12 @@ pub async fn start_p2p_node(
             // Receipt broadcast
             Some(receipt) = receipt_receiver.recv() => {
                 log::info!("Broadcasting Cross-Shard Receipt: {}", receipt.original_tx_id);
-                let json = serde_json::to_vec(&receipt).unwrap();
-                if let Err(e) = swarm.behaviour_mut().gossipsub.publish(topics.receipts.clone(), json) {
+                let data = encode_gossip(&receipt).unwrap();
+                if let Err(e) = swarm.behaviour_mut().gossipsub.publish(topics.receipts.clone(), data) {
                     log::error!("Gossip receipt publish error: {:?}", e);
                 }
+                mempool.track_receipt(&receipt);
+                let _ = app_handle.emit("new-receipt", &receipt);
             }
 
             // VDF proof broadcast
@@ -234,6 +324,38 @@ pub async fn start_p2p_node(
                 }
             }
 
+            // Gossip blocks whose VDF check finished on the blocking pool
+            Some((block, origin, verdict)) = verified_rx.recv() => {
+                let peer_id = origin.propagation_source;
+                let verdict = if *node_type.lock().unwrap() == crate::NodeType::Light {
+                    handle_light_block(
+                        block,
+                        peer_id,
+                        verdict,
+                        &mut swarm,
+                        &storage,
+                        &chain_index,
+                        &app_handle,
+                    )
+                } else {
+                    handle_verified_block(
+                        block,
+                        peer_id,
+                        verdict,
+                        &mut swarm,
+                        &storage,
+                        &mempool,
+                        &consensus,
+                        &chain_index,
+                        &app_handle,
+                        &mut orphans,
+                        &topics,
+                        bls_key.as_ref(),
+                    )
+                };
+                settle_gossip(&mut swarm, &peer_scores, &consensus, &origin, verdict, &app_handle);
+            }
+
             // Swarm events
             event = swarm.select_next_some() => {
                 handle_swarm_event(
@@ -247,14 +369,34 @@ pub async fn start_p2p_node(
                     &is_synced,
                     &peer_count,
                     &relay_addrs,
+                    &mut relay_failover,
                     &mut relay_peer_id_opt,
                     &relay_connected,
                     &node_type,
                     &topics,
-                    &mut network_graph,
+                    &network_graph,
+                    &mut header_sync,
+                    &peer_scores,
+                    &peer_latencies,
+                    &mut peer_limit,
+                    &verified_tx,
+                    &mut pending_queries,
+                    &mut gzip_peers,
                 );
             }
         }
+
+        // A block that just landed, gossiped or synced, may be an orphan's parent
+        if !orphans.is_empty() {
+            connect_orphans(
+                &mut orphans,
+                &storage,
+                &mempool,
+                &consensus,
+                &chain_index,
+                &app_handle,
+            );
+        }
     }
     Ok(())
 }
@@ -263,16 +405,70 @@ pub async fn start_p2p_node(
 // Helper Structs
 // =============================================================================
 
+/// Gossiped block, where it came from, and its VDF check result
+type VdfVerdict = (Block, GossipOrigin, Result<(), String>);
+
+/// A gossip message's id and senders, kept until its validation is reported
+#[derive(Clone)]
+struct GossipOrigin {
+    message_id: gossipsub::MessageId,
+    /// Mesh peer that forwarded the message to us
+    propagation_source: PeerId,
+    /// Peer that signed and published it
+    source: Option<PeerId>,
+}
+
+/// What gossipsub is told about a received message
+enum GossipVerdict {
+    /// Valid; deliver and forward it
+    Accept,
+    /// Harmless but not worth forwarding: duplicate, stale or unreadable
+    Ignore,
+    /// Provably invalid; dropped, and its publisher charged with the offense
+    Reject(Offense),
+    /// Block whose VDF check is still running; settled by `handle_verified_block`
+    Pending,
+}
+
+/// Local callers waiting for the peer's answer to a `P2PCommand::QueryPeer`
+type PendingQueries = HashMap<
+    libp2p::request_response::RequestId,
+    tokio::sync::oneshot::Sender<Result<SyncResponse, String>>,
+>;
+
 /// Gossipsub topics used by the network
 pub struct GossipTopics {
+    /// Shard whose topics we are subscribed to
+    pub shard_id: u16,
     pub shard_blocks: gossipsub::IdentTopic,
     pub shard_txs: gossipsub::IdentTopic,
+    pub tx_cancels: gossipsub::IdentTopic,
     pub receipts: gossipsub::IdentTopic,
+    /// Committee keys and CrossLink votes for the beacon
+    pub crosslinks: gossipsub::IdentTopic,
     pub vdf_proofs: gossipsub::IdentTopic,
     pub topology: gossipsub::IdentTopic,
     pub node_status: gossipsub::IdentTopic,
 }
 
+impl GossipTopics {
+    /// Topics for a node on shard `shard_id`
+    fn new(shard_id: u16) -> Self {
+        let [blocks, txs, tx_cancels] = shard_topic_names(shard_id);
+        GossipTopics {
+            shard_id,
+            shard_blocks: gossipsub::IdentTopic::new(blocks),
+            shard_txs: gossipsub::IdentTopic::new(txs),
+            tx_cancels: gossipsub::IdentTopic::new(tx_cancels),
+            receipts: gossipsub::IdentTopic::new("centichain-receipts"),
+            crosslinks: gossipsub::IdentTopic::new("centichain-crosslinks"),
+            vdf_proofs: gossipsub::IdentTopic::new("centichain-vdf-proofs"),
+            topology: gossipsub::IdentTopic::new("centichain-topology"),
+            node_status: gossipsub::IdentTopic::new("centichain-node-status"),
+        }
+    }
+}
+
 // =============================================================================
 // Helper Functions
 // =============================================================================
@@ -280,6 +476,7 @@ pub struct GossipTopics {
 /// Builds the libp2p swarm with all required behaviours
 fn build_swarm(
     local_key: identity::Keypair,
+    chain_id: u32,
 ) -> Result<libp2p::Swarm<CentichainBehaviour>, Box<dyn std::error::Error>> {
     let swarm = SwarmBuilder::with_existing_identity(local_key.clone())
         .with_tokio()
@@ -295,6 +492,8 @@ fn build_swarm(
             let gossipsub_config = gossipsub::ConfigBuilder::default()
                 .heartbeat_interval(Duration::from_secs(10))
                 .validation_mode(gossipsub::ValidationMode::Strict)
+                // Forwarded only once reported valid; see `settle_gossip`
+                .validate_messages()
                 .message_id_fn(message_id_fn)
                 .build()
                 .map_err(|msg| io::Error::new(io::ErrorKind::Other, msg))?;
@@ -319,10 +518,10 @@ fn build_swarm(
             let dcutr = libp2p::dcutr::Behaviour::new(key.public().to_peer_id());
 
             // Identify
-            let identify = libp2p::identify::Behaviour::new(libp2p::identify::Config::new(
-                "/centichain/1.0.0".to_string(),
-                key.public(),
-            ));
+            let identify = libp2p::identify::Behaviour::new(
+                libp2p::identify::Config::new(identify_protocol_version(chain_id), key.public())
+                    .with_agent_version(identify_agent_version()),
+            );
 
             // Ping
             let ping = libp2p::ping::Behaviour::new(
@@ -355,6 +554,70 @@ fn build_swarm(
     Ok(swarm)
 }
 
+/// Announces this node's BLS key, so its cross-link votes can be checked,
+/// and registers it locally.
+fn announce_committee_key(
+    swarm: &mut libp2p::Swarm<CentichainBehaviour>,
+    topics: &GossipTopics,
+    consensus: &Arc<Mutex<Consensus>>,
+    local_peer_id: &PeerId,
+    bls_key: Option<&SecretKey>,
+) {
+    let Some(bls_key) = bls_key else {
+        return;
+    };
+    let key = CommitteeKey::new(local_peer_id.to_string(), bls_key);
+    let data = encode_gossip(&BeaconMessage::Key(key.clone())).unwrap();
+    if let Err(e) = swarm
+        .behaviour_mut()
+        .gossipsub
+        .publish(topics.crosslinks.clone(), data)
+    {
+        log::debug!("Gossip committee key publish error: {:?}", e);
+    }
+    if let Err(e) = consensus.lock().unwrap().beacon.register_key(key) {
+        log::warn!("Own committee key rejected: {}", e);
+    }
+}
+
+/// Signs the CrossLink of `block`, a block of our shard we produced or
+/// accepted, and sends the vote to the beacon.
+fn publish_crosslink_vote(
+    swarm: &mut libp2p::Swarm<CentichainBehaviour>,
+    topics: &GossipTopics,
+    consensus: &Arc<Mutex<Consensus>>,
+    block: &Block,
+    bls_key: Option<&SecretKey>,
+) {
+    let Some(bls_key) = bls_key else {
+        return;
+    };
+    let vote = CrossLinkVote::new(block, bls_key);
+    let data = encode_gossip(&BeaconMessage::Vote(vote.clone())).unwrap();
+    if let Err(e) = swarm
+        .behaviour_mut()
+        .gossipsub
+        .publish(topics.crosslinks.clone(), data)
+    {
+        log::debug!("Gossip cross-link vote publish error: {:?}", e);
+    }
+    // Counted locally too; the vote may complete the link
+    match consensus.lock().unwrap().record_crosslink_vote(vote) {
+        Ok(Some(link)) => log::info!(
+            "Shard {} cross-linked at block {} by {} signers",
+            link.shard_id,
+            link.block_height,
+            link.signers.len()
+        ),
+        Ok(None) => {}
+        Err(e) => log::debug!(
+            "Own cross-link vote for block {} not counted: {}",
+            block.index,
+            e
+        ),
+    }
+}
+
 /// Sets up gossipsub topics and subscriptions
 fn setup_topics(
     swarm: &mut libp2p::Swarm<CentichainBehaviour>,
@@ -363,18 +626,11 @@ fn setup_topics(
 ) -> Result<GossipTopics, Box<dyn std::error::Error>> {
     let shard_id = {
         let c = consensus.lock().unwrap();
-        c.get_assigned_shard(&local_peer_id.to_string(), 0)
+        c.get_assigned_shard(&local_peer_id.to_string(), c.current_epoch())
     };
     log::info!("P2P: Subscribing to Shard #{} topics", shard_id);
 
-    let topics = GossipTopics {
-        shard_blocks: gossipsub::IdentTopic::new(format!("centichain-shard-{}-blocks", shard_id)),
-        shard_txs: gossipsub::IdentTopic::new(format!("centichain-shard-{}-txs", shard_id)),
-        receipts: gossipsub::IdentTopic::new("centichain-receipts"),
-        vdf_proofs: gossipsub::IdentTopic::new("centichain-vdf-proofs"),
-        topology: gossipsub::IdentTopic::new("centichain-topology"),
-        node_status: gossipsub::IdentTopic::new("centichain-node-status"),
-    };
+    let topics = GossipTopics::new(shard_id);
 
     swarm
         .behaviour_mut()
@@ -384,10 +640,18 @@ fn setup_topics(
         .behaviour_mut()
         .gossipsub
         .subscribe(&topics.shard_txs)?;
+    swarm
+        .behaviour_mut()
+        .gossipsub
+        .subscribe(&topics.tx_cancels)?;
     swarm
         .behaviour_mut()
         .gossipsub
         .subscribe(&topics.receipts)?;
+    swarm
+        .behaviour_mut()
+        .gossipsub
+        .subscribe(&topics.crosslinks)?;
     swarm
         .behaviour_mut()
         .gossipsub
@@ -404,77 +668,153 @@ fn setup_topics(
     Ok(topics)
 }
 
-/// Connects to relay servers
-fn connect_to_relays(
+/// Moves the shard topic subscriptions to the shard assigned for the
+/// current epoch, if that is not the one we follow.
+fn follow_shard_assignment(
     swarm: &mut libp2p::Swarm<CentichainBehaviour>,
-    relay_addrs: &[String],
+    consensus: &Arc<Mutex<Consensus>>,
     local_peer_id: &PeerId,
+    topics: &mut GossipTopics,
+) {
+    let change = {
+        let c = consensus.lock().unwrap();
+        shard_change(
+            &c,
+            &local_peer_id.to_string(),
+            c.current_epoch(),
+            topics.shard_id,
+        )
+    };
+    let Some(change) = change else {
+        return;
+    };
+    log::info!(
+        "P2P: Reassigned from Shard #{} to #{}, moving topic subscriptions",
+        change.from,
+        change.to
+    );
+
+    let gossipsub = &mut swarm.behaviour_mut().gossipsub;
+    for name in &change.leave {
+        if let Err(e) = gossipsub.unsubscribe(&gossipsub::IdentTopic::new(name)) {
+            log::warn!("P2P: Failed to leave {}: {:?}", name, e);
+        }
+    }
+    for name in &change.join {
+        if let Err(e) = gossipsub.subscribe(&gossipsub::IdentTopic::new(name)) {
+            log::error!("P2P: Failed to join {}: {:?}", name, e);
+        }
+    }
+    *topics = GossipTopics::new(change.to);
+}
+
+/// Moves to our new shard and hands pending transactions to the shards now
+/// responsible for their senders as soon as the validator count changes the
+/// number of active shards.
+fn follow_shard_count(
+    swarm: &mut libp2p::Swarm<CentichainBehaviour>,
     consensus: &Arc<Mutex<Consensus>>,
-    relay_connected: &Arc<AtomicBool>,
+    mempool: &Arc<Mempool>,
+    local_peer_id: &PeerId,
+    topics: &mut GossipTopics,
+    watch: &mut ShardCountWatch,
     app_handle: &AppHandle,
-) -> Option<PeerId> {
-    let mut relay_connected_count = 0;
-    let mut relay_peer_id_opt = None;
-
-    for relay_str in relay_addrs {
-        if let Ok(relay_addr_parsed) = relay_str.parse::<libp2p::Multiaddr>() {
-            match swarm.dial(relay_addr_parsed.clone()) {
-                Ok(_) => {
-                    log::info!("Dialing relay: {}", relay_addr_parsed);
-                    if let Err(e) =
-                        swarm.listen_on(relay_addr_parsed.clone().with(Protocol::P2pCircuit))
-                    {
-                        log::error!("Failed to listen on relay circuit {}: {}", relay_str, e);
-                    } else {
-                        log::info!(
-                            "Listening on relay circuit {} for incoming P2P connections.",
-                            relay_str
-                        );
-                        let external_addr = relay_addr_parsed
-                            .clone()
-                            .with(Protocol::P2pCircuit)
-                            .with(Protocol::P2p(*local_peer_id));
-                        log::info!("Announcing external address: {}", external_addr);
-                        swarm.add_external_address(external_addr);
-                        relay_connected_count += 1;
-
-                        if relay_peer_id_opt.is_none() {
-                            relay_peer_id_opt = relay_addr_parsed.iter().find_map(|p| match p {
-                                Protocol::P2p(id) => Some(id),
-                                _ => None,
-                            });
-
-                            if let Some(rid) = relay_peer_id_opt {
-                                log::info!("P2P Init: Relay PeerID identified: {}", rid);
-                                consensus.lock().unwrap().nodes.remove(&rid.to_string());
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    log::error!("Failed to dial relay {}: {}", relay_str, e);
-                }
+) {
+    let (previous, by_shard) = {
+        let c = consensus.lock().unwrap();
+        let Some(previous) = watch.observe(&c) else {
+            return;
+        };
+        let by_shard = mempool.pending_by_shard(|sender| c.get_assigned_shard(sender, 0));
+        (previous, by_shard)
+    };
+    log::info!(
+        "P2P: Active shards changed from {} to {}",
+        previous,
+        watch.active_shards()
+    );
+    follow_shard_assignment(swarm, consensus, local_peer_id, topics);
+
+    let gossipsub = &mut swarm.behaviour_mut().gossipsub;
+    let mut rerouted = 0;
+    for (shard_id, txs) in by_shard {
+        if shard_id == topics.shard_id {
+            continue;
+        }
+        let [_, txs_topic, _] = shard_topic_names(shard_id);
+        let topic = gossipsub::IdentTopic::new(txs_topic);
+        for tx in txs {
+            match gossipsub.publish(topic.clone(), encode_gossip(&tx).unwrap()) {
+                Ok(_) => rerouted += 1,
+                Err(e) => log::warn!(
+                    "P2P: Failed to re-route tx {} to Shard #{}: {:?}",
+                    tx.id,
+                    shard_id,
+                    e
+                ),
             }
-        } else {
-            log::error!("Invalid relay address: {}", relay_str);
         }
     }
+    let _ = app_handle.emit(
+        "shard-count-changed",
+        serde_json::json!({
+            "previous": previous,
+            "active_shards": watch.active_shards(),
+            "shard_id": topics.shard_id,
+            "rerouted": rerouted,
+        }),
+    );
+}
 
-    if relay_connected_count == 0 {
-        let _ = app_handle.emit("relay-status", "disconnected");
-        log::warn!("Warning: Failed to connect to any relay. Operating in restricted mode.");
-    } else {
-        let _ = app_handle.emit(
-            "relay-status",
-            format!("Connected ({} relays)", relay_connected_count),
-        );
-        relay_connected.store(true, Ordering::Relaxed);
-        if let Some(rid) = relay_peer_id_opt {
-            consensus.lock().unwrap().nodes.remove(&rid.to_string());
+/// Dials the next relay candidate when no relay is active or being dialed
+fn dial_next_relay(
+    swarm: &mut libp2p::Swarm<CentichainBehaviour>,
+    relay_failover: &mut RelayFailover,
+    app_handle: &AppHandle,
+) {
+    let Some(relay_str) = relay_failover.next_dial(Instant::now()) else {
+        return;
+    };
+    match relay_str.parse::<libp2p::Multiaddr>() {
+        Ok(relay_addr) => match swarm.dial(relay_addr) {
+            Ok(_) => {
+                log::info!("Dialing relay: {}", relay_str);
+                let _ = app_handle.emit("relay-status", "connecting");
+            }
+            Err(e) => {
+                log::error!("Failed to dial relay {}: {}", relay_str, e);
+                relay_failover.on_dial_failed(&relay_str, Instant::now());
+            }
+        },
+        Err(_) => {
+            log::error!("Invalid relay address: {}", relay_str);
+            relay_failover.on_dial_failed(&relay_str, Instant::now());
         }
     }
+}
 
-    relay_peer_id_opt
+/// Listens on circuits through the newly active relay and announces that address
+fn listen_via_relay(
+    swarm: &mut libp2p::Swarm<CentichainBehaviour>,
+    relay_addr: &libp2p::Multiaddr,
+    relay_peer_id: PeerId,
+) {
+    let mut relay_addr = relay_addr.clone();
+    if !relay_addr.iter().any(|p| matches!(p, Protocol::P2p(_))) {
+        relay_addr.push(Protocol::P2p(relay_peer_id));
+    }
+    let circuit_addr = relay_addr.with(Protocol::P2pCircuit);
+    if let Err(e) = swarm.listen_on(circuit_addr.clone()) {
+        log::error!("Failed to listen on relay circuit {}: {}", circuit_addr, e);
+        return;
+    }
+    log::info!(
+        "Listening on relay circuit {} for incoming P2P connections.",
+        circuit_addr
+    );
+    let external_addr = circuit_addr.with(Protocol::P2p(*swarm.local_peer_id()));
+    log::info!("Announcing external address: {}", external_addr);
+    swarm.add_external_address(external_addr);
 }
 
 /// Handles startup state transitions
@@ -507,8 +847,13 @@ fn handle_startup_state(
             }
         }
         NodeStartupState::RelayConnectionFailed => {
-            let _ = app_handle.emit("node-status", "Error: Relay Not Found");
-            let _ = app_handle.emit("relay-status", "Connection Failed");
+            // Relays keep being retried; resume startup once one answers
+            if relay_connected.load(Ordering::Relaxed) {
+                log::info!("Startup: Relay Connected after retry. Switching to Discovery Phase.");
+                let _ = app_handle.emit("node-status", "Searching for Network...");
+                *startup_state = NodeStartupState::new_discovering();
+                let _ = swarm.behaviour_mut().kad.bootstrap();
+            }
         }
         NodeStartupState::DiscoveringPeers { start_time } => {
             let _ = app_handle.emit("node-status", "Searching for Network...");
@@ -531,6 +876,8 @@ fn handle_command(
     local_peer_id: &PeerId,
     relay_peer_id_opt: Option<PeerId>,
     topics: &GossipTopics,
+    pending_queries: &mut PendingQueries,
+    peer_latencies: &Arc<Mutex<HashMap<String, u64>>>,
 ) {
     match cmd {
         P2PCommand::SyncWithNetwork => {
@@ -575,6 +922,88 @@ fn handle_command(
                 log::info!("P2P: Mining status broadcast complete");
             }
         }
+        P2PCommand::AnnounceLeaving(reply) => {
+            log::info!("P2P: Announcing shutdown to peers");
+            let farewell = crate::chain::NodeStatusUpdate::leaving(local_peer_id.to_string());
+            if let Ok(data) = serde_json::to_vec(&farewell) {
+                if let Err(e) = swarm
+                    .behaviour_mut()
+                    .gossipsub
+                    .publish(topics.node_status.clone(), data)
+                {
+                    log::warn!("P2P: Shutdown announcement not sent: {:?}", e);
+                }
+            }
+            let _ = reply.send(());
+        }
+        P2PCommand::BroadcastCancellation(cancel) => {
+            log::info!("P2P: Broadcasting cancellation of tx {}", cancel.tx_id);
+            match encode_gossip(&cancel) {
+                Ok(data) => {
+                    if let Err(e) = swarm
+                        .behaviour_mut()
+                        .gossipsub
+                        .publish(topics.tx_cancels.clone(), data)
+                    {
+                        log::error!("Gossip cancellation publish error: {:?}", e);
+                    }
+                }
+                Err(e) => log::error!("Failed to encode cancellation: {}", e),
+            }
+        }
+        P2PCommand::QueryPeer { request, reply } => {
+            let peer = swarm
+                .connected_peers()
+                .find(|peer| Some(**peer) != relay_peer_id_opt)
+                .copied();
+            match peer {
+                Some(peer) => {
+                    log::debug!("P2P: Querying {} for {:?}", peer, request);
+                    let request_id = swarm.behaviour_mut().sync.send_request(&peer, request);
+                    pending_queries.insert(request_id, reply);
+                }
+                None => {
+                    let _ = reply.send(Err("No connected peers to ask".into()));
+                }
+            }
+        }
+        P2PCommand::DialPeer(address) => dial_peer(swarm, address),
+        P2PCommand::AddPeer(address) => add_peer(swarm, address),
+        P2PCommand::DisconnectPeer(peer) => disconnect_peer(swarm, peer),
+        P2PCommand::GetConnectedPeers(reply) => {
+            let consensus = consensus.lock().unwrap();
+            let latencies = peer_latencies.lock().unwrap();
+            let peers = swarm
+                .connected_peers()
+                .map(|peer| {
+                    let peer_id = peer.to_string();
+                    let node = consensus.nodes.get(&peer_id);
+                    PeerInfo {
+                        trust_score: node.map_or(0.0, |n| n.trust_score),
+                        is_verified: node.is_some_and(|n| n.is_verified),
+                        latency: latencies.get(&peer_id).copied().unwrap_or(0),
+                        addresses: node.map(|n| n.addresses.clone()).unwrap_or_default(),
+                        peer_id,
+                    }
+                })
+                .collect();
+            let _ = reply.send(peers);
+        }
+    }
+}
+
+impl PeerControl for libp2p::Swarm<CentichainBehaviour> {
+    fn dial(&mut self, address: Multiaddr) -> Result<(), String> {
+        libp2p::Swarm::dial(self, address).map_err(|e| e.to_string())
+    }
+
+    fn disconnect(&mut self, peer: PeerId) -> Result<(), String> {
+        self.disconnect_peer_id(peer)
+            .map_err(|_| format!("Not connected to {}", peer))
+    }
+
+    fn add_address(&mut self, peer: PeerId, address: Multiaddr) {
+        self.behaviour_mut().kad.add_address(&peer, address);
     }
 }
 
@@ -623,7 +1052,7 @@ fn handle_periodic_sync(
 fn broadcast_topology(
     swarm: &mut libp2p::Swarm<CentichainBehaviour>,
     local_peer_id: &PeerId,
-    network_graph: &mut HashMap<String, Vec<String>>,
+    network_graph: &Mutex<HashMap<String, Vec<String>>>,
     topics: &GossipTopics,
     app_handle: &AppHandle,
 ) {
@@ -631,8 +1060,12 @@ fn broadcast_topology(
 
     let update = TopologyUpdate::new(local_peer_id.to_string(), connected_peers.clone());
 
-    network_graph.insert(local_peer_id.to_string(), connected_peers);
-    let _ = app_handle.emit("network-topology-update", network_graph.clone());
+    let graph = {
+        let mut graph = network_graph.lock().unwrap();
+        graph.insert(local_peer_id.to_string(), connected_peers);
+        graph.clone()
+    };
+    let _ = app_handle.emit("network-topology-update", graph);
 
     match serde_json::to_vec(&update) {
         Ok(json) => {
@@ -648,26 +1081,109 @@ fn broadcast_topology(
     }
 }
 
-/// Updates peer and validator counts
-fn update_peer_counts(
+/// Dials the most recently seen peers from previous runs
+fn dial_known_peers(
+    swarm: &mut libp2p::Swarm<CentichainBehaviour>,
+    storage: &Storage,
+    max_peers: usize,
+) {
+    let known = match storage.load_peers(KNOWN_PEERS_DIAL_ON_START.min(max_peers)) {
+        Ok(known) => known,
+        Err(e) => {
+            log::warn!("Could not load known peers: {:?}", e);
+            return;
+        }
+    };
+    for record in known {
+        let Ok(peer_id) = record.peer_id.parse::<PeerId>() else {
+            continue;
+        };
+        let addresses: Vec<Multiaddr> = record
+            .addresses
+            .iter()
+            .filter_map(|a| a.parse().ok())
+            .collect();
+        if addresses.is_empty() {
+            continue;
+        }
+        for addr in &addresses {
+            swarm
+                .behaviour_mut()
+                .kad
+                .add_address(&peer_id, addr.clone());
+        }
+        log::info!("Dialing known peer {}", peer_id);
+        let opts = DialOpts::peer_id(peer_id).addresses(addresses).build();
+        if let Err(e) = swarm.dial(opts) {
+            log::debug!("Dial to known peer {} failed: {:?}", peer_id, e);
+        }
+    }
+}
+
+/// Stores an identified peer's listen addresses for the next startup
+fn remember_peer(storage: &Storage, peer_id: PeerId, listen_addrs: &[Multiaddr]) {
+    let addresses: Vec<String> = listen_addrs
+        .iter()
+        .filter(|a| !a.iter().any(|p| matches!(p, Protocol::P2pCircuit)))
+        .map(|a| a.to_string())
+        .collect();
+    if addresses.is_empty() {
+        return;
+    }
+    let now = crate::utils::unix_now();
+    let record = crate::storage::PeerRecord {
+        peer_id: peer_id.to_string(),
+        addresses,
+        last_seen: now,
+        manual: false,
+    };
+    if let Err(e) = storage
+        .save_peer(&record)
+        .and_then(|_| storage.prune_peers(now, KNOWN_PEERS_MAX, KNOWN_PEER_MAX_AGE_SECS))
+    {
+        log::warn!("Could not store peer {}: {:?}", peer_id, e);
+    }
+}
+
+/// Updates the local node's entry in the topology graph with its current connections
+fn record_local_connections(
     swarm: &libp2p::Swarm<CentichainBehaviour>,
-    peer_count: &Arc<AtomicUsize>,
-    validator_count: &Arc<AtomicUsize>,
-    consensus: &Arc<Mutex<Consensus>>,
-    relay_peer_id_opt: Option<PeerId>,
-    startup_state: &NodeStartupState,
-    app_handle: &AppHandle,
+    network_graph: &Mutex<HashMap<String, Vec<String>>>,
 ) {
+    let connected_peers = swarm.connected_peers().map(|p| p.to_string()).collect();
+    network_graph
+        .lock()
+        .unwrap()
+        .insert(swarm.local_peer_id().to_string(), connected_peers);
+}
+
+/// Connected peers, not counting the active relay
+fn non_relay_peer_count(
+    swarm: &libp2p::Swarm<CentichainBehaviour>,
+    relay_peer_id_opt: Option<PeerId>,
+) -> usize {
     let total_peers = swarm.network_info().num_peers();
     let relay_is_conn = relay_peer_id_opt
         .map(|rid| swarm.is_connected(&rid))
         .unwrap_or(false);
-    let valid_peers = if relay_is_conn {
+    if relay_is_conn {
         total_peers.saturating_sub(1)
     } else {
         total_peers
-    };
+    }
+}
 
+/// Updates peer and validator counts
+fn update_peer_counts(
+    swarm: &libp2p::Swarm<CentichainBehaviour>,
+    peer_count: &Arc<AtomicUsize>,
+    validator_count: &Arc<AtomicUsize>,
+    consensus: &Arc<Mutex<Consensus>>,
+    relay_peer_id_opt: Option<PeerId>,
+    startup_state: &NodeStartupState,
+    app_handle: &AppHandle,
+) {
+    let valid_peers = non_relay_peer_count(swarm, relay_peer_id_opt);
     peer_count.store(valid_peers, Ordering::Relaxed);
     let _ = app_handle.emit("peer-count", valid_peers);
 
@@ -692,6 +1208,37 @@ fn update_peer_counts(
     let _ = app_handle.emit("validator-count", v_count);
 }
 
+/// Tells the UI that no more peers are being accepted or dialed
+fn emit_peer_limit_reached(app_handle: &AppHandle, peer_limit: &PeerLimit, connected: usize) {
+    log::info!(
+        "Peer limit reached ({}/{}), no longer accepting new peers",
+        connected,
+        peer_limit.max_peers()
+    );
+    let _ = app_handle.emit(
+        "peer-limit-reached",
+        serde_json::json!({ "max_peers": peer_limit.max_peers(), "connected": connected }),
+    );
+}
+
+/// Stores the round-trip time of a ping in milliseconds; a failed ping clears it
+pub(crate) fn record_ping(
+    peer_latencies: &Mutex<HashMap<String, u64>>,
+    peer: &PeerId,
+    result: &Result<Duration, ping::Failure>,
+) {
+    let mut latencies = peer_latencies.lock().unwrap();
+    match result {
+        Ok(rtt) => {
+            latencies.insert(peer.to_string(), rtt.as_millis() as u64);
+        }
+        Err(e) => {
+            log::debug!("Ping to {} failed: {:?}", peer, e);
+            latencies.remove(&peer.to_string());
+        }
+    }
+}
+
 /// Handles swarm events
 fn handle_swarm_event<THandlerErr: std::error::Error>(
     event: SwarmEvent<CentichainBehaviourEvent, THandlerErr>,
@@ -704,15 +1251,24 @@ fn handle_swarm_event<THandlerErr: std::error::Error>(
     is_synced: &Arc<AtomicBool>,
     peer_count: &Arc<AtomicUsize>,
     relay_addrs: &[String],
+    relay_failover: &mut RelayFailover,
     relay_peer_id_opt: &mut Option<PeerId>,
     relay_connected: &Arc<AtomicBool>,
     node_type: &Arc<Mutex<crate::NodeType>>,
     topics: &GossipTopics,
-    network_graph: &mut HashMap<String, Vec<String>>,
+    network_graph: &Mutex<HashMap<String, Vec<String>>>,
+    header_sync: &mut HeaderSync,
+    peer_scores: &Arc<Mutex<PeerScores>>,
+    peer_latencies: &Arc<Mutex<HashMap<String, u64>>>,
+    peer_limit: &mut PeerLimit,
+    verified_tx: &tokio::sync::mpsc::Sender<VdfVerdict>,
+    pending_queries: &mut PendingQueries,
+    gzip_peers: &mut HashSet<PeerId>,
 ) {
     match event {
         SwarmEvent::NewListenAddr { address, .. } => {
             log::info!("Local node is listening on {:?}", address);
+            let _ = app_handle.emit("listen-addr", address.to_string());
         }
 
         SwarmEvent::Behaviour(CentichainBehaviourEvent::Identify(
@@ -724,6 +1280,30 @@ fn handle_swarm_event<THandlerErr: std::error::Error>(
                 info.agent_version
             );
 
+            // Relays serve every network; anyone else must speak our protocol on our chain
+            let is_relay = Some(peer_id) == *relay_peer_id_opt
+                || info
+                    .listen_addrs
+                    .iter()
+                    .any(|a| relay_addrs.iter().any(|r| a.to_string().contains(r)));
+            if !is_relay {
+                if let Err(reason) =
+                    check_peer_protocol(&info.protocol_version, crate::chain::chain_id())
+                {
+                    reject_incompatible_peer(swarm, consensus, peer_id, &reason, app_handle);
+                    return;
+                }
+            }
+
+            if !is_relay {
+                remember_peer(storage, peer_id, &info.listen_addrs);
+            }
+            if has_capability(&info.agent_version, CAPABILITY_GZIP_SYNC) {
+                gzip_peers.insert(peer_id);
+            } else {
+                gzip_peers.remove(&peer_id);
+            }
+
             for addr in &info.listen_addrs {
                 let is_relay_addr = relay_addrs.iter().any(|r| addr.to_string().contains(r));
                 if !is_relay_addr {
@@ -747,8 +1327,21 @@ fn handle_swarm_event<THandlerErr: std::error::Error>(
             }
         }
 
+        SwarmEvent::Behaviour(CentichainBehaviourEvent::Ping(ping::Event {
+            peer, result, ..
+        })) => {
+            record_ping(peer_latencies, &peer, &result);
+        }
+
         SwarmEvent::Behaviour(CentichainBehaviourEvent::Mdns(mdns::Event::Discovered(list))) => {
             for (peer_id, _multiaddr) in list {
+                if peer_scores
+                    .lock()
+                    .unwrap()
+                    .is_banned(&peer_id, Instant::now())
+                {
+                    continue;
+                }
                 swarm.behaviour_mut().gossipsub.add_explicit_peer(&peer_id);
                 let _ = swarm
                     .behaviour_mut()
@@ -769,26 +1362,84 @@ fn handle_swarm_event<THandlerErr: std::error::Error>(
         }
 
         SwarmEvent::Behaviour(CentichainBehaviourEvent::Gossipsub(gossipsub::Event::Message {
-            propagation_source: peer_id,
+            propagation_source,
+            message_id,
             message,
-            ..
         })) => {
-            handle_gossip_message(
+            let origin = GossipOrigin {
+                message_id,
+                propagation_source,
+                source: message.source,
+            };
+            let verdict = handle_gossip_message(
                 &message,
-                peer_id,
+                &origin,
                 storage,
                 mempool,
                 consensus,
-                chain_index,
                 topics,
                 network_graph,
                 app_handle,
+                verified_tx,
             );
+            settle_gossip(swarm, peer_scores, consensus, &origin, verdict, app_handle);
+        }
+
+        SwarmEvent::Behaviour(CentichainBehaviourEvent::Gossipsub(
+            gossipsub::Event::Subscribed { peer_id, topic },
+        )) if topic == topics.node_status.hash() => {
+            // Peers that saw us leave before a restart learn we are back
+            let local_peer_id = swarm.local_peer_id().to_string();
+            let Some(mining_active) = consensus
+                .lock()
+                .unwrap()
+                .get_peer_mining_status(&local_peer_id)
+            else {
+                return;
+            };
+            let status_update = crate::chain::NodeStatusUpdate::new(local_peer_id, mining_active);
+            if let Ok(data) = serde_json::to_vec(&status_update) {
+                if let Err(e) = swarm
+                    .behaviour_mut()
+                    .gossipsub
+                    .publish(topics.node_status.clone(), data)
+                {
+                    log::debug!(
+                        "P2P: Status for new subscriber {} not sent: {:?}",
+                        peer_id,
+                        e
+                    );
+                }
+            }
         }
 
         SwarmEvent::Behaviour(CentichainBehaviourEvent::Sync(
             libp2p::request_response::Event::Message { peer, message, .. },
         )) => {
+            // Answers to local queries go back to the waiting caller
+            let message = match message {
+                libp2p::request_response::Message::Response {
+                    request_id,
+                    response,
+                } => {
+                    let response = decompress_response(response);
+                    match (pending_queries.remove(&request_id), response) {
+                        (Some(reply), response) => {
+                            let _ = reply.send(response);
+                            return;
+                        }
+                        (None, Ok(response)) => libp2p::request_response::Message::Response {
+                            request_id,
+                            response,
+                        },
+                        (None, Err(e)) => {
+                            log::warn!("P2P Sync: Unreadable response from {}: {}", peer, e);
+                            return;
+                        }
+                    }
+                }
+                message => message,
+            };
             handle_sync_message(
                 message,
                 peer,
@@ -800,19 +1451,56 @@ fn handle_swarm_event<THandlerErr: std::error::Error>(
                 is_synced,
                 node_type,
                 *relay_peer_id_opt,
+                header_sync,
+                peer_scores,
+                gzip_peers,
                 app_handle,
             );
         }
 
-        SwarmEvent::ConnectionEstablished {
-            peer_id, endpoint, ..
-        } => {
-            if endpoint.is_dialer() {
+        SwarmEvent::Behaviour(CentichainBehaviourEvent::Sync(
+            libp2p::request_response::Event::OutboundFailure {
+                request_id, error, ..
+            },
+        )) => {
+            if let Some(reply) = pending_queries.remove(&request_id) {
+                let _ = reply.send(Err(format!("Peer query failed: {}", error)));
+            }
+        }
+
+        SwarmEvent::ConnectionEstablished {
+            peer_id, endpoint, ..
+        } => {
+            if peer_scores
+                .lock()
+                .unwrap()
+                .is_banned(&peer_id, Instant::now())
+            {
+                log::info!("Dropping connection from banned peer {}", peer_id);
+                let _ = swarm.disconnect_peer_id(peer_id);
+                return;
+            }
+            // Existing connections are kept; only newcomers past the cap are refused
+            let connected = non_relay_peer_count(swarm, *relay_peer_id_opt);
+            if !endpoint.is_dialer()
+                && Some(peer_id) != *relay_peer_id_opt
+                && !peer_limit.admits(connected)
+            {
+                log::info!("Peer limit reached, refusing inbound peer {}", peer_id);
+                let _ = swarm.disconnect_peer_id(peer_id);
+                return;
+            }
+            if endpoint.is_dialer() {
                 let remote_addr = endpoint.get_remote_address().to_string();
-                if relay_addrs.iter().any(|r| remote_addr.contains(r)) {
-                    log::info!("Connection established with Relay: {}", peer_id);
+                if let Some(active) = relay_failover.on_connected(&remote_addr) {
+                    let active = active.to_string();
+                    log::info!("Connection established with Relay {} ({})", active, peer_id);
+                    listen_via_relay(swarm, endpoint.get_remote_address(), peer_id);
                     *relay_peer_id_opt = Some(peer_id);
-                    let _ = app_handle.emit("relay-status", "connected");
+                    let _ = app_handle.emit(
+                        "relay-status",
+                        serde_json::json!({ "state": "connected", "address": active }),
+                    );
                     let _ = app_handle.emit("relay-info", peer_id.to_string());
                     relay_connected.store(true, Ordering::Relaxed);
                     consensus.lock().unwrap().nodes.remove(&peer_id.to_string());
@@ -824,38 +1512,50 @@ fn handle_swarm_event<THandlerErr: std::error::Error>(
                 consensus.lock().unwrap().register_node(peer_id.to_string());
             }
 
-            let total_peers = swarm.network_info().num_peers();
-            let relay_is_conn = relay_peer_id_opt
-                .map(|rid| swarm.is_connected(&rid))
-                .unwrap_or(false);
-            let valid_peers = if relay_is_conn {
-                total_peers.saturating_sub(1)
-            } else {
-                total_peers
-            };
+            let valid_peers = non_relay_peer_count(swarm, *relay_peer_id_opt);
             peer_count.store(valid_peers, Ordering::Relaxed);
+            if peer_limit.update(valid_peers) {
+                emit_peer_limit_reached(app_handle, peer_limit, valid_peers);
+            }
+            record_local_connections(swarm, network_graph);
         }
 
         SwarmEvent::ConnectionClosed {
-            peer_id, endpoint, ..
+            peer_id,
+            endpoint,
+            num_established,
+            ..
         } => {
             let remote_addr = endpoint.get_remote_address().to_string();
-            if relay_addrs.iter().any(|r| remote_addr.contains(r)) {
-                log::warn!("Relay connection closed: {}", peer_id);
+            if num_established == 0 {
+                peer_latencies.lock().unwrap().remove(&peer_id.to_string());
+                gzip_peers.remove(&peer_id);
+            }
+            if num_established == 0 && relay_failover.on_disconnected(&remote_addr) {
+                log::warn!(
+                    "Relay connection closed: {} ({}), failing over",
+                    remote_addr,
+                    peer_id
+                );
                 let _ = app_handle.emit("relay-status", "disconnected");
                 relay_connected.store(false, Ordering::Relaxed);
+                *relay_peer_id_opt = None;
+                dial_next_relay(swarm, relay_failover, app_handle);
             }
 
-            let total_peers = swarm.network_info().num_peers();
-            let relay_is_conn = relay_peer_id_opt
-                .map(|rid| swarm.is_connected(&rid))
-                .unwrap_or(false);
-            let valid_peers = if relay_is_conn {
-                total_peers.saturating_sub(1)
-            } else {
-                total_peers
-            };
+            let valid_peers = non_relay_peer_count(swarm, *relay_peer_id_opt);
             peer_count.store(valid_peers, Ordering::Relaxed);
+            peer_limit.update(valid_peers);
+            record_local_connections(swarm, network_graph);
+        }
+
+        SwarmEvent::OutgoingConnectionError {
+            error: DialError::Transport(attempts),
+            ..
+        } => {
+            for (addr, _) in &attempts {
+                relay_failover.on_dial_failed(&addr.to_string(), Instant::now());
+            }
         }
 
         SwarmEvent::Behaviour(CentichainBehaviourEvent::RelayClient(
@@ -876,10 +1576,20 @@ fn handle_swarm_event<THandlerErr: std::error::Error>(
             kad::Event::OutboundQueryProgressed { result, .. },
         )) => {
             if let kad::QueryResult::GetClosestPeers(Ok(ok)) = result {
-                for peer in ok.peers {
-                    if Some(peer) != *relay_peer_id_opt && !swarm.is_connected(&peer) {
-                        let _ = swarm.dial(peer);
-                    }
+                let scores = peer_scores.lock().unwrap();
+                let connected = non_relay_peer_count(swarm, *relay_peer_id_opt);
+                let candidates: Vec<PeerId> = ok
+                    .peers
+                    .into_iter()
+                    .filter(|peer| {
+                        Some(*peer) != *relay_peer_id_opt
+                            && !swarm.is_connected(peer)
+                            && !scores.is_banned(peer, Instant::now())
+                    })
+                    .take(peer_limit.dial_budget(connected))
+                    .collect();
+                for peer in candidates {
+                    let _ = swarm.dial(peer);
                 }
             }
         }
@@ -888,22 +1598,20 @@ fn handle_swarm_event<THandlerErr: std::error::Error>(
             peer,
             ..
         })) => {
-            if Some(peer) != *relay_peer_id_opt && !swarm.is_connected(&peer) {
+            let banned = peer_scores.lock().unwrap().is_banned(&peer, Instant::now());
+            let connected = non_relay_peer_count(swarm, *relay_peer_id_opt);
+            if Some(peer) != *relay_peer_id_opt
+                && !swarm.is_connected(&peer)
+                && !banned
+                && peer_limit.dial_budget(connected) > 0
+            {
                 let dial_opts = libp2p::swarm::dial_opts::DialOpts::peer_id(peer)
                     .condition(libp2p::swarm::dial_opts::PeerCondition::Disconnected)
                     .build();
                 let _ = swarm.dial(dial_opts);
             }
 
-            let total_peers = swarm.network_info().num_peers();
-            let relay_is_conn = relay_peer_id_opt
-                .map(|rid| swarm.is_connected(&rid))
-                .unwrap_or(false);
-            let valid_peers = if relay_is_conn {
-                total_peers.saturating_sub(1)
-            } else {
-                total_peers
-            };
+            let valid_peers = non_relay_peer_count(swarm, *relay_peer_id_opt);
             peer_count.store(valid_peers, Ordering::Relaxed);
         }
 
@@ -911,81 +1619,464 @@ fn handle_swarm_event<THandlerErr: std::error::Error>(
     }
 }
 
-/// Handles gossipsub messages
-fn handle_gossip_message(
-    message: &gossipsub::Message,
+/// Applies pooled orphans that extend the local tip, in order
+fn connect_orphans(
+    orphans: &mut OrphanPool,
+    storage: &Arc<Storage>,
+    mempool: &Arc<Mempool>,
+    consensus: &Arc<Mutex<Consensus>>,
+    chain_index: &Arc<AtomicU64>,
+    app_handle: &AppHandle,
+) {
+    let tip = storage.get_latest_index().unwrap_or(0);
+    let Ok(Some(tip)) = storage.get_block(tip) else {
+        return;
+    };
+    for block in orphans.drain_connected(&tip.hash) {
+        match ingest_vdf_verified_block(storage, mempool, consensus, &block) {
+            BlockAcceptResult::Accepted => {
+                log::info!("Connected orphan block #{}", block.index);
+                chain_index.store(block.index, Ordering::Relaxed);
+                let _ = app_handle.emit("new-block", block);
+            }
+            result => {
+                log::warn!("Orphan block #{} not applied: {:?}", block.index, result);
+                break;
+            }
+        }
+    }
+    // Orphans at or below the tip lost their place in the chain
+    orphans.discard_up_to(storage.get_latest_index().unwrap_or(tip.index));
+}
+
+/// Ingests a gossiped block once its VDF check has completed; `peer_id` relayed it
+#[allow(clippy::too_many_arguments)]
+fn handle_verified_block(
+    block: Block,
     peer_id: PeerId,
+    verdict: Result<(), String>,
+    swarm: &mut libp2p::Swarm<CentichainBehaviour>,
     storage: &Arc<Storage>,
     mempool: &Arc<Mempool>,
     consensus: &Arc<Mutex<Consensus>>,
     chain_index: &Arc<AtomicU64>,
+    app_handle: &AppHandle,
+    orphans: &mut OrphanPool,
     topics: &GossipTopics,
-    network_graph: &mut HashMap<String, Vec<String>>,
+    bls_key: Option<&SecretKey>,
+) -> GossipVerdict {
+    let (result, evidence) = match verdict {
+        Ok(()) => ingest_gossip_block(storage, mempool, consensus, &block),
+        Err(reason) => (BlockAcceptResult::Rejected(reason), None),
+    };
+    if let Some(evidence) = evidence {
+        log::warn!(
+            "Equivocation: {} produced {} and {} for slot {}",
+            evidence.author,
+            evidence.first_hash,
+            evidence.second_hash,
+            evidence.slot
+        );
+        let _ = app_handle.emit("equivocation-detected", evidence);
+    }
+    match result {
+        BlockAcceptResult::Accepted => {
+            chain_index.store(block.index, Ordering::Relaxed);
+            if block.shard_id == topics.shard_id as u32 {
+                publish_crosslink_vote(swarm, topics, consensus, &block, bls_key);
+            }
+            let _ = app_handle.emit("new-block", block);
+            GossipVerdict::Accept
+        }
+        BlockAcceptResult::Reorged { replaced } => {
+            log::info!(
+                "Fork choice: block #{} from {} replaced {:?}",
+                block.index,
+                peer_id,
+                replaced
+            );
+            chain_index.store(block.index, Ordering::Relaxed);
+            let _ = app_handle.emit(
+                "reorg",
+                serde_json::json!({
+                    "index": block.index,
+                    "hash": block.hash,
+                    "replaced": replaced,
+                }),
+            );
+            let _ = app_handle.emit("new-block", block);
+            GossipVerdict::Accept
+        }
+        BlockAcceptResult::Duplicate => GossipVerdict::Ignore,
+        BlockAcceptResult::Stale(reason) => {
+            log::debug!(
+                "Stale gossip block #{} via {}: {}",
+                block.index,
+                peer_id,
+                reason
+            );
+            GossipVerdict::Ignore
+        }
+        BlockAcceptResult::NeedsSync { missing_from } => {
+            log::info!(
+                "Block #{} needs sync from height {}",
+                block.index,
+                missing_from
+            );
+            // The sender has the branch this block builds on
+            swarm
+                .behaviour_mut()
+                .sync
+                .send_request(&peer_id, blocks_range_request(missing_from, block.index));
+            // Its VDF checked out; keep it in case the parent arrives by gossip first
+            orphans.insert(block);
+            GossipVerdict::Ignore
+        }
+        BlockAcceptResult::Rejected(reason) => {
+            log::warn!(
+                "Rejected gossip block #{} by {} (via {}): {}",
+                block.index,
+                block.author,
+                peer_id,
+                reason
+            );
+            let _ = app_handle.emit(
+                "block-rejected",
+                serde_json::json!({
+                    "index": block.index,
+                    "hash": block.hash,
+                    "author": block.author,
+                    "peer": peer_id.to_string(),
+                    "reason": reason,
+                }),
+            );
+            GossipVerdict::Reject(Offense::InvalidBlock)
+        }
+        BlockAcceptResult::LowDiskSpace(low) => {
+            report_low_disk(&low, app_handle);
+            GossipVerdict::Ignore
+        }
+        BlockAcceptResult::LocalError(e) => {
+            log::error!("Could not apply gossip block #{}: {}", block.index, e);
+            GossipVerdict::Ignore
+        }
+    }
+}
+
+/// Light nodes keep only the header of a gossiped block
+fn handle_light_block(
+    block: Block,
+    peer_id: PeerId,
+    verdict: Result<(), String>,
+    swarm: &mut libp2p::Swarm<CentichainBehaviour>,
+    storage: &Arc<Storage>,
+    chain_index: &Arc<AtomicU64>,
+    app_handle: &AppHandle,
+) -> GossipVerdict {
+    if let Err(reason) = verdict {
+        log::warn!("Light: rejected gossip block #{}: {}", block.index, reason);
+        return GossipVerdict::Reject(Offense::InvalidBlock);
+    }
+    match accept_headers(storage, &[Header::from_block(&block)]) {
+        Ok(tip) => {
+            chain_index.store(tip, Ordering::Relaxed);
+            let _ = app_handle.emit("new-block", block);
+            GossipVerdict::Accept
+        }
+        Err(e) => {
+            // Usually a gap or a fork; header sync with the sender sorts it out
+            log::debug!("Light: header #{} not applied: {}", block.index, e);
+            swarm
+                .behaviour_mut()
+                .sync
+                .send_request(&peer_id, SyncRequest::GetHeight);
+            GossipVerdict::Ignore
+        }
+    }
+}
+
+/// Disconnects a peer whose identify info failed the protocol handshake
+fn reject_incompatible_peer(
+    swarm: &mut libp2p::Swarm<CentichainBehaviour>,
+    consensus: &Arc<Mutex<Consensus>>,
+    peer: PeerId,
+    reason: &Incompatibility,
     app_handle: &AppHandle,
 ) {
+    log::warn!("Disconnecting incompatible peer {}: {}", peer, reason);
+    consensus.lock().unwrap().nodes.remove(&peer.to_string());
+    let _ = swarm.disconnect_peer_id(peer);
+    let _ = app_handle.emit(
+        "incompatible-peer",
+        serde_json::json!({ "peer_id": peer.to_string(), "reason": reason.to_string() }),
+    );
+}
+
+/// Charges `peer` for `offense`, disconnecting and banning it once its score runs out
+fn punish_peer(
+    swarm: &mut libp2p::Swarm<CentichainBehaviour>,
+    peer_scores: &Arc<Mutex<PeerScores>>,
+    consensus: &Arc<Mutex<Consensus>>,
+    peer: PeerId,
+    offense: Offense,
+    app_handle: &AppHandle,
+) {
+    let banned = peer_scores
+        .lock()
+        .unwrap()
+        .penalize(peer, offense, Instant::now());
+    log::debug!("Penalized peer {} for {:?}", peer, offense);
+    if banned {
+        log::warn!("Banning peer {} after {:?}", peer, offense);
+        let _ = swarm.disconnect_peer_id(peer);
+        consensus.lock().unwrap().nodes.remove(&peer.to_string());
+        let _ = app_handle.emit("peer-banned", peer.to_string());
+    }
+}
+
+/// Reports `verdict` to gossipsub, which forwards accepted messages only. A
+/// rejected message is charged to its publisher, not to the mesh peer that
+/// relayed it; fork-choice losers and duplicates are merely ignored.
+fn settle_gossip(
+    swarm: &mut libp2p::Swarm<CentichainBehaviour>,
+    peer_scores: &Arc<Mutex<PeerScores>>,
+    consensus: &Arc<Mutex<Consensus>>,
+    origin: &GossipOrigin,
+    verdict: GossipVerdict,
+    app_handle: &AppHandle,
+) {
+    let acceptance = match verdict {
+        GossipVerdict::Pending => return,
+        GossipVerdict::Accept => gossipsub::MessageAcceptance::Accept,
+        GossipVerdict::Ignore => gossipsub::MessageAcceptance::Ignore,
+        GossipVerdict::Reject(offense) => {
+            if let Some(source) = origin.source {
+                punish_peer(swarm, peer_scores, consensus, source, offense, app_handle);
+            }
+            gossipsub::MessageAcceptance::Reject
+        }
+    };
+    if let Err(e) = swarm
+        .behaviour_mut()
+        .gossipsub
+        .report_message_validation_result(
+            &origin.message_id,
+            &origin.propagation_source,
+            acceptance,
+        )
+    {
+        log::debug!(
+            "Gossip: validation of {} not reported: {:?}",
+            origin.message_id,
+            e
+        );
+    }
+}
+
+/// Warns that a block from the network was not written for lack of disk space
+fn report_low_disk(low: &LowDiskSpace, app_handle: &AppHandle) {
+    log::warn!("P2P: {}; block not written", low);
+    let _ = app_handle.emit("low-disk", low);
+}
+
+/// Handles gossipsub messages; returns what to report to gossipsub about them
+fn handle_gossip_message(
+    message: &gossipsub::Message,
+    origin: &GossipOrigin,
+    storage: &Arc<Storage>,
+    mempool: &Arc<Mempool>,
+    consensus: &Arc<Mutex<Consensus>>,
+    topics: &GossipTopics,
+    network_graph: &Mutex<HashMap<String, Vec<String>>>,
+    app_handle: &AppHandle,
+    verified_tx: &tokio::sync::mpsc::Sender<VdfVerdict>,
+) -> GossipVerdict {
     if message.topic.as_str() == topics.shard_blocks.hash().as_str() {
-        if let Ok(block) = serde_json::from_slice::<Block>(&message.data) {
-            log::info!("Received Gossip Block #{} from {}", block.index, peer_id);
-            match ingest_block(storage, mempool, consensus, &block, false) {
-                BlockAcceptResult::Accepted => {
-                    chain_index.store(block.index, Ordering::Relaxed);
-                    let _ = app_handle.emit("new-block", block);
+        let Ok(block) = decode_gossip::<Block>(&message.data) else {
+            return GossipVerdict::Ignore;
+        };
+        log::info!(
+            "Received Gossip Block #{} from {}",
+            block.index,
+            origin.propagation_source
+        );
+        // VDF verification is CPU-bound; keep it off the swarm loop
+        let verified_tx = verified_tx.clone();
+        let origin = origin.clone();
+        tokio::spawn(async move {
+            let verdict = verify_block_vdf(&block).await;
+            let _ = verified_tx.send((block, origin, verdict)).await;
+        });
+        GossipVerdict::Pending
+    } else if message.topic.as_str() == topics.shard_txs.hash().as_str() {
+        let Ok(tx) = decode_gossip::<Transaction>(&message.data) else {
+            return GossipVerdict::Ignore;
+        };
+        match mempool.add_transaction(tx.clone()) {
+            Ok(true) => {
+                let _ = app_handle.emit("new-transaction", tx);
+                GossipVerdict::Accept
+            }
+            // Already pending or mined; seen from another mesh peer
+            Ok(false) => GossipVerdict::Ignore,
+            Err(e) => {
+                log::debug!("Rejected gossip tx {}: {}", tx.id, e);
+                match tx.validate() {
+                    Ok(()) => GossipVerdict::Ignore,
+                    Err(_) => GossipVerdict::Reject(Offense::InvalidTransaction),
                 }
-                BlockAcceptResult::Duplicate => {}
-                BlockAcceptResult::NeedsSync { missing_from } => {
-                    log::info!(
-                        "Block #{} needs sync from height {}",
-                        block.index, missing_from
+            }
+        }
+    } else if message.topic.as_str() == topics.tx_cancels.hash().as_str() {
+        let Ok(cancel) = decode_gossip::<TxCancellation>(&message.data) else {
+            return GossipVerdict::Ignore;
+        };
+        match mempool.cancel_transaction(&cancel) {
+            Ok(dropped) => {
+                for tx in dropped {
+                    log::info!("Mempool: dropped {} on cancellation by its sender", tx.id);
+                    let _ = app_handle.emit(
+                        "tx-dropped",
+                        serde_json::json!({ "id": tx.id, "reason": "cancelled" }),
                     );
                 }
-                BlockAcceptResult::Rejected(reason) => {
-                    log::warn!("Rejected gossip block #{}: {}", block.index, reason);
+                GossipVerdict::Accept
+            }
+            Err(e) => {
+                log::debug!("Ignored cancellation of {}: {}", cancel.tx_id, e);
+                match cancel.verify() {
+                    Ok(()) => GossipVerdict::Ignore,
+                    Err(_) => GossipVerdict::Reject(Offense::InvalidTransaction),
                 }
             }
         }
-    } else if message.topic.as_str() == topics.shard_txs.hash().as_str() {
-        if let Ok(tx) = serde_json::from_slice::<Transaction>(&message.data) {
-            if let Err(e) = mempool.add_transaction(tx.clone()) {
-                log::debug!("Rejected gossip tx {}: {}", tx.id, e);
-            } else {
-                let _ = app_handle.emit("new-transaction", tx);
-            }
+    } else if message.topic.as_str() == topics.receipts.hash().as_str() {
+        let Ok(receipt) = decode_gossip::<crate::chain::Receipt>(&message.data) else {
+            return GossipVerdict::Ignore;
+        };
+        if receipt.verify_inclusion().is_err() {
+            return GossipVerdict::Reject(Offense::InvalidTransaction);
         }
-    } else if message.topic.as_str() == topics.vdf_proofs.hash().as_str() {
-        if let Ok(msg) = serde_json::from_slice::<crate::chain::VdfProofMessage>(&message.data) {
-            log::info!("Received VDF Proof from {}", msg.peer_id);
-            let mut c = consensus.lock().unwrap();
-            if c.verify_peer(msg.peer_id.clone(), msg.proof) {
+        let my_shard = {
+            let c = consensus.lock().unwrap();
+            c.local_peer_id
+                .as_ref()
+                .map(|pid| c.get_assigned_shard(pid, c.current_epoch()))
+        };
+        // Proven receipts for other shards are passed on to their nodes
+        if my_shard != Some(receipt.target_shard) {
+            return GossipVerdict::Accept;
+        }
+        match mempool.add_receipt(receipt.clone()) {
+            Ok(()) => {
                 log::info!(
-                    "Verified peer {} via VDF! Trust Score set to 1.0",
-                    msg.peer_id
+                    "Queued cross-shard receipt {} ({} -> {})",
+                    receipt.original_tx_id,
+                    receipt.source_shard,
+                    receipt.target_shard
                 );
-                c.persist_to_storage(storage);
-                let _ = app_handle.emit("peer-update", msg.peer_id);
-            } else {
-                log::warn!("Invalid VDF Proof from {}", msg.peer_id);
+                mempool.track_receipt(&receipt);
+                let _ = app_handle.emit("new-receipt", &receipt);
+                GossipVerdict::Accept
+            }
+            Err(e) => {
+                log::debug!("Ignored receipt {}: {}", receipt.original_tx_id, e);
+                GossipVerdict::Ignore
             }
         }
-    } else if message.topic.as_str() == topics.topology.hash().as_str() {
-        if let Ok(msg) = serde_json::from_slice::<TopologyUpdate>(&message.data) {
-            network_graph.insert(msg.source, msg.connections);
-            let _ = app_handle.emit("network-topology-update", network_graph.clone());
+    } else if message.topic.as_str() == topics.crosslinks.hash().as_str() {
+        match decode_gossip::<BeaconMessage>(&message.data) {
+            Ok(BeaconMessage::Key(key)) => {
+                // Only a validator itself may announce its key
+                if origin.source.map(|source| source.to_string()) != Some(key.peer_id.clone()) {
+                    return GossipVerdict::Ignore;
+                }
+                let peer = key.peer_id.clone();
+                if let Err(e) = consensus.lock().unwrap().beacon.register_key(key) {
+                    log::warn!("Invalid committee key from {}: {}", peer, e);
+                    return GossipVerdict::Reject(Offense::InvalidTransaction);
+                }
+                GossipVerdict::Accept
+            }
+            Ok(BeaconMessage::Vote(vote)) => {
+                // Committees differ while validator views converge; don't penalize
+                match consensus.lock().unwrap().record_crosslink_vote(vote) {
+                    Ok(Some(link)) => {
+                        log::info!(
+                            "Shard {} cross-linked at block {} by {} signers",
+                            link.shard_id,
+                            link.block_height,
+                            link.signers.len()
+                        );
+                        let _ = app_handle.emit("crosslink", &link);
+                        GossipVerdict::Accept
+                    }
+                    Ok(None) => GossipVerdict::Accept,
+                    Err(e) => {
+                        log::debug!(
+                            "Ignored cross-link vote via {}: {}",
+                            origin.propagation_source,
+                            e
+                        );
+                        GossipVerdict::Ignore
+                    }
+                }
+            }
+            Err(_) => GossipVerdict::Ignore,
+        }
+    } else if message.topic.as_str() == topics.vdf_proofs.hash().as_str() {
+        let Ok(msg) = serde_json::from_slice::<crate::chain::VdfProofMessage>(&message.data) else {
+            return GossipVerdict::Ignore;
+        };
+        log::info!("Received VDF Proof from {}", msg.peer_id);
+        let mut c = consensus.lock().unwrap();
+        if c.verify_peer(msg.peer_id.clone(), msg.proof) {
+            log::info!(
+                "Verified peer {} via VDF! Trust Score set to 1.0",
+                msg.peer_id
+            );
+            c.persist_to_storage(storage);
+            let _ = app_handle.emit("peer-update", msg.peer_id);
+            GossipVerdict::Accept
+        } else {
+            // Also the answer for peers not yet known here; not proof of a bad VDF
+            log::warn!("Invalid VDF Proof from {}", msg.peer_id);
+            GossipVerdict::Ignore
         }
+    } else if message.topic.as_str() == topics.topology.hash().as_str() {
+        let Ok(msg) = serde_json::from_slice::<TopologyUpdate>(&message.data) else {
+            return GossipVerdict::Ignore;
+        };
+        let graph = {
+            let mut graph = network_graph.lock().unwrap();
+            graph.insert(msg.source, msg.connections);
+            graph.clone()
+        };
+        let _ = app_handle.emit("network-topology-update", graph);
+        GossipVerdict::Accept
     } else if message.topic.as_str() == topics.node_status.hash().as_str() {
-        if let Ok(status_update) =
+        let Ok(status_update) =
             serde_json::from_slice::<crate::chain::NodeStatusUpdate>(&message.data)
-        {
+        else {
+            return GossipVerdict::Ignore;
+        };
+        if status_update.leaving {
+            log::info!("P2P: Peer {} is leaving the network", status_update.peer_id);
+        } else {
             log::info!(
                 "P2P: Received mining status update from {}: mining_active={}",
                 status_update.peer_id,
                 status_update.mining_active
             );
+        }
 
-            let mut c = consensus.lock().unwrap();
-            c.set_peer_mining_status(&status_update.peer_id, status_update.mining_active);
+        let mut c = consensus.lock().unwrap();
+        c.set_peer_mining_status(&status_update.peer_id, status_update.mining_active);
 
-            let _ = app_handle.emit("peer-mining-status", &status_update);
-        }
+        let _ = app_handle.emit("peer-mining-status", &status_update);
+        GossipVerdict::Accept
+    } else {
+        GossipVerdict::Ignore
     }
 }
 
@@ -1001,68 +2092,124 @@ fn handle_sync_message(
     is_synced: &Arc<AtomicBool>,
     node_type: &Arc<Mutex<crate::NodeType>>,
     _relay_peer_id_opt: Option<PeerId>,
+    header_sync: &mut HeaderSync,
+    peer_scores: &Arc<Mutex<PeerScores>>,
+    gzip_peers: &HashSet<PeerId>,
     app_handle: &AppHandle,
 ) {
     match message {
         libp2p::request_response::Message::Request {
             request, channel, ..
-        } => match request {
-            SyncRequest::GetHeight => {
-                let height = storage.get_latest_index().unwrap_or(0);
-                log::info!(
-                    "P2P Sync: Responding to GetHeight from {} with {}",
+        } => {
+            let within_rate = peer_scores
+                .lock()
+                .unwrap()
+                .within_sync_rate(peer, Instant::now());
+            if !within_rate {
+                log::warn!("P2P Sync: {} exceeded the sync request rate", peer);
+                punish_peer(
+                    swarm,
+                    peer_scores,
+                    consensus,
                     peer,
-                    height
+                    Offense::SyncFlood,
+                    app_handle,
                 );
-                let _ = swarm
-                    .behaviour_mut()
-                    .sync
-                    .send_response(channel, SyncResponse::Height(height));
+                return;
             }
-            SyncRequest::GetBlock(index) => {
-                let block_opt = storage.get_block(index).unwrap_or(None);
-                let _ = swarm
-                    .behaviour_mut()
-                    .sync
-                    .send_response(channel, SyncResponse::Block(block_opt));
+            // Still served, but only the first MAX_SYNC_RANGE_SPAN blocks
+            if is_oversized(&request) {
+                log::warn!(
+                    "P2P Sync: {} asked for an oversized range {:?}",
+                    peer,
+                    request
+                );
+                punish_peer(
+                    swarm,
+                    peer_scores,
+                    consensus,
+                    peer,
+                    Offense::OversizedRequest,
+                    app_handle,
+                );
             }
-            SyncRequest::GetBlocksRange(start, end) => {
-                let mut blocks = Vec::new();
-                for i in start..=end {
-                    if let Ok(Some(b)) = storage.get_block(i) {
-                        blocks.push(b);
-                    } else {
-                        break;
-                    }
-                }
-                let _ = swarm
-                    .behaviour_mut()
-                    .sync
-                    .send_response(channel, SyncResponse::BlocksBatch(blocks));
+            log::debug!("P2P Sync: Serving {:?} for {}", request, peer);
+            let mut response = serve_sync_request(storage, mempool, request);
+            if gzip_peers.contains(&peer) {
+                response = compress_response(response);
             }
-            SyncRequest::GetMempool => {
-                let txs = mempool.get_pending_transactions();
-                let _ = swarm
-                    .behaviour_mut()
-                    .sync
-                    .send_response(channel, SyncResponse::Mempool(txs));
+            let _ = swarm.behaviour_mut().sync.send_response(channel, response);
+        }
+        libp2p::request_response::Message::Response { response, .. } => match response {
+            // Light nodes sync headers only
+            SyncResponse::Height(remote_height)
+                if *node_type.lock().unwrap() == crate::NodeType::Light =>
+            {
+                let start = storage
+                    .get_header_tip()
+                    .ok()
+                    .flatten()
+                    .map_or(0, |tip| tip + 1);
+                if remote_height >= start {
+                    let end = remote_height.min(start + SYNC_HEADERS_BATCH - 1);
+                    let msg = format!("Syncing headers {}..{}", start, end);
+                    log::info!("P2P Sync: {}", msg);
+                    let _ = app_handle.emit("node-status", msg);
+                    let progress = SyncProgress::new(start.saturating_sub(1), remote_height);
+                    let _ = app_handle.emit("sync-progress", progress);
+                    swarm
+                        .behaviour_mut()
+                        .sync
+                        .send_request(&peer, SyncRequest::GetHeaders(start, end));
+                } else if !is_synced.load(Ordering::Relaxed) {
+                    log::info!("P2P Sync: Headers synced to #{}", start.saturating_sub(1));
+                    is_synced.store(true, Ordering::Relaxed);
+                    let _ = app_handle.emit("node-status", "Active");
+                    let progress = SyncProgress::synced(start.saturating_sub(1));
+                    let _ = app_handle.emit("sync-progress", progress);
+                }
             }
-            SyncRequest::GetHeaders(start, end) => {
-                let mut headers = Vec::new();
-                for i in start..=end {
-                    if let Ok(Some(b)) = storage.get_block(i) {
-                        headers.push(crate::chain::Header::from_block(&b));
-                    } else {
-                        break;
+            SyncResponse::HeadersBatch(headers)
+                if *node_type.lock().unwrap() == crate::NodeType::Light =>
+            {
+                let Some(first) = headers.first() else {
+                    return;
+                };
+                let parent = match first.index {
+                    0 => None,
+                    i => storage.get_header(i - 1).ok().flatten(),
+                };
+                if parent.is_some_and(|p| p.hash != first.previous_hash) {
+                    // Peer is on a different branch: fetch headers from below the fork
+                    let from = first.index.saturating_sub(MAX_REORG_DEPTH);
+                    swarm
+                        .behaviour_mut()
+                        .sync
+                        .send_request(&peer, SyncRequest::GetHeaders(from, first.index));
+                    return;
+                }
+                match accept_headers(storage, &headers) {
+                    Ok(tip) => {
+                        log::info!("P2P Sync: Headers applied up to #{}", tip);
+                        chain_index.store(tip, Ordering::Relaxed);
+                        swarm
+                            .behaviour_mut()
+                            .sync
+                            .send_request(&peer, SyncRequest::GetHeight);
+                    }
+                    Err(e) => {
+                        log::warn!("P2P Sync: Rejected headers from {}: {}", peer, e);
+                        punish_peer(
+                            swarm,
+                            peer_scores,
+                            consensus,
+                            peer,
+                            Offense::InvalidBlock,
+                            app_handle,
+                        );
                     }
                 }
-                let _ = swarm
-                    .behaviour_mut()
-                    .sync
-                    .send_response(channel, SyncResponse::HeadersBatch(headers));
             }
-        },
-        libp2p::request_response::Message::Response { response, .. } => match response {
             SyncResponse::Height(remote_height) => {
                 let local_height = chain_index.load(Ordering::Relaxed);
                 let total_blocks = storage.get_total_blocks().unwrap_or(0);
@@ -1073,14 +2220,31 @@ fn handle_sync_message(
                     total_blocks
                 );
 
-                let start = if total_blocks == 0 {
-                    0
-                } else {
-                    local_height + 1
+                // Resume above the persisted checkpoint instead of rescanning the chain
+                let start = match advance_sync_checkpoint(storage) {
+                    Ok(checkpoint) => checkpoint.map_or(0, |height| height + 1),
+                    Err(e) => {
+                        log::warn!("P2P Sync: Sync checkpoint unavailable: {}", e);
+                        if total_blocks == 0 {
+                            0
+                        } else {
+                            local_height + 1
+                        }
+                    }
                 };
 
                 if remote_height >= start {
-                    let end = (start + 100).min(remote_height);
+                    // Bodies only for headers we have already verified
+                    let (request, end) = match header_sync.committed_through(start) {
+                        Some(committed) => {
+                            let end = committed.min(start + SYNC_BLOCKS_BATCH - 1);
+                            (SyncRequest::GetBlocksRange(start, end), end)
+                        }
+                        None => {
+                            let end = remote_height.min(start + SYNC_HEADERS_BATCH - 1);
+                            (SyncRequest::GetHeaders(start, end), end)
+                        }
+                    };
                     let msg = format!("Batch Syncing {}..{}", start, end);
                     log::info!("P2P Sync: {}", msg);
                     let _ = app_handle.emit("node-status", msg);
@@ -1094,10 +2258,11 @@ fn handle_sync_message(
                         })
                         .to_string(),
                     );
-                    swarm
-                        .behaviour_mut()
-                        .sync
-                        .send_request(&peer, SyncRequest::GetBlocksRange(start, end));
+                    let _ = app_handle.emit(
+                        "sync-progress",
+                        SyncProgress::new(local_height, remote_height),
+                    );
+                    swarm.behaviour_mut().sync.send_request(&peer, request);
                 } else if !is_synced.load(Ordering::Relaxed) {
                     if total_blocks > 0 {
                         log::info!(
@@ -1107,6 +2272,8 @@ fn handle_sync_message(
                         );
                         is_synced.store(true, Ordering::Relaxed);
                         let _ = app_handle.emit("node-status", "Active");
+                        let _ =
+                            app_handle.emit("sync-progress", SyncProgress::synced(local_height));
                     } else if remote_height > 0 {
                         log::info!(
                             "P2P Sync: Local is empty, Remote is at {}. requesting genesis...",
@@ -1123,34 +2290,267 @@ fn handle_sync_message(
                     }
                 }
             }
-            SyncResponse::BlocksBatch(blocks) => {
-                let mut last_idx = 0;
-                for block in blocks {
-                    last_idx = block.index;
-                    log::info!("P2P Sync: Batch Received Block #{}", block.index);
-                    match ingest_block(storage, mempool, consensus, &block, false) {
-                        BlockAcceptResult::Accepted => {
-                            chain_index.store(block.index, Ordering::Relaxed);
-                            let _ = app_handle.emit("new-block", block);
-                        }
-                        BlockAcceptResult::Duplicate => {}
-                        BlockAcceptResult::NeedsSync { .. } => {
-                            log::warn!("Sync batch out of order at block #{}", block.index);
-                        }
-                        BlockAcceptResult::Rejected(reason) => {
-                            log::warn!("Sync rejected block #{}: {}", block.index, reason);
+            SyncResponse::HeadersBatch(headers) => {
+                let (Some(first), Some(last)) = (headers.first(), headers.last()) else {
+                    return;
+                };
+                let parent = match first.index {
+                    0 => None,
+                    i => match storage.get_block(i - 1) {
+                        Ok(Some(b)) => Some(Header::from_block(&b)),
+                        _ => {
+                            log::warn!("P2P Sync: Headers from {} start past our tip", peer);
+                            return;
                         }
-                    }
+                    },
+                };
+                if parent
+                    .as_ref()
+                    .is_some_and(|p| p.hash != first.previous_hash)
+                {
+                    // Peer is on a different branch: fetch bodies from below the fork
+                    let from = first.index.saturating_sub(MAX_REORG_DEPTH);
+                    log::info!(
+                        "P2P Sync: Headers from {} fork below #{}, fetching {}..{}",
+                        peer,
+                        first.index,
+                        from,
+                        first.index
+                    );
+                    swarm
+                        .behaviour_mut()
+                        .sync
+                        .send_request(&peer, blocks_range_request(from, first.index));
+                    return;
+                }
+                let verified = validate_header_chain(parent.as_ref(), &headers)
+                    .and_then(|_| verify_checkpoints(&crate::chain::checkpoints(), &headers));
+                if let Err(e) = verified {
+                    log::warn!("P2P Sync: Rejected headers from {}: {}", peer, e);
+                    punish_peer(
+                        swarm,
+                        peer_scores,
+                        consensus,
+                        peer,
+                        Offense::InvalidBlock,
+                        app_handle,
+                    );
+                    return;
                 }
+
+                header_sync.commit(&headers);
+                let end = last.index.min(first.index + SYNC_BLOCKS_BATCH - 1);
                 log::info!(
-                    "P2P Sync: Batch processed up to {}. Checking height...",
+                    "P2P Sync: Verified headers {}..{}, fetching bodies {}..{}",
+                    first.index,
+                    last.index,
+                    first.index,
+                    end
+                );
+                swarm
+                    .behaviour_mut()
+                    .sync
+                    .send_request(&peer, SyncRequest::GetBlocksRange(first.index, end));
+            }
+            SyncResponse::BlocksBatch(blocks)
+                if blocks.iter().any(|b| forks_local_chain(storage, b)) =>
+            {
+                let last_idx = blocks.last().map(|b| b.index).unwrap_or(0);
+                log::info!(
+                    "P2P Sync: Peer {} presents a fork up to #{}",
+                    peer,
                     last_idx
                 );
+                match ingest_branch(storage, mempool, consensus, &blocks) {
+                    BlockAcceptResult::Reorged { replaced } => {
+                        let tip = storage.get_latest_index().unwrap_or(last_idx);
+                        chain_index.store(tip, Ordering::Relaxed);
+                        log::info!("P2P Sync: Reorganized to #{}, orphaned {:?}", tip, replaced);
+                        let _ = app_handle.emit(
+                            "reorg",
+                            serde_json::json!({
+                                "index": tip,
+                                "hash": blocks.last().map(|b| b.hash.clone()),
+                                "replaced": replaced,
+                            }),
+                        );
+                        if let Ok(Some(block)) = storage.get_block(tip) {
+                            let _ = app_handle.emit("new-block", block);
+                        }
+                    }
+                    BlockAcceptResult::NeedsSync { missing_from } => {
+                        swarm
+                            .behaviour_mut()
+                            .sync
+                            .send_request(&peer, blocks_range_request(missing_from, last_idx));
+                        return;
+                    }
+                    BlockAcceptResult::Rejected(reason) => {
+                        log::warn!("P2P Sync: Fork from {} rejected: {}", peer, reason);
+                        punish_peer(
+                            swarm,
+                            peer_scores,
+                            consensus,
+                            peer,
+                            Offense::InvalidBlock,
+                            app_handle,
+                        );
+                    }
+                    BlockAcceptResult::Stale(reason) => {
+                        log::info!("P2P Sync: Kept local chain over {}'s: {}", peer, reason);
+                    }
+                    // Nothing more can be written; sync resumes once space is freed
+                    BlockAcceptResult::LowDiskSpace(low) => {
+                        report_low_disk(&low, app_handle);
+                        return;
+                    }
+                    BlockAcceptResult::LocalError(e) => {
+                        log::error!("P2P Sync: Could not apply fork from {}: {}", peer, e);
+                        return;
+                    }
+                    BlockAcceptResult::Accepted | BlockAcceptResult::Duplicate => {}
+                }
                 swarm
                     .behaviour_mut()
                     .sync
                     .send_request(&peer, SyncRequest::GetHeight);
             }
+            SyncResponse::BlocksBatch(mut blocks) => {
+                // Only the run that continues the stored chain is applied; a hole
+                // is backfilled before anything past it
+                let next_index = match storage.get_total_blocks().unwrap_or(0) {
+                    0 => 0,
+                    _ => storage.get_latest_index().unwrap_or(0) + 1,
+                };
+                let gap = find_gap(next_index, &blocks);
+                if let Some(gap) = &gap {
+                    log::info!(
+                        "P2P Sync: Batch from {} skips {}..{}, backfilling",
+                        peer,
+                        gap.from,
+                        gap.to
+                    );
+                    blocks.truncate(gap.contiguous);
+                }
+
+                let mut verified = Vec::with_capacity(blocks.len());
+                for block in blocks {
+                    if !header_sync.matches(&block) {
+                        log::warn!(
+                            "P2P Sync: Block #{} from {} does not match its verified header",
+                            block.index,
+                            peer
+                        );
+                        header_sync.clear();
+                        break;
+                    }
+                    header_sync.release(block.index);
+                    verified.push(block);
+                }
+                let last_idx = verified.last().map_or(0, |b| b.index);
+
+                match ingest_batch(storage, mempool, consensus, &verified) {
+                    BlockAcceptResult::Accepted => {
+                        log::info!("P2P Sync: Batch of {} blocks applied", verified.len());
+                        chain_index.store(last_idx, Ordering::Relaxed);
+                        for block in verified {
+                            let _ = app_handle.emit("new-block", block);
+                        }
+                    }
+                    BlockAcceptResult::Rejected(reason) => {
+                        log::warn!("Sync rejected batch: {}", reason);
+                        punish_peer(
+                            swarm,
+                            peer_scores,
+                            consensus,
+                            peer,
+                            Offense::InvalidBlock,
+                            app_handle,
+                        );
+                    }
+                    BlockAcceptResult::LowDiskSpace(low) => {
+                        report_low_disk(&low, app_handle);
+                        return;
+                    }
+                    BlockAcceptResult::LocalError(e) => {
+                        log::error!("P2P Sync: Could not apply batch from {}: {}", peer, e);
+                        return;
+                    }
+                    // Overlaps or forks the local chain: run fork choice block by block
+                    _ => {
+                        for block in verified {
+                            log::info!("P2P Sync: Batch Received Block #{}", block.index);
+                            match ingest_block(storage, mempool, consensus, &block, false) {
+                                BlockAcceptResult::Accepted => {
+                                    chain_index.store(block.index, Ordering::Relaxed);
+                                    let _ = app_handle.emit("new-block", block);
+                                }
+                                BlockAcceptResult::Reorged { replaced } => {
+                                    chain_index.store(block.index, Ordering::Relaxed);
+                                    let _ = app_handle.emit(
+                                        "reorg",
+                                        serde_json::json!({
+                                            "index": block.index,
+                                            "hash": block.hash,
+                                            "replaced": replaced,
+                                        }),
+                                    );
+                                    let _ = app_handle.emit("new-block", block);
+                                }
+                                BlockAcceptResult::Duplicate => {}
+                                BlockAcceptResult::Stale(reason) => {
+                                    log::debug!(
+                                        "Sync kept local block #{}: {}",
+                                        block.index,
+                                        reason
+                                    );
+                                }
+                                BlockAcceptResult::NeedsSync { .. } => {
+                                    log::warn!("Sync batch out of order at block #{}", block.index);
+                                }
+                                BlockAcceptResult::Rejected(reason) => {
+                                    log::warn!("Sync rejected block #{}: {}", block.index, reason);
+                                    punish_peer(
+                                        swarm,
+                                        peer_scores,
+                                        consensus,
+                                        peer,
+                                        Offense::InvalidBlock,
+                                        app_handle,
+                                    );
+                                    break;
+                                }
+                                BlockAcceptResult::LowDiskSpace(low) => {
+                                    report_low_disk(&low, app_handle);
+                                    return;
+                                }
+                                BlockAcceptResult::LocalError(e) => {
+                                    log::error!(
+                                        "Sync could not apply block #{}: {}",
+                                        block.index,
+                                        e
+                                    );
+                                    return;
+                                }
+                            }
+                        }
+                    }
+                }
+                if let Err(e) = advance_sync_checkpoint(storage) {
+                    log::warn!("P2P Sync: Failed to save sync checkpoint: {}", e);
+                }
+                let follow_up = match gap {
+                    Some(gap) => gap.request(),
+                    None => {
+                        log::info!(
+                            "P2P Sync: Batch processed up to {}. Checking height...",
+                            last_idx
+                        );
+                        SyncRequest::GetHeight
+                    }
+                };
+                swarm.behaviour_mut().sync.send_request(&peer, follow_up);
+            }
             SyncResponse::Block(Some(block)) => {
                 log::info!("P2P Sync: Received Block #{}", block.index);
                 match ingest_block(storage, mempool, consensus, &block, false) {
@@ -1175,12 +2575,39 @@ fn handle_sync_message(
                                 .send_request(&peer, SyncRequest::GetHeight);
                         }
                     }
+                    BlockAcceptResult::Reorged { replaced } => {
+                        chain_index.store(block.index, Ordering::Relaxed);
+                        let _ = app_handle.emit(
+                            "reorg",
+                            serde_json::json!({
+                                "index": block.index,
+                                "hash": block.hash,
+                                "replaced": replaced,
+                            }),
+                        );
+                        let _ = app_handle.emit("new-block", block);
+                    }
                     BlockAcceptResult::Duplicate => {}
+                    BlockAcceptResult::Stale(reason) => {
+                        log::debug!("Sync kept local block #{}: {}", block.index, reason);
+                    }
                     BlockAcceptResult::NeedsSync { missing_from } => {
                         log::info!("Sync needs blocks from {}", missing_from);
                     }
                     BlockAcceptResult::Rejected(reason) => {
                         log::warn!("Sync rejected block #{}: {}", block.index, reason);
+                        punish_peer(
+                            swarm,
+                            peer_scores,
+                            consensus,
+                            peer,
+                            Offense::InvalidBlock,
+                            app_handle,
+                        );
+                    }
+                    BlockAcceptResult::LowDiskSpace(low) => report_low_disk(&low, app_handle),
+                    BlockAcceptResult::LocalError(e) => {
+                        log::error!("Sync could not apply block #{}: {}", block.index, e);
                     }
                 }
             }
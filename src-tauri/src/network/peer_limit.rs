@@ -0,0 +1,72 @@
+//! # Peer Limit
+//!
+//! Enforces `AppSettings.max_peers`. Counts exclude the relay. Existing
+//! connections are never evicted to make room: once the limit is reached new
+//! inbound connections are closed and discovered peers are no longer dialed.
+
+/// Connection cap over non-relay peers
+#[derive(Debug)]
+pub struct PeerLimit {
+    max_peers: usize,
+    at_capacity: bool,
+}
+
+impl PeerLimit {
+    pub fn new(max_peers: u32) -> Self {
+        Self {
+            max_peers: max_peers as usize,
+            at_capacity: false,
+        }
+    }
+
+    pub fn max_peers(&self) -> usize {
+        self.max_peers
+    }
+
+    /// Whether a new inbound connection may stay, given `connected` peers including it.
+    pub fn admits(&self, connected: usize) -> bool {
+        connected <= self.max_peers
+    }
+
+    /// How many more peers may be dialed with `connected` peers already connected.
+    pub fn dial_budget(&self, connected: usize) -> usize {
+        self.max_peers.saturating_sub(connected)
+    }
+
+    /// Records the current peer count; returns true when it has just reached the limit.
+    pub fn update(&mut self, connected: usize) -> bool {
+        let was_at_capacity = self.at_capacity;
+        self.at_capacity = connected >= self.max_peers;
+        self.at_capacity && !was_at_capacity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_dials_once_cap_is_reached() {
+        let mut limit = PeerLimit::new(3);
+        let discovered = ["peer-a", "peer-b", "peer-c", "peer-d"];
+        let dials = |limit: &PeerLimit, connected| {
+            discovered.iter().take(limit.dial_budget(connected)).count()
+        };
+        assert_eq!(dials(&limit, 1), 2);
+
+        assert!(!limit.update(2));
+        assert!(limit.update(3), "reaching the cap is reported");
+        assert!(!limit.update(3), "only once");
+        assert_eq!(dials(&limit, 3), 0);
+
+        assert!(limit.admits(3));
+        assert!(
+            !limit.admits(4),
+            "inbound connections past the cap are closed"
+        );
+
+        // Dropping below the cap re-arms the event
+        assert!(!limit.update(2));
+        assert!(limit.update(3));
+    }
+}
@@ -0,0 +1,238 @@
+//! # Peer Scoring
+//!
+//! Misbehaviour accounting for remote peers. Offenses lower a peer's score,
+//! which slowly recovers; a peer whose score runs out is banned for a cooldown
+//! during which it is disconnected and not dialed.
+
+use crate::utils::constants::{
+    PEER_BAN_SECS, PEER_BAN_THRESHOLD, PEER_PENALTY_INVALID_BLOCK, PEER_PENALTY_INVALID_TX,
+    PEER_PENALTY_OVERSIZED_REQUEST, PEER_PENALTY_SYNC_FLOOD, PEER_SCORE_MAX,
+    PEER_SCORE_RECOVERY_PER_SEC, SYNC_RATE_WINDOW_SECS, SYNC_REQUESTS_PER_WINDOW,
+};
+use libp2p::PeerId;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Misbehaviour that costs a peer score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Offense {
+    /// Sent a block that failed validation
+    InvalidBlock,
+    /// Sent a transaction with a bad signature
+    InvalidTransaction,
+    /// Exceeded the sync request rate
+    SyncFlood,
+    /// Asked for a wider block range than is served at once
+    OversizedRequest,
+}
+
+impl Offense {
+    fn penalty(self) -> f64 {
+        match self {
+            Offense::InvalidBlock => PEER_PENALTY_INVALID_BLOCK,
+            Offense::InvalidTransaction => PEER_PENALTY_INVALID_TX,
+            Offense::SyncFlood => PEER_PENALTY_SYNC_FLOOD,
+            Offense::OversizedRequest => PEER_PENALTY_OVERSIZED_REQUEST,
+        }
+    }
+}
+
+/// Peer score as reported to the UI
+#[derive(Serialize, Debug, Clone)]
+pub struct PeerScoreInfo {
+    pub peer_id: String,
+    pub score: f64,
+    /// Seconds left on the ban, if the peer is banned
+    pub banned_for_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+struct PeerScore {
+    score: f64,
+    updated: Instant,
+    window_start: Instant,
+    window_requests: u32,
+}
+
+impl PeerScore {
+    fn new(now: Instant) -> Self {
+        Self {
+            score: PEER_SCORE_MAX,
+            updated: now,
+            window_start: now,
+            window_requests: 0,
+        }
+    }
+
+    fn score_at(&self, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        (self.score + elapsed * PEER_SCORE_RECOVERY_PER_SEC).min(PEER_SCORE_MAX)
+    }
+}
+
+/// Scores and temporary bans for every peer that has misbehaved or sent sync requests.
+#[derive(Debug, Default)]
+pub struct PeerScores {
+    scores: HashMap<PeerId, PeerScore>,
+    banned: HashMap<PeerId, Instant>,
+}
+
+impl PeerScores {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current score, including recovery since the last offense.
+    pub fn score(&self, peer: &PeerId, now: Instant) -> f64 {
+        self.scores
+            .get(peer)
+            .map_or(PEER_SCORE_MAX, |s| s.score_at(now))
+    }
+
+    pub fn is_banned(&self, peer: &PeerId, now: Instant) -> bool {
+        self.banned.get(peer).is_some_and(|until| *until > now)
+    }
+
+    /// Charges `peer` for `offense`; returns true if this bans the peer.
+    pub fn penalize(&mut self, peer: PeerId, offense: Offense, now: Instant) -> bool {
+        self.prune(now);
+        if self.is_banned(&peer, now) {
+            return false;
+        }
+
+        let entry = self
+            .scores
+            .entry(peer)
+            .or_insert_with(|| PeerScore::new(now));
+        entry.score = entry.score_at(now) - offense.penalty();
+        entry.updated = now;
+
+        if entry.score <= PEER_BAN_THRESHOLD {
+            self.scores.remove(&peer);
+            self.banned
+                .insert(peer, now + Duration::from_secs(PEER_BAN_SECS));
+            return true;
+        }
+        false
+    }
+
+    /// Counts a sync request; false once the peer exceeds the rate for this window.
+    pub fn within_sync_rate(&mut self, peer: PeerId, now: Instant) -> bool {
+        self.prune(now);
+        let entry = self
+            .scores
+            .entry(peer)
+            .or_insert_with(|| PeerScore::new(now));
+        if now.saturating_duration_since(entry.window_start)
+            >= Duration::from_secs(SYNC_RATE_WINDOW_SECS)
+        {
+            entry.window_start = now;
+            entry.window_requests = 0;
+        }
+        entry.window_requests += 1;
+        entry.window_requests <= SYNC_REQUESTS_PER_WINDOW
+    }
+
+    /// Scores of all tracked peers, lowest first.
+    pub fn snapshot(&self, now: Instant) -> Vec<PeerScoreInfo> {
+        let mut infos: Vec<PeerScoreInfo> = self
+            .scores
+            .iter()
+            .map(|(peer, s)| PeerScoreInfo {
+                peer_id: peer.to_string(),
+                score: s.score_at(now),
+                banned_for_secs: None,
+            })
+            .collect();
+        infos.extend(
+            self.banned
+                .iter()
+                .filter(|(_, until)| **until > now)
+                .map(|(peer, until)| PeerScoreInfo {
+                    peer_id: peer.to_string(),
+                    score: PEER_BAN_THRESHOLD,
+                    banned_for_secs: Some(until.saturating_duration_since(now).as_secs()),
+                }),
+        );
+        infos.sort_by(|a, b| a.score.total_cmp(&b.score));
+        infos
+    }
+
+    /// Drops expired bans and peers that have fully recovered.
+    fn prune(&mut self, now: Instant) {
+        self.banned.retain(|_, until| *until > now);
+        let window = Duration::from_secs(SYNC_RATE_WINDOW_SECS);
+        self.scores.retain(|_, s| {
+            s.score_at(now) < PEER_SCORE_MAX
+                || now.saturating_duration_since(s.window_start) < window
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn penalties_recover_over_time() {
+        let mut scores = PeerScores::new();
+        let peer = PeerId::random();
+        let start = Instant::now();
+
+        assert!(!scores.penalize(peer, Offense::InvalidBlock, start));
+        let penalized = PEER_SCORE_MAX - PEER_PENALTY_INVALID_BLOCK;
+        assert_eq!(scores.score(&peer, start), penalized);
+
+        let later = start + Duration::from_secs(100);
+        assert_eq!(
+            scores.score(&peer, later),
+            penalized + 100.0 * PEER_SCORE_RECOVERY_PER_SEC
+        );
+
+        // Recovery never exceeds the starting score
+        assert_eq!(
+            scores.score(&peer, start + Duration::from_secs(100_000)),
+            PEER_SCORE_MAX
+        );
+    }
+
+    #[test]
+    fn crossing_threshold_bans_until_cooldown_ends() {
+        let mut scores = PeerScores::new();
+        let peer = PeerId::random();
+        let now = Instant::now();
+
+        assert!(!scores.penalize(peer, Offense::InvalidBlock, now));
+        assert!(!scores.penalize(peer, Offense::InvalidBlock, now));
+        assert!(!scores.is_banned(&peer, now));
+        assert!(scores.penalize(peer, Offense::InvalidBlock, now));
+        assert!(scores.is_banned(&peer, now));
+
+        // Further offenses while banned don't re-trigger a ban
+        assert!(!scores.penalize(peer, Offense::InvalidTransaction, now));
+
+        let snapshot = scores.snapshot(now);
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].banned_for_secs, Some(PEER_BAN_SECS));
+
+        let after = now + Duration::from_secs(PEER_BAN_SECS);
+        assert!(!scores.is_banned(&peer, after));
+        assert_eq!(scores.score(&peer, after), PEER_SCORE_MAX);
+    }
+
+    #[test]
+    fn sync_rate_limit_resets_each_window() {
+        let mut scores = PeerScores::new();
+        let peer = PeerId::random();
+        let now = Instant::now();
+
+        for _ in 0..SYNC_REQUESTS_PER_WINDOW {
+            assert!(scores.within_sync_rate(peer, now));
+        }
+        assert!(!scores.within_sync_rate(peer, now));
+
+        let next_window = now + Duration::from_secs(SYNC_RATE_WINDOW_SECS);
+        assert!(scores.within_sync_rate(peer, next_window));
+    }
+}
@@ -0,0 +1,204 @@
+//! # Relay Failover
+//!
+//! Dials the configured relays one at a time, in order, and remembers which one
+//! is active. When the active relay drops the next address is tried; when every
+//! relay is unreachable, rounds are retried with exponential backoff.
+
+use crate::utils::constants::{
+    RELAY_DIAL_TIMEOUT_SECS, RELAY_MAX_BACKOFF_SECS, RELAY_RETRY_DELAY_SECS,
+};
+use std::time::{Duration, Instant};
+
+/// Failover state over the configured relay addresses
+#[derive(Debug)]
+pub struct RelayFailover {
+    addrs: Vec<String>,
+    /// Index of the next address to try
+    next: usize,
+    active: Option<usize>,
+    /// Address being dialed and when the attempt is given up
+    dialing: Option<(usize, Instant)>,
+    /// Failed attempts since the last success or backoff
+    failures_in_round: usize,
+    /// Consecutive rounds in which no relay was reachable
+    failed_rounds: u32,
+    retry_at: Option<Instant>,
+}
+
+impl RelayFailover {
+    pub fn new(addrs: Vec<String>) -> Self {
+        Self {
+            addrs,
+            next: 0,
+            active: None,
+            dialing: None,
+            failures_in_round: 0,
+            failed_rounds: 0,
+            retry_at: None,
+        }
+    }
+
+    /// Address of the connected relay, if any
+    pub fn active(&self) -> Option<&str> {
+        self.active.map(|i| self.addrs[i].as_str())
+    }
+
+    /// Index of the configured relay `addr` was dialed at (it may carry a `/p2p/..`
+    /// suffix); connections relayed through it don't count.
+    fn index_of(&self, addr: &str) -> Option<usize> {
+        if addr.contains("/p2p-circuit") {
+            return None;
+        }
+        self.addrs.iter().position(|relay| {
+            addr == relay
+                || addr
+                    .strip_prefix(relay.as_str())
+                    .is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+
+    pub fn is_relay_addr(&self, addr: &str) -> bool {
+        self.index_of(addr).is_some()
+    }
+
+    /// Next address to dial, once no relay is active, no dial is in flight and
+    /// any backoff has elapsed. A dial that outlived its timeout counts as failed.
+    pub fn next_dial(&mut self, now: Instant) -> Option<String> {
+        if self.addrs.is_empty() || self.active.is_some() {
+            return None;
+        }
+        if let Some((index, deadline)) = self.dialing {
+            if now < deadline {
+                return None;
+            }
+            self.fail(index, now);
+        }
+        if self.retry_at.is_some_and(|at| now < at) {
+            return None;
+        }
+        self.retry_at = None;
+
+        let index = self.next;
+        self.dialing = Some((index, now + Duration::from_secs(RELAY_DIAL_TIMEOUT_SECS)));
+        Some(self.addrs[index].clone())
+    }
+
+    /// A connection to `remote_addr` was established; returns the relay it made active.
+    pub fn on_connected(&mut self, remote_addr: &str) -> Option<&str> {
+        let index = self.index_of(remote_addr)?;
+        self.active = Some(index);
+        self.dialing = None;
+        self.failures_in_round = 0;
+        self.failed_rounds = 0;
+        self.retry_at = None;
+        Some(self.addrs[index].as_str())
+    }
+
+    /// Dialing `addr` failed.
+    pub fn on_dial_failed(&mut self, addr: &str, now: Instant) {
+        if let Some((index, _)) = self.dialing {
+            if self.index_of(addr) == Some(index) {
+                self.fail(index, now);
+            }
+        }
+    }
+
+    /// The connection to `remote_addr` closed; returns true if it was the active
+    /// relay, in which case the next address is tried.
+    pub fn on_disconnected(&mut self, remote_addr: &str) -> bool {
+        match self.active {
+            Some(index) if self.index_of(remote_addr) == Some(index) => {
+                self.active = None;
+                self.next = (index + 1) % self.addrs.len();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn fail(&mut self, index: usize, now: Instant) {
+        self.dialing = None;
+        self.next = (index + 1) % self.addrs.len();
+        self.failures_in_round += 1;
+        if self.failures_in_round >= self.addrs.len() {
+            self.failures_in_round = 0;
+            self.failed_rounds += 1;
+            let backoff = Self::backoff(self.failed_rounds);
+            log::warn!(
+                "All {} relays unreachable, retrying in {}s",
+                self.addrs.len(),
+                backoff.as_secs()
+            );
+            self.retry_at = Some(now + backoff);
+        }
+    }
+
+    /// Delay before the next round after `failed_rounds` rounds in a row failed
+    fn backoff(failed_rounds: u32) -> Duration {
+        let secs = RELAY_RETRY_DELAY_SECS
+            .saturating_mul(1u64 << failed_rounds.saturating_sub(1).min(16))
+            .min(RELAY_MAX_BACKOFF_SECS);
+        Duration::from_secs(secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failover() -> RelayFailover {
+        RelayFailover::new(vec![
+            "/ip4/10.0.0.1/tcp/9090".into(),
+            "/ip4/10.0.0.2/tcp/9090".into(),
+        ])
+    }
+
+    #[test]
+    fn fails_over_to_next_relay_in_order() {
+        let mut relays = failover();
+        let now = Instant::now();
+
+        let first = relays.next_dial(now).unwrap();
+        assert_eq!(first, "/ip4/10.0.0.1/tcp/9090");
+        assert_eq!(relays.next_dial(now), None, "one dial at a time");
+
+        relays.on_dial_failed(&first, now);
+        let second = relays.next_dial(now).unwrap();
+        assert_eq!(second, "/ip4/10.0.0.2/tcp/9090");
+
+        let connected = format!("{second}/p2p/12D3KooWRelay");
+        assert_eq!(relays.on_connected(&connected), Some(second.as_str()));
+        assert_eq!(relays.active(), Some(second.as_str()));
+        assert_eq!(relays.next_dial(now), None);
+
+        // Active relay drops: the next address (wrapping around) is tried
+        assert!(relays.on_disconnected(&connected));
+        assert_eq!(relays.active(), None);
+        assert_eq!(relays.next_dial(now).as_deref(), Some(first.as_str()));
+
+        // Peers reached through a relay circuit are not the relay itself
+        assert!(!relays.is_relay_addr(&format!("{first}/p2p/12D3KooWRelay/p2p-circuit")));
+    }
+
+    #[test]
+    fn backs_off_when_every_relay_is_unreachable() {
+        let mut relays = failover();
+        let now = Instant::now();
+
+        let first = relays.next_dial(now).unwrap();
+        relays.on_dial_failed(&first, now);
+        // The second dial times out instead of failing outright
+        relays.next_dial(now).unwrap();
+        let timed_out = now + Duration::from_secs(RELAY_DIAL_TIMEOUT_SECS);
+        assert_eq!(relays.next_dial(timed_out), None, "backing off");
+
+        let retry = timed_out + Duration::from_secs(RELAY_RETRY_DELAY_SECS);
+        assert_eq!(relays.next_dial(retry).as_deref(), Some(first.as_str()));
+
+        assert!(RelayFailover::backoff(2) > RelayFailover::backoff(1));
+        assert_eq!(
+            RelayFailover::backoff(30),
+            Duration::from_secs(RELAY_MAX_BACKOFF_SECS)
+        );
+    }
+}
@@ -0,0 +1,734 @@
+//! # Sync Protocol
+//!
+//! Transport-independent parts of the sync protocol: answering requests from
+//! local storage and header-first verification of a peer's chain.
+//!
+//! A catching-up node first fetches headers, checks their hashes and
+//! `previous_hash` links against its own tip, and only then downloads the
+//! bodies of the range it has committed to. Light nodes stop after the
+//! headers and fetch a body only when one is asked for.
+//!
+//! Large block batches are gzipped for peers that advertise
+//! `CAPABILITY_GZIP_SYNC`; everyone else gets them as they are.
+
+use crate::chain::{
+    check_checkpoint, checkpoints, is_finalized, Block, Checkpoint, Header, SyncRequest,
+    SyncResponse,
+};
+use crate::consensus::mempool::Mempool;
+use crate::storage::Storage;
+use crate::utils::constants::{
+    MAX_SYNC_DECOMPRESSED_BYTES, MAX_SYNC_RANGE_SPAN, SYNC_COMPRESSION_THRESHOLD,
+    SYNC_HEADERS_BATCH,
+};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+/// Answers a sync request from local storage.
+pub fn serve_sync_request(
+    storage: &Storage,
+    mempool: &Mempool,
+    request: SyncRequest,
+) -> SyncResponse {
+    match request {
+        SyncRequest::GetHeight => SyncResponse::Height(storage.get_latest_index().unwrap_or(0)),
+        SyncRequest::GetBlock(index) => {
+            SyncResponse::Block(storage.get_block(index).unwrap_or(None))
+        }
+        SyncRequest::GetBlocksRange(start, end) => {
+            SyncResponse::BlocksBatch(read_range(storage, start, capped_range_end(start, end)))
+        }
+        SyncRequest::GetHeaders(start, end) => {
+            let end = end.min(start.saturating_add(SYNC_HEADERS_BATCH - 1));
+            let headers = read_range(storage, start, end)
+                .iter()
+                .map(Header::from_block)
+                .collect();
+            SyncResponse::HeadersBatch(headers)
+        }
+        SyncRequest::GetMempool => SyncResponse::Mempool(mempool.get_pending_transactions()),
+        SyncRequest::GetBlockByHash(hash) => {
+            SyncResponse::Block(storage.get_block_by_hash(&hash).unwrap_or(None))
+        }
+        SyncRequest::GetBalance(address) => {
+            SyncResponse::Balance(storage.calculate_balance(&address).unwrap_or(0))
+        }
+        SyncRequest::GetTransaction(id) => SyncResponse::Block(
+            storage
+                .get_transaction_by_id(&id)
+                .unwrap_or(None)
+                .map(|(_, block)| block),
+        ),
+    }
+}
+
+/// Gzips a `BlocksBatch` above `SYNC_COMPRESSION_THRESHOLD`; anything else
+/// is returned unchanged.
+pub fn compress_response(response: SyncResponse) -> SyncResponse {
+    let SyncResponse::BlocksBatch(blocks) = &response else {
+        return response;
+    };
+    let body = match bincode::serialize(blocks) {
+        Ok(body) if body.len() > SYNC_COMPRESSION_THRESHOLD => body,
+        _ => return response,
+    };
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    match encoder.write_all(&body).and_then(|_| encoder.finish()) {
+        Ok(gzipped) => SyncResponse::CompressedBlocksBatch(gzipped),
+        Err(e) => {
+            log::warn!("Sync: Sending block batch uncompressed: {}", e);
+            response
+        }
+    }
+}
+
+/// Turns a `CompressedBlocksBatch` back into the `BlocksBatch` it carries;
+/// other responses pass through.
+pub fn decompress_response(response: SyncResponse) -> Result<SyncResponse, String> {
+    let SyncResponse::CompressedBlocksBatch(gzipped) = response else {
+        return Ok(response);
+    };
+    let mut body = Vec::new();
+    GzDecoder::new(gzipped.as_slice())
+        .take(MAX_SYNC_DECOMPRESSED_BYTES + 1)
+        .read_to_end(&mut body)
+        .map_err(|e| format!("Invalid gzip block batch: {}", e))?;
+    if body.len() as u64 > MAX_SYNC_DECOMPRESSED_BYTES {
+        return Err("Compressed block batch is too large".into());
+    }
+    bincode::deserialize(&body)
+        .map(SyncResponse::BlocksBatch)
+        .map_err(|e| format!("Invalid compressed block batch: {}", e))
+}
+
+/// `GetBlocksRange` for `start..=end`, shortened to what peers serve at once;
+/// the rest is asked for once this part is in.
+pub fn blocks_range_request(start: u64, end: u64) -> SyncRequest {
+    SyncRequest::GetBlocksRange(start, capped_range_end(start, end))
+}
+
+/// Whether `request` asks for more blocks than are served at once.
+pub fn is_oversized(request: &SyncRequest) -> bool {
+    matches!(
+        request,
+        SyncRequest::GetBlocksRange(start, end) if capped_range_end(*start, *end) < *end
+    )
+}
+
+fn capped_range_end(start: u64, end: u64) -> u64 {
+    end.min(start.saturating_add(MAX_SYNC_RANGE_SPAN - 1))
+}
+
+/// Stored blocks `start..=end`, stopping at the first gap.
+fn read_range(storage: &Storage, start: u64, end: u64) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    for i in start..=end {
+        match storage.get_block(i) {
+            Ok(Some(b)) => blocks.push(b),
+            _ => break,
+        }
+    }
+    blocks
+}
+
+/// Checks that every header hashes to its `hash` and that the batch forms a
+/// contiguous chain on top of `parent` (`None` when the batch starts at genesis).
+pub fn validate_header_chain(parent: Option<&Header>, headers: &[Header]) -> Result<(), String> {
+    let mut prev = parent;
+    for header in headers {
+        if header.hash != header.calculate_hash() {
+            return Err(format!("Header #{} hash mismatch", header.index));
+        }
+        match prev {
+            None if header.index != 0 => {
+                return Err(format!("Header #{} has no parent", header.index));
+            }
+            Some(p) if header.index != p.index + 1 => {
+                return Err(format!(
+                    "Non-contiguous header #{} (expected {})",
+                    header.index,
+                    p.index + 1
+                ));
+            }
+            Some(p) if header.previous_hash != p.hash => {
+                return Err(format!(
+                    "Header #{} does not link to #{}",
+                    header.index, p.index
+                ));
+            }
+            _ => {}
+        }
+        prev = Some(header);
+    }
+    Ok(())
+}
+
+/// Checks every header at a checkpoint height against the pinned hash.
+pub fn verify_checkpoints(checkpoints: &[Checkpoint], headers: &[Header]) -> Result<(), String> {
+    for header in headers {
+        check_checkpoint(checkpoints, header.index, &header.hash)?;
+    }
+    Ok(())
+}
+
+/// Checks each header's VDF difficulty against its parent's and its VDF proof.
+pub fn verify_header_work(parent: Option<&Header>, headers: &[Header]) -> Result<(), String> {
+    let mut prev = parent;
+    for header in headers {
+        header
+            .validate_difficulty(prev)
+            .map_err(|e| format!("Header #{}: {}", header.index, e))?;
+        if !header.is_vdf_valid() {
+            return Err(format!("Header #{} has an invalid VDF proof", header.index));
+        }
+        prev = Some(header);
+    }
+    Ok(())
+}
+
+/// Validates `headers` on top of the stored header chain and stores them; this
+/// is how a light node syncs. A batch may replace headers that are not final
+/// yet. Returns the new header tip.
+pub fn accept_headers(storage: &Storage, headers: &[Header]) -> Result<u64, String> {
+    let (Some(first), Some(last)) = (headers.first(), headers.last()) else {
+        return Err("Empty header batch".into());
+    };
+    let parent = match first.index {
+        0 => None,
+        i => Some(
+            storage
+                .get_header(i - 1)
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("Header #{} has no stored parent", first.index))?,
+        ),
+    };
+    validate_header_chain(parent.as_ref(), headers)?;
+    verify_checkpoints(&checkpoints(), headers)?;
+    verify_header_work(parent.as_ref(), headers)?;
+
+    let tip = storage.get_header_tip().map_err(|e| e.to_string())?;
+    if let Some(tip) = tip {
+        for header in headers.iter().take_while(|h| h.index <= tip) {
+            let stored = storage
+                .get_header(header.index)
+                .map_err(|e| e.to_string())?;
+            if stored.is_some_and(|s| s.hash != header.hash) {
+                if is_finalized(header.index, tip) {
+                    return Err(format!(
+                        "Header #{} would replace a finalized header",
+                        header.index
+                    ));
+                }
+                break;
+            }
+        }
+    }
+
+    storage.save_headers(headers).map_err(|e| e.to_string())?;
+    Ok(storage
+        .get_header_tip()
+        .map_err(|e| e.to_string())?
+        .unwrap_or(last.index))
+}
+
+/// Checks a body fetched on demand against the stored header at its height.
+pub fn verify_body(storage: &Storage, block: &Block) -> Result<(), String> {
+    let header = storage
+        .get_header(block.index)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("No header stored for block #{}", block.index))?;
+    if header.hash != block.hash {
+        return Err(format!("Block #{} does not match its header", block.index));
+    }
+    block.validate_integrity()?;
+    // The merkle root commits to ids, which user transactions derive from their contents
+    match block
+        .transactions
+        .iter()
+        .find(|tx| !tx.is_system() && !tx.is_receipt_mint() && tx.id != tx.compute_id())
+    {
+        Some(tx) => Err(format!("Transaction {} does not match its id", tx.id)),
+        None => Ok(()),
+    }
+}
+
+/// Blocks missing between the local chain and a received batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncGap {
+    /// Leading blocks of the batch that apply before the gap
+    pub contiguous: usize,
+    pub from: u64,
+    pub to: u64,
+}
+
+impl SyncGap {
+    /// Request that backfills the gap, or its first [`MAX_SYNC_RANGE_SPAN`] blocks.
+    pub fn request(&self) -> SyncRequest {
+        blocks_range_request(self.from, self.to)
+    }
+}
+
+/// First hole in `blocks` when they should continue the chain at `next_index`.
+/// Blocks below `next_index` overlap the local chain and are left to fork choice.
+pub fn find_gap(next_index: u64, blocks: &[Block]) -> Option<SyncGap> {
+    let mut expected = next_index;
+    for (contiguous, block) in blocks.iter().enumerate() {
+        if block.index > expected {
+            return Some(SyncGap {
+                contiguous,
+                from: expected,
+                to: block.index - 1,
+            });
+        }
+        expected = expected.max(block.index + 1);
+    }
+    None
+}
+
+/// Moves the persisted sync checkpoint up over the blocks stored contiguously
+/// above it and returns it; `None` while there is no genesis block.
+pub fn advance_sync_checkpoint(storage: &Storage) -> Result<Option<u64>, String> {
+    let saved = storage.get_sync_checkpoint().map_err(|e| e.to_string())?;
+    let mut next = saved.map_or(0, |height| height + 1);
+    while storage
+        .get_block(next)
+        .map_err(|e| e.to_string())?
+        .is_some()
+    {
+        next += 1;
+    }
+    let checkpoint = next.checked_sub(1);
+    if let Some(height) = checkpoint.filter(|_| checkpoint != saved) {
+        storage
+            .save_sync_checkpoint(height)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(checkpoint)
+}
+
+/// Payload of the `sync-progress` event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct SyncProgress {
+    /// Local chain height
+    pub current: u64,
+    /// Best height known from peers
+    pub target: u64,
+    /// 0-100, rounded down
+    pub percent: u8,
+}
+
+impl SyncProgress {
+    pub fn new(current: u64, target: u64) -> Self {
+        let percent = if current >= target {
+            100
+        } else {
+            (current as u128 * 100 / target as u128) as u8
+        };
+        SyncProgress {
+            current,
+            target,
+            percent,
+        }
+    }
+
+    /// Progress once the local chain has caught up with `height`.
+    pub fn synced(height: u64) -> Self {
+        SyncProgress::new(height, height)
+    }
+}
+
+/// Header hashes validated during header-first sync whose bodies are still pending.
+#[derive(Debug, Default)]
+pub struct HeaderSync {
+    committed: HashMap<u64, String>,
+}
+
+impl HeaderSync {
+    /// Records a validated header batch; bodies must match these hashes.
+    pub fn commit(&mut self, headers: &[Header]) {
+        for header in headers {
+            self.committed.insert(header.index, header.hash.clone());
+        }
+    }
+
+    /// Last index of the committed run starting at `start`, if any.
+    pub fn committed_through(&self, start: u64) -> Option<u64> {
+        let mut end = None;
+        let mut index = start;
+        while self.committed.contains_key(&index) {
+            end = Some(index);
+            index += 1;
+        }
+        end
+    }
+
+    /// True if `block` is the body committed to at its height, or none was committed.
+    pub fn matches(&self, block: &Block) -> bool {
+        self.committed
+            .get(&block.index)
+            .is_none_or(|hash| *hash == block.hash)
+    }
+
+    /// Drops the commitment for a height once its body has been processed.
+    pub fn release(&mut self, index: u64) {
+        self.committed.remove(&index);
+    }
+
+    pub fn clear(&mut self) {
+        self.committed.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::{
+        calculate_mining_reward, Transaction, SYSTEM_SIG_GENESIS, SYSTEM_SIG_REWARD,
+    };
+    use crate::consensus::vdf::CentichainVDF;
+    use std::sync::Arc;
+
+    const ZERO_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+    fn chain_of(len: u64) -> (Arc<Storage>, Mempool) {
+        let storage = Arc::new(Storage::temp());
+        let mut previous_hash = ZERO_HASH.to_string();
+        for index in 0..len {
+            let coinbase = Transaction {
+                id: format!("coinbase-{index}"),
+                sender: "SYSTEM".into(),
+                receiver: "miner".into(),
+                amount: calculate_mining_reward(index),
+                shard_id: 0,
+                timestamp: 0,
+                nonce: 0,
+                fee: 0,
+                signature: if index == 0 {
+                    SYSTEM_SIG_GENESIS.into()
+                } else {
+                    SYSTEM_SIG_REWARD.into()
+                },
+                sender_pubkey: String::new(),
+                outputs: Vec::new(),
+                memo: None,
+                not_before_height: None,
+                alias: None,
+            };
+            let mut block = Block::new(
+                index,
+                "miner".into(),
+                vec![coinbase],
+                previous_hash,
+                100,
+                100,
+                0,
+                0,
+                calculate_mining_reward(index),
+            );
+            block.state_root = storage.compute_state_root_after(&block).unwrap();
+            let challenge = block.calculate_hash();
+            block.vdf_proof = CentichainVDF::new(100).solve(challenge.as_bytes());
+            block.hash = block.calculate_hash();
+            storage.save_block(&block).unwrap();
+            previous_hash = block.hash;
+        }
+        let mempool = Mempool::new(storage.clone());
+        (storage, mempool)
+    }
+
+    fn headers(response: SyncResponse) -> Vec<Header> {
+        match response {
+            SyncResponse::HeadersBatch(headers) => headers,
+            other => panic!("expected HeadersBatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn header_batch_over_ten_blocks_validates() {
+        let (storage, mempool) = chain_of(10);
+
+        let batch = headers(serve_sync_request(
+            &storage,
+            &mempool,
+            SyncRequest::GetHeaders(0, 20),
+        ));
+        assert_eq!(batch.len(), 10);
+        assert_eq!(validate_header_chain(None, &batch), Ok(()));
+
+        // A later range validates against the local block it extends.
+        let parent = Header::from_block(&storage.get_block(4).unwrap().unwrap());
+        let tail = headers(serve_sync_request(
+            &storage,
+            &mempool,
+            SyncRequest::GetHeaders(5, 9),
+        ));
+        assert_eq!(validate_header_chain(Some(&parent), &tail), Ok(()));
+
+        let mut sync = HeaderSync::default();
+        sync.commit(&tail);
+        assert_eq!(sync.committed_through(5), Some(9));
+        assert!(sync.matches(&storage.get_block(7).unwrap().unwrap()));
+    }
+
+    #[test]
+    fn oversized_block_ranges_are_clamped() {
+        let (storage, mempool) = chain_of(10);
+
+        let huge = SyncRequest::GetBlocksRange(3, u64::MAX);
+        assert!(is_oversized(&huge));
+        match serve_sync_request(&storage, &mempool, huge) {
+            SyncResponse::BlocksBatch(blocks) => assert_eq!(blocks.len(), 7),
+            other => panic!("expected BlocksBatch, got {:?}", other),
+        }
+
+        let capped = blocks_range_request(3, 10_000);
+        assert!(
+            matches!(capped, SyncRequest::GetBlocksRange(3, end) if end == 2 + MAX_SYNC_RANGE_SPAN)
+        );
+        assert!(!is_oversized(&capped));
+        assert!(!is_oversized(&blocks_range_request(0, 9)));
+    }
+
+    #[test]
+    fn large_block_batches_round_trip_through_gzip() {
+        let (storage, mempool) = chain_of(300);
+        let blocks =
+            match serve_sync_request(&storage, &mempool, SyncRequest::GetBlocksRange(0, 299)) {
+                SyncResponse::BlocksBatch(blocks) => blocks,
+                other => panic!("expected BlocksBatch, got {:?}", other),
+            };
+        let serialized = bincode::serialize(&blocks).unwrap();
+        assert!(serialized.len() > SYNC_COMPRESSION_THRESHOLD);
+
+        let compressed = compress_response(SyncResponse::BlocksBatch(blocks.clone()));
+        match &compressed {
+            SyncResponse::CompressedBlocksBatch(gzipped) => {
+                assert!(gzipped.len() < serialized.len())
+            }
+            other => panic!("expected CompressedBlocksBatch, got {:?}", other),
+        }
+        match decompress_response(compressed).unwrap() {
+            SyncResponse::BlocksBatch(decoded) => {
+                assert_eq!(bincode::serialize(&decoded).unwrap(), serialized)
+            }
+            other => panic!("expected BlocksBatch, got {:?}", other),
+        }
+
+        // Small batches go out as they are; garbage is refused
+        match compress_response(SyncResponse::BlocksBatch(blocks[..2].to_vec())) {
+            SyncResponse::BlocksBatch(small) => assert_eq!(small.len(), 2),
+            other => panic!("expected BlocksBatch, got {:?}", other),
+        }
+        assert!(decompress_response(SyncResponse::CompressedBlocksBatch(vec![1, 2, 3])).is_err());
+    }
+
+    #[test]
+    fn tampered_or_unlinked_headers_are_rejected() {
+        let (storage, mempool) = chain_of(10);
+        let batch = headers(serve_sync_request(
+            &storage,
+            &mempool,
+            SyncRequest::GetHeaders(0, 9),
+        ));
+
+        let mut tampered = batch.clone();
+        tampered[6].timestamp += 1;
+        assert_eq!(
+            validate_header_chain(None, &tampered),
+            Err("Header #6 hash mismatch".into())
+        );
+
+        let mut gapped = batch.clone();
+        gapped.remove(3);
+        assert!(validate_header_chain(None, &gapped)
+            .unwrap_err()
+            .contains("Non-contiguous"));
+
+        let wrong_parent = batch[2].clone();
+        assert!(validate_header_chain(Some(&wrong_parent), &batch[5..])
+            .unwrap_err()
+            .contains("Non-contiguous"));
+        assert_eq!(
+            validate_header_chain(None, &batch[1..]),
+            Err("Header #1 has no parent".into())
+        );
+    }
+
+    #[test]
+    fn headers_off_a_checkpoint_are_rejected() {
+        let (storage, mempool) = chain_of(10);
+        let batch = headers(serve_sync_request(
+            &storage,
+            &mempool,
+            SyncRequest::GetHeaders(0, 9),
+        ));
+        let pinned = |hash: String| vec![Checkpoint { height: 4, hash }];
+
+        assert_eq!(
+            verify_checkpoints(&pinned(batch[4].hash.clone()), &batch),
+            Ok(())
+        );
+        // A fabricated history forks away from the pinned block
+        let forged = pinned("ab".repeat(32));
+        assert!(verify_checkpoints(&forged, &batch).is_err());
+        assert_eq!(verify_checkpoints(&forged, &batch[5..]), Ok(()));
+    }
+
+    #[test]
+    fn batch_with_a_hole_requests_the_gap() {
+        let (storage, _) = chain_of(10);
+        let batch = |range: &[u64]| -> Vec<Block> {
+            range
+                .iter()
+                .map(|&i| storage.get_block(i).unwrap().unwrap())
+                .collect()
+        };
+
+        let holed = batch(&[5, 6, 8, 9]);
+        let gap = find_gap(5, &holed).unwrap();
+        assert_eq!((gap.contiguous, gap.from, gap.to), (2, 7, 7));
+        assert!(matches!(gap.request(), SyncRequest::GetBlocksRange(7, 7)));
+
+        // A batch starting past the tip keeps nothing and backfills up to it
+        let ahead = find_gap(3, &batch(&[6, 7])).unwrap();
+        assert_eq!((ahead.contiguous, ahead.from, ahead.to), (0, 3, 5));
+
+        // A wide hole is backfilled a served range at a time
+        let wide = SyncGap {
+            contiguous: 0,
+            from: 3,
+            to: 3 + 10 * MAX_SYNC_RANGE_SPAN,
+        };
+        assert!(!is_oversized(&wide.request()));
+        assert!(matches!(wide.request(), SyncRequest::GetBlocksRange(3, _)));
+
+        assert_eq!(find_gap(5, &batch(&[5, 6, 7])), None);
+        assert_eq!(find_gap(5, &batch(&[3, 4, 5, 6])), None);
+    }
+
+    #[test]
+    fn progress_is_local_height_over_remote_height() {
+        let percents: Vec<u8> = [0, 250, 500, 999, 1000, 1200]
+            .iter()
+            .map(|&local| SyncProgress::new(local, 1000).percent)
+            .collect();
+        assert_eq!(percents, vec![0, 25, 50, 99, 100, 100]);
+
+        let synced = SyncProgress::synced(42);
+        assert_eq!(
+            (synced.current, synced.target, synced.percent),
+            (42, 42, 100)
+        );
+        assert_eq!(SyncProgress::new(0, 0).percent, 100);
+        assert_eq!(SyncProgress::new(u64::MAX - 1, u64::MAX).percent, 99);
+    }
+
+    #[test]
+    fn sync_checkpoint_survives_a_restart() {
+        let (source, _) = chain_of(8);
+        let block = |index| source.get_block(index).unwrap().unwrap();
+        let storage = Storage::temp();
+        assert_eq!(advance_sync_checkpoint(&storage), Ok(None));
+        for index in 0..4 {
+            storage.save_block(&block(index)).unwrap();
+        }
+        assert_eq!(advance_sync_checkpoint(&storage), Ok(Some(3)));
+        // The reserved key is not an account
+        assert_eq!(storage.compute_state_root().unwrap(), block(3).state_root);
+
+        // Block 4 is missing, so the checkpoint stays below it
+        storage.save_block(&block(5)).unwrap();
+        assert_eq!(advance_sync_checkpoint(&storage), Ok(Some(3)));
+
+        let storage = storage.reopen();
+        assert_eq!(storage.get_sync_checkpoint().unwrap(), Some(3));
+        storage.save_block(&block(4)).unwrap();
+        assert_eq!(advance_sync_checkpoint(&storage), Ok(Some(5)));
+    }
+
+    #[test]
+    fn light_node_stores_only_verified_headers() {
+        let (full, mempool) = chain_of(10);
+        let batch = headers(serve_sync_request(
+            &full,
+            &mempool,
+            SyncRequest::GetHeaders(0, 9),
+        ));
+        let light = Storage::temp();
+
+        assert_eq!(accept_headers(&light, &batch[..5]), Ok(4));
+        assert_eq!(accept_headers(&light, &batch[5..]), Ok(9));
+        assert_eq!(light.get_header_tip().unwrap(), Some(9));
+        assert_eq!(light.get_header(7).unwrap().unwrap().hash, batch[7].hash);
+        assert_eq!(light.get_total_blocks().unwrap(), 0);
+
+        // A body fetched on demand must match its header
+        let block = full.get_block(7).unwrap().unwrap();
+        assert_eq!(verify_body(&light, &block), Ok(()));
+        let mut forged = block.clone();
+        forged.transactions[0].id = "coinbase-forged".into();
+        assert!(verify_body(&light, &forged).is_err());
+        assert!(verify_body(&light, &full.get_block(9).unwrap().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn headers_without_valid_work_are_rejected() {
+        let (full, mempool) = chain_of(4);
+        let batch = headers(serve_sync_request(
+            &full,
+            &mempool,
+            SyncRequest::GetHeaders(0, 3),
+        ));
+        let light = Storage::temp();
+
+        let mut unproven = batch.clone();
+        unproven[2].vdf_proof = "00".repeat(32);
+        unproven[2].hash = unproven[2].calculate_hash();
+        unproven[3].previous_hash = unproven[2].hash.clone();
+        unproven[3].hash = unproven[3].calculate_hash();
+        assert_eq!(
+            accept_headers(&light, &unproven),
+            Err("Header #2 has an invalid VDF proof".into())
+        );
+
+        let mut jumped = batch.clone();
+        jumped[1].vdf_difficulty *= 10;
+        assert!(verify_header_work(None, &jumped)
+            .unwrap_err()
+            .contains("VDF difficulty"));
+
+        // Nothing above the stored tip without its parent
+        assert!(accept_headers(&light, &batch[2..]).is_err());
+        assert_eq!(light.get_header_tip().unwrap(), None);
+    }
+
+    #[test]
+    fn block_is_served_by_hash() {
+        let (storage, mempool) = chain_of(10);
+        let stored = storage.get_block(6).unwrap().unwrap();
+
+        match serve_sync_request(
+            &storage,
+            &mempool,
+            SyncRequest::GetBlockByHash(stored.hash.clone()),
+        ) {
+            SyncResponse::Block(Some(block)) => {
+                assert_eq!(block.index, 6);
+                assert_eq!(block.hash, stored.hash);
+            }
+            other => panic!("expected Block, got {:?}", other),
+        }
+
+        assert!(matches!(
+            serve_sync_request(
+                &storage,
+                &mempool,
+                SyncRequest::GetBlockByHash("ff".repeat(32)),
+            ),
+            SyncResponse::Block(None)
+        ));
+    }
+}
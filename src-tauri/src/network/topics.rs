@@ -0,0 +1,128 @@
+//! # Shard Topics
+//!
+//! Blocks, transactions and cancellations are gossiped on per-shard topics.
+//! A node's shard is reassigned every epoch, and whenever the validator count
+//! changes the number of shards, so it has to move its subscriptions along.
+
+use crate::consensus::Consensus;
+
+/// Names of a shard's block, transaction and cancellation topics
+pub fn shard_topic_names(shard_id: u16) -> [String; 3] {
+    [
+        format!("centichain-shard-{}-blocks", shard_id),
+        format!("centichain-shard-{}-txs", shard_id),
+        format!("centichain-shard-{}-tx-cancels", shard_id),
+    ]
+}
+
+/// Subscriptions to swap when the local node moves to another shard
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShardChange {
+    pub from: u16,
+    pub to: u16,
+    /// Topics of the old shard, to unsubscribe from
+    pub leave: [String; 3],
+    /// Topics of the new shard, to subscribe to
+    pub join: [String; 3],
+}
+
+/// The move needed when `peer_id`, subscribed to shard `current`, is
+/// assigned another shard in `epoch`; `None` while it stays put.
+pub fn shard_change(
+    consensus: &Consensus,
+    peer_id: &str,
+    epoch: u64,
+    current: u16,
+) -> Option<ShardChange> {
+    let assigned = consensus.get_assigned_shard(peer_id, epoch);
+    (assigned != current).then(|| ShardChange {
+        from: current,
+        to: assigned,
+        leave: shard_topic_names(current),
+        join: shard_topic_names(assigned),
+    })
+}
+
+/// Active shard count last seen by the P2P loop. Crossing a multiple of
+/// `VALIDATORS_PER_SHARD` validators changes it, and with it every node's
+/// shard and every sender's routing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShardCountWatch {
+    active_shards: u16,
+}
+
+impl ShardCountWatch {
+    pub fn new(consensus: &Consensus) -> Self {
+        ShardCountWatch {
+            active_shards: consensus.calculate_active_shards(),
+        }
+    }
+
+    pub fn active_shards(&self) -> u16 {
+        self.active_shards
+    }
+
+    /// Recomputes the shard count; returns the previous count when it changed.
+    pub fn observe(&mut self, consensus: &Consensus) -> Option<u16> {
+        let active_shards = consensus.calculate_active_shards();
+        if active_shards == self.active_shards {
+            return None;
+        }
+        Some(std::mem::replace(&mut self.active_shards, active_shards))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_shard_assignment_swaps_topics() {
+        let mut consensus = Consensus::new();
+        for i in 0..100 {
+            consensus.register_node(format!("node{}", i));
+        }
+        assert_eq!(consensus.calculate_active_shards(), 2);
+
+        let current = consensus.get_assigned_shard("node0", 0);
+        assert_eq!(shard_change(&consensus, "node0", 0, current), None);
+
+        // Some later epoch puts node0 on the other shard
+        let epoch = (1..100)
+            .find(|&epoch| consensus.get_assigned_shard("node0", epoch) != current)
+            .expect("assignment rotates across epochs");
+        let change = shard_change(&consensus, "node0", epoch, current).unwrap();
+        assert_eq!((change.from, change.to), (current, 1 - current));
+        assert_eq!(
+            change.leave[0],
+            format!("centichain-shard-{}-blocks", current)
+        );
+        assert_eq!(change.join, shard_topic_names(1 - current));
+    }
+
+    #[test]
+    fn crossing_the_validator_threshold_reassigns_shards() {
+        let mut consensus = Consensus::new();
+        for i in 0..99 {
+            consensus.register_node(format!("node{}", i));
+        }
+        let mut watch = ShardCountWatch::new(&consensus);
+        assert_eq!(watch.active_shards(), 1);
+        // With a single shard nobody moves
+        assert!((0..99).all(|i| shard_change(&consensus, &format!("node{}", i), 0, 0).is_none()));
+
+        // The 100th validator splits the network in two
+        consensus.register_node("node99".into());
+        assert_eq!(watch.observe(&consensus), Some(1));
+        assert_eq!(watch.active_shards(), 2);
+        assert_eq!(watch.observe(&consensus), None);
+
+        let moved = (0..100)
+            .map(|i| format!("node{}", i))
+            .find(|pid| consensus.get_assigned_shard(pid, 0) == 1)
+            .expect("some validator lands on the new shard");
+        let change = shard_change(&consensus, &moved, 0, 0).unwrap();
+        assert_eq!((change.from, change.to), (0, 1));
+        assert_eq!(change.join, shard_topic_names(1));
+    }
+}
@@ -2,9 +2,10 @@
 //!
 //! Helper functions for block production in the mining loop.
 
-use crate::chain::{self, SYSTEM_SIG_GENESIS, SYSTEM_SIG_REWARD};
+use crate::chain::{self, FeePolicy, SYSTEM_SIG_GENESIS, SYSTEM_SIG_REWARD};
 use crate::consensus::Consensus;
 use crate::storage::Storage;
+use std::collections::HashSet;
 use std::sync::{Arc, Mutex};
 
 // =============================================================================
@@ -26,6 +27,34 @@ pub fn run_auto_pruning(storage: &Arc<Storage>) {
     }
 }
 
+/// Collects blocks that became final since the last announcement
+///
+/// `announced` is the highest final index already reported; it is moved up
+/// to the current final height. Returned blocks are lowest index first.
+pub fn take_newly_finalized(storage: &Storage, announced: &mut Option<u64>) -> Vec<chain::Block> {
+    let tip = storage.get_latest_index().unwrap_or(0);
+    let Some(final_height) = chain::finalized_height(tip) else {
+        return Vec::new();
+    };
+    let from = announced.map_or(0, |height| height + 1);
+    if from > final_height {
+        return Vec::new();
+    }
+    *announced = Some(final_height);
+    (from..=final_height)
+        .filter_map(|index| storage.get_block(index).ok().flatten())
+        .collect()
+}
+
+/// Address the coinbase pays: the configured reward address if it is a valid
+/// PeerId, otherwise the producing wallet.
+pub fn reward_receiver(reward_address: Option<&str>, wallet_address: &str) -> String {
+    reward_address
+        .filter(|address| address.parse::<libp2p::PeerId>().is_ok())
+        .unwrap_or(wallet_address)
+        .to_string()
+}
+
 /// Creates a coinbase transaction for block reward
 ///
 /// The coinbase transaction is the first transaction in each block,
@@ -47,8 +76,14 @@ pub fn create_coinbase_tx(
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap()
                 .as_secs(),
+            nonce: 0,
+            fee: 0,
             signature: SYSTEM_SIG_GENESIS.to_string(),
             sender_pubkey: String::new(),
+            outputs: Vec::new(),
+            memo: None,
+            not_before_height: None,
+            alias: None,
         }
     } else {
         chain::Transaction {
@@ -61,30 +96,66 @@ pub fn create_coinbase_tx(
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap()
                 .as_secs(),
+            nonce: 0,
+            fee: 0,
             signature: SYSTEM_SIG_REWARD.to_string(),
             sender_pubkey: String::new(),
+            outputs: Vec::new(),
+            memo: None,
+            not_before_height: None,
+            alias: None,
         }
     }
 }
 
+/// Sets the coinbase payout once the block's fees are known
+///
+/// The receiver gets the block reward plus the miner's share of `total_fees`;
+/// the treasury share becomes a second output and the burned share is left out.
+pub fn pay_coinbase_fees(
+    coinbase: &mut chain::Transaction,
+    block_reward: u64,
+    total_fees: u64,
+    policy: &FeePolicy,
+) {
+    let split = policy.split(total_fees);
+    coinbase.amount = block_reward + total_fees - split.burned;
+    coinbase.outputs = policy.coinbase_outputs(&coinbase.receiver, block_reward, total_fees);
+}
+
 /// Collects transactions for this shard and generates cross-shard receipts
 ///
-/// Filters pending transactions to only include those belonging to this shard,
-/// and generates receipts for any cross-shard transfers.
+/// Filters pending transactions to only include those belonging to this shard
+/// and mature at `block_index`, and generates receipts for any cross-shard
+/// transfers. A skipped transaction holds back its sender's later nonces.
 pub fn collect_shard_transactions(
     coinbase_tx: chain::Transaction,
     pending_txs: &[chain::Transaction],
     my_shard_id: u16,
+    block_index: u64,
     consensus: &Arc<Mutex<Consensus>>,
     _receipt_sender: &Arc<Mutex<Option<tokio::sync::mpsc::Sender<crate::chain::Receipt>>>>,
 ) -> (Vec<chain::Transaction>, Vec<crate::chain::Receipt>) {
     let mut block_txs = vec![coinbase_tx];
     let mut receipts = Vec::new();
     let mut current_size = 300; // Approx coinbase size
+    let mut held_back: HashSet<&str> = HashSet::new();
 
     for tx in pending_txs.iter() {
-        // Check shard routing
-        if tx.shard_id != my_shard_id {
+        // Route by the sender's shard now rather than the one stamped at signing;
+        // the two differ once the active shard count has changed
+        let sender_shard = consensus.lock().unwrap().get_assigned_shard(&tx.sender, 0);
+        if sender_shard != my_shard_id {
+            continue;
+        }
+
+        if held_back.contains(tx.sender.as_str()) {
+            continue;
+        }
+
+        // Time-locked transactions wait in the mempool until they mature
+        if !tx.is_mature(block_index) {
+            held_back.insert(&tx.sender);
             continue;
         }
 
@@ -98,6 +169,24 @@ pub fn collect_shard_transactions(
             break;
         }
 
+        // Batch payments carry no receipts, so every output must stay in this shard
+        if !tx.outputs.is_empty() {
+            let c = consensus.lock().unwrap();
+            if tx
+                .outputs
+                .iter()
+                .any(|(receiver, _)| c.get_assigned_shard(receiver, 0) != my_shard_id)
+            {
+                log::warn!(
+                    "Skipping batch payment {}: outputs leave shard {}",
+                    tx.id,
+                    my_shard_id
+                );
+                held_back.insert(&tx.sender);
+                continue;
+            }
+        }
+
         // Generate cross-shard receipt if needed
         let target_shard = {
             let c = consensus.lock().unwrap();
@@ -113,7 +202,11 @@ pub fn collect_shard_transactions(
                 receiver: tx.receiver.clone(),
                 block_hash: "pending".to_string(),
                 merkle_proof: vec![],
+                tx_index: 0,
                 status: crate::chain::ReceiptStatus::Pending,
+                source_header: None,
+                source_tx: None,
+                crosslink: None,
             };
             receipts.push(receipt);
             log::info!(
@@ -130,6 +223,48 @@ pub fn collect_shard_transactions(
     (block_txs, receipts)
 }
 
+/// Fills in the block hash and Merkle inclusion path for receipts generated from `block`
+///
+/// Must run after the block is sealed so `block_hash` is final.
+pub fn attach_receipt_proofs(receipts: &mut [crate::chain::Receipt], block: &chain::Block) {
+    let tx_ids: Vec<String> = block.transactions.iter().map(|tx| tx.id.clone()).collect();
+    let tree = chain::MerkleTree::build(&tx_ids);
+
+    for receipt in receipts.iter_mut() {
+        if let Some(index) = tx_ids.iter().position(|id| *id == receipt.original_tx_id) {
+            receipt.prove_in(block, index, tree.proof(index));
+        }
+    }
+}
+
+/// Appends mints for queued cross-shard receipts that target this shard and
+/// returns the receipts minted, with their CrossLinks, for the block to carry
+///
+/// Receipts whose source block is not cross-linked yet, and those over the
+/// per-block transaction cap, stay queued for a later block.
+pub fn append_receipt_mints(
+    block_txs: &mut Vec<chain::Transaction>,
+    pending_receipts: &[crate::chain::Receipt],
+    my_shard_id: u16,
+    consensus: &Consensus,
+) -> Vec<crate::chain::Receipt> {
+    let mut minted = Vec::new();
+    for receipt in pending_receipts {
+        if block_txs.len() >= crate::utils::constants::MAX_TXS_PER_BLOCK as usize {
+            break;
+        }
+        if receipt.target_shard != my_shard_id {
+            continue;
+        }
+        let Some(receipt) = consensus.crosslinked_receipt(receipt) else {
+            continue;
+        };
+        block_txs.push(receipt.to_mint_tx());
+        minted.push(receipt);
+    }
+    minted
+}
+
 /// Slashes validators who missed their slots
 ///
 /// Called during block production to penalize validators who
@@ -162,3 +297,102 @@ pub fn slash_missed_slots(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coinbase_pays_configured_reward_address() {
+        let wallet = libp2p::identity::Keypair::generate_ed25519()
+            .public()
+            .to_peer_id()
+            .to_string();
+        let cold = libp2p::identity::Keypair::generate_ed25519()
+            .public()
+            .to_peer_id()
+            .to_string();
+
+        let receiver = reward_receiver(Some(&cold), &wallet);
+        assert_eq!(create_coinbase_tx(&receiver, 5, 100, 0).receiver, cold);
+
+        assert_eq!(reward_receiver(None, &wallet), wallet);
+        assert_eq!(reward_receiver(Some("garbage"), &wallet), wallet);
+    }
+
+    #[test]
+    fn finalized_blocks_are_announced_once() {
+        let storage = Storage::temp();
+        let depth = crate::utils::constants::FINALITY_DEPTH;
+        let mut announced = None;
+        for index in 0..=depth {
+            let coinbase = create_coinbase_tx("miner", index, 1_000, 0);
+            let block = chain::Block::new(
+                index,
+                "miner".into(),
+                vec![coinbase],
+                String::new(),
+                100,
+                100,
+                0,
+                0,
+                1_000,
+            );
+            storage.save_block(&block).unwrap();
+            if index + 2 == depth {
+                assert!(take_newly_finalized(&storage, &mut announced).is_empty());
+            }
+        }
+
+        let finalized: Vec<u64> = take_newly_finalized(&storage, &mut announced)
+            .iter()
+            .map(|b| b.index)
+            .collect();
+        assert_eq!(finalized, vec![0, 1]);
+        assert_eq!(announced, Some(1));
+        assert!(take_newly_finalized(&storage, &mut announced).is_empty());
+    }
+
+    #[test]
+    fn time_locked_tx_waits_for_maturity() {
+        let sender = libp2p::identity::Keypair::generate_ed25519()
+            .public()
+            .to_peer_id()
+            .to_string();
+        let tx = |nonce, lock| chain::Transaction {
+            id: format!("tx-{nonce}"),
+            sender: sender.clone(),
+            receiver: sender.clone(),
+            amount: 10_000,
+            shard_id: 0,
+            timestamp: 0,
+            nonce,
+            fee: 0,
+            signature: String::new(),
+            sender_pubkey: String::new(),
+            outputs: Vec::new(),
+            memo: None,
+            not_before_height: lock,
+            alias: None,
+        };
+        let pending = vec![tx(0, Some(10)), tx(1, None)];
+        let consensus = Arc::new(Mutex::new(Consensus::new()));
+        let collect = |height| {
+            let coinbase = create_coinbase_tx(&sender, height, 100, 0);
+            let (txs, _) = collect_shard_transactions(
+                coinbase,
+                &pending,
+                0,
+                height,
+                &consensus,
+                &Arc::new(Mutex::new(None)),
+            );
+            txs.into_iter().skip(1).map(|tx| tx.id).collect::<Vec<_>>()
+        };
+
+        // The later nonce waits behind the locked one
+        assert!(collect(9).is_empty());
+        assert_eq!(collect(10), vec!["tx-0", "tx-1"]);
+        assert_eq!(collect(11), vec!["tx-0", "tx-1"]);
+    }
+}
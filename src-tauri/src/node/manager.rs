@@ -2,6 +2,7 @@ use crate::chain;
 use crate::network;
 use crate::node::{mining, vdf};
 use crate::state::{AppSettings, AppState};
+use crate::wallet::encryption::StoredKey;
 use std::sync::atomic::Ordering;
 use tauri::{AppHandle, State};
 
@@ -13,6 +14,16 @@ pub async fn start_node_service(
     {
         let wallet_guard = state.wallet.lock().unwrap();
         if wallet_guard.is_none() {
+            let locked = state
+                .storage
+                .get_wallet_keys()
+                .ok()
+                .flatten()
+                .and_then(|json| StoredKey::parse(&json).ok())
+                .is_some_and(|key| key.is_encrypted());
+            if locked {
+                return Err("Wallet is locked. Unlock it with your password first.".to_string());
+            }
             return Err(
                 "Wallet required to start node. Please create or import a wallet first."
                     .to_string(),
@@ -60,11 +71,14 @@ pub async fn start_node_service(
     };
 
     // Fetch settings
-    let settings = match state.storage.get_setting("app_settings") {
-        Ok(Some(json)) => serde_json::from_str::<AppSettings>(&json).unwrap_or_default(),
-        _ => AppSettings::default(),
-    };
+    let settings = AppSettings::load(&state.storage);
     let relay_addresses = settings.relay_addresses.clone();
+    let max_peers = settings.max_peers;
+    let listen_port = settings.listen_port;
+    let chain_id = settings.chain_id;
+    crate::chain::set_chain_id(chain_id);
+    crate::chain::set_fee_policy(settings.fee_policy.clone());
+    crate::chain::set_checkpoints(settings.checkpoints.clone());
     let (cmd_tx, cmd_rx) = tokio::sync::mpsc::channel(100);
 
     // Store P2P command sender in AppState for broadcasting mining status changes
@@ -83,6 +97,9 @@ pub async fn start_node_service(
     let chain_index_p2p = state.chain_index.clone();
     let node_type_p2p = state.node_type.clone();
     let relay_connected_p2p = state.relay_connected.clone();
+    let peer_scores_p2p = state.peer_scores.clone();
+    let peer_latencies_p2p = state.peer_latencies.clone();
+    let network_graph_p2p = state.network_graph.clone();
     let app_handle_p2p = app_handle.clone();
 
     // === CRITICAL FIX ===
@@ -95,6 +112,11 @@ pub async fn start_node_service(
 
         let mut c = state.consensus.lock().unwrap();
         c.set_local_peer_id(local_peer_id.to_string());
+        // A previous stop retired us from leader election
+        c.set_peer_mining_status(
+            &local_peer_id.to_string(),
+            state.mining_enabled.load(Ordering::Relaxed),
+        );
 
         // If we have an existing chain, force activate ourselves immediately
         // This ensures we stay eligible when peers join
@@ -135,6 +157,12 @@ pub async fn start_node_service(
             relay_connected_p2p,
             wallet_keypair,
             cmd_rx,
+            peer_scores_p2p,
+            peer_latencies_p2p,
+            network_graph_p2p,
+            max_peers,
+            listen_port,
+            chain_id,
         )
         .await
         {
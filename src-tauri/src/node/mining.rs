@@ -10,12 +10,15 @@
 //! IMPORTANT: This loop is designed to be non-blocking and yield-friendly
 //! to allow P2P and VDF operations to run concurrently.
 
-use crate::chain::{ingest_block, BlockAcceptResult};
+use crate::chain::{ingest_block, retarget_difficulty, BlockAcceptResult};
 use crate::consensus::mempool::Mempool;
 use crate::consensus::vdf::CentichainVDF;
 use crate::consensus::Consensus;
-use crate::state::NodeType;
+use crate::state::{AppSettings, NodeType};
 use crate::storage::Storage;
+use crate::utils::constants::{
+    DIFFICULTY_RETARGET_WINDOW, MEMPOOL_SWEEP_INTERVAL_SECS, MIN_VDF_DIFFICULTY,
+};
 use crate::wallet::Wallet;
 use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
@@ -23,7 +26,8 @@ use std::time::Duration;
 use tauri::{AppHandle, Emitter};
 
 use super::helpers::{
-    collect_shard_transactions, create_coinbase_tx, run_auto_pruning, slash_missed_slots,
+    append_receipt_mints, attach_receipt_proofs, collect_shard_transactions, create_coinbase_tx,
+    pay_coinbase_fees, reward_receiver, run_auto_pruning, slash_missed_slots, take_newly_finalized,
 };
 use super::network_init::initialize_network_state;
 use super::relay::{emit_relay_error, wait_for_relay, RELAY_CONNECTION_TIMEOUT};
@@ -127,7 +131,6 @@ pub fn spawn_mining_loop(
             is_running,
             run_id,
             my_run_id,
-            validator_count,
             storage,
             mempool,
             consensus,
@@ -156,7 +159,6 @@ async fn block_production_loop(
     is_running: Arc<AtomicBool>,
     run_id: Arc<AtomicU64>,
     my_run_id: u64,
-    validator_count: Arc<AtomicUsize>,
     storage: Arc<Storage>,
     mempool: Arc<Mempool>,
     consensus: Arc<Mutex<Consensus>>,
@@ -172,6 +174,9 @@ async fn block_production_loop(
 ) {
     let mut last_production_time = std::time::Instant::now();
     let mut last_log_time = std::time::Instant::now();
+    let mut last_sweep_time = std::time::Instant::now();
+    let mut finalized = crate::chain::finalized_height(storage.get_latest_index().unwrap_or(0));
+    let mut low_disk = false;
 
     loop {
         // Check if we should stop
@@ -193,6 +198,43 @@ async fn block_production_loop(
         // Auto-pruning check
         run_auto_pruning(&storage);
 
+        // Drop transactions that have waited too long to be mined
+        if last_sweep_time.elapsed() >= Duration::from_secs(MEMPOOL_SWEEP_INTERVAL_SECS) {
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            for tx in mempool.sweep_expired(now) {
+                log::info!("Mempool: dropped expired tx {}", tx.id);
+                let _ = app_handle.emit(
+                    "tx-dropped",
+                    serde_json::json!({ "id": tx.id, "reason": "expired" }),
+                );
+            }
+            last_sweep_time = std::time::Instant::now();
+        }
+
+        // Announce blocks that are now too deep to be reorganized away
+        for block in take_newly_finalized(&storage, &mut finalized) {
+            let _ = app_handle.emit(
+                "block-finalized",
+                serde_json::json!({ "index": block.index, "hash": block.hash }),
+            );
+        }
+
+        // Pause production while the disk is nearly full; block writes would fail
+        match storage.check_disk_space() {
+            Ok(()) => low_disk = false,
+            Err(e) => {
+                if !low_disk {
+                    log::warn!("Mining Loop: {}; block production paused", e);
+                    let _ = app_handle.emit("low-disk", &e);
+                }
+                low_disk = true;
+                continue;
+            }
+        }
+
         // Skip if not synced
         if !is_synced.load(Ordering::Relaxed) {
             continue;
@@ -229,7 +271,8 @@ async fn block_production_loop(
             last_log_time = std::time::Instant::now();
         }
 
-        if !enabled {
+        // Light nodes hold no state to build blocks on
+        if !enabled || *node_type.lock().unwrap() == NodeType::Light {
             continue;
         }
 
@@ -263,7 +306,7 @@ async fn block_production_loop(
         }
 
         // === BLOCK PRODUCTION ===
-        let pending_txs = mempool.get_pending_transactions();
+        let pending_txs = mempool.get_pending_sorted();
 
         // Only produce if enough time passed or enough transactions
         if elapsed < crate::utils::constants::TARGET_BLOCK_TIME && pending_txs.len() < 100 {
@@ -300,31 +343,40 @@ async fn block_production_loop(
             crate::chain::calculate_mining_reward(target_idx)
         };
 
-        let total_fees: u64 = pending_txs
-            .iter()
-            .map(|tx| crate::chain::calculate_fee(tx.amount))
-            .sum();
-
-        // Create coinbase transaction
-        let coinbase_tx =
-            create_coinbase_tx(&current_wallet_addr, target_idx, block_reward, total_fees);
+        // Create coinbase transaction (fees are added once the block is filled)
+        let settings = AppSettings::load(&storage);
+        let reward_addr = reward_receiver(settings.reward_address.as_deref(), &current_wallet_addr);
+        let coinbase_tx = create_coinbase_tx(&reward_addr, target_idx, block_reward, 0);
 
-        // Filter and collect transactions for this shard
-        let (block_txs, generated_receipts) = collect_shard_transactions(
+        // Filter and collect transactions for this shard, highest fee first
+        let (mut block_txs, mut generated_receipts) = collect_shard_transactions(
             coinbase_tx,
             &pending_txs,
             my_shard,
+            target_idx,
             &consensus,
             &receipt_sender,
         );
 
-        // Broadcast generated receipts
-        for receipt in generated_receipts {
-            if let Some(sender) = receipt_sender.lock().unwrap().as_ref() {
-                let _ = sender.try_send(receipt);
-            }
+        // Only fees of included transactions are paid out, split per fee policy
+        let total_fees: u64 = block_txs.iter().skip(1).map(|tx| tx.fee()).sum();
+        if target_idx > 0 {
+            pay_coinbase_fees(
+                &mut block_txs[0],
+                block_reward,
+                total_fees,
+                &crate::chain::fee_policy(),
+            );
         }
 
+        // Settle incoming cross-shard receipts whose source block is cross-linked
+        let minted_receipts = append_receipt_mints(
+            &mut block_txs,
+            &mempool.get_pending_receipts(),
+            my_shard,
+            &consensus.lock().unwrap(),
+        );
+
         // Get previous block hash
         let prev_hash = if target_idx == 0 {
             "0000000000000000000000000000000000000000000000000000000000000000".to_string()
@@ -338,12 +390,14 @@ async fn block_production_loop(
                 })
         };
 
-        // Calculate adaptive VDF difficulty
-        let current_validators = validator_count.load(Ordering::Relaxed);
-        let adaptive_difficulty = if current_validators <= 1 {
-            100 // Fast solo production
+        // Retarget VDF difficulty from recent block intervals
+        let adaptive_difficulty = if target_idx == 0 {
+            MIN_VDF_DIFFICULTY
         } else {
-            100 + (current_validators as u64 * 100)
+            let recent = storage
+                .get_recent_blocks(DIFFICULTY_RETARGET_WINDOW)
+                .unwrap_or_default();
+            retarget_difficulty(&recent)
         };
 
         // Create block
@@ -358,6 +412,16 @@ async fn block_production_loop(
             total_fees,
             block_reward,
         );
+        new_block.receipts = minted_receipts;
+
+        // Commit to the post-block balances before sealing
+        match storage.compute_state_root_after(&new_block) {
+            Ok(root) => new_block.state_root = root,
+            Err(e) => {
+                log::error!("Mining Loop: State root computation failed: {}", e);
+                continue;
+            }
+        }
 
         // Solve VDF (quick for block production)
         let _ = app_handle.emit("node-status", "Active (Mining)");
@@ -365,6 +429,14 @@ async fn block_production_loop(
         let challenge = new_block.calculate_hash();
         new_block.vdf_proof = vdf.solve(challenge.as_bytes());
         new_block.hash = new_block.calculate_hash();
+        if let Some(wallet) = wallet_store.lock().unwrap().as_ref() {
+            if let Err(e) = wallet
+                .get_keypair()
+                .and_then(|keypair| new_block.sign_with_keypair(&keypair))
+            {
+                log::warn!("Mining Loop: Block {} left unsigned: {}", target_idx, e);
+            }
+        }
         new_block.size = new_block.calculate_size();
 
         // Slash missed slots
@@ -374,7 +446,11 @@ async fn block_production_loop(
         match ingest_block(&storage, &mempool, &consensus, &new_block, false) {
             BlockAcceptResult::Accepted => {}
             other => {
-                log::error!("Mining Loop: Block {} not accepted: {:?}", target_idx, other);
+                log::error!(
+                    "Mining Loop: Block {} not accepted: {:?}",
+                    target_idx,
+                    other
+                );
                 continue;
             }
         }
@@ -389,10 +465,16 @@ async fn block_production_loop(
         mined_by_me_count.fetch_add(1, Ordering::Relaxed);
         let _ = app_handle.emit("new-block", new_block.clone());
 
-        // Broadcast to network
+        // Broadcast to network; receipts carry their source header for the target shard
+        attach_receipt_proofs(&mut generated_receipts, &new_block);
         if let Err(e) = block_sender.send(Box::new(new_block)).await {
             log::error!("Failed to broadcast block: {}", e);
         }
+        for receipt in generated_receipts {
+            if let Some(sender) = receipt_sender.lock().unwrap().as_ref() {
+                let _ = sender.try_send(receipt);
+            }
+        }
 
         log::info!("Mining Loop: Block {} produced and broadcast", target_idx);
     }
@@ -9,6 +9,7 @@
 //! - `network_init`: Network discovery and synchronization
 //! - `helpers`: Block production helper functions
 //! - `manager`: Node service management
+//! - `shutdown`: Stopping the node cleanly
 //! - `vdf`: VDF solver and heartbeat
 
 pub mod helpers;
@@ -16,11 +17,13 @@ pub mod manager;
 pub mod mining;
 pub mod network_init;
 pub mod relay;
+pub mod shutdown;
 pub mod vdf;
 
 // Re-exports for convenience
 pub use helpers::{
-    collect_shard_transactions, create_coinbase_tx, run_auto_pruning, slash_missed_slots,
+    append_receipt_mints, attach_receipt_proofs, collect_shard_transactions, create_coinbase_tx,
+    pay_coinbase_fees, reward_receiver, run_auto_pruning, slash_missed_slots, take_newly_finalized,
 };
 pub use manager::start_node_service;
 pub use mining::spawn_mining_loop;
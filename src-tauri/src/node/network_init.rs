@@ -2,7 +2,7 @@
 //!
 //! Handles Phase 2 of the mining loop: network discovery and synchronization.
 
-use crate::chain::{self, try_accept_block, BlockAcceptResult, Transaction, SYSTEM_SIG_GENESIS};
+use crate::chain::{self, try_accept_block, BlockAcceptResult};
 use crate::consensus::vdf::CentichainVDF;
 use crate::consensus::Consensus;
 use crate::storage::Storage;
@@ -268,28 +268,35 @@ pub async fn create_genesis_block(
 ) {
     let _ = app_handle.emit("node-status", "Creating Genesis Block...");
 
-    let genesis_tx = Transaction {
-        id: "genesis".to_string(),
-        sender: "SYSTEM".to_string(),
-        receiver: wallet_addr.to_string(),
-        amount: crate::utils::constants::GENESIS_SUPPLY,
-        shard_id: 0,
-        timestamp: 0,
-        signature: SYSTEM_SIG_GENESIS.to_string(),
-        sender_pubkey: String::new(),
+    // Operators pre-allocate balances in genesis.json; otherwise this wallet
+    // receives the whole genesis supply
+    let config_path = crate::utils::paths::data_dir().join(chain::GENESIS_CONFIG_FILE);
+    let config = match chain::GenesisConfig::load(&config_path) {
+        Ok(Some(config)) if config.chain_id != chain::chain_id() => {
+            log::error!(
+                "{} is for chain {}, but this node runs chain {}",
+                config_path.display(),
+                config.chain_id,
+                chain::chain_id()
+            );
+            return;
+        }
+        Ok(Some(config)) => config,
+        Ok(None) => chain::GenesisConfig::single(wallet_addr),
+        Err(e) => {
+            log::error!("{}", e);
+            return;
+        }
     };
+    let mut genesis_block = config.block(wallet_addr);
 
-    let mut genesis_block = chain::Block::new(
-        0,
-        wallet_addr.to_string(),
-        vec![genesis_tx],
-        "0000000000000000000000000000000000000000000000000000000000000000".to_string(),
-        100,
-        100, // Low difficulty for genesis
-        0,
-        0,
-        crate::utils::constants::GENESIS_SUPPLY,
-    );
+    match storage.compute_state_root_after(&genesis_block) {
+        Ok(root) => genesis_block.state_root = root,
+        Err(e) => {
+            log::error!("Failed to compute genesis state root: {}", e);
+            return;
+        }
+    }
 
     // Solve VDF for genesis (quick, low difficulty)
     let vdf = CentichainVDF::new(genesis_block.vdf_difficulty);
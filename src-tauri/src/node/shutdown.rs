@@ -0,0 +1,47 @@
+//! # Shutdown Module
+//!
+//! Stopping the node: leave leader election, let the P2P task say goodbye,
+//! then stop every loop of the current run.
+
+use crate::consensus::Consensus;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How long the P2P task keeps running after announcing that we leave, so
+/// the farewell and anything still queued reaches the network
+pub const SHUTDOWN_DRAIN: Duration = Duration::from_millis(1500);
+
+/// Stops the loops of the current run and takes the local node out of
+/// leader election.
+pub fn halt(is_running: &AtomicBool, run_id: &AtomicU64, consensus: &Mutex<Consensus>) {
+    consensus.lock().unwrap().retire_local();
+    is_running.store(false, Ordering::Relaxed);
+    // Loops also compare run ids, so a quick restart can't revive them
+    run_id.fetch_add(1, Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn halted_node_is_stopped_and_not_eligible() {
+        let mut consensus = Consensus::new();
+        consensus.set_local_peer_id("local".to_string());
+        consensus.force_activate_local();
+        assert!(consensus.is_eligible_for_leadership(&"local".to_string()));
+
+        let consensus = Mutex::new(consensus);
+        let is_running = AtomicBool::new(true);
+        let run_id = AtomicU64::new(3);
+        halt(&is_running, &run_id, &consensus);
+
+        assert!(!is_running.load(Ordering::Relaxed));
+        assert_eq!(run_id.load(Ordering::Relaxed), 4);
+        let consensus = consensus.lock().unwrap();
+        assert!(!consensus.is_eligible_for_leadership(&"local".to_string()));
+        // Activation survives, so a restart doesn't go through quarantine again
+        assert!(consensus.nodes["local"].activated_at.is_some());
+    }
+}
@@ -11,6 +11,7 @@ use crate::chain::VdfProofMessage;
 use crate::consensus::vdf::CentichainVDF;
 use crate::consensus::Consensus;
 use crate::state::VdfStatus;
+use crate::utils::constants::VDF_BENCHMARK_DIFFICULTY;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
@@ -26,9 +27,6 @@ const VDF_BASE_DIFFICULTY: u64 = 3_000_000;
 /// Additional difficulty per network validator (Sybil resistance)
 const VDF_DIFFICULTY_PER_VALIDATOR: u64 = 500_000;
 
-/// Small difficulty for heartbeat benchmarking (keeps UI responsive)
-const VDF_HEARTBEAT_DIFFICULTY: u64 = 50_000;
-
 // =============================================================================
 // VDF Heartbeat - Performance Monitoring
 // =============================================================================
@@ -58,15 +56,11 @@ pub fn spawn_vdf_heartbeat(
 
             // Run VDF benchmark in blocking thread pool (non-blocking to async runtime)
             let benchmark_result = tokio::task::spawn_blocking(|| {
-                let start = std::time::Instant::now();
-                let vdf = CentichainVDF::new(VDF_HEARTBEAT_DIFFICULTY);
-                vdf.solve(b"heartbeat_challenge");
-                start.elapsed()
+                CentichainVDF::new(VDF_BENCHMARK_DIFFICULTY).measure_ips()
             })
             .await;
 
-            if let Ok(elapsed) = benchmark_result {
-                let ips = (VDF_HEARTBEAT_DIFFICULTY as f64 / elapsed.as_secs_f64()) as u64;
+            if let Ok(ips) = benchmark_result {
                 vdf_ips.store(ips, Ordering::Relaxed);
 
                 // Emit status update every second
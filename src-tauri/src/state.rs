@@ -1,6 +1,6 @@
 use crate::chain::Transaction;
 use crate::consensus::mempool::Mempool;
-use crate::consensus::Consensus;
+use crate::consensus::{Consensus, QuarantinePolicy};
 use crate::storage::Storage;
 use crate::wallet::Wallet;
 use std::sync::atomic::{AtomicBool, AtomicUsize};
@@ -18,6 +18,8 @@ pub struct VdfStatus {
 pub enum NodeType {
     Full,
     Pruned,
+    /// Keeps only block headers; bodies and balances come from full peers
+    Light,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
@@ -28,12 +30,25 @@ pub struct AppSettings {
     pub allow_relay_free_mode: bool,  // Allow operation without relay if DHT has peers
     pub mining_enabled: bool,
     pub max_peers: u32,
+    pub listen_port: u16, // TCP port for inbound peers, 0 = OS-assigned
     pub node_type: NodeType,
+    pub chain_id: u32, // Network to join; must match peers and stored blocks
+    pub reward_address: Option<String>, // Coinbase receiver; None pays the wallet
+    pub fee_policy: crate::chain::FeePolicy, // Fee burn/treasury split; must match peers
+    pub checkpoints: Vec<crate::chain::Checkpoint>, // Trusted (height, hash) pins for sync
+    pub data_dir: Option<String>, // Where the database lives; applied on restart
+    pub min_free_disk_mb: u64, // Blocks are not written below this much free space
+    pub quarantine_base_secs: u64, // New validators wait this long plus...
+    pub quarantine_per_validator_secs: u64, // ...this much per known validator,
+    pub quarantine_max_secs: u64, // ...but no longer than this
 }
 
 impl Default for AppSettings {
     fn default() -> Self {
-        use crate::utils::constants::{DEFAULT_MAX_PEERS, RELAY_ADDRESSES};
+        use crate::utils::constants::{
+            BASE_QUARANTINE_SECS, DEFAULT_CHAIN_ID, DEFAULT_MAX_PEERS, DEFAULT_MIN_FREE_DISK_MB,
+            MAX_QUARANTINE_SECS, QUARANTINE_PER_VALIDATOR_SECS, RELAY_ADDRESSES,
+        };
         Self {
             node_name: "Centichain-Node-01".to_string(),
             relay_addresses: RELAY_ADDRESSES
@@ -43,11 +58,60 @@ impl Default for AppSettings {
             allow_relay_free_mode: true, // Enable DHT-only fallback
             mining_enabled: true,
             max_peers: DEFAULT_MAX_PEERS,
+            listen_port: 0,
             node_type: NodeType::Pruned, // Default to home-user friendly
+            chain_id: DEFAULT_CHAIN_ID,
+            reward_address: None,
+            fee_policy: crate::chain::FeePolicy::default(),
+            checkpoints: Vec::new(),
+            data_dir: None,
+            min_free_disk_mb: DEFAULT_MIN_FREE_DISK_MB,
+            quarantine_base_secs: BASE_QUARANTINE_SECS,
+            quarantine_per_validator_secs: QUARANTINE_PER_VALIDATOR_SECS,
+            quarantine_max_secs: MAX_QUARANTINE_SECS,
         }
     }
 }
 
+impl AppSettings {
+    /// Settings saved in storage, or the defaults
+    pub fn load(storage: &Storage) -> Self {
+        match storage.get_setting("app_settings") {
+            Ok(Some(json)) => serde_json::from_str(&json).unwrap_or_default(),
+            _ => Self::default(),
+        }
+    }
+
+    /// How long new validators are quarantined
+    pub fn quarantine_policy(&self) -> QuarantinePolicy {
+        QuarantinePolicy {
+            base_secs: self.quarantine_base_secs,
+            per_validator_secs: self.quarantine_per_validator_secs,
+            max_secs: self.quarantine_max_secs,
+        }
+    }
+
+    /// Rejects values the UI could send but the node can't use
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(address) = &self.reward_address {
+            address
+                .parse::<libp2p::PeerId>()
+                .map_err(|_| format!("Invalid reward address: {}", address))?;
+        }
+        self.fee_policy.validate()?;
+        for checkpoint in &self.checkpoints {
+            checkpoint.validate()?;
+        }
+        if self.node_type == NodeType::Light && self.mining_enabled {
+            return Err("Light nodes keep no chain state and cannot mine".into());
+        }
+        if self.quarantine_max_secs < self.quarantine_base_secs {
+            return Err("Quarantine cap is below its base duration".into());
+        }
+        Ok(())
+    }
+}
+
 // Shared state
 pub struct AppState {
     pub wallet: Arc<Mutex<Option<Wallet>>>,
@@ -69,4 +133,10 @@ pub struct AppState {
     pub vdf_ips: Arc<std::sync::atomic::AtomicU64>,
     /// Channel to send commands to P2P module (for broadcasting mining status, etc.)
     pub p2p_cmd_sender: Arc<Mutex<Option<tokio::sync::mpsc::Sender<crate::network::P2PCommand>>>>,
+    /// Misbehaviour scores and bans, maintained by the P2P loop
+    pub peer_scores: Arc<Mutex<crate::network::peer_score::PeerScores>>,
+    /// Latest ping round-trip per peer in milliseconds, maintained by the P2P loop
+    pub peer_latencies: Arc<Mutex<std::collections::HashMap<String, u64>>>,
+    /// Peer -> connected peers, from topology gossip and our own connections
+    pub network_graph: Arc<Mutex<std::collections::HashMap<String, Vec<String>>>>,
 }
@@ -1,5 +1,11 @@
-use crate::chain::Block;
-use redb::{Database, Error, ReadableTable, TableDefinition};
+use crate::chain::{calculate_merkle_root, merkle_root_from_leaves, Block, Header};
+use crate::utils::constants::DEFAULT_MIN_FREE_DISK_MB;
+use redb::{Database, Durability, Error, ReadableTable, TableDefinition, WriteTransaction};
+use sha2::{Digest, Sha256};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 const BLOCKS_TABLE: TableDefinition<u64, &str> = TableDefinition::new("blocks");
@@ -7,10 +13,78 @@ const WALLET_TABLE: TableDefinition<&str, &str> = TableDefinition::new("wallet")
 const SETTINGS_TABLE: TableDefinition<&str, &str> = TableDefinition::new("settings");
 const MEMPOOL_TABLE: TableDefinition<&str, &str> = TableDefinition::new("mempool");
 const STATE_TABLE: TableDefinition<&str, u64> = TableDefinition::new("state");
+/// Reserved `STATE_TABLE` key holding the highest contiguous synced height. It
+/// is not a valid address and is left out of the state root.
+const SYNC_CHECKPOINT_KEY: &str = "__sync_checkpoint";
 const TX_INDEX_TABLE: TableDefinition<&str, u64> = TableDefinition::new("tx_index");
+/// Validator `NodeState`s as JSON, keyed by peer id.
+const VALIDATORS_TABLE: TableDefinition<&str, &str> = TableDefinition::new("validators");
+/// Cross-shard receipts already minted on this shard: `original_tx_id` -> block index.
+const APPLIED_RECEIPTS_TABLE: TableDefinition<&str, u64> = TableDefinition::new("applied_receipts");
+/// Next expected transaction nonce per sender address.
+const NONCES_TABLE: TableDefinition<&str, u64> = TableDefinition::new("nonces");
+/// Block hash -> block index.
+const BLOCK_HASHES_TABLE: TableDefinition<&str, u64> = TableDefinition::new("block_hashes");
+/// Blocks on the chain per author address.
+const AUTHOR_COUNT_TABLE: TableDefinition<&str, u64> = TableDefinition::new("author_counts");
+/// Transactions touching an address: `(address, block index, position in block)`.
+const ADDRESS_TXS_TABLE: TableDefinition<(&str, u64, u32), ()> =
+    TableDefinition::new("address_txs");
+/// `PeerRecord`s as JSON, keyed by peer id.
+const PEERS_TABLE: TableDefinition<&str, &str> = TableDefinition::new("peers");
+/// `EquivocationEvidence` as JSON, keyed by `(author, slot)`.
+const EQUIVOCATIONS_TABLE: TableDefinition<(&str, u64), &str> =
+    TableDefinition::new("equivocations");
+/// Reported `EquivocationEvidence` that led to a slash, as JSON, keyed by
+/// `(author, slot)`.
+const SLASHING_TABLE: TableDefinition<(&str, u64), &str> = TableDefinition::new("slashings");
+/// Chain-wide supply counters, e.g. [`BURNED_FEES_KEY`].
+const SUPPLY_TABLE: TableDefinition<&str, u64> = TableDefinition::new("supply");
+/// Total fees burned by the fee policy
+const BURNED_FEES_KEY: &str = "burned_fees";
+/// User transactions on the chain; coinbase and genesis transactions are not counted
+const TOTAL_TXS_KEY: &str = "total_transactions";
+/// On-chain aliases: name -> owner address.
+const ALIAS_TABLE: TableDefinition<&str, &str> = TableDefinition::new("aliases");
+/// Block `Header`s as JSON, the only chain data a light node keeps.
+const HEADERS_TABLE: TableDefinition<u64, &str> = TableDefinition::new("headers");
+
+/// A peer this node was connected to, kept to dial it again after a restart.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct PeerRecord {
+    pub peer_id: String,
+    pub addresses: Vec<String>,
+    /// Unix time (seconds) the peer was last identified
+    pub last_seen: u64,
+    /// Added by the user with `add_peer`; never pruned
+    #[serde(default)]
+    pub manual: bool,
+}
+
+/// Why a block write was refused: the disk is nearly full.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, thiserror::Error)]
+#[error("Low disk space: {available} bytes free, {required} required")]
+pub struct LowDiskSpace {
+    pub available: u64,
+    pub required: u64,
+}
+
+/// Why a block does not apply to the stored state: the block itself is at fault.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{0}")]
+pub struct InvalidBlockState(pub String);
 
 pub struct Storage {
     db: Arc<Database>,
+    path: PathBuf,
+    /// Blocks are not written when the disk has fewer free bytes than this
+    min_free_disk: AtomicU64,
+    /// Free bytes on the disk holding `path`
+    free_space: fn(&Path) -> std::io::Result<u64>,
+    /// Write transactions committed by `save_block` / `save_blocks_batch`
+    block_commits: AtomicU64,
+    /// Blocks loaded by `get_block`
+    pub(crate) block_reads: AtomicU64,
 }
 
 impl Storage {
@@ -24,62 +98,478 @@ impl Storage {
             let _ = write_txn.open_table(MEMPOOL_TABLE)?;
             let _ = write_txn.open_table(STATE_TABLE)?;
             let _ = write_txn.open_table(TX_INDEX_TABLE)?;
+            let _ = write_txn.open_table(APPLIED_RECEIPTS_TABLE)?;
+            let _ = write_txn.open_table(VALIDATORS_TABLE)?;
+            let _ = write_txn.open_table(NONCES_TABLE)?;
+            let _ = write_txn.open_table(PEERS_TABLE)?;
+            let _ = write_txn.open_table(EQUIVOCATIONS_TABLE)?;
+            let _ = write_txn.open_table(SLASHING_TABLE)?;
+            let _ = write_txn.open_table(SUPPLY_TABLE)?;
+            let _ = write_txn.open_table(ALIAS_TABLE)?;
+            let _ = write_txn.open_table(HEADERS_TABLE)?;
+
+            // Databases created before the hash index existed get it backfilled once
+            let mut block_hashes = write_txn.open_table(BLOCK_HASHES_TABLE)?;
+            if block_hashes.is_empty()? {
+                let blocks = write_txn.open_table(BLOCKS_TABLE)?;
+                for item in blocks.iter()? {
+                    let (index, value) = item?;
+                    if let Ok(block) = serde_json::from_str::<Block>(value.value()) {
+                        block_hashes.insert(block.hash.as_str(), index.value())?;
+                    }
+                }
+            }
+
+            // Likewise for the address index
+            let mut address_txs = write_txn.open_table(ADDRESS_TXS_TABLE)?;
+            if address_txs.is_empty()? {
+                let blocks = write_txn.open_table(BLOCKS_TABLE)?;
+                for item in blocks.iter()? {
+                    let (_, value) = item?;
+                    if let Ok(block) = serde_json::from_str::<Block>(value.value()) {
+                        for (key, _) in address_keys(&block) {
+                            address_txs.insert(key, ())?;
+                        }
+                    }
+                }
+            }
+
+            // And for the per-author block counts
+            let mut author_counts = write_txn.open_table(AUTHOR_COUNT_TABLE)?;
+            if author_counts.is_empty()? {
+                let blocks = write_txn.open_table(BLOCKS_TABLE)?;
+                for item in blocks.iter()? {
+                    let (_, value) = item?;
+                    if let Ok(block) = serde_json::from_str::<Block>(value.value()) {
+                        let count = author_counts
+                            .get(block.author.as_str())?
+                            .map_or(0, |v| v.value());
+                        author_counts.insert(block.author.as_str(), count + 1)?;
+                    }
+                }
+            }
         }
         write_txn.commit()?;
 
-        Ok(Storage { db: Arc::new(db) })
+        Ok(Storage {
+            db: Arc::new(db),
+            path: PathBuf::from(path),
+            min_free_disk: AtomicU64::new(DEFAULT_MIN_FREE_DISK_MB * 1024 * 1024),
+            free_space: |path| fs2::available_space(path),
+            block_commits: AtomicU64::new(0),
+            block_reads: AtomicU64::new(0),
+        })
+    }
+
+    /// Sets the free space below which [`Storage::check_disk_space`] fails.
+    pub fn set_min_free_disk(&self, bytes: u64) {
+        self.min_free_disk.store(bytes, Ordering::Relaxed);
     }
 
+    /// Fails when the disk holding the database is short of free space. A
+    /// disk that can't be queried lets writes through.
+    pub fn check_disk_space(&self) -> Result<(), LowDiskSpace> {
+        let required = self.min_free_disk.load(Ordering::Relaxed);
+        match (self.free_space)(&self.path) {
+            Ok(available) if available < required => Err(LowDiskSpace {
+                available,
+                required,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Applies `block` on top of the stored chain. Saving a block that is
+    /// already stored is a no-op; a different block at a stored index is an
+    /// error, since replacing blocks is left to [`Storage::reorg_to`].
     pub fn save_block(&self, block: &Block) -> Result<(), anyhow::Error> {
+        self.check_disk_space()?;
         let write_txn = self.db.begin_write()?;
-        {
-            let mut blocks_table = write_txn.open_table(BLOCKS_TABLE)?;
-            let mut state_table = write_txn.open_table(STATE_TABLE)?;
-            let mut tx_index = write_txn.open_table(TX_INDEX_TABLE)?;
+        match Self::is_stored(&write_txn, block) {
+            Ok(false) => {}
+            Ok(true) => {
+                write_txn.abort()?;
+                return Ok(());
+            }
+            Err(e) => {
+                write_txn.abort()?;
+                return Err(e);
+            }
+        }
+        Self::apply_block(&write_txn, block)?;
+        write_txn.commit()?;
+        self.block_commits.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
 
-            let json = serde_json::to_string(block)?;
-            blocks_table.insert(block.index, json.as_str())?;
+    /// Applies consecutive blocks in a single write transaction, checking each
+    /// block's state root as it goes. Blocks already stored are skipped, as in
+    /// `save_block`. Nothing is written unless every block applies.
+    pub fn save_blocks_batch(&self, blocks: &[Block]) -> Result<(), anyhow::Error> {
+        self.check_disk_space()?;
+        let write_txn = self.db.begin_write()?;
+        for block in blocks {
+            match Self::is_stored(&write_txn, block) {
+                Ok(false) => {}
+                Ok(true) => continue,
+                Err(e) => {
+                    write_txn.abort()?;
+                    return Err(e);
+                }
+            }
+            if let Err(e) = Self::apply_block(&write_txn, block) {
+                write_txn.abort()?;
+                return Err(e);
+            }
+            let root = {
+                let table = write_txn.open_table(STATE_TABLE)?;
+                Self::state_root_of(&table)?
+            };
+            if root != block.state_root {
+                write_txn.abort()?;
+                let reason = format!("State root mismatch at block {}", block.index);
+                return Err(InvalidBlockState(reason).into());
+            }
+        }
+        write_txn.commit()?;
+        self.block_commits.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
 
-            for tx in &block.transactions {
-                tx_index.insert(tx.id.as_str(), block.index)?;
+    /// Whether `block` is already stored at its index; a different block
+    /// there is an error.
+    fn is_stored(write_txn: &WriteTransaction, block: &Block) -> Result<bool, anyhow::Error> {
+        let table = write_txn.open_table(BLOCKS_TABLE)?;
+        let Some(json) = table.get(block.index)? else {
+            return Ok(false);
+        };
+        let stored: Block = serde_json::from_str(json.value())?;
+        if stored.hash != block.hash {
+            anyhow::bail!(
+                "Block {} is already stored with hash {}",
+                block.index,
+                stored.hash
+            );
+        }
+        Ok(true)
+    }
+
+    /// Deterministic Merkle root over the sorted, non-zero `(address, balance)` pairs in `STATE_TABLE`.
+    pub fn compute_state_root(&self) -> Result<String, anyhow::Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(STATE_TABLE)?;
+        Self::state_root_of(&table)
+    }
+
+    /// State root the chain would have after applying `block`; nothing is persisted.
+    pub fn compute_state_root_after(&self, block: &Block) -> Result<String, anyhow::Error> {
+        let write_txn = self.db.begin_write()?;
+        Self::apply_block(&write_txn, block)?;
+        let root = {
+            let table = write_txn.open_table(STATE_TABLE)?;
+            Self::state_root_of(&table)?
+        };
+        write_txn.abort()?;
+        Ok(root)
+    }
+
+    fn state_root_of(
+        table: &impl ReadableTable<&'static str, u64>,
+    ) -> Result<String, anyhow::Error> {
+        let mut leaves = Vec::new();
+        for item in table.iter()? {
+            let (address, balance) = item?;
+            // Empty accounts are left behind by spends/reverts; they carry no state
+            if balance.value() == 0 || address.value() == SYNC_CHECKPOINT_KEY {
+                continue;
             }
+            let mut hasher = Sha256::new();
+            hasher.update(format!("{}:{}", address.value(), balance.value()).as_bytes());
+            leaves.push(hasher.finalize().to_vec());
+        }
+        Ok(merkle_root_from_leaves(leaves))
+    }
 
-            // Update state based on transactions
-            for tx in &block.transactions {
-                // Handle Sender (Deduct amount + fee)
-                if tx.sender != "SYSTEM" {
-                    let current_balance = state_table
-                        .get(tx.sender.as_str())?
-                        .map(|v| v.value())
-                        .unwrap_or(0);
-                    let fee = crate::chain::calculate_fee(tx.amount);
-                    let deduction = tx.amount.saturating_add(fee);
-                    state_table.insert(
-                        tx.sender.as_str(),
-                        current_balance.saturating_sub(deduction),
-                    )?;
+    /// Writes the block and its balance/index effects into an open write transaction.
+    fn apply_block(write_txn: &WriteTransaction, block: &Block) -> Result<(), anyhow::Error> {
+        let mut blocks_table = write_txn.open_table(BLOCKS_TABLE)?;
+        let mut state_table = write_txn.open_table(STATE_TABLE)?;
+        let mut tx_index = write_txn.open_table(TX_INDEX_TABLE)?;
+        let mut applied_receipts = write_txn.open_table(APPLIED_RECEIPTS_TABLE)?;
+        let mut nonces = write_txn.open_table(NONCES_TABLE)?;
+        let mut block_hashes = write_txn.open_table(BLOCK_HASHES_TABLE)?;
+        let mut address_txs = write_txn.open_table(ADDRESS_TXS_TABLE)?;
+        let mut supply = write_txn.open_table(SUPPLY_TABLE)?;
+        let mut aliases = write_txn.open_table(ALIAS_TABLE)?;
+        let mut author_counts = write_txn.open_table(AUTHOR_COUNT_TABLE)?;
+
+        let json = serde_json::to_string(block)?;
+        blocks_table.insert(block.index, json.as_str())?;
+        block_hashes.insert(block.hash.as_str(), block.index)?;
+        let authored = author_counts
+            .get(block.author.as_str())?
+            .map_or(0, |v| v.value());
+        author_counts.insert(block.author.as_str(), authored + 1)?;
+
+        for tx in &block.transactions {
+            tx_index.insert(tx.id.as_str(), block.index)?;
+        }
+        for (key, _) in address_keys(block) {
+            address_txs.insert(key, ())?;
+        }
+
+        let burned = block.burned_fees();
+        if burned > 0 {
+            let total = supply.get(BURNED_FEES_KEY)?.map(|v| v.value()).unwrap_or(0);
+            supply.insert(BURNED_FEES_KEY, total.saturating_add(burned))?;
+        }
+        let user_txs = block.user_transaction_count();
+        if user_txs > 0 {
+            let total = supply.get(TOTAL_TXS_KEY)?.map(|v| v.value()).unwrap_or(0);
+            supply.insert(TOTAL_TXS_KEY, total.saturating_add(user_txs))?;
+        }
+
+        // Update state based on transactions
+        for tx in &block.transactions {
+            // Cross-shard mints: nothing to deduct, but each receipt settles only once
+            if let Some(receipt_id) = tx.receipt_source_id() {
+                if applied_receipts.get(receipt_id)?.is_some() {
+                    let reason = format!("Receipt {} already applied", receipt_id);
+                    return Err(InvalidBlockState(reason).into());
+                }
+                applied_receipts.insert(receipt_id, block.index)?;
+            }
+            // First registration of a name wins; a later claim invalidates its block
+            if let Some(alias) = &tx.alias {
+                if aliases.get(alias.as_str())?.is_some() {
+                    let reason = format!("Alias {} already registered", alias);
+                    return Err(InvalidBlockState(reason).into());
+                }
+                aliases.insert(alias.as_str(), tx.sender.as_str())?;
+            }
+
+            // Handle Sender (Check nonce, deduct amount + fee)
+            if !tx.is_system() && !tx.is_receipt_mint() {
+                let expected_nonce = nonces
+                    .get(tx.sender.as_str())?
+                    .map(|v| v.value())
+                    .unwrap_or(0);
+                if tx.nonce != expected_nonce {
+                    let reason = format!(
+                        "Out-of-order nonce for {}: got {}, expected {}",
+                        tx.sender, tx.nonce, expected_nonce
+                    );
+                    return Err(InvalidBlockState(reason).into());
                 }
+                nonces.insert(tx.sender.as_str(), expected_nonce + 1)?;
 
-                // Handle Receiver (Add amount)
-                let current_recv_balance = state_table
-                    .get(tx.receiver.as_str())?
+                let current_balance = state_table
+                    .get(tx.sender.as_str())?
                     .map(|v| v.value())
                     .unwrap_or(0);
+                let deduction = tx.amount.saturating_add(tx.fee());
                 state_table.insert(
-                    tx.receiver.as_str(),
-                    current_recv_balance.saturating_add(tx.amount),
+                    tx.sender.as_str(),
+                    current_balance.saturating_sub(deduction),
                 )?;
             }
 
-            // Note: Mining reward (COINBASE) is already a transaction from SYSTEM to Author in modern blocks.
-            // If it's an old block or missing coinbase, we can add it here if needed,
-            // but the mining loop in lib.rs already creates a SYSTEM transaction.
+            // Handle Receivers (Add amount)
+            for (receiver, amount) in tx.credits() {
+                let current_recv_balance =
+                    state_table.get(receiver)?.map(|v| v.value()).unwrap_or(0);
+                state_table.insert(receiver, current_recv_balance.saturating_add(amount))?;
+            }
         }
-        write_txn.commit()?;
+
+        // Note: Mining reward (COINBASE) is already a transaction from SYSTEM to Author in modern blocks.
+        // If it's an old block or missing coinbase, we can add it here if needed,
+        // but the mining loop in lib.rs already creates a SYSTEM transaction.
         Ok(())
     }
 
+    /// Undoes everything `apply_block` wrote for `block`.
+    fn revert_block(write_txn: &WriteTransaction, block: &Block) -> Result<(), anyhow::Error> {
+        let mut blocks_table = write_txn.open_table(BLOCKS_TABLE)?;
+        let mut state_table = write_txn.open_table(STATE_TABLE)?;
+        let mut tx_index = write_txn.open_table(TX_INDEX_TABLE)?;
+        let mut applied_receipts = write_txn.open_table(APPLIED_RECEIPTS_TABLE)?;
+        let mut nonces = write_txn.open_table(NONCES_TABLE)?;
+        let mut block_hashes = write_txn.open_table(BLOCK_HASHES_TABLE)?;
+        let mut address_txs = write_txn.open_table(ADDRESS_TXS_TABLE)?;
+        let mut supply = write_txn.open_table(SUPPLY_TABLE)?;
+        let mut aliases = write_txn.open_table(ALIAS_TABLE)?;
+        let mut author_counts = write_txn.open_table(AUTHOR_COUNT_TABLE)?;
+
+        blocks_table.remove(block.index)?;
+        block_hashes.remove(block.hash.as_str())?;
+        let authored = author_counts
+            .get(block.author.as_str())?
+            .map_or(0, |v| v.value());
+        if authored > 1 {
+            author_counts.insert(block.author.as_str(), authored - 1)?;
+        } else {
+            author_counts.remove(block.author.as_str())?;
+        }
+        for (key, _) in address_keys(block) {
+            address_txs.remove(key)?;
+        }
+        let checkpoint = state_table.get(SYNC_CHECKPOINT_KEY)?.map(|v| v.value());
+        if checkpoint.is_some_and(|height| height >= block.index) {
+            match block.index.checked_sub(1) {
+                Some(parent) => state_table.insert(SYNC_CHECKPOINT_KEY, parent)?,
+                None => state_table.remove(SYNC_CHECKPOINT_KEY)?,
+            };
+        }
+
+        let burned = block.burned_fees();
+        if burned > 0 {
+            let total = supply.get(BURNED_FEES_KEY)?.map(|v| v.value()).unwrap_or(0);
+            supply.insert(BURNED_FEES_KEY, total.saturating_sub(burned))?;
+        }
+        let user_txs = block.user_transaction_count();
+        if user_txs > 0 {
+            let total = supply.get(TOTAL_TXS_KEY)?.map(|v| v.value()).unwrap_or(0);
+            supply.insert(TOTAL_TXS_KEY, total.saturating_sub(user_txs))?;
+        }
+
+        for tx in block.transactions.iter().rev() {
+            let indexed_at = tx_index.get(tx.id.as_str())?.map(|v| v.value());
+            if indexed_at == Some(block.index) {
+                tx_index.remove(tx.id.as_str())?;
+            }
+            if let Some(receipt_id) = tx.receipt_source_id() {
+                applied_receipts.remove(receipt_id)?;
+            }
+            if let Some(alias) = &tx.alias {
+                aliases.remove(alias.as_str())?;
+            }
+
+            for (receiver, amount) in tx.credits() {
+                let current_recv_balance =
+                    state_table.get(receiver)?.map(|v| v.value()).unwrap_or(0);
+                state_table.insert(receiver, current_recv_balance.saturating_sub(amount))?;
+            }
+
+            if !tx.is_system() && !tx.is_receipt_mint() {
+                if tx.nonce == 0 {
+                    nonces.remove(tx.sender.as_str())?;
+                } else {
+                    nonces.insert(tx.sender.as_str(), tx.nonce)?;
+                }
+
+                let current_balance = state_table
+                    .get(tx.sender.as_str())?
+                    .map(|v| v.value())
+                    .unwrap_or(0);
+                let refund = tx.amount.saturating_add(tx.fee());
+                state_table.insert(tx.sender.as_str(), current_balance.saturating_add(refund))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the local chain from `blocks[0].index` upward with `blocks`,
+    /// reverting the orphaned blocks and applying the new branch in a single
+    /// write transaction. Returns the orphaned blocks, lowest index first.
+    pub fn reorg_to(&self, blocks: Vec<Block>) -> Result<Vec<Block>, anyhow::Error> {
+        let first = blocks
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("Empty reorg branch"))?;
+        if first.index == 0 {
+            anyhow::bail!("Cannot reorg the genesis block");
+        }
+        let parent = self
+            .get_block(first.index - 1)?
+            .ok_or_else(|| anyhow::anyhow!("Missing fork parent {}", first.index - 1))?;
+        if first.previous_hash != parent.hash {
+            anyhow::bail!("Branch does not attach to block {}", parent.index);
+        }
+        for pair in blocks.windows(2) {
+            if pair[1].index != pair[0].index + 1 || pair[1].previous_hash != pair[0].hash {
+                anyhow::bail!("Branch is not contiguous at block {}", pair[1].index);
+            }
+        }
+
+        let tip = self.get_latest_index()?;
+        let mut orphaned = Vec::new();
+        for index in first.index..=tip {
+            if let Some(block) = self.get_block(index)? {
+                orphaned.push(block);
+            }
+        }
+
+        let write_txn = self.db.begin_write()?;
+        for block in orphaned.iter().rev() {
+            Self::revert_block(&write_txn, block)?;
+        }
+        for block in &blocks {
+            if let Err(e) = Self::apply_block(&write_txn, block) {
+                write_txn.abort()?;
+                return Err(e);
+            }
+            let root = {
+                let table = write_txn.open_table(STATE_TABLE)?;
+                Self::state_root_of(&table)?
+            };
+            if root != block.state_root {
+                write_txn.abort()?;
+                let reason = format!("State root mismatch at block {}", block.index);
+                return Err(InvalidBlockState(reason).into());
+            }
+        }
+        write_txn.commit()?;
+        Ok(orphaned)
+    }
+
+    /// Rebuilds the derived tables (balances, nonces, applied receipts, tx index,
+    /// burned fees, aliases) by replaying every stored block in order; returns the
+    /// resulting state root. Fails without changing anything if a block's
+    /// transactions were pruned.
+    pub fn recompute_state(&self) -> Result<String, anyhow::Error> {
+        let write_txn = self.db.begin_write()?;
+        let blocks = {
+            let table = write_txn.open_table(BLOCKS_TABLE)?;
+            let mut blocks = Vec::new();
+            for item in table.iter()? {
+                let (_, value) = item?;
+                let block: Block = serde_json::from_str(value.value())?;
+                ensure_not_pruned(&block)?;
+                blocks.push(block);
+            }
+            blocks
+        };
+
+        // The stored blocks are unchanged, so the sync checkpoint survives the replay
+        {
+            let mut state_table = write_txn.open_table(STATE_TABLE)?;
+            let checkpoint = state_table.get(SYNC_CHECKPOINT_KEY)?.map(|v| v.value());
+            clear_table(&mut state_table)?;
+            if let Some(height) = checkpoint {
+                state_table.insert(SYNC_CHECKPOINT_KEY, height)?;
+            }
+        }
+        clear_table(&mut write_txn.open_table(NONCES_TABLE)?)?;
+        clear_table(&mut write_txn.open_table(APPLIED_RECEIPTS_TABLE)?)?;
+        clear_table(&mut write_txn.open_table(TX_INDEX_TABLE)?)?;
+        clear_table(&mut write_txn.open_table(SUPPLY_TABLE)?)?;
+        clear_table(&mut write_txn.open_table(ALIAS_TABLE)?)?;
+        clear_table(&mut write_txn.open_table(AUTHOR_COUNT_TABLE)?)?;
+        for block in &blocks {
+            Self::apply_block(&write_txn, block)?;
+        }
+
+        let root = {
+            let table = write_txn.open_table(STATE_TABLE)?;
+            Self::state_root_of(&table)?
+        };
+        write_txn.commit()?;
+        Ok(root)
+    }
+
     pub fn get_block(&self, index: u64) -> Result<Option<Block>, anyhow::Error> {
+        self.block_reads.fetch_add(1, Ordering::Relaxed);
         let read_txn = self.db.begin_read()?;
         let table = read_txn.open_table(BLOCKS_TABLE)?;
         let result = match table.get(index)? {
@@ -110,6 +600,8 @@ impl Storage {
         Ok(blocks)
     }
 
+    /// Page `page` (1-based) of `limit` blocks, newest first. Blocks are keyed
+    /// by contiguous index, so the page is read as one key range.
     pub fn get_blocks_paginated(
         &self,
         page: usize,
@@ -117,34 +609,27 @@ impl Storage {
     ) -> Result<Vec<Block>, anyhow::Error> {
         let read_txn = self.db.begin_read()?;
         let table = read_txn.open_table(BLOCKS_TABLE)?;
-        let mut blocks = Vec::new();
 
-        let total = table.len()?;
-        if total == 0 {
-            return Ok(blocks);
-        }
-
-        let skip = (page.saturating_sub(1)) * limit;
-        let mut iter = table.iter()?;
-
-        // Skip logic
-        for _ in 0..skip {
-            if iter.next_back().is_none() {
-                break;
-            }
+        let Some(tip) = table
+            .iter()?
+            .next_back()
+            .transpose()?
+            .map(|(k, _)| k.value())
+        else {
+            return Ok(Vec::new());
+        };
+        let skip = (page.saturating_sub(1) as u64).saturating_mul(limit as u64);
+        if limit == 0 || skip > tip {
+            return Ok(Vec::new());
         }
+        let newest = tip - skip;
+        let oldest = newest.saturating_sub(limit as u64 - 1);
 
-        // Take from back
-        while blocks.len() < limit {
-            if let Some(res) = iter.next_back() {
-                let (_, value) = res?;
-                let block: Block = serde_json::from_str(value.value())?;
-                blocks.push(block);
-            } else {
-                break;
-            }
+        let mut blocks = Vec::new();
+        for item in table.range(oldest..=newest)?.rev() {
+            let (_, value) = item?;
+            blocks.push(serde_json::from_str(value.value())?);
         }
-
         Ok(blocks)
     }
 
@@ -167,7 +652,21 @@ impl Storage {
         Ok(table.len()?)
     }
 
-    // Save wallet keys securely (in real app, encrypt this!)
+    /// Writes every stored block to `out` as one JSON line, lowest index first.
+    /// Runs inside a single read transaction, so concurrent writes don't tear it.
+    pub fn export_blocks(&self, out: &mut impl std::io::Write) -> Result<u64, anyhow::Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(BLOCKS_TABLE)?;
+        let mut count = 0;
+        for res in table.iter()? {
+            let (_, value) = res?;
+            writeln!(out, "{}", value.value())?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Stores the serialized wallet key (see `wallet::encryption::StoredKey`).
     pub fn save_wallet_keys(&self, keys_json: &str) -> Result<(), anyhow::Error> {
         let write_txn = self.db.begin_write()?;
         {
@@ -188,15 +687,114 @@ impl Storage {
         Ok(result)
     }
 
+    /// Seed and account list of an HD wallet, stored beside its active key.
+    pub fn save_wallet_hd(&self, record_json: &str) -> Result<(), anyhow::Error> {
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(WALLET_TABLE)?;
+            table.insert("hd_record", record_json)?;
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    pub fn get_wallet_hd(&self) -> Result<Option<String>, anyhow::Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(WALLET_TABLE)?;
+        let result = table
+            .get("hd_record")?
+            .map(|guard| guard.value().to_string());
+        Ok(result)
+    }
+
+    pub fn delete_wallet_hd(&self) -> Result<(), anyhow::Error> {
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(WALLET_TABLE)?;
+            table.remove("hd_record")?;
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
     pub fn delete_wallet_keys(&self) -> Result<(), anyhow::Error> {
         let write_txn = self.db.begin_write()?;
         {
             let mut table = write_txn.open_table(WALLET_TABLE)?;
             table.remove("main_key")?;
+            table.remove("hd_record")?;
         }
         write_txn.commit()?;
         Ok(())
     }
+    /// Balance of `address` as of block `height`, replayed from genesis.
+    /// Fails if any block up to `height` has had its transactions pruned.
+    pub fn balance_at_height(&self, address: &str, height: u64) -> Result<u64, anyhow::Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(BLOCKS_TABLE)?;
+        let tip = match table.iter()?.next_back() {
+            Some(item) => item?.0.value(),
+            None => anyhow::bail!("Chain is empty"),
+        };
+        if height > tip {
+            anyhow::bail!("Height {} is above the chain tip ({})", height, tip);
+        }
+
+        let mut balance = 0u64;
+        for item in table.range(0..=height)? {
+            let (_, value) = item?;
+            let block: Block = serde_json::from_str(value.value())?;
+            ensure_not_pruned(&block)?;
+            // Same effects, in the same order, as `apply_block`
+            for tx in &block.transactions {
+                if tx.sender == address && !tx.is_system() && !tx.is_receipt_mint() {
+                    balance = balance.saturating_sub(tx.amount.saturating_add(tx.fee()));
+                }
+                for (receiver, amount) in tx.credits() {
+                    if receiver == address {
+                        balance = balance.saturating_add(amount);
+                    }
+                }
+            }
+        }
+        Ok(balance)
+    }
+
+    /// Transactions sent or received by `address` with their block index, newest
+    /// first. Pages are 0-based.
+    pub fn get_transactions_for_address(
+        &self,
+        address: &str,
+        page: usize,
+        limit: usize,
+    ) -> Result<Vec<(crate::chain::Transaction, u64)>, anyhow::Error> {
+        let read_txn = self.db.begin_read()?;
+        let index = read_txn.open_table(ADDRESS_TXS_TABLE)?;
+        let blocks = read_txn.open_table(BLOCKS_TABLE)?;
+
+        let mut block: Option<Block> = None;
+        let mut txs = Vec::new();
+        let range = index.range((address, 0, 0)..=(address, u64::MAX, u32::MAX))?;
+        for item in range.rev().skip(page.saturating_mul(limit)).take(limit) {
+            let (key, _) = item?;
+            let (_, block_index, position) = key.value();
+            if block.as_ref().map(|b| b.index) != Some(block_index) {
+                let json = blocks
+                    .get(block_index)?
+                    .ok_or_else(|| anyhow::anyhow!("Indexed block {} is missing", block_index))?;
+                block = Some(serde_json::from_str(json.value())?);
+            }
+            let tx = block
+                .as_ref()
+                .and_then(|b| b.transactions.get(position as usize))
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Indexed tx {}:{} is missing", block_index, position)
+                })?;
+            txs.push((tx.clone(), block_index));
+        }
+        Ok(txs)
+    }
+
     pub fn calculate_balance(&self, address: &str) -> Result<u64, anyhow::Error> {
         let read_txn = self.db.begin_read()?;
         let table = read_txn.open_table(STATE_TABLE)?;
@@ -208,33 +806,133 @@ impl Storage {
 
         Ok(balance)
     }
-    pub fn count_blocks_by_author(&self, address: &str) -> Result<u64, anyhow::Error> {
+
+    /// The `limit` largest non-zero balances, richest first and ties by
+    /// address. Holds at most `limit` accounts in memory, whatever the state size.
+    pub fn get_top_balances(&self, limit: usize) -> Result<Vec<(String, u64)>, anyhow::Error> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
         let read_txn = self.db.begin_read()?;
-        let table = read_txn.open_table(BLOCKS_TABLE)?;
-        let mut count = 0;
-        let iter = table.iter()?;
-        for item in iter {
-            let (_, value) = item?;
-            let block: Block = serde_json::from_str(value.value())?;
-            if block.author == address {
-                count += 1;
+        let table = read_txn.open_table(STATE_TABLE)?;
+
+        // Min-heap: its top is the poorest holder kept, first to be displaced
+        let mut top: BinaryHeap<Reverse<(u64, Reverse<String>)>> = BinaryHeap::with_capacity(limit);
+        for item in table.iter()? {
+            let (address, balance) = item?;
+            let (address, balance) = (address.value(), balance.value());
+            if balance == 0 || address == SYNC_CHECKPOINT_KEY {
+                continue;
+            }
+            let richer = top.len() < limit
+                || top
+                    .peek()
+                    .is_some_and(|Reverse((min, Reverse(min_address)))| {
+                        (balance, Reverse(address)) > (*min, Reverse(min_address.as_str()))
+                    });
+            if richer {
+                if top.len() == limit {
+                    top.pop();
+                }
+                top.push(Reverse((balance, Reverse(address.to_string()))));
             }
         }
-        Ok(count)
+        Ok(top
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((balance, Reverse(address)))| (address, balance))
+            .collect())
     }
 
-    pub fn get_block_by_hash(&self, hash: &str) -> Result<Option<Block>, anyhow::Error> {
+    /// Blocks on the chain authored by `address`.
+    pub fn count_blocks_by_author(&self, address: &str) -> Result<u64, anyhow::Error> {
         let read_txn = self.db.begin_read()?;
-        let table = read_txn.open_table(BLOCKS_TABLE)?;
-        let iter = table.iter()?;
-        for item in iter {
-            let (_, value) = item?;
-            let block: Block = serde_json::from_str(value.value())?;
-            if block.hash == hash {
-                return Ok(Some(block));
+        let table = read_txn.open_table(AUTHOR_COUNT_TABLE)?;
+        let count = table.get(address)?.map_or(0, |v| v.value());
+        Ok(count)
+    }
+
+    /// Stores consecutive headers. A header that differs from the stored one at
+    /// its index starts a new branch, so the stored headers above the batch go.
+    pub fn save_headers(&self, headers: &[Header]) -> Result<(), anyhow::Error> {
+        let Some(last) = headers.last() else {
+            return Ok(());
+        };
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(HEADERS_TABLE)?;
+            let mut forked = false;
+            for header in headers {
+                let json = serde_json::to_string(header)?;
+                let replaced = table.insert(header.index, json.as_str())?;
+                forked |= replaced.is_some_and(|old| old.value() != json);
+            }
+            if forked {
+                let stale: Vec<u64> = table
+                    .range(last.index + 1..)?
+                    .map(|item| item.map(|(index, _)| index.value()))
+                    .collect::<Result<_, _>>()?;
+                for index in stale {
+                    table.remove(index)?;
+                }
             }
         }
-        Ok(None)
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    pub fn get_header(&self, index: u64) -> Result<Option<Header>, anyhow::Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(HEADERS_TABLE)?;
+        let result = match table.get(index)? {
+            Some(guard) => Some(serde_json::from_str(guard.value())?),
+            None => None,
+        };
+        Ok(result)
+    }
+
+    /// Index of the highest stored header, `None` before any header arrived.
+    pub fn get_header_tip(&self) -> Result<Option<u64>, anyhow::Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(HEADERS_TABLE)?;
+        let mut iter = table.iter()?;
+        let tip = match iter.next_back() {
+            Some(item) => Some(item?.0.value()),
+            None => None,
+        };
+        Ok(tip)
+    }
+
+    /// Highest height up to which the chain is known to be contiguous, as
+    /// recorded by sync; `None` if sync never recorded one.
+    pub fn get_sync_checkpoint(&self) -> Result<Option<u64>, anyhow::Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(STATE_TABLE)?;
+        let checkpoint = table.get(SYNC_CHECKPOINT_KEY)?.map(|v| v.value());
+        Ok(checkpoint)
+    }
+
+    pub fn save_sync_checkpoint(&self, height: u64) -> Result<(), anyhow::Error> {
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(STATE_TABLE)?;
+            table.insert(SYNC_CHECKPOINT_KEY, height)?;
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    pub fn get_block_by_hash(&self, hash: &str) -> Result<Option<Block>, anyhow::Error> {
+        let index = {
+            let read_txn = self.db.begin_read()?;
+            let block_hashes = read_txn.open_table(BLOCK_HASHES_TABLE)?;
+            let entry = block_hashes.get(hash)?;
+            entry.map(|guard| guard.value())
+        };
+        let Some(index) = index else {
+            return Ok(None);
+        };
+        Ok(self.get_block(index)?.filter(|block| block.hash == hash))
     }
 
     pub fn get_transaction_by_id(
@@ -248,61 +946,248 @@ impl Storage {
             entry.map(|guard| guard.value())
         };
 
-        if let Some(idx) = indexed_block {
-            if let Some(block) = self.get_block(idx)? {
-                if let Some(tx) = block.transactions.iter().find(|t| t.id == tx_id) {
-                    return Ok(Some((tx.clone(), block)));
+        if let Some(idx) = indexed_block {
+            if let Some(block) = self.get_block(idx)? {
+                if let Some(tx) = block.transactions.iter().find(|t| t.id == tx_id) {
+                    return Ok(Some((tx.clone(), block)));
+                }
+            }
+        }
+
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(BLOCKS_TABLE)?;
+        let iter = table.iter()?;
+        for item in iter {
+            let (_, value) = item?;
+            let block: Block = serde_json::from_str(value.value())?;
+            if let Some(tx) = block.transactions.iter().find(|t| t.id == tx_id) {
+                return Ok(Some((tx.clone(), block)));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn is_receipt_applied(&self, original_tx_id: &str) -> Result<bool, anyhow::Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(APPLIED_RECEIPTS_TABLE)?;
+        let found = table.get(original_tx_id)?.is_some();
+        Ok(found)
+    }
+
+    /// Nonce the sender's next transaction must carry (number of mined txs sent).
+    pub fn get_next_nonce(&self, address: &str) -> Result<u64, anyhow::Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(NONCES_TABLE)?;
+        let nonce = table.get(address)?.map(|v| v.value()).unwrap_or(0);
+        Ok(nonce)
+    }
+
+    pub fn is_tx_mined(&self, tx_id: &str) -> Result<bool, anyhow::Error> {
+        let read_txn = self.db.begin_read()?;
+        let tx_index = read_txn.open_table(TX_INDEX_TABLE)?;
+        let found = tx_index.get(tx_id)?.is_some();
+        Ok(found)
+    }
+
+    /// Replaces the persisted validator set with `nodes`.
+    pub fn save_consensus_nodes(
+        &self,
+        nodes: &std::collections::HashMap<String, crate::consensus::NodeState>,
+    ) -> Result<(), anyhow::Error> {
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(VALIDATORS_TABLE)?;
+            let stale: Vec<String> = table
+                .iter()?
+                .map(|i| i.unwrap().0.value().to_string())
+                .filter(|peer_id| !nodes.contains_key(peer_id))
+                .collect();
+            for peer_id in stale {
+                table.remove(peer_id.as_str())?;
+            }
+            for node in nodes.values() {
+                let json = serde_json::to_string(node)?;
+                table.insert(node.peer_id.as_str(), json.as_str())?;
+            }
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    pub fn load_consensus_nodes(
+        &self,
+    ) -> Result<std::collections::HashMap<String, crate::consensus::NodeState>, anyhow::Error> {
+        let nodes = self.load_all_node_states()?;
+        if !nodes.is_empty() {
+            return Ok(nodes);
+        }
+
+        // Legacy: whole map stored as one JSON blob in settings
+        match self.get_setting("consensus_nodes")? {
+            Some(s) => Ok(serde_json::from_str(&s)?),
+            None => Ok(std::collections::HashMap::new()),
+        }
+    }
+
+    pub fn save_node_state(&self, node: &crate::consensus::NodeState) -> Result<(), anyhow::Error> {
+        let json = serde_json::to_string(node)?;
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(VALIDATORS_TABLE)?;
+            table.insert(node.peer_id.as_str(), json.as_str())?;
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    pub fn load_all_node_states(
+        &self,
+    ) -> Result<std::collections::HashMap<String, crate::consensus::NodeState>, anyhow::Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(VALIDATORS_TABLE)?;
+        let mut nodes = std::collections::HashMap::new();
+        for item in table.iter()? {
+            let (peer_id, value) = item?;
+            let node: crate::consensus::NodeState = serde_json::from_str(value.value())?;
+            nodes.insert(peer_id.value().to_string(), node);
+        }
+        Ok(nodes)
+    }
+
+    /// Stores `record`, replacing the peer's previous one. A manually added
+    /// peer stays manual and keeps the addresses it was added with.
+    pub fn save_peer(&self, record: &PeerRecord) -> Result<(), anyhow::Error> {
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(PEERS_TABLE)?;
+            let previous = table
+                .get(record.peer_id.as_str())?
+                .map(|v| serde_json::from_str::<PeerRecord>(v.value()))
+                .transpose()?;
+            let mut record = record.clone();
+            if let Some(previous) = previous.filter(|p| p.manual || record.manual) {
+                record.manual = true;
+                for address in previous.addresses {
+                    if !record.addresses.contains(&address) {
+                        record.addresses.push(address);
+                    }
                 }
             }
+            let json = serde_json::to_string(&record)?;
+            table.insert(record.peer_id.as_str(), json.as_str())?;
         }
+        write_txn.commit()?;
+        Ok(())
+    }
 
+    /// Up to `limit` known peers: manually added ones first, then the most
+    /// recently seen.
+    pub fn load_peers(&self, limit: usize) -> Result<Vec<PeerRecord>, anyhow::Error> {
         let read_txn = self.db.begin_read()?;
-        let table = read_txn.open_table(BLOCKS_TABLE)?;
-        let iter = table.iter()?;
-        for item in iter {
+        let table = read_txn.open_table(PEERS_TABLE)?;
+        let mut peers = Vec::new();
+        for item in table.iter()? {
             let (_, value) = item?;
-            let block: Block = serde_json::from_str(value.value())?;
-            if let Some(tx) = block.transactions.iter().find(|t| t.id == tx_id) {
-                return Ok(Some((tx.clone(), block)));
-            }
+            peers.push(serde_json::from_str::<PeerRecord>(value.value())?);
         }
-        Ok(None)
+        peers.sort_by_key(|p| (std::cmp::Reverse(p.manual), std::cmp::Reverse(p.last_seen)));
+        peers.truncate(limit);
+        Ok(peers)
     }
 
-    pub fn is_tx_mined(&self, tx_id: &str) -> Result<bool, anyhow::Error> {
-        let read_txn = self.db.begin_read()?;
-        let tx_index = read_txn.open_table(TX_INDEX_TABLE)?;
-        let found = tx_index.get(tx_id)?.is_some();
-        Ok(found)
+    /// Forgets peers not seen within `max_age_secs` of `now`, then the least
+    /// recently seen ones beyond `max_count`; returns how many were removed.
+    /// Manually added peers are kept.
+    pub fn prune_peers(
+        &self,
+        now: u64,
+        max_count: usize,
+        max_age_secs: u64,
+    ) -> Result<usize, anyhow::Error> {
+        let write_txn = self.db.begin_write()?;
+        let removed = {
+            let mut table = write_txn.open_table(PEERS_TABLE)?;
+            let mut peers: Vec<(String, u64)> = Vec::new();
+            for item in table.iter()? {
+                let (peer_id, value) = item?;
+                let record: PeerRecord = serde_json::from_str(value.value())?;
+                if !record.manual {
+                    peers.push((peer_id.value().to_string(), record.last_seen));
+                }
+            }
+            peers.sort_by_key(|(_, last_seen)| std::cmp::Reverse(*last_seen));
+            let stale: Vec<String> = peers
+                .into_iter()
+                .enumerate()
+                .filter(|(rank, (_, last_seen))| {
+                    *rank >= max_count || last_seen.saturating_add(max_age_secs) < now
+                })
+                .map(|(_, (peer_id, _))| peer_id)
+                .collect();
+            for peer_id in &stale {
+                table.remove(peer_id.as_str())?;
+            }
+            stale.len()
+        };
+        write_txn.commit()?;
+        Ok(removed)
     }
 
-    pub fn save_consensus_nodes(
+    pub fn save_equivocation(
         &self,
-        nodes: &std::collections::HashMap<String, crate::consensus::NodeState>,
+        evidence: &crate::consensus::EquivocationEvidence,
+    ) -> Result<(), anyhow::Error> {
+        self.save_evidence(EQUIVOCATIONS_TABLE, evidence)
+    }
+
+    /// All recorded equivocation evidence, ordered by author then slot.
+    pub fn load_equivocations(
+        &self,
+    ) -> Result<Vec<crate::consensus::EquivocationEvidence>, anyhow::Error> {
+        self.load_evidence(EQUIVOCATIONS_TABLE)
+    }
+
+    pub fn save_slashing(
+        &self,
+        evidence: &crate::consensus::EquivocationEvidence,
+    ) -> Result<(), anyhow::Error> {
+        self.save_evidence(SLASHING_TABLE, evidence)
+    }
+
+    /// Evidence of every reported slash, ordered by author then slot.
+    pub fn load_slashings(
+        &self,
+    ) -> Result<Vec<crate::consensus::EquivocationEvidence>, anyhow::Error> {
+        self.load_evidence(SLASHING_TABLE)
+    }
+
+    fn save_evidence(
+        &self,
+        definition: TableDefinition<(&str, u64), &str>,
+        evidence: &crate::consensus::EquivocationEvidence,
     ) -> Result<(), anyhow::Error> {
-        let json = serde_json::to_string(nodes)?;
+        let json = serde_json::to_string(evidence)?;
         let write_txn = self.db.begin_write()?;
         {
-            let mut table = write_txn.open_table(SETTINGS_TABLE)?;
-            table.insert("consensus_nodes", json.as_str())?;
+            let mut table = write_txn.open_table(definition)?;
+            table.insert((evidence.author.as_str(), evidence.slot), json.as_str())?;
         }
         write_txn.commit()?;
         Ok(())
     }
 
-    pub fn load_consensus_nodes(
+    fn load_evidence(
         &self,
-    ) -> Result<std::collections::HashMap<String, crate::consensus::NodeState>, anyhow::Error> {
-        let json = {
-            let read_txn = self.db.begin_read()?;
-            let table = read_txn.open_table(SETTINGS_TABLE)?;
-            let entry = table.get("consensus_nodes")?;
-            entry.map(|guard| guard.value().to_string())
-        };
-        match json {
-            Some(s) => Ok(serde_json::from_str(&s)?),
-            None => Ok(std::collections::HashMap::new()),
+        definition: TableDefinition<(&str, u64), &str>,
+    ) -> Result<Vec<crate::consensus::EquivocationEvidence>, anyhow::Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(definition)?;
+        let mut evidence = Vec::new();
+        for item in table.iter()? {
+            let (_, value) = item?;
+            evidence.push(serde_json::from_str(value.value())?);
         }
+        Ok(evidence)
     }
 
     pub fn save_setting(&self, key: &str, value: &str) -> Result<(), anyhow::Error> {
@@ -315,6 +1200,54 @@ impl Storage {
         Ok(())
     }
 
+    /// Commits an empty transaction with immediate durability, so every
+    /// earlier commit is on disk when this returns.
+    pub fn flush(&self) -> Result<(), anyhow::Error> {
+        let mut write_txn = self.db.begin_write()?;
+        write_txn.set_durability(Durability::Immediate);
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    /// Fees burned by all blocks on the chain.
+    pub fn get_burned_fees(&self) -> Result<u64, anyhow::Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(SUPPLY_TABLE)?;
+        let burned = table.get(BURNED_FEES_KEY)?.map(|v| v.value()).unwrap_or(0);
+        Ok(burned)
+    }
+
+    /// User transactions in all blocks on the chain.
+    pub fn count_total_transactions(&self) -> Result<u64, anyhow::Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(SUPPLY_TABLE)?;
+        let total = table.get(TOTAL_TXS_KEY)?.map(|v| v.value()).unwrap_or(0);
+        Ok(total)
+    }
+
+    /// Sum of every balance in the state table, for checking it against the
+    /// issued supply.
+    pub fn sum_balances(&self) -> Result<u64, anyhow::Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(STATE_TABLE)?;
+        let mut total = 0u64;
+        for item in table.iter()? {
+            let (address, balance) = item?;
+            if address.value() != SYNC_CHECKPOINT_KEY {
+                total = total.saturating_add(balance.value());
+            }
+        }
+        Ok(total)
+    }
+
+    /// Owner of an on-chain alias.
+    pub fn resolve_alias(&self, alias: &str) -> Result<Option<String>, anyhow::Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(ALIAS_TABLE)?;
+        let owner = table.get(alias)?.map(|v| v.value().to_string());
+        Ok(owner)
+    }
+
     pub fn get_setting(&self, key: &str) -> Result<Option<String>, anyhow::Error> {
         let read_txn = self.db.begin_read()?;
         let table = read_txn.open_table(SETTINGS_TABLE)?;
@@ -394,6 +1327,56 @@ impl Storage {
             for k in tx_keys {
                 tx_index.remove(k.as_str())?;
             }
+
+            let mut applied_receipts = write_txn.open_table(APPLIED_RECEIPTS_TABLE)?;
+            let receipt_keys: Vec<String> = applied_receipts
+                .iter()?
+                .map(|i| i.unwrap().0.value().to_string())
+                .collect();
+            for k in receipt_keys {
+                applied_receipts.remove(k.as_str())?;
+            }
+
+            let mut nonces = write_txn.open_table(NONCES_TABLE)?;
+            let nonce_keys: Vec<String> = nonces
+                .iter()?
+                .map(|i| i.unwrap().0.value().to_string())
+                .collect();
+            for k in nonce_keys {
+                nonces.remove(k.as_str())?;
+            }
+
+            let mut block_hashes = write_txn.open_table(BLOCK_HASHES_TABLE)?;
+            let hash_keys: Vec<String> = block_hashes
+                .iter()?
+                .map(|i| i.unwrap().0.value().to_string())
+                .collect();
+            for k in hash_keys {
+                block_hashes.remove(k.as_str())?;
+            }
+
+            let mut address_txs = write_txn.open_table(ADDRESS_TXS_TABLE)?;
+            let addr_keys: Vec<(String, u64, u32)> = address_txs
+                .iter()?
+                .map(|i| {
+                    let entry = i.unwrap();
+                    let (address, index, position) = entry.0.value();
+                    (address.to_string(), index, position)
+                })
+                .collect();
+            for (address, index, position) in addr_keys {
+                address_txs.remove((address.as_str(), index, position))?;
+            }
+
+            clear_table(&mut write_txn.open_table(ALIAS_TABLE)?)?;
+            clear_table(&mut write_txn.open_table(AUTHOR_COUNT_TABLE)?)?;
+            clear_table(&mut write_txn.open_table(SUPPLY_TABLE)?)?;
+
+            let mut headers = write_txn.open_table(HEADERS_TABLE)?;
+            let header_keys: Vec<u64> = headers.iter()?.map(|i| i.unwrap().0.value()).collect();
+            for k in header_keys {
+                headers.remove(k)?;
+            }
         }
         write_txn.commit()?;
         Ok(())
@@ -436,9 +1419,14 @@ impl Storage {
         let mut count = 0;
         {
             let mut table = write_txn.open_table(BLOCKS_TABLE)?;
+            let mut address_txs = write_txn.open_table(ADDRESS_TXS_TABLE)?;
             for (idx, json) in to_prune {
                 let mut block: Block = serde_json::from_str(&json)?;
                 if !block.transactions.is_empty() {
+                    // Pruned transactions drop out of address history
+                    for (key, _) in address_keys(&block) {
+                        address_txs.remove(key)?;
+                    }
                     block.transactions = Vec::new();
                     let pruned_json = serde_json::to_string(&block)?;
                     table.insert(idx, pruned_json.as_str())?;
@@ -469,3 +1457,869 @@ impl Storage {
         Ok(())
     }
 }
+
+/// Address index keys for `block`, each with the transaction it points at.
+/// User transactions are indexed under sender and receivers; mints only under
+/// the receiver.
+fn address_keys(block: &Block) -> Vec<((&str, u64, u32), &crate::chain::Transaction)> {
+    let mut keys = Vec::new();
+    for (position, tx) in block.transactions.iter().enumerate() {
+        let position = position as u32;
+        let mut addresses: Vec<&str> = tx.credits().into_iter().map(|(r, _)| r).collect();
+        if !tx.is_system() && !tx.is_receipt_mint() {
+            addresses.push(tx.sender.as_str());
+        }
+        addresses.sort_unstable();
+        addresses.dedup();
+        for address in addresses {
+            keys.push(((address, block.index, position), tx));
+        }
+    }
+    keys
+}
+
+/// History pruning empties a block's transactions; its merkle root gives it away.
+fn ensure_not_pruned(block: &Block) -> Result<(), anyhow::Error> {
+    if block.merkle_root != calculate_merkle_root(&block.transactions) {
+        anyhow::bail!("History for block {} has been pruned", block.index);
+    }
+    Ok(())
+}
+
+/// Removes every entry from a string-keyed table.
+fn clear_table<V: redb::RedbValue + 'static>(
+    table: &mut redb::Table<&str, V>,
+) -> Result<(), anyhow::Error> {
+    let keys: Vec<String> = table
+        .iter()?
+        .map(|item| item.map(|(k, _)| k.value().to_string()))
+        .collect::<Result<_, _>>()?;
+    for key in keys {
+        table.remove(key.as_str())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+impl Storage {
+    /// Empty database in a fresh file under the system temp directory.
+    pub(crate) fn temp() -> Storage {
+        let path =
+            std::env::temp_dir().join(format!("centichain-test-{}.db", uuid::Uuid::new_v4()));
+        Storage::new(path.to_str().unwrap()).unwrap()
+    }
+
+    /// Closes the database and opens its file again, as a restart would.
+    pub(crate) fn reopen(self) -> Storage {
+        let path = self.path.clone();
+        drop(self);
+        Storage::new(path.to_str().unwrap()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::{calculate_fee, Receipt, ReceiptStatus, Transaction, SYSTEM_SIG_REWARD};
+
+    fn block_with(index: u64, mut txs: Vec<Transaction>) -> Block {
+        txs.insert(
+            0,
+            Transaction {
+                id: format!("coinbase-{index}"),
+                sender: "SYSTEM".into(),
+                receiver: "miner".into(),
+                amount: 1_000,
+                shard_id: 0,
+                timestamp: 0,
+                nonce: 0,
+                fee: 0,
+                signature: SYSTEM_SIG_REWARD.into(),
+                sender_pubkey: String::new(),
+                outputs: Vec::new(),
+                memo: None,
+                not_before_height: None,
+                alias: None,
+            },
+        );
+        Block::new(
+            index,
+            "miner".into(),
+            txs,
+            String::new(),
+            100,
+            100,
+            0,
+            0,
+            1_000,
+        )
+    }
+
+    #[test]
+    fn receipt_mint_applies_once() {
+        let storage = Storage::temp();
+        let receipt = Receipt {
+            original_tx_id: "src-tx".into(),
+            source_shard: 1,
+            target_shard: 0,
+            amount: 5_000,
+            receiver: "alice".into(),
+            block_hash: "pending".into(),
+            merkle_proof: vec![],
+            tx_index: 0,
+            status: ReceiptStatus::Pending,
+            source_header: None,
+            source_tx: None,
+            crosslink: None,
+        };
+
+        storage
+            .save_block(&block_with(0, vec![receipt.to_mint_tx()]))
+            .unwrap();
+        assert!(storage.is_receipt_applied("src-tx").unwrap());
+        assert_eq!(storage.calculate_balance("alice").unwrap(), 5_000);
+        assert_eq!(
+            storage
+                .calculate_balance(crate::chain::SYSTEM_XSHARD)
+                .unwrap(),
+            0
+        );
+
+        assert!(storage
+            .save_block(&block_with(1, vec![receipt.to_mint_tx()]))
+            .is_err());
+        assert_eq!(storage.calculate_balance("alice").unwrap(), 5_000);
+        assert!(storage.get_block(1).unwrap().is_none());
+    }
+
+    #[test]
+    fn nonces_apply_in_order_and_reject_replays() {
+        let storage = build_chain();
+        assert_eq!(storage.get_next_nonce("alice").unwrap(), 1);
+
+        let mut pay = transfer("c", "miner", "carol", 10);
+        pay.nonce = 1;
+        storage.save_block(&block_with(2, vec![pay])).unwrap();
+        assert_eq!(storage.get_next_nonce("miner").unwrap(), 2);
+
+        // Same nonce under a fresh id is still a replay
+        let mut replay = transfer("c-again", "miner", "carol", 10);
+        replay.nonce = 1;
+        assert!(storage.save_block(&block_with(3, vec![replay])).is_err());
+        assert_eq!(storage.calculate_balance("carol").unwrap(), 10);
+
+        let mut next = transfer("d", "miner", "carol", 10);
+        next.nonce = 2;
+        storage.save_block(&block_with(3, vec![next])).unwrap();
+        assert_eq!(storage.get_next_nonce("miner").unwrap(), 3);
+        assert_eq!(storage.calculate_balance("carol").unwrap(), 20);
+    }
+
+    #[test]
+    fn batch_payment_credits_every_output() {
+        let storage = build_chain();
+        let mut reward = transfer("reward", "SYSTEM", "alice", 10_000);
+        reward.signature = SYSTEM_SIG_REWARD.into();
+        storage.save_block(&block_with(2, vec![reward])).unwrap();
+
+        let mut batch = transfer("batch", "alice", "bob", 150);
+        batch.nonce = 1;
+        batch.outputs = vec![
+            ("bob".into(), 50),
+            ("carol".into(), 60),
+            ("dave".into(), 40),
+        ];
+        let alice = storage.calculate_balance("alice").unwrap();
+        let bob = storage.calculate_balance("bob").unwrap();
+        let spent = batch.amount + batch.fee();
+        storage.save_block(&block_with(3, vec![batch])).unwrap();
+
+        for (account, balance) in [
+            ("alice", alice - spent),
+            ("bob", bob + 50),
+            ("carol", 60),
+            ("dave", 40),
+        ] {
+            assert_eq!(
+                storage.calculate_balance(account).unwrap(),
+                balance,
+                "{account}"
+            );
+        }
+        assert_eq!(storage.balance_at_height("dave", 2).unwrap(), 0);
+        assert_eq!(storage.balance_at_height("dave", 3).unwrap(), 40);
+        let history = storage
+            .get_transactions_for_address("carol", 0, 10)
+            .unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].0.id, "batch");
+    }
+
+    #[test]
+    fn coinbase_fee_split_credits_treasury_and_counts_burns() {
+        let storage = build_chain();
+        let mut block = block_with(2, vec![]);
+        block.transactions[0].amount = 1_300;
+        block.transactions[0].outputs = vec![("miner".into(), 1_200), ("treasury".into(), 100)];
+        block.total_fees = 500;
+        block.total_reward = 1_500;
+        let miner = storage.calculate_balance("miner").unwrap();
+        storage.save_block(&block).unwrap();
+
+        assert_eq!(storage.calculate_balance("miner").unwrap(), miner + 1_200);
+        assert_eq!(storage.calculate_balance("treasury").unwrap(), 100);
+        assert_eq!(storage.get_burned_fees().unwrap(), 200);
+        assert_eq!(
+            crate::chain::calculate_circulating_supply(2, 200),
+            crate::chain::calculate_circulating_supply(2, 0) - 200
+        );
+
+        // Replaying the chain arrives at the same total
+        storage.recompute_state().unwrap();
+        assert_eq!(storage.get_burned_fees().unwrap(), 200);
+    }
+
+    #[test]
+    fn balances_add_up_to_the_issued_supply() {
+        use crate::chain::{calculate_circulating_supply, calculate_mining_reward};
+        use crate::utils::constants::GENESIS_SUPPLY;
+
+        let storage = Storage::temp();
+        let mut mint = transfer("genesis", "SYSTEM", "alice", GENESIS_SUPPLY);
+        mint.signature = crate::chain::SYSTEM_SIG_GENESIS.into();
+        let genesis = Block::new(
+            0,
+            "alice".into(),
+            vec![mint],
+            String::new(),
+            100,
+            100,
+            0,
+            0,
+            GENESIS_SUPPLY,
+        );
+        storage.save_block(&genesis).unwrap();
+
+        // Block 1 pays 60% of the fee to its miner and burns the rest
+        let payment = transfer("pay", "alice", "bob", 5_000);
+        let fee = payment.fee();
+        let mut block = block_with(1, vec![payment]);
+        block.transactions[0].amount = calculate_mining_reward(1) + fee * 6 / 10;
+        block.total_fees = fee;
+        block.total_reward = calculate_mining_reward(1) + fee;
+        storage.save_block(&block).unwrap();
+        let mut block = block_with(2, vec![]);
+        block.transactions[0].amount = calculate_mining_reward(2);
+        block.total_reward = calculate_mining_reward(2);
+        storage.save_block(&block).unwrap();
+
+        let burned = storage.get_burned_fees().unwrap();
+        assert_eq!(burned, fee - fee * 6 / 10);
+        assert_eq!(
+            storage.sum_balances().unwrap(),
+            calculate_circulating_supply(2, burned)
+        );
+        assert_eq!(storage.count_total_transactions().unwrap(), 1);
+
+        storage.recompute_state().unwrap();
+        assert_eq!(storage.count_total_transactions().unwrap(), 1);
+        // Reorganizing the payment away takes it out of the count
+        let mut rival = block_with(1, vec![]);
+        rival.transactions[0].amount = calculate_mining_reward(1);
+        rival.total_reward = calculate_mining_reward(1);
+        rival.previous_hash = genesis.hash.clone();
+        let scratch = Storage::temp();
+        scratch.save_block(&genesis).unwrap();
+        rival.state_root = scratch.compute_state_root_after(&rival).unwrap();
+        rival.hash = rival.calculate_hash();
+        storage.reorg_to(vec![rival]).unwrap();
+        assert_eq!(storage.count_total_transactions().unwrap(), 0);
+        assert_eq!(
+            storage.sum_balances().unwrap(),
+            calculate_circulating_supply(1, 0)
+        );
+    }
+
+    #[test]
+    fn resaving_a_block_is_idempotent() {
+        let storage = Storage::temp();
+        let block = block_with(0, vec![]);
+        storage.save_block(&block).unwrap();
+        storage.save_block(&block).unwrap();
+        assert_eq!(storage.calculate_balance("miner").unwrap(), 1_000);
+        assert_eq!(storage.get_latest_index().unwrap(), 0);
+    }
+
+    #[test]
+    fn conflicting_block_at_a_stored_index_is_rejected() {
+        let storage = Storage::temp();
+        storage.save_block(&block_with(0, vec![])).unwrap();
+
+        let mut rival = block_with(0, vec![]);
+        rival.timestamp += 1;
+        rival.hash = rival.calculate_hash();
+        assert!(storage.save_block(&rival).is_err());
+        assert_eq!(storage.calculate_balance("miner").unwrap(), 1_000);
+        assert_ne!(storage.get_block(0).unwrap().unwrap().hash, rival.hash);
+    }
+
+    #[test]
+    fn first_alias_registration_wins() {
+        let storage = build_chain();
+        let mut reward = transfer("reward", "SYSTEM", "alice", 10_000);
+        reward.signature = SYSTEM_SIG_REWARD.into();
+        let mut claim = transfer("claim", "alice", "alice", 0);
+        claim.nonce = 1;
+        claim.alias = Some("alice".into());
+        storage.save_block(&block_with(2, vec![reward])).unwrap();
+        let alice = storage.calculate_balance("alice").unwrap();
+        storage.save_block(&block_with(3, vec![claim])).unwrap();
+
+        assert_eq!(
+            storage.resolve_alias("alice").unwrap().as_deref(),
+            Some("alice")
+        );
+        assert_eq!(
+            storage.calculate_balance("alice").unwrap(),
+            alice - calculate_fee(0)
+        );
+
+        // A second claim on the name makes its block invalid
+        let mut squat = transfer("squat", "bob", "bob", 0);
+        squat.alias = Some("alice".into());
+        assert!(storage.save_block(&block_with(4, vec![squat])).is_err());
+        assert_eq!(storage.get_latest_index().unwrap(), 3);
+
+        storage.recompute_state().unwrap();
+        assert_eq!(
+            storage.resolve_alias("alice").unwrap().as_deref(),
+            Some("alice")
+        );
+        storage.reset_blocks().unwrap();
+        assert_eq!(storage.resolve_alias("alice").unwrap(), None);
+    }
+
+    fn transfer(id: &str, sender: &str, receiver: &str, amount: u64) -> Transaction {
+        Transaction {
+            id: id.into(),
+            sender: sender.into(),
+            receiver: receiver.into(),
+            amount,
+            shard_id: 0,
+            timestamp: 0,
+            nonce: 0,
+            fee: 0,
+            signature: String::new(),
+            sender_pubkey: String::new(),
+            outputs: Vec::new(),
+            memo: None,
+            not_before_height: None,
+            alias: None,
+        }
+    }
+
+    fn build_chain() -> Storage {
+        let storage = Storage::temp();
+        let blocks = vec![
+            block_with(0, vec![transfer("a", "miner", "alice", 300)]),
+            block_with(1, vec![transfer("b", "alice", "bob", 100)]),
+        ];
+        for block in blocks {
+            let predicted = storage.compute_state_root_after(&block).unwrap();
+            storage.save_block(&block).unwrap();
+            assert_eq!(storage.compute_state_root().unwrap(), predicted);
+        }
+        storage
+    }
+
+    #[test]
+    fn identical_chains_share_state_root() {
+        let first = build_chain();
+        let second = build_chain();
+
+        let root = first.compute_state_root().unwrap();
+        assert_eq!(root, second.compute_state_root().unwrap());
+        assert_ne!(root, "0".repeat(64));
+    }
+
+    /// Blocks `1..=len` on top of `parent`, each paying `receiver`; sealed with
+    /// the state root they produce on `storage` without persisting them.
+    fn fork(storage: &Storage, parent: &Block, receiver: &str, len: u64) -> Vec<Block> {
+        let scratch = Storage::temp();
+        for index in 0..parent.index {
+            scratch
+                .save_block(&storage.get_block(index).unwrap().unwrap())
+                .unwrap();
+        }
+        scratch.save_block(parent).unwrap();
+
+        let mut prev = parent.clone();
+        let mut branch = Vec::new();
+        for index in parent.index + 1..=parent.index + len {
+            let mut reward = transfer(&format!("{receiver}-{index}"), "SYSTEM", receiver, 10);
+            reward.signature = SYSTEM_SIG_REWARD.into();
+            let mut block = block_with(index, vec![reward]);
+            block.previous_hash = prev.hash.clone();
+            block.state_root = scratch.compute_state_root_after(&block).unwrap();
+            block.hash = block.calculate_hash();
+            scratch.save_block(&block).unwrap();
+            branch.push(block.clone());
+            prev = block;
+        }
+        branch
+    }
+
+    #[test]
+    fn reorg_to_replaces_shorter_fork() {
+        let storage = build_chain();
+        let parent = storage.get_block(1).unwrap().unwrap();
+        let short = fork(&storage, &parent, "carol", 3);
+        let long = fork(&storage, &parent, "dave", 4);
+
+        for block in &short {
+            storage.save_block(block).unwrap();
+        }
+        assert_eq!(storage.calculate_balance("carol").unwrap(), 30);
+
+        let orphaned = storage.reorg_to(long.clone()).unwrap();
+        let orphaned: Vec<_> = orphaned.iter().map(|b| b.hash.clone()).collect();
+        let short: Vec<_> = short.iter().map(|b| b.hash.clone()).collect();
+        assert_eq!(orphaned, short);
+
+        // Balances match a node that only ever saw the longer fork
+        let expected = build_chain();
+        for block in &long {
+            expected.save_block(block).unwrap();
+        }
+        for account in ["miner", "alice", "bob", "carol", "dave"] {
+            assert_eq!(
+                storage.calculate_balance(account).unwrap(),
+                expected.calculate_balance(account).unwrap(),
+                "{account}"
+            );
+        }
+        assert_eq!(storage.calculate_balance("dave").unwrap(), 40);
+        assert_eq!(storage.get_latest_index().unwrap(), 5);
+        assert!(!storage.is_tx_mined("carol-2").unwrap());
+        assert!(storage.get_block_by_hash(&short[0]).unwrap().is_none());
+        assert_eq!(
+            storage
+                .get_block_by_hash(&long[3].hash)
+                .unwrap()
+                .unwrap()
+                .index,
+            5
+        );
+        assert_eq!(
+            storage.compute_state_root().unwrap(),
+            expected.compute_state_root().unwrap()
+        );
+    }
+
+    #[test]
+    fn reorg_to_is_atomic_on_bad_state_root() {
+        let storage = build_chain();
+        let parent = storage.get_block(1).unwrap().unwrap();
+        let short = fork(&storage, &parent, "carol", 2);
+        let mut long = fork(&storage, &parent, "dave", 3);
+        for block in &short {
+            storage.save_block(block).unwrap();
+        }
+        let root_before = storage.compute_state_root().unwrap();
+
+        long[2].state_root = "bogus".into();
+        assert!(storage.reorg_to(long).is_err());
+        assert_eq!(storage.compute_state_root().unwrap(), root_before);
+        assert_eq!(storage.get_block(3).unwrap().unwrap().hash, short[1].hash);
+    }
+
+    #[test]
+    fn top_balances_are_richest_first() {
+        let storage = Storage::temp();
+        let write_txn = storage.db.begin_write().unwrap();
+        {
+            let mut state = write_txn.open_table(STATE_TABLE).unwrap();
+            for (address, balance) in [
+                ("carol", 300),
+                ("alice", 500),
+                ("dave", 0),
+                ("erin", 50),
+                ("bob", 300),
+                ("frank", 900),
+            ] {
+                state.insert(address, balance).unwrap();
+            }
+            state.insert(SYNC_CHECKPOINT_KEY, 10_000).unwrap();
+        }
+        write_txn.commit().unwrap();
+
+        let top = |limit| {
+            storage
+                .get_top_balances(limit)
+                .unwrap()
+                .into_iter()
+                .map(|(address, balance)| format!("{address}:{balance}"))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(top(3), ["frank:900", "alice:500", "bob:300"]);
+        // Empty accounts and the sync checkpoint are not holders
+        assert_eq!(
+            top(10),
+            ["frank:900", "alice:500", "bob:300", "carol:300", "erin:50"]
+        );
+        assert!(top(0).is_empty());
+    }
+
+    #[test]
+    fn recompute_state_repairs_corrupted_balance() {
+        let storage = build_chain();
+        let root = storage.compute_state_root().unwrap();
+        let bob = storage.calculate_balance("bob").unwrap();
+
+        let write_txn = storage.db.begin_write().unwrap();
+        {
+            let mut state = write_txn.open_table(STATE_TABLE).unwrap();
+            state.insert("bob", 1_000_000).unwrap();
+            state.insert("mallory", 42).unwrap();
+            let mut author_counts = write_txn.open_table(AUTHOR_COUNT_TABLE).unwrap();
+            author_counts.insert("miner", 7).unwrap();
+            author_counts.insert("mallory", 3).unwrap();
+        }
+        write_txn.commit().unwrap();
+        assert_ne!(storage.compute_state_root().unwrap(), root);
+
+        assert_eq!(storage.recompute_state().unwrap(), root);
+        assert_eq!(storage.calculate_balance("bob").unwrap(), bob);
+        assert_eq!(storage.calculate_balance("mallory").unwrap(), 0);
+        assert_eq!(storage.get_next_nonce("alice").unwrap(), 1);
+        assert!(storage.is_tx_mined("b").unwrap());
+        assert_eq!(storage.count_blocks_by_author("miner").unwrap(), 2);
+        assert_eq!(storage.count_blocks_by_author("mallory").unwrap(), 0);
+    }
+
+    /// Blocks `0..count`, each paying `receiver`, sealed with their state roots.
+    fn sealed_chain(receiver: &str, count: u64) -> Vec<Block> {
+        let scratch = Storage::temp();
+        (0..count)
+            .map(|index| {
+                let mut pay = transfer(&format!("{receiver}-{index}"), "miner", receiver, 10);
+                pay.nonce = index;
+                let mut block = block_with(index, vec![pay]);
+                block.state_root = scratch.compute_state_root_after(&block).unwrap();
+                scratch.save_block(&block).unwrap();
+                block
+            })
+            .collect()
+    }
+
+    #[test]
+    fn batch_insert_commits_once() {
+        let blocks = sealed_chain("carol", 100);
+
+        let per_block = Storage::temp();
+        for block in &blocks {
+            per_block.save_block(block).unwrap();
+        }
+        let batched = Storage::temp();
+        batched.save_blocks_batch(&blocks).unwrap();
+
+        assert_eq!(per_block.block_commits.load(Ordering::Relaxed), 100);
+        assert_eq!(batched.block_commits.load(Ordering::Relaxed), 1);
+        assert_eq!(batched.get_latest_index().unwrap(), 99);
+        assert_eq!(
+            batched.compute_state_root().unwrap(),
+            per_block.compute_state_root().unwrap()
+        );
+        assert_eq!(batched.calculate_balance("carol").unwrap(), 1_000);
+        assert_eq!(batched.get_next_nonce("miner").unwrap(), 100);
+    }
+
+    #[test]
+    fn batch_insert_is_all_or_nothing() {
+        let mut blocks = sealed_chain("carol", 5);
+        blocks[3].state_root = "bogus".into();
+
+        let storage = Storage::temp();
+        assert!(storage.save_blocks_batch(&blocks).is_err());
+        assert_eq!(storage.get_total_blocks().unwrap(), 0);
+        assert_eq!(storage.calculate_balance("carol").unwrap(), 0);
+        assert_eq!(storage.block_commits.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn overlapping_batch_skips_stored_blocks() {
+        let blocks = sealed_chain("carol", 5);
+        let storage = Storage::temp();
+        storage.save_blocks_batch(&blocks[..3]).unwrap();
+
+        storage.save_blocks_batch(&blocks[1..]).unwrap();
+        assert_eq!(storage.get_latest_index().unwrap(), 4);
+        assert_eq!(storage.compute_state_root().unwrap(), blocks[4].state_root);
+        assert_eq!(storage.calculate_balance("carol").unwrap(), 50);
+        assert_eq!(storage.count_blocks_by_author("miner").unwrap(), 5);
+
+        // A different block at a stored index fails the whole batch
+        let conflicting = sealed_chain("dave", 6);
+        assert!(storage.save_blocks_batch(&conflicting[4..]).is_err());
+        assert_eq!(storage.get_latest_index().unwrap(), 4);
+        assert_eq!(storage.calculate_balance("dave").unwrap(), 0);
+    }
+
+    #[test]
+    fn author_counts_track_saved_blocks() {
+        let storage = Storage::temp();
+        for index in 0..6 {
+            let mut block = block_with(index, vec![]);
+            block.author = if index % 3 == 0 { "alice" } else { "bob" }.into();
+            storage.save_block(&block).unwrap();
+        }
+
+        let full_scan = |author: &str| {
+            let read_txn = storage.db.begin_read().unwrap();
+            let table = read_txn.open_table(BLOCKS_TABLE).unwrap();
+            let count = table
+                .iter()
+                .unwrap()
+                .map(|item| serde_json::from_str::<Block>(item.unwrap().1.value()).unwrap())
+                .filter(|block| block.author == author)
+                .count();
+            count as u64
+        };
+        for (author, count) in [("alice", 2), ("bob", 4), ("carol", 0)] {
+            assert_eq!(storage.count_blocks_by_author(author).unwrap(), count);
+            assert_eq!(full_scan(author), count);
+        }
+
+        // A database from before the counter gets it backfilled on open
+        let write_txn = storage.db.begin_write().unwrap();
+        write_txn.delete_table(AUTHOR_COUNT_TABLE).unwrap();
+        write_txn.commit().unwrap();
+        let reopened = storage.reopen();
+        assert_eq!(reopened.count_blocks_by_author("alice").unwrap(), 2);
+        assert_eq!(reopened.count_blocks_by_author("bob").unwrap(), 4);
+    }
+
+    /// Pagination as it was before key ranges: step back from the tip.
+    fn paginate_from_the_end(storage: &Storage, page: usize, limit: usize) -> Vec<u64> {
+        let read_txn = storage.db.begin_read().unwrap();
+        let table = read_txn.open_table(BLOCKS_TABLE).unwrap();
+        let mut iter = table.iter().unwrap();
+        for _ in 0..page.saturating_sub(1) * limit {
+            iter.next_back();
+        }
+        (0..limit)
+            .map_while(|_| iter.next_back())
+            .map(|item| item.unwrap().0.value())
+            .collect()
+    }
+
+    #[test]
+    fn pages_are_read_as_key_ranges() {
+        let storage = Storage::temp();
+        assert!(storage.get_blocks_paginated(1, 10).unwrap().is_empty());
+        storage
+            .save_blocks_batch(&sealed_chain("carol", 25))
+            .unwrap();
+
+        let indices = |page, limit| -> Vec<u64> {
+            storage
+                .get_blocks_paginated(page, limit)
+                .unwrap()
+                .iter()
+                .map(|b| b.index)
+                .collect()
+        };
+        for (page, limit) in [(0, 10), (1, 10), (2, 10), (3, 10), (4, 10), (1, 25), (2, 7)] {
+            assert_eq!(
+                indices(page, limit),
+                paginate_from_the_end(&storage, page, limit),
+                "page {page} of {limit}"
+            );
+        }
+        assert_eq!(indices(1, 3), vec![24, 23, 22]);
+        // The last page stops at genesis, pages past it are empty
+        assert_eq!(indices(3, 10), (0..5).rev().collect::<Vec<_>>());
+        assert!(indices(4, 10).is_empty());
+        assert!(indices(1, 0).is_empty());
+        // Deep pages are bounded by the key range, not by a walk from the tip
+        assert!(indices(usize::MAX, usize::MAX).is_empty());
+    }
+
+    #[test]
+    fn blocks_are_not_written_on_a_full_disk() {
+        let blocks = sealed_chain("carol", 3);
+        let mut storage = Storage::temp();
+        storage.set_min_free_disk(1024);
+        storage.free_space = |_| Ok(512);
+
+        let low = LowDiskSpace {
+            available: 512,
+            required: 1024,
+        };
+        assert_eq!(storage.check_disk_space(), Err(low.clone()));
+        let err = storage.save_block(&blocks[0]).unwrap_err();
+        assert_eq!(err.downcast_ref::<LowDiskSpace>(), Some(&low));
+        let err = storage.save_blocks_batch(&blocks).unwrap_err();
+        assert_eq!(err.downcast_ref::<LowDiskSpace>(), Some(&low));
+        assert_eq!(storage.get_total_blocks().unwrap(), 0);
+
+        storage.free_space = |_| Ok(4096);
+        storage.save_blocks_batch(&blocks).unwrap();
+        assert_eq!(storage.get_latest_index().unwrap(), 2);
+    }
+
+    #[test]
+    fn balance_at_height_replays_history() {
+        let storage = Storage::temp();
+        let mut first = transfer("t1", "miner", "alice", 500);
+        first.nonce = 0;
+        let mut second = transfer("t2", "miner", "alice", 200);
+        second.nonce = 1;
+        for block in [
+            block_with(0, vec![]),
+            block_with(1, vec![first]),
+            block_with(2, vec![second]),
+        ] {
+            storage.save_block(&block).unwrap();
+        }
+
+        let at = |address, height| storage.balance_at_height(address, height).unwrap();
+        assert_eq!(
+            [at("alice", 0), at("alice", 1), at("alice", 2)],
+            [0, 500, 700]
+        );
+        // Each block pays the miner 1_000; each transfer costs amount + 1_000 fee
+        assert_eq!(
+            [at("miner", 0), at("miner", 1), at("miner", 2)],
+            [1_000, 500, 300]
+        );
+        assert_eq!(at("miner", 2), storage.calculate_balance("miner").unwrap());
+        assert!(storage.balance_at_height("alice", 3).is_err());
+
+        storage.prune_history(1).unwrap();
+        let err = storage.balance_at_height("alice", 2).unwrap_err();
+        assert!(err.to_string().contains("pruned"), "{err}");
+    }
+
+    #[test]
+    fn address_history_pages_newest_first() {
+        let storage = Storage::temp();
+        let mut first = transfer("t1", "miner", "alice", 500);
+        first.nonce = 0;
+        let mut second = transfer("t2", "miner", "alice", 200);
+        second.nonce = 1;
+        for block in [
+            block_with(0, vec![]),
+            block_with(1, vec![first]),
+            block_with(2, vec![second]),
+        ] {
+            storage.save_block(&block).unwrap();
+        }
+
+        let page = |address, page, limit| -> Vec<(String, u64)> {
+            storage
+                .get_transactions_for_address(address, page, limit)
+                .unwrap()
+                .into_iter()
+                .map(|(tx, index)| (tx.id, index))
+                .collect()
+        };
+        let ids = |items: &[(&str, u64)]| -> Vec<(String, u64)> {
+            items.iter().map(|(id, i)| (id.to_string(), *i)).collect()
+        };
+
+        // Sends and rewards for the miner, receives for alice
+        assert_eq!(page("miner", 0, 2), ids(&[("t2", 2), ("coinbase-2", 2)]));
+        assert_eq!(page("miner", 1, 2), ids(&[("t1", 1), ("coinbase-1", 1)]));
+        assert_eq!(page("miner", 2, 2), ids(&[("coinbase-0", 0)]));
+        assert!(page("miner", 3, 2).is_empty());
+        assert!(page("miner", usize::MAX, usize::MAX).is_empty());
+        assert_eq!(page("alice", 0, 10), ids(&[("t2", 2), ("t1", 1)]));
+        assert!(page("bob", 0, 10).is_empty());
+
+        storage.reset_blocks().unwrap();
+        assert!(page("alice", 0, 10).is_empty());
+    }
+
+    #[test]
+    fn peer_records_round_trip_and_prune() {
+        let storage = Storage::temp();
+        let record = |peer: &str, last_seen| PeerRecord {
+            peer_id: peer.into(),
+            addresses: vec![format!("/ip4/10.0.0.1/tcp/{last_seen}")],
+            last_seen,
+            manual: false,
+        };
+        for (peer, last_seen) in [("a", 100), ("b", 300), ("c", 200), ("d", 10)] {
+            storage.save_peer(&record(peer, last_seen)).unwrap();
+        }
+        // Seeing a peer again replaces its record
+        storage.save_peer(&record("a", 400)).unwrap();
+
+        assert_eq!(
+            storage.load_peers(10).unwrap(),
+            vec![
+                record("a", 400),
+                record("b", 300),
+                record("c", 200),
+                record("d", 10)
+            ]
+        );
+        assert_eq!(storage.load_peers(1).unwrap(), vec![record("a", 400)]);
+
+        // "d" is too old and "c" falls outside the cap of two
+        assert_eq!(storage.prune_peers(450, 2, 300).unwrap(), 2);
+        assert_eq!(
+            storage.load_peers(10).unwrap(),
+            vec![record("a", 400), record("b", 300)]
+        );
+    }
+
+    #[test]
+    fn manual_peers_are_kept_and_dialed_first() {
+        let storage = Storage::temp();
+        let added = PeerRecord {
+            peer_id: "friend".into(),
+            addresses: vec!["/ip4/203.0.113.5/tcp/9000".into()],
+            last_seen: 0,
+            manual: true,
+        };
+        storage.save_peer(&added).unwrap();
+        storage
+            .save_peer(&PeerRecord {
+                peer_id: "other".into(),
+                addresses: vec!["/ip4/10.0.0.2/tcp/9000".into()],
+                last_seen: 500,
+                manual: false,
+            })
+            .unwrap();
+        // Identify reports a private address; the one it was added with stays
+        storage
+            .save_peer(&PeerRecord {
+                peer_id: "friend".into(),
+                addresses: vec!["/ip4/192.168.1.4/tcp/9000".into()],
+                last_seen: 100,
+                manual: false,
+            })
+            .unwrap();
+
+        assert_eq!(storage.prune_peers(1_000, 0, 300).unwrap(), 1);
+        let friend = &storage.load_peers(1).unwrap()[0];
+        assert!(friend.manual);
+        assert_eq!(
+            friend.addresses,
+            vec!["/ip4/192.168.1.4/tcp/9000", "/ip4/203.0.113.5/tcp/9000"]
+        );
+    }
+
+    #[test]
+    fn state_root_dry_run_does_not_persist() {
+        let storage = Storage::temp();
+        let before = storage.compute_state_root().unwrap();
+        storage
+            .compute_state_root_after(&block_with(0, vec![]))
+            .unwrap();
+        assert_eq!(storage.compute_state_root().unwrap(), before);
+        assert!(storage.get_block(0).unwrap().is_none());
+    }
+}
@@ -24,12 +24,71 @@ pub const MAX_RELAY_ATTEMPTS_PER_ADDRESS: u32 = 10;
 /// Delay between relay connection attempts (seconds)
 pub const RELAY_RETRY_DELAY_SECS: u64 = 3;
 
+/// Time allowed for a relay dial before trying the next relay (seconds)
+pub const RELAY_DIAL_TIMEOUT_SECS: u64 = 10;
+
+/// Upper bound on the backoff between relay retry rounds (seconds)
+pub const RELAY_MAX_BACKOFF_SECS: u64 = 60;
+
 /// Minimum DHT peers required to operate without relay
 pub const DHT_PEER_THRESHOLD_FOR_RELAY_FREE: usize = 3;
 
+/// Network identifier of mainnet. Peers and blocks from another chain id are
+/// rejected, so devnets and testnets must pick their own.
+pub const DEFAULT_CHAIN_ID: u32 = 1;
+
 /// Maximum number of peer connections
 pub const DEFAULT_MAX_PEERS: u32 = 50;
 
+/// Most peer records kept for reconnecting after a restart
+pub const KNOWN_PEERS_MAX: usize = 200;
+
+/// Peer records not seen for this long are forgotten (7 days)
+pub const KNOWN_PEER_MAX_AGE_SECS: u64 = 7 * 24 * 3600;
+
+/// Most recently seen peers dialed directly on startup
+pub const KNOWN_PEERS_DIAL_ON_START: usize = 8;
+
+/// Score a peer starts with and recovers back to
+pub const PEER_SCORE_MAX: f64 = 100.0;
+
+/// Peers whose score falls to this value are banned
+pub const PEER_BAN_THRESHOLD: f64 = 0.0;
+
+/// Score a penalized peer regains per second of good behaviour
+pub const PEER_SCORE_RECOVERY_PER_SEC: f64 = 0.1;
+
+/// Penalty for a block that fails validation
+pub const PEER_PENALTY_INVALID_BLOCK: f64 = 40.0;
+
+/// Penalty for a transaction with a bad signature
+pub const PEER_PENALTY_INVALID_TX: f64 = 10.0;
+
+/// Penalty for exceeding the sync request rate
+pub const PEER_PENALTY_SYNC_FLOOD: f64 = 20.0;
+
+/// How long a banned peer is disconnected and not dialed (10 minutes)
+pub const PEER_BAN_SECS: u64 = 600;
+
+/// Sync requests a peer may send per rate window
+pub const SYNC_REQUESTS_PER_WINDOW: u32 = 50;
+
+/// Length of the sync request rate window (seconds)
+pub const SYNC_RATE_WINDOW_SECS: u64 = 10;
+
+/// Penalty for asking for more blocks than are served at once
+pub const PEER_PENALTY_OVERSIZED_REQUEST: f64 = 5.0;
+
+/// Most blocks served for one `GetBlocksRange` request
+pub const MAX_SYNC_RANGE_SPAN: u64 = 500;
+
+/// Block batches larger than this (bytes, serialized) are gzipped for peers
+/// that can read them
+pub const SYNC_COMPRESSION_THRESHOLD: usize = 64 * 1024;
+
+/// Largest compressed block batch accepted once decompressed (64 MiB)
+pub const MAX_SYNC_DECOMPRESSED_BYTES: u64 = 64 * 1024 * 1024;
+
 // ============================================================================
 // Consensus Configuration
 // ============================================================================
@@ -43,9 +102,36 @@ pub const EPOCH_DURATION: u64 = 600;
 /// Base quarantine duration for solo nodes (5 minutes)
 pub const BASE_QUARANTINE_SECS: u64 = 300;
 
+/// Quarantine added per known validator (1 hour)
+pub const QUARANTINE_PER_VALIDATOR_SECS: u64 = 3600;
+
 /// Maximum quarantine duration (72 hours)
 pub const MAX_QUARANTINE_SECS: u64 = 72 * 3600;
 
+/// How far (seconds) a block's timestamp may run ahead of the local clock
+pub const MAX_BLOCK_TIME_DRIFT_SECS: u64 = 120;
+
+/// Deepest fork (in blocks below the tip) a node will reorganize to
+pub const MAX_REORG_DEPTH: u64 = 100;
+
+/// Confirmations after which a block is final and never reorganized away
+pub const FINALITY_DEPTH: u64 = 12;
+
+/// Slots a block author's production is remembered for equivocation checks
+pub const EQUIVOCATION_WINDOW_SLOTS: u64 = 300;
+
+/// Headers requested per round of header-first sync
+pub const SYNC_HEADERS_BATCH: u64 = 500;
+
+/// Block bodies requested per sync round
+pub const SYNC_BLOCKS_BATCH: u64 = 100;
+
+/// Gossiped blocks held while waiting for their parent
+pub const MAX_ORPHAN_BLOCKS: usize = 100;
+
+/// How long a light node waits for a full peer to answer a query (seconds)
+pub const LIGHT_QUERY_TIMEOUT_SECS: u64 = 10;
+
 // ============================================================================
 // Performance Parameters (1500 TPS per Shard)
 // ============================================================================
@@ -53,12 +139,56 @@ pub const MAX_QUARANTINE_SECS: u64 = 72 * 3600;
 /// Target block time in seconds
 pub const TARGET_BLOCK_TIME: u64 = 2;
 
+/// Holders listed by the rich list when no limit is given
+pub const RICH_LIST_DEFAULT_LIMIT: usize = 100;
+
+/// Most holders one rich list query may ask for
+pub const RICH_LIST_MAX_LIMIT: usize = 1_000;
+
+/// Most transactions one page of an address history may hold
+pub const ADDRESS_TXS_MAX_LIMIT: usize = 1_000;
+
 /// Maximum transactions per block (3000 tx / 2s = 1500 TPS)
 pub const MAX_TXS_PER_BLOCK: u64 = 3_000;
 
+/// Recent blocks measured for realized throughput when no window is given
+pub const PERFORMANCE_STATS_WINDOW: usize = 100;
+
+/// Maximum receivers credited by one batch payment
+pub const MAX_TX_OUTPUTS: usize = 100;
+
+/// Maximum size of a transaction memo in bytes
+pub const MAX_MEMO_BYTES: usize = 256;
+
+/// Length bounds of an on-chain alias
+pub const MIN_ALIAS_LEN: usize = 3;
+pub const MAX_ALIAS_LEN: usize = 32;
+
 /// Maximum block size in bytes (1.5 MB)
 pub const MAX_BLOCK_SIZE: u64 = 1_500_000;
 
+/// Free disk space (MB) below which blocks are not written
+pub const DEFAULT_MIN_FREE_DISK_MB: u64 = 256;
+
+/// Maximum pending transactions held in the mempool
+pub const MAX_MEMPOOL_SIZE: usize = 50_000;
+
+/// Cross-shard receipts whose progress is kept for the UI
+pub const MAX_TRACKED_RECEIPTS: usize = 500;
+
+/// How long (seconds) the mempool remembers mined transaction ids, so
+/// re-gossiped copies are dropped without being validated again
+pub const RECENTLY_MINED_TTL_SECS: u64 = 600;
+
+/// Minimum fee increase (percent) for a replace-by-fee transaction
+pub const MIN_RBF_FEE_BUMP_PERCENT: u64 = 10;
+
+/// Age (seconds) after which an unmined transaction is dropped from the mempool
+pub const MEMPOOL_TX_MAX_AGE_SECS: u64 = 3 * 3600;
+
+/// How often the mining loop sweeps expired transactions out of the mempool
+pub const MEMPOOL_SWEEP_INTERVAL_SECS: u64 = 60;
+
 // ============================================================================
 // Synchronization Parameters
 // ============================================================================
@@ -107,6 +237,21 @@ pub const VDF_BASE_DIFFICULTY: u64 = 3_000_000;
 /// Additional difficulty per validator
 pub const VDF_DIFFICULTY_PER_VALIDATOR: u64 = 500_000;
 
+/// Lowest VDF difficulty a block may use
+pub const MIN_VDF_DIFFICULTY: u64 = 100;
+
+/// Squarings timed to measure a node's VDF speed (small, to stay responsive)
+pub const VDF_BENCHMARK_DIFFICULTY: u64 = 50_000;
+
+/// Seconds between VDF speed measurements on the headless RPC node
+pub const VDF_BENCHMARK_INTERVAL_SECS: u64 = 30;
+
+/// Recent blocks whose average interval drives the difficulty retarget
+pub const DIFFICULTY_RETARGET_WINDOW: usize = 20;
+
+/// A block's difficulty may differ from its parent's by at most 1/N of it
+pub const DIFFICULTY_MAX_STEP_DIVISOR: u64 = 4;
+
 // ============================================================================
 // Storage
 // ============================================================================
@@ -120,3 +265,7 @@ pub const PRUNED_HISTORY_BLOCKS: u64 = 2000;
 
 /// Validators per shard (auto-sharding threshold)
 pub const VALIDATORS_PER_SHARD: usize = 50;
+
+/// CrossLinks the beacon keeps per shard; receipts from older blocks can no
+/// longer be minted
+pub const CROSSLINK_HISTORY: usize = 1_000;
@@ -0,0 +1,137 @@
+//! # Log Format
+//!
+//! Logs are plain text unless `LOG_FORMAT=json` asks for one JSON object per
+//! line, for aggregators like Loki or ELK. The level filter can be changed
+//! at runtime through the handle kept by `install`.
+
+use flexi_logger::{DeferredNow, LogSpecification, Logger, LoggerHandle, Record};
+use log::LevelFilter;
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+/// Environment variable that selects the log format
+pub const LOG_FORMAT_ENV: &str = "LOG_FORMAT";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl LogFormat {
+    /// `json` (any case) selects JSON lines; anything else, or nothing, is text.
+    pub fn parse(value: Option<&str>) -> Self {
+        match value {
+            Some(value) if value.trim().eq_ignore_ascii_case("json") => LogFormat::Json,
+            _ => LogFormat::Text,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::parse(std::env::var(LOG_FORMAT_ENV).ok().as_deref())
+    }
+}
+
+/// Writes `record` as a JSON object with timestamp, level, target and message.
+pub fn json_format(
+    w: &mut dyn Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    let line = serde_json::json!({
+        "timestamp": now.format_rfc3339(),
+        "level": record.level().as_str(),
+        "target": record.target(),
+        "message": record.args().to_string(),
+    });
+    write!(w, "{}", line)
+}
+
+/// Switches `logger` to JSON lines when `LOG_FORMAT=json`; file output,
+/// rotation and retention stay as configured.
+pub fn apply_log_format(logger: Logger) -> Logger {
+    match LogFormat::from_env() {
+        LogFormat::Json => logger.format(json_format),
+        LogFormat::Text => logger,
+    }
+}
+
+/// Handle of the running logger, set at startup
+static LOGGER: RwLock<Option<LoggerHandle>> = RwLock::new(None);
+
+/// Filter the running logger was started or last reloaded with
+static LOG_SPEC: RwLock<String> = RwLock::new(String::new());
+
+/// Keeps the handle of the started logger so its filter can be reloaded.
+pub fn install(handle: LoggerHandle, spec: &str) {
+    *LOGGER.write().unwrap() = Some(handle);
+    *LOG_SPEC.write().unwrap() = spec.to_string();
+}
+
+/// The active filter, e.g. `info, centichain_lib=debug`.
+pub fn log_level() -> String {
+    LOG_SPEC.read().unwrap().clone()
+}
+
+/// Parses one of `off`, `error`, `warn`, `info`, `debug` or `trace`.
+pub fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    LevelFilter::from_str(level.trim()).map_err(|_| {
+        format!(
+            "Invalid log level '{}': expected off, error, warn, info, debug or trace",
+            level
+        )
+    })
+}
+
+/// Reloads the running logger with `level` for every module, without restart.
+pub fn set_log_level(level: &str) -> Result<(), String> {
+    let filter = parse_level(level)?;
+    if let Some(handle) = LOGGER.read().unwrap().as_ref() {
+        handle.set_new_spec(LogSpecification::builder().default(filter).build());
+    }
+    *LOG_SPEC.write().unwrap() = filter.as_str().to_lowercase();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_lines_carry_the_record_fields() {
+        let mut out = Vec::new();
+        let args = format_args!("Block {} accepted", 7);
+        let record = Record::builder()
+            .level(log::Level::Warn)
+            .target("centichain_lib::network::p2p")
+            .args(args)
+            .build();
+        json_format(&mut out, &mut DeferredNow::new(), &record).unwrap();
+
+        let line: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(line["level"], "WARN");
+        assert_eq!(line["target"], "centichain_lib::network::p2p");
+        assert_eq!(line["message"], "Block 7 accepted");
+        let timestamp = line["timestamp"].as_str().unwrap();
+        assert!(chrono::DateTime::parse_from_rfc3339(timestamp).is_ok());
+    }
+
+    #[test]
+    fn text_is_the_default_format() {
+        assert_eq!(LogFormat::parse(None), LogFormat::Text);
+        assert_eq!(LogFormat::parse(Some("plain")), LogFormat::Text);
+        assert_eq!(LogFormat::parse(Some("JSON")), LogFormat::Json);
+    }
+
+    #[test]
+    fn only_known_levels_are_accepted() {
+        assert!(set_log_level("verbose").is_err());
+        assert!(set_log_level("").is_err());
+
+        set_log_level(" DEBUG ").unwrap();
+        assert_eq!(log_level(), "debug");
+        assert!(set_log_level("loud").is_err());
+        assert_eq!(log_level(), "debug");
+    }
+}
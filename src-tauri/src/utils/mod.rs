@@ -3,5 +3,15 @@
 //! Common constants, types, and helper functions used across Centichain.
 
 pub mod constants;
+pub mod logging;
+pub mod paths;
 
 pub use constants::*;
+
+/// Current Unix time in seconds
+pub fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
@@ -0,0 +1,134 @@
+//! # Data Directory
+//!
+//! Where the node keeps its database and `genesis.json`. The
+//! [`DATA_DIR_ENV`] variable wins over `AppSettings.data_dir`, which wins over
+//! the platform's per-user data directory.
+
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Environment variable overriding the data directory
+pub const DATA_DIR_ENV: &str = "CENTICHAIN_DATA_DIR";
+
+/// File name of the node database inside the data directory
+pub const DB_FILE_NAME: &str = "centichain.db";
+
+/// The platform data directory, or the temp dir where there is none.
+pub fn default_data_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("centichain")
+}
+
+/// Picks the data directory: `env`, then `configured`, then `default`.
+/// Empty values count as unset.
+pub fn resolve_data_dir(
+    env: Option<String>,
+    configured: Option<String>,
+    default: PathBuf,
+) -> PathBuf {
+    env.into_iter()
+        .chain(configured)
+        .map(|dir| dir.trim().to_string())
+        .find(|dir| !dir.is_empty())
+        .map_or(default, PathBuf::from)
+}
+
+/// Databases from older installs: wherever the temp-dir fallback put them.
+pub fn legacy_db_paths() -> Vec<PathBuf> {
+    let temp = std::env::temp_dir();
+    vec![
+        temp.join("centichain").join(DB_FILE_NAME),
+        temp.join("antigravity.db"),
+    ]
+}
+
+/// Copies the first existing database in `sources` to `target` unless
+/// `target` already exists; returns the one copied.
+pub fn migrate_db(sources: &[PathBuf], target: &Path) -> Result<Option<PathBuf>, String> {
+    if target.exists() {
+        return Ok(None);
+    }
+    let Some(source) = sources
+        .iter()
+        .find(|s| s.is_file() && s.as_path() != target)
+    else {
+        return Ok(None);
+    };
+    if let Some(dir) = target.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+    }
+    std::fs::copy(source, target).map_err(|e| {
+        format!(
+            "Cannot copy {} to {}: {}",
+            source.display(),
+            target.display(),
+            e
+        )
+    })?;
+    Ok(Some(source.clone()))
+}
+
+/// Data directory this node opened its database in, set at startup
+static DATA_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// The data directory in use; the default one before startup sets it.
+pub fn data_dir() -> PathBuf {
+    DATA_DIR
+        .read()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(default_data_dir)
+}
+
+pub fn set_data_dir(dir: PathBuf) {
+    *DATA_DIR.write().unwrap() = Some(dir);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_beats_settings_beats_default() {
+        let default = PathBuf::from("/default");
+        let resolve = |env: Option<&str>, configured: Option<&str>| {
+            resolve_data_dir(
+                env.map(String::from),
+                configured.map(String::from),
+                default.clone(),
+            )
+        };
+
+        assert_eq!(
+            resolve(Some("/env"), Some("/settings")),
+            PathBuf::from("/env")
+        );
+        assert_eq!(resolve(None, Some("/settings")), PathBuf::from("/settings"));
+        assert_eq!(resolve(None, None), default);
+        assert_eq!(
+            resolve(Some(" "), Some("/settings")),
+            PathBuf::from("/settings")
+        );
+        assert_eq!(resolve(None, Some("")), default);
+    }
+
+    #[test]
+    fn legacy_db_is_copied_once() {
+        let root = std::env::temp_dir().join(format!("centichain-test-{}", uuid::Uuid::new_v4()));
+        let legacy = root.join("antigravity.db");
+        let target = root.join("data").join(DB_FILE_NAME);
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(&legacy, b"chain").unwrap();
+
+        let sources = [root.join("missing.db"), legacy.clone()];
+        assert_eq!(migrate_db(&sources, &target), Ok(Some(legacy.clone())));
+        assert_eq!(std::fs::read(&target).unwrap(), b"chain");
+
+        std::fs::write(&legacy, b"newer").unwrap();
+        assert_eq!(migrate_db(&sources, &target), Ok(None));
+        assert_eq!(std::fs::read(&target).unwrap(), b"chain");
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,125 @@
+//! # Wallet Key Encryption
+//!
+//! Password-based encryption of the wallet keypair at rest
+//! (scrypt key derivation + AES-256-GCM).
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+/// scrypt cost parameter (N = 2^15), r = 8, p = 1.
+const SCRYPT_LOG_N: u8 = 15;
+
+/// Keypair encrypted with a password-derived key.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EncryptedKey {
+    /// Wallet address, kept in clear so a locked wallet can still be identified.
+    pub address: String,
+    pub kdf: String,
+    pub log_n: u8,
+    pub salt: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+/// Wallet key as persisted in `WALLET_TABLE`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum StoredKey {
+    /// Legacy format: raw protobuf keypair bytes.
+    Plain(Vec<u8>),
+    Encrypted(EncryptedKey),
+}
+
+impl StoredKey {
+    pub fn parse(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("Invalid stored wallet key: {}", e))
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(self).map_err(|e| e.to_string())
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        matches!(self, StoredKey::Encrypted(_))
+    }
+}
+
+fn derive_key(password: &str, salt: &[u8], log_n: u8) -> Result<[u8; 32], String> {
+    let params = scrypt::Params::new(log_n, 8, 1, 32).map_err(|e| e.to_string())?;
+    let mut key = [0u8; 32];
+    scrypt::scrypt(password.as_bytes(), salt, &params, &mut key).map_err(|e| e.to_string())?;
+    Ok(key)
+}
+
+/// Encrypts `keypair_bytes` with a key derived from `password`.
+pub fn encrypt_key(
+    keypair_bytes: &[u8],
+    address: &str,
+    password: &str,
+) -> Result<EncryptedKey, String> {
+    let mut salt = [0u8; 16];
+    let mut nonce = [0u8; 12];
+    rand::thread_rng().fill_bytes(&mut salt);
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let key = derive_key(password, &salt, SCRYPT_LOG_N)?;
+    let cipher = Aes256Gcm::new_from_slice(&key).map_err(|e| e.to_string())?;
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), keypair_bytes)
+        .map_err(|_| "Wallet encryption failed".to_string())?;
+
+    Ok(EncryptedKey {
+        address: address.to_string(),
+        kdf: "scrypt".to_string(),
+        log_n: SCRYPT_LOG_N,
+        salt: hex::encode(salt),
+        nonce: hex::encode(nonce),
+        ciphertext: hex::encode(ciphertext),
+    })
+}
+
+/// Decrypts the keypair bytes; fails on a wrong password or tampered data.
+pub fn decrypt_key(encrypted: &EncryptedKey, password: &str) -> Result<Vec<u8>, String> {
+    if encrypted.kdf != "scrypt" {
+        return Err(format!("Unsupported KDF: {}", encrypted.kdf));
+    }
+    let salt = hex::decode(&encrypted.salt).map_err(|_| "Invalid salt".to_string())?;
+    let nonce = hex::decode(&encrypted.nonce).map_err(|_| "Invalid nonce".to_string())?;
+    let ciphertext =
+        hex::decode(&encrypted.ciphertext).map_err(|_| "Invalid ciphertext".to_string())?;
+    if nonce.len() != 12 {
+        return Err("Invalid nonce".into());
+    }
+
+    let key = derive_key(password, &salt, encrypted.log_n)?;
+    let cipher = Aes256Gcm::new_from_slice(&key).map_err(|e| e.to_string())?;
+    cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+        .map_err(|_| "Incorrect password".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_decrypt_roundtrip() {
+        let secret = b"protobuf-keypair-bytes".to_vec();
+        let encrypted = encrypt_key(&secret, "12D3KooWTest", "hunter2").unwrap();
+
+        assert_eq!(decrypt_key(&encrypted, "hunter2").unwrap(), secret);
+        assert!(decrypt_key(&encrypted, "wrong").is_err());
+    }
+
+    #[test]
+    fn stored_key_detects_legacy_plaintext() {
+        let legacy = serde_json::to_string(&vec![8u8, 1, 18, 64]).unwrap();
+        assert!(!StoredKey::parse(&legacy).unwrap().is_encrypted());
+
+        let encrypted = encrypt_key(&[1, 2, 3], "addr", "pw").unwrap();
+        let json = StoredKey::Encrypted(encrypted).to_json().unwrap();
+        assert!(StoredKey::parse(&json).unwrap().is_encrypted());
+    }
+}
@@ -0,0 +1,116 @@
+//! # Hierarchical Deterministic Accounts
+//!
+//! Accounts are ed25519 keys derived from the mnemonic seed along the BIP44
+//! path `m/44'/COIN_TYPE'/account'/0'/0'`, using SLIP-0010 (ed25519 only
+//! supports hardened derivation, so every level is hardened).
+
+use super::encryption::StoredKey;
+use hmac::{Hmac, Mac};
+use libp2p::identity::Keypair;
+use serde::{Deserialize, Serialize};
+use sha2::Sha512;
+
+type HmacSha512 = Hmac<Sha512>;
+
+/// SLIP-44 coin type used in the account path (1 = testnet)
+pub const COIN_TYPE: u32 = 1;
+
+/// Offset marking a hardened derivation index
+const HARDENED: u32 = 0x8000_0000;
+
+/// Master seed plus account bookkeeping, persisted next to the active key.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HdRecord {
+    /// The BIP39 seed, encrypted like the wallet key
+    pub seed: StoredKey,
+    /// Number of accounts added so far (indices `0..accounts`)
+    pub accounts: u32,
+    /// Index of the account the wallet signs with
+    pub active: u32,
+}
+
+impl HdRecord {
+    pub fn parse(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("Invalid stored HD wallet: {}", e))
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(self).map_err(|e| e.to_string())
+    }
+}
+
+/// One derived account as reported to the UI
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AccountInfo {
+    pub index: u32,
+    pub address: String,
+    pub active: bool,
+}
+
+fn hmac_sha512(key: &[u8], parts: &[&[u8]]) -> [u8; 64] {
+    let mut mac = HmacSha512::new_from_slice(key).expect("HMAC accepts any key length");
+    for part in parts {
+        mac.update(part);
+    }
+    mac.finalize().into_bytes().into()
+}
+
+/// SLIP-0010 ed25519 private key at `path` (indices are hardened here).
+fn derive_key(seed: &[u8], path: &[u32]) -> [u8; 32] {
+    let i = hmac_sha512(b"ed25519 seed", &[seed]);
+    let (mut key, mut chain_code) = (i[..32].to_vec(), i[32..].to_vec());
+    for index in path {
+        let i = hmac_sha512(
+            &chain_code,
+            &[&[0u8], &key, &(index | HARDENED).to_be_bytes()],
+        );
+        key = i[..32].to_vec();
+        chain_code = i[32..].to_vec();
+    }
+    key.try_into().expect("32-byte key")
+}
+
+/// Keypair of account `index` in the wallet with BIP39 `seed`.
+pub fn derive_account(seed: &[u8], index: u32) -> Result<Keypair, String> {
+    if index >= HARDENED {
+        return Err(format!("Account index {} out of range", index));
+    }
+    let key = derive_key(seed, &[44, COIN_TYPE, index, 0, 0]);
+    Keypair::ed25519_from_bytes(key).map_err(|e| format!("Invalid derived key: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_slip10_test_vector() {
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        assert_eq!(
+            hex::encode(derive_key(&seed, &[])),
+            "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7"
+        );
+        assert_eq!(
+            hex::encode(derive_key(&seed, &[0])),
+            "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3"
+        );
+    }
+
+    #[test]
+    fn same_mnemonic_derives_same_accounts() {
+        let phrase = "legal winner thank year wave sausage worth useful legal winner thank yellow";
+        let address = |index| {
+            let seed = bip39::Mnemonic::parse(phrase).unwrap().to_seed("");
+            derive_account(&seed, index)
+                .unwrap()
+                .public()
+                .to_peer_id()
+                .to_string()
+        };
+
+        assert_eq!(address(0), address(0));
+        assert_eq!(address(3), address(3));
+        assert_ne!(address(0), address(1));
+        assert!(derive_account(&[0u8; 64], HARDENED).is_err());
+    }
+}
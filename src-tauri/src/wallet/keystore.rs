@@ -0,0 +1,148 @@
+//! # Keystore Files
+//!
+//! Password-encrypted JSON export of a wallet key, laid out like an Ethereum
+//! v3 keystore so it can sit in a cloud backup. The key is sealed the same way
+//! as at rest (scrypt + AES-256-GCM); GCM's tag replaces the separate MAC.
+
+use super::encryption::{self, EncryptedKey};
+use super::Wallet;
+use serde::{Deserialize, Serialize};
+
+/// Keystore layout version
+pub const KEYSTORE_VERSION: u32 = 3;
+
+const CIPHER: &str = "aes-256-gcm";
+
+/// scrypt `r`, `p` and key length used by `encryption`
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+const SCRYPT_DKLEN: u32 = 32;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Keystore {
+    pub version: u32,
+    pub id: String,
+    pub address: String,
+    pub crypto: KeystoreCrypto,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KeystoreCrypto {
+    pub cipher: String,
+    pub ciphertext: String,
+    pub cipherparams: CipherParams,
+    pub kdf: String,
+    pub kdfparams: KdfParams,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CipherParams {
+    pub nonce: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KdfParams {
+    pub n: u64,
+    pub r: u32,
+    pub p: u32,
+    pub dklen: u32,
+    pub salt: String,
+}
+
+impl Keystore {
+    /// Encrypts the wallet key with `password`.
+    pub fn seal(wallet: &Wallet, password: &str) -> Result<Self, String> {
+        if password.is_empty() {
+            return Err("Password is required".into());
+        }
+        let sealed = encryption::encrypt_key(&wallet.keypair, &wallet.address, password)?;
+        Ok(Keystore {
+            version: KEYSTORE_VERSION,
+            id: uuid::Uuid::new_v4().to_string(),
+            address: sealed.address,
+            crypto: KeystoreCrypto {
+                cipher: CIPHER.to_string(),
+                ciphertext: sealed.ciphertext,
+                cipherparams: CipherParams {
+                    nonce: sealed.nonce,
+                },
+                kdf: sealed.kdf,
+                kdfparams: KdfParams {
+                    n: 1 << sealed.log_n,
+                    r: SCRYPT_R,
+                    p: SCRYPT_P,
+                    dklen: SCRYPT_DKLEN,
+                    salt: sealed.salt,
+                },
+            },
+        })
+    }
+
+    /// Decrypts the keystore back into a wallet.
+    pub fn open(&self, password: &str) -> Result<Wallet, String> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(format!("Unsupported keystore version {}", self.version));
+        }
+        let crypto = &self.crypto;
+        if crypto.cipher != CIPHER {
+            return Err(format!("Unsupported cipher: {}", crypto.cipher));
+        }
+        let params = &crypto.kdfparams;
+        if !params.n.is_power_of_two()
+            || (params.r, params.p, params.dklen) != (SCRYPT_R, SCRYPT_P, SCRYPT_DKLEN)
+        {
+            return Err("Unsupported scrypt parameters".into());
+        }
+
+        let sealed = EncryptedKey {
+            address: self.address.clone(),
+            kdf: crypto.kdf.clone(),
+            log_n: params.n.trailing_zeros() as u8,
+            salt: params.salt.clone(),
+            nonce: crypto.cipherparams.nonce.clone(),
+            ciphertext: crypto.ciphertext.clone(),
+        };
+        let wallet = Wallet::from_keypair_bytes(encryption::decrypt_key(&sealed, password)?)?;
+        if wallet.address != self.address {
+            return Err("Keystore key does not match its address".into());
+        }
+        Ok(wallet)
+    }
+
+    pub fn parse(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("Invalid keystore: {}", e))
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keystore_round_trips_through_json() {
+        let wallet = Wallet::new();
+        let json = Keystore::seal(&wallet, "hunter2")
+            .unwrap()
+            .to_json()
+            .unwrap();
+        assert!(!json.contains(&hex::encode(&wallet.keypair)));
+
+        let restored = Keystore::parse(&json).unwrap().open("hunter2").unwrap();
+        assert_eq!(restored.address, wallet.address);
+        assert_eq!(restored.keypair, wallet.keypair);
+    }
+
+    #[test]
+    fn wrong_password_is_rejected() {
+        let keystore = Keystore::seal(&Wallet::new(), "hunter2").unwrap();
+        assert_eq!(
+            keystore.open("hunter3").unwrap_err(),
+            "Incorrect password".to_string()
+        );
+        assert!(Keystore::seal(&Wallet::new(), "").is_err());
+    }
+}
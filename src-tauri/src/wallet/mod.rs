@@ -1,4 +1,8 @@
-use libp2p::identity::Keypair;
+pub mod encryption;
+pub mod hd;
+pub mod keystore;
+
+use libp2p::identity::{Keypair, PublicKey};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -8,6 +12,9 @@ pub struct Wallet {
     pub alias: Option<String>,
     #[serde(skip)] // Don't serialize private key easily
     pub keypair: Vec<u8>, // Stored as bytes for simplicity in this demo
+    #[serde(skip)]
+    pub seed: Option<Vec<u8>>, // BIP39 seed of an HD wallet, None for a single imported key
+    pub account: Option<u32>, // Derivation index of the active HD account
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -16,6 +23,7 @@ pub struct WalletInfo {
     pub balance: u64,
     pub alias: Option<String>,
     pub private_key: Option<String>,
+    pub account: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -38,16 +46,76 @@ impl Wallet {
             address: peer_id,
             alias: None,
             keypair: keypair.to_protobuf_encoding().unwrap(),
+            seed: None,
+            account: None,
+        }
+    }
+
+    /// Rebuilds a wallet from protobuf-encoded keypair bytes.
+    pub fn from_keypair_bytes(keypair_bytes: Vec<u8>) -> Result<Self, String> {
+        let keypair = Keypair::from_protobuf_encoding(&keypair_bytes)
+            .map_err(|e| format!("Invalid keypair data: {}", e))?;
+
+        Ok(Wallet {
+            start_timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            address: keypair.public().to_peer_id().to_string(),
+            alias: None,
+            keypair: keypair_bytes,
+            seed: None,
+            account: None,
+        })
+    }
+
+    /// Opens account `index` of the HD wallet with BIP39 `seed`.
+    pub fn from_seed(seed: Vec<u8>, index: u32) -> Result<Self, String> {
+        let keypair = hd::derive_account(&seed, index)?;
+        let mut wallet = Self::from_keypair_bytes(
+            keypair
+                .to_protobuf_encoding()
+                .map_err(|e| format!("Keypair encoding failed: {}", e))?,
+        )?;
+        wallet.seed = Some(seed);
+        wallet.account = Some(index);
+        Ok(wallet)
+    }
+
+    /// Restores a wallet from a 12-word mnemonic (the HD wallet at its first
+    /// account) or from a hex-encoded protobuf keypair.
+    pub fn import(secret: &str) -> Result<Self, String> {
+        let secret = secret.trim();
+        if secret.split_whitespace().count() == 12 {
+            let mnemonic =
+                bip39::Mnemonic::parse(secret).map_err(|e| format!("Invalid mnemonic: {}", e))?;
+            Self::from_seed(mnemonic.to_seed("").to_vec(), 0)
+        } else {
+            let keypair_bytes = hex::decode(secret).map_err(|e| format!("Invalid hex: {}", e))?;
+            Self::from_keypair_bytes(keypair_bytes)
         }
     }
 
-    pub fn get_keypair(&self) -> Keypair {
-        Keypair::from_protobuf_encoding(&self.keypair).expect("Invalid keypair")
+    /// Account `index` derived from this wallet's seed, keeping its start time.
+    pub fn derive_account(&self, index: u32) -> Result<Self, String> {
+        let seed = self
+            .seed
+            .clone()
+            .ok_or("Wallet has no seed to derive accounts from")?;
+        let mut account = Self::from_seed(seed, index)?;
+        account.start_timestamp = self.start_timestamp;
+        Ok(account)
+    }
+
+    pub fn get_keypair(&self) -> Result<Keypair, String> {
+        Keypair::from_protobuf_encoding(&self.keypair)
+            .map_err(|e| format!("Invalid keypair data: {}", e))
     }
 
-    pub fn sign_message(&self, message: &[u8]) -> Vec<u8> {
-        let keypair = self.get_keypair();
-        keypair.sign(message).expect("Signing failed")
+    pub fn sign_message(&self, message: &[u8]) -> Result<Vec<u8>, String> {
+        self.get_keypair()?
+            .sign(message)
+            .map_err(|e| format!("Signing failed: {}", e))
     }
 
     pub fn set_alias(&mut self, alias: String) {
@@ -55,3 +123,79 @@ impl Wallet {
         self.alias = Some(alias);
     }
 }
+
+/// Public key inlined in a PeerId address; ed25519 ids embed the whole key.
+pub fn public_key_from_address(address: &str) -> Result<PublicKey, String> {
+    let peer_id: libp2p::PeerId = address.parse().map_err(|_| "Invalid address".to_string())?;
+    // Identity multihash: code 0x00, digest length, protobuf-encoded key
+    let bytes = peer_id.to_bytes();
+    let key = match bytes.as_slice() {
+        [0x00, len, key @ ..] if *len as usize == key.len() => key,
+        _ => return Err("Address does not embed its public key".into()),
+    };
+    PublicKey::try_decode_protobuf(key).map_err(|e| format!("Invalid public key: {}", e))
+}
+
+/// Whether hex `signature` is `address`'s signature over `message`.
+pub fn verify_message(address: &str, message: &[u8], signature: &str) -> Result<bool, String> {
+    let public_key = public_key_from_address(address)?;
+    let signature = hex::decode(signature).map_err(|_| "Invalid signature hex".to_string())?;
+    Ok(public_key.verify(message, &signature))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signed_message_verifies_against_address() {
+        let wallet = Wallet::new();
+        let signature = hex::encode(wallet.sign_message(b"withdraw to 0xabc").unwrap());
+
+        assert_eq!(
+            verify_message(&wallet.address, b"withdraw to 0xabc", &signature),
+            Ok(true)
+        );
+        let other = Wallet::new();
+        assert_eq!(
+            verify_message(&other.address, b"withdraw to 0xabc", &signature),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn tampered_message_fails_verification() {
+        let wallet = Wallet::new();
+        let signature = hex::encode(wallet.sign_message(b"withdraw to 0xabc").unwrap());
+
+        assert_eq!(
+            verify_message(&wallet.address, b"withdraw to 0xabd", &signature),
+            Ok(false)
+        );
+        assert!(verify_message(&wallet.address, b"withdraw to 0xabc", "zz").is_err());
+        assert!(verify_message("not-an-address", b"withdraw to 0xabc", &signature).is_err());
+    }
+
+    #[test]
+    fn bad_import_secrets_are_errors() {
+        let wallet = Wallet::new();
+        let restored = Wallet::import(&hex::encode(&wallet.keypair)).unwrap();
+        assert_eq!(restored.address, wallet.address);
+
+        // Twelve words that are not a valid BIP39 phrase
+        let words = "abandon ".repeat(12);
+        assert!(Wallet::import(&words)
+            .unwrap_err()
+            .starts_with("Invalid mnemonic"));
+        assert!(Wallet::import("zz").unwrap_err().starts_with("Invalid hex"));
+        assert!(Wallet::import("abc").is_err());
+        // Well-formed hex that is not a protobuf keypair
+        assert!(Wallet::import(&"ff".repeat(68)).is_err());
+        assert!(Wallet::import("").is_err());
+
+        let mut corrupted = wallet.clone();
+        corrupted.keypair.truncate(10);
+        assert!(corrupted.get_keypair().is_err());
+        assert!(corrupted.sign_message(b"hello").is_err());
+    }
+}
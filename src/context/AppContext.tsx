@@ -9,6 +9,7 @@ export interface WalletInfo {
     alias?: string;
     balance?: number;
     private_key?: string;
+    account?: number | null;
 }
 
 export interface Transaction {
@@ -18,6 +19,10 @@ export interface Transaction {
     amount: number;
     timestamp: number;
     signature: string;
+    outputs?: [string, number][];
+    memo?: string | null;
+    not_before_height?: number | null;
+    alias?: string | null;
 }
 
 export interface Block {
@@ -86,6 +91,8 @@ interface AppContextType {
     refreshWallet: () => Promise<void>;
     refreshBlockHeight: () => Promise<void>;
     connectedRelay: string | null;
+    relayAddress: string | null;
+    listenAddrs: string[];
     vdfStatus: VdfStatus | null;
     selfNodeInfo: SelfNodeInfo | null;
     consensusStatus: NodeConsensusStatus | null;
@@ -104,6 +111,8 @@ export function AppProvider({ children }: { children: ReactNode }) {
     const [nodeStatus, setNodeStatus] = useState<string>("Stopped");
     const [relayStatus, setRelayStatus] = useState<string>("Disconnected");
     const [connectedRelay, setConnectedRelay] = useState<string | null>(null);
+    const [relayAddress, setRelayAddress] = useState<string | null>(null);
+    const [listenAddrs, setListenAddrs] = useState<string[]>([]);
     const [patience, setPatience] = useState<number>(0);
     const [peers, setPeers] = useState<number>(0);
     const [loading, setLoading] = useState<boolean>(true);
@@ -137,9 +146,15 @@ export function AppProvider({ children }: { children: ReactNode }) {
 
         const unlistenRelay = listen('relay-status', (event: any) => {
             console.log("Relay Status Event:", event.payload);
-            setRelayStatus(event.payload);
-            if (event.payload.toLowerCase() === 'disconnected') {
+            // Either a plain status or { state, address } for the active relay
+            const status: string = typeof event.payload === 'string' ? event.payload : event.payload.state;
+            setRelayStatus(status);
+            if (typeof event.payload !== 'string' && event.payload.address) {
+                setRelayAddress(event.payload.address);
+            }
+            if (status.toLowerCase() === 'disconnected') {
                 setConnectedRelay(null);
+                setRelayAddress(null);
             }
         });
 
@@ -165,6 +180,13 @@ export function AppProvider({ children }: { children: ReactNode }) {
             setConnectedRelay(relayName);
         });
 
+        const unlistenListenAddr = listen('listen-addr', (event: any) => {
+            const addr = event.payload as string;
+            // Circuit addresses belong to the relay, not our own socket
+            if (addr.includes('/p2p-circuit')) return;
+            setListenAddrs(prev => prev.includes(addr) ? prev : [...prev, addr]);
+        });
+
         const unlistenPeerCount = listen('peer-count', (event: any) => {
             setPeers(event.payload);
         });
@@ -194,6 +216,7 @@ export function AppProvider({ children }: { children: ReactNode }) {
             unlistenRelay.then(f => f());
             unlistenNodeError.then(f => f());
             unlistenRelayInfo.then(f => f());
+            unlistenListenAddr.then(f => f());
             unlistenPeerCount.then(f => f());
             unlistenNewBlock.then(f => f());
             unlistenVdf.then(f => f());
@@ -291,6 +314,8 @@ export function AppProvider({ children }: { children: ReactNode }) {
             setNodeStatus("Stopped");
             setRelayStatus("Disconnected");
             setConnectedRelay(null);
+            setRelayAddress(null);
+            setListenAddrs([]);
             setPeers(0);
         } catch (e) {
             console.error("Failed to stop node", e);
@@ -348,6 +373,8 @@ export function AppProvider({ children }: { children: ReactNode }) {
             nodeStatus,
             relayStatus,
             connectedRelay,
+            relayAddress,
+            listenAddrs,
             patience,
             peers,
             loading,
@@ -1,5 +1,6 @@
 import { type ClassValue, clsx } from "clsx";
 import { twMerge } from "tailwind-merge";
+import type { CommandError } from "../types";
 
 
 export function cn(...inputs: ClassValue[]) {
@@ -22,3 +23,12 @@ export function formatPatience(seconds: number): string {
 
     return parts.join(' ');
 }
+
+export function isCommandError(err: unknown): err is CommandError {
+    return typeof err === "object" && err !== null && "kind" in err && "message" in err;
+}
+
+// Display text of a command error, whether structured or a plain string
+export function errorMessage(err: unknown): string {
+    return isCommandError(err) ? err.message : String(err);
+}
@@ -23,6 +23,7 @@ import { formatNumber, calculateFee } from '../utils/format';
 export default function Mempool() {
     const [mempool, setMempool] = useState<Transaction[]>([]);
     const [selectedTx, setSelectedTx] = useState<Transaction | null>(null);
+    const [capacity, setCapacity] = useState<number | null>(null);
 
     useEffect(() => {
         fetchMempool();
@@ -35,6 +36,8 @@ export default function Mempool() {
             const txs = await invoke<Transaction[]>('get_mempool_transactions');
             const sortedTxs = txs.sort((a, b) => b.timestamp - a.timestamp);
             setMempool(sortedTxs);
+            const status = await invoke<{ size: number; capacity: number }>('get_mempool_status');
+            setCapacity(status.capacity);
         } catch (err) {
             console.error('Failed to fetch mempool:', err);
         }
@@ -59,7 +62,7 @@ export default function Mempool() {
                     </div>
                     <div className="px-8 py-3 rounded-full glass-panel border-orange-500/20 bg-orange-500/5">
                         <span className="text-[11px] font-black text-orange-500 tracking-[0.2em] uppercase italic">
-                            Pipeline Occupancy: {mempool.length} TXS
+                            Pipeline Occupancy: {mempool.length}{capacity !== null && ` / ${formatNumber(capacity, false)}`} TXS
                         </span>
                     </div>
                 </div>
@@ -48,7 +48,8 @@ export default function Network() {
     const [copiedId, setCopiedId] = useState<string | null>(null);
     const [syncInfo, setSyncInfo] = useState<{ state: string, current: number, target: number, peer: string } | null>(null);
     const [consensusState, setConsensusState] = useState<ConsensusStateResponse | null>(null);
-    const { height, relayStatus, connectedRelay, nodeStatus } = useApp(); // Get nodeStatus
+    const { height, relayStatus, connectedRelay, relayAddress, listenAddrs, nodeStatus } = useApp(); // Get nodeStatus
+    const listenPort = listenAddrs.map(a => a.match(/\/tcp\/(\d+)/)?.[1]).find(Boolean);
     const { success } = useToast();
 
     if (nodeStatus === "Relay Unreachable") {
@@ -366,6 +367,12 @@ export default function Network() {
                                         <ShieldAlert className="w-4 h-4 text-red-500" />
                                     ) : null}
                                 </div>
+                                {relayAddress && relayStatus.toLowerCase() === 'connected' && (
+                                    <span className="text-[10px] font-mono text-muted-foreground truncate z-10">{relayAddress}</span>
+                                )}
+                                {listenPort && (
+                                    <span className="text-[10px] font-mono text-muted-foreground truncate z-10">Listening on TCP port {listenPort}</span>
+                                )}
                             </div>
 
                             <div className="p-5 rounded-2xl bg-secondary/30 border border-border/50 flex flex-col gap-2 relative overflow-hidden group hover:border-primary/20 transition-colors">
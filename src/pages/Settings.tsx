@@ -11,7 +11,9 @@ import {
     Database,
     Zap,
     Fingerprint,
-    Trash2
+    Trash2,
+    Feather,
+    RefreshCw
 } from "lucide-react";
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
@@ -26,7 +28,14 @@ interface AppSettings {
     allow_relay_free_mode: boolean;
     mining_enabled: boolean;
     max_peers: number;
-    node_type: "Full" | "Pruned";
+    listen_port: number;
+    node_type: "Full" | "Pruned" | "Light";
+    chain_id: number;
+    reward_address: string | null;
+    fee_policy?: { burn_bps: number; treasury_bps: number; treasury_address: string | null };
+    checkpoints?: { height: number; hash: string }[];
+    data_dir?: string | null;
+    min_free_disk_mb?: number;
 }
 
 export default function Settings() {
@@ -38,7 +47,10 @@ export default function Settings() {
         allow_relay_free_mode: true,
         mining_enabled: true,
         max_peers: 50,
+        listen_port: 0,
         node_type: "Pruned",
+        chain_id: 1,
+        reward_address: null,
     });
     const [loading, setLoading] = useState(true);
     const [isWipeModalOpen, setIsWipeModalOpen] = useState(false);
@@ -71,6 +83,15 @@ export default function Settings() {
         setIsWipeModalOpen(true);
     };
 
+    const handleRecompute = async () => {
+        try {
+            const root = await invoke<string>("recompute_state");
+            success(`Balances rebuilt. State root ${root.slice(0, 12)}...`);
+        } catch (err) {
+            error("Failed to rebuild balances: " + err);
+        }
+    };
+
     const confirmReset = async () => {
         setIsWipeModalOpen(false);
         try {
@@ -152,6 +173,69 @@ export default function Settings() {
                                 placeholder="/ip4/127.0.0.1/tcp/9090"
                             />
                         </div>
+                        <div className="space-y-2">
+                            <label className="text-xs font-semibold uppercase text-muted-foreground flex justify-between">
+                                Listen Port
+                                <span className="text-[10px] text-orange-500 font-bold">(Requires Node Restart)</span>
+                            </label>
+                            <input
+                                type="number"
+                                min="0"
+                                max="65535"
+                                value={settings.listen_port}
+                                onChange={(e) => setSettings({ ...settings, listen_port: parseInt(e.target.value) || 0 })}
+                                className="w-full px-3 py-2 rounded-md border border-input bg-background/50 text-sm font-mono focus:outline-none focus:ring-2 focus:ring-ring"
+                                placeholder="0 (random)"
+                            />
+                        </div>
+                        <div className="space-y-2">
+                            <label className="text-xs font-semibold uppercase text-muted-foreground flex justify-between">
+                                Chain ID
+                                <span className="text-[10px] text-orange-500 font-bold">(Requires Restart & Data Reset)</span>
+                            </label>
+                            <input
+                                type="number"
+                                min="0"
+                                value={settings.chain_id}
+                                onChange={(e) => setSettings({ ...settings, chain_id: parseInt(e.target.value) || 0 })}
+                                className="w-full px-3 py-2 rounded-md border border-input bg-background/50 text-sm font-mono focus:outline-none focus:ring-2 focus:ring-ring"
+                                placeholder="1 (mainnet)"
+                            />
+                        </div>
+                        <div className="space-y-2">
+                            <label className="text-xs font-semibold uppercase text-muted-foreground">Reward Address</label>
+                            <input
+                                type="text"
+                                value={settings.reward_address ?? ""}
+                                onChange={(e) => setSettings({ ...settings, reward_address: e.target.value.trim() || null })}
+                                className="w-full px-3 py-2 rounded-md border border-input bg-background/50 text-sm font-mono focus:outline-none focus:ring-2 focus:ring-ring"
+                                placeholder="Wallet address (default)"
+                            />
+                        </div>
+                        <div className="space-y-2">
+                            <label className="text-xs font-semibold uppercase text-muted-foreground flex justify-between">
+                                Data Directory
+                                <span className="text-[10px] text-orange-500 font-bold">(Requires Restart)</span>
+                            </label>
+                            <input
+                                type="text"
+                                value={settings.data_dir ?? ""}
+                                onChange={(e) => setSettings({ ...settings, data_dir: e.target.value.trim() || null })}
+                                className="w-full px-3 py-2 rounded-md border border-input bg-background/50 text-sm font-mono focus:outline-none focus:ring-2 focus:ring-ring"
+                                placeholder="App data folder (default)"
+                            />
+                        </div>
+                        <div className="space-y-2">
+                            <label className="text-xs font-semibold uppercase text-muted-foreground">Min Free Disk (MB)</label>
+                            <input
+                                type="number"
+                                min="0"
+                                value={settings.min_free_disk_mb ?? 256}
+                                onChange={(e) => setSettings({ ...settings, min_free_disk_mb: parseInt(e.target.value) || 0 })}
+                                className="w-full px-3 py-2 rounded-md border border-input bg-background/50 text-sm font-mono focus:outline-none focus:ring-2 focus:ring-ring"
+                                placeholder="256"
+                            />
+                        </div>
                         <div className="space-y-3">
                             <div className="flex justify-between items-center">
                                 <label className="text-xs font-semibold uppercase text-muted-foreground">Max Peers</label>
@@ -219,7 +303,7 @@ export default function Settings() {
 
                         <div className="space-y-2">
                             <label className="text-xs font-semibold uppercase text-muted-foreground">Storage Mode</label>
-                            <div className="grid grid-cols-2 gap-2">
+                            <div className="grid grid-cols-3 gap-2">
                                 <NodeOption
                                     active={settings.node_type === "Pruned"}
                                     onClick={() => setSettings({ ...settings, node_type: "Pruned" })}
@@ -232,6 +316,12 @@ export default function Settings() {
                                     label="Full"
                                     icon={<Database className="w-3 h-3" />}
                                 />
+                                <NodeOption
+                                    active={settings.node_type === "Light"}
+                                    onClick={() => setSettings({ ...settings, node_type: "Light", mining_enabled: false })}
+                                    label="Light"
+                                    icon={<Feather className="w-3 h-3" />}
+                                />
                             </div>
                         </div>
                     </div>
@@ -249,9 +339,14 @@ export default function Settings() {
                                 <p className="text-sm text-red-600/80 dark:text-red-400/80">Irreversible actions that affect your node's data.</p>
                             </div>
                         </div>
-                        <Button variant="destructive" onClick={handleReset} className="gap-2">
-                            <Trash2 className="w-4 h-4" /> Wipe Chain Data
-                        </Button>
+                        <div className="flex items-center gap-2">
+                            <Button variant="outline" onClick={handleRecompute} className="gap-2">
+                                <RefreshCw className="w-4 h-4" /> Rebuild Balances
+                            </Button>
+                            <Button variant="destructive" onClick={handleReset} className="gap-2">
+                                <Trash2 className="w-4 h-4" /> Wipe Chain Data
+                            </Button>
+                        </div>
                     </div>
                 </div>
             </div>
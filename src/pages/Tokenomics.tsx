@@ -19,6 +19,7 @@ interface TokenomicsInfo {
     total_supply: number;
     max_supply: number;
     circulating_supply: number;
+    burned_fees: number;
     remaining_supply: number;
     next_halving_at: number;
     blocks_until_halving: number;
@@ -7,13 +7,14 @@ import PageTransition from "../components/PageTransition";
 import { useToast } from "../context/ToastContext";
 import { useApp } from "../context/AppContext";
 import { formatNumber, calculateFee, parseAmount } from "../utils/format";
-import { cn } from "../lib/utils";
+import { cn, errorMessage } from "../lib/utils";
 
 export default function Transactions() {
     const { success, error } = useToast();
     const { wallet, refreshWallet, recentBlocks } = useApp();
     const [receiver, setReceiver] = useState('');
     const [amount, setAmount] = useState('');
+    const [memo, setMemo] = useState('');
     const [loading, setLoading] = useState(false);
 
     const atomicAmount = parseAmount(amount);
@@ -45,8 +46,10 @@ export default function Transactions() {
     async function sendTransaction(e: React.FormEvent) {
         e.preventDefault();
 
-        if (!receiver.startsWith('12D3') || receiver.length < 40) {
-            error("Invalid address format. Addresses should start with '12D3' and be 40+ characters.");
+        // Anything that is not an address is resolved as an on-chain alias
+        const isAddress = receiver.startsWith('12D3') && receiver.length >= 40;
+        if (!isAddress && !/^[a-z0-9_-]{3,32}$/.test(receiver)) {
+            error("Enter an address starting with '12D3' or a registered alias.");
             return;
         }
 
@@ -54,14 +57,16 @@ export default function Transactions() {
         try {
             const id = await invoke<string>('submit_transaction', {
                 receiver,
-                amount: atomicAmount
+                amount: atomicAmount,
+                memo: memo.trim() || null
             });
             success(`Transaction successful! ID: ${id.substring(0, 8)}...`);
             setReceiver('');
             setAmount('');
+            setMemo('');
             refreshWallet();
         } catch (err) {
-            error(`Failed to send: ${err}`);
+            error(`Failed to send: ${errorMessage(err)}`);
         } finally {
             setLoading(false);
         }
@@ -134,7 +139,7 @@ export default function Transactions() {
 
                                 {/* Receiver Input */}
                                 <div className="space-y-3">
-                                    <label className="text-xs font-semibold text-muted-foreground px-1">Receiver Address</label>
+                                    <label className="text-xs font-semibold text-muted-foreground px-1">Receiver Address or Alias</label>
                                     <div className="relative group">
                                         <div className="absolute left-4 top-1/2 -translate-y-1/2 p-1.5 rounded-lg bg-secondary text-muted-foreground group-focus-within:text-primary transition-colors">
                                             <User className="w-4 h-4" />
@@ -144,12 +149,25 @@ export default function Transactions() {
                                             value={receiver}
                                             onChange={(e) => setReceiver(e.target.value)}
                                             className="w-full bg-secondary/30 border border-border focus:border-primary/50 rounded-2xl py-4 pl-14 pr-4 text-xs font-mono font-medium text-foreground focus:outline-none focus:ring-4 focus:ring-primary/10 transition-all placeholder:text-muted-foreground/50"
-                                            placeholder="12D3..."
+                                            placeholder="12D3... or alias"
                                             required
                                         />
                                     </div>
                                 </div>
 
+                                {/* Memo Input */}
+                                <div className="space-y-3">
+                                    <label className="text-xs font-semibold text-muted-foreground px-1">Memo (optional)</label>
+                                    <input
+                                        type="text"
+                                        value={memo}
+                                        onChange={(e) => setMemo(e.target.value)}
+                                        className="w-full bg-secondary/30 border border-border focus:border-primary/50 rounded-2xl py-4 px-4 text-xs font-medium text-foreground focus:outline-none focus:ring-4 focus:ring-primary/10 transition-all placeholder:text-muted-foreground/50"
+                                        placeholder="Invoice or deposit reference"
+                                        maxLength={256}
+                                    />
+                                </div>
+
                                 <div className="flex-1" />
 
                                 <Button
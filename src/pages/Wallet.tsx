@@ -1,4 +1,4 @@
-import { cn } from "../lib/utils";
+import { cn, errorMessage } from "../lib/utils";
 import { Button } from "../components/ui/button";
 import { Wallet as WalletIcon, Send, RefreshCw, Copy, Plus, Key, ShieldCheck, History as HistoryIcon, Pickaxe, ArrowRight, UserCircle, Download } from "lucide-react";
 import { useApp, WalletExport, Block, Transaction } from "../context/AppContext";
@@ -51,7 +51,7 @@ export default function Wallet() {
             success("Wallet imported successfully!");
             setIsImporting(false);
         } catch (err) {
-            error("Import failed: " + err);
+            error("Import failed: " + errorMessage(err));
         }
     };
 
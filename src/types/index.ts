@@ -20,3 +20,19 @@ export interface Transaction {
     timestamp: number;
     sender_bump: number;
 }
+
+// Structured error returned by commands that have moved off plain strings
+export interface CommandError {
+    kind:
+        | "NoWallet"
+        | "WalletLocked"
+        | "InsufficientFunds"
+        | "NotConnected"
+        | "InvalidAddress"
+        | "InvalidKey"
+        | "StorageError"
+        | "Other";
+    message: string;
+    balance?: number;
+    required?: number;
+}